@@ -0,0 +1,231 @@
+//! Benchmarks for the core execution paths
+//!
+//! Flow-dependent benchmarks run over nodes of 10, 50 and 100 steps.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::collections::HashMap;
+use vesper_core::contracts::ContractValidator;
+use vesper_core::executor::ExecutionContext;
+use vesper_core::types::Contracts;
+use vesper_core::{SemanticExecutor, Value, VesperLoader};
+
+const FLOW_SIZES: [usize; 3] = [10, 50, 100];
+
+/// Node with `steps` chained additions over inputs `a` and `b`
+fn arithmetic_node(steps: usize) -> String {
+    let mut yaml = String::from(
+        "node_id: add_v1\ntype: function\nintent: add numbers\n\n\
+         inputs:\n  a:\n    type: integer\n  b:\n    type: integer\n\nflow:\n",
+    );
+    for i in 0..steps {
+        let left = if i == 0 {
+            "a".to_string()
+        } else {
+            format!("sum{}", i - 1)
+        };
+        yaml.push_str(&format!(
+            "  - step: add{i}\n    operation: arithmetic\n    expression: \"{left} + b\"\n    output: sum{i}\n"
+        ));
+    }
+    yaml
+}
+
+/// Node with `steps` templates each substituting five inputs
+fn template_node(steps: usize) -> String {
+    let mut yaml =
+        String::from("node_id: greet_v1\ntype: function\nintent: greet users\n\ninputs:\n");
+    for name in ["a", "b", "c", "d", "e"] {
+        yaml.push_str(&format!("  {name}:\n    type: string\n"));
+    }
+    yaml.push_str("\nflow:\n");
+    for i in 0..steps {
+        yaml.push_str(&format!(
+            "  - step: greet{i}\n    operation: string_template\n    template: \"{{a}}, {{b}}, {{c}}, {{d}} and {{e}}\"\n"
+        ));
+    }
+    yaml
+}
+
+fn executor_for(yaml: &str) -> SemanticExecutor {
+    let mut executor = SemanticExecutor::new();
+    executor.register(VesperLoader::new().load_string(yaml).unwrap());
+    executor
+}
+
+fn arithmetic_simple(c: &mut Criterion) {
+    let mut group = c.benchmark_group("arithmetic_simple");
+    group.sample_size(10);
+    let inputs: HashMap<String, Value> = [("a", 2), ("b", 3)]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), Value::Int(value)))
+        .collect();
+
+    for steps in FLOW_SIZES {
+        let executor = executor_for(&arithmetic_node(steps));
+        group.bench_with_input(BenchmarkId::from_parameter(steps), &inputs, |b, inputs| {
+            b.iter(|| {
+                for _ in 0..10_000 {
+                    black_box(executor.execute("add_v1", inputs.clone()).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn template_substitution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("template_substitution");
+    group.sample_size(10);
+    let inputs: HashMap<String, Value> = ["a", "b", "c", "d", "e"]
+        .into_iter()
+        .map(|name| (name.to_string(), Value::from(name.to_uppercase())))
+        .collect();
+
+    for steps in FLOW_SIZES {
+        let executor = executor_for(&template_node(steps));
+        group.bench_with_input(BenchmarkId::from_parameter(steps), &inputs, |b, inputs| {
+            b.iter(|| {
+                for _ in 0..10_000 {
+                    black_box(executor.execute("greet_v1", inputs.clone()).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn contract_check(c: &mut Criterion) {
+    let contracts = Contracts {
+        preconditions: vec![
+            "amount > 0".to_string(),
+            "amount <= 10000".to_string(),
+            "currency == USD".to_string(),
+            "retries < 5".to_string(),
+            "priority != 0".to_string(),
+        ],
+        postconditions: Vec::new(),
+        invariants: Vec::new(),
+    };
+    let inputs: HashMap<String, Value> = [
+        ("amount", Value::Int(250)),
+        ("currency", Value::from("USD")),
+        ("retries", Value::Int(1)),
+        ("priority", Value::Int(2)),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+    let validator = ContractValidator::new();
+
+    c.bench_function("contract_check", |b| {
+        b.iter(|| {
+            for _ in 0..10_000 {
+                validator
+                    .check_preconditions(black_box(&contracts), black_box(&inputs))
+                    .unwrap();
+            }
+        })
+    });
+}
+
+fn load_and_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load_and_parse");
+    group.sample_size(10);
+    let loader = VesperLoader::new();
+
+    for steps in FLOW_SIZES {
+        let yaml = arithmetic_node(steps);
+        group.bench_with_input(BenchmarkId::from_parameter(steps), &yaml, |b, yaml| {
+            b.iter(|| {
+                for _ in 0..1_000 {
+                    black_box(loader.load_string(yaml).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// 1,000-step flow producing short strings; compare runs with and without
+/// the `sso` feature to see the effect of inline string storage
+fn short_strings(c: &mut Criterion) {
+    let mut group = c.benchmark_group("short_strings");
+    group.sample_size(10);
+    let mut yaml = String::from(
+        "node_id: tag_v1\ntype: function\nintent: build tags\n\n\
+         inputs:\n  id:\n    type: string\n\nflow:\n",
+    );
+    for i in 0..1_000 {
+        yaml.push_str(&format!(
+            "  - step: tag{i}\n    operation: string_template\n    template: \"t{i}-{{id}}\"\n    output: tag{i}\n"
+        ));
+    }
+    let executor = executor_for(&yaml);
+    let inputs: HashMap<String, Value> = [("id".to_string(), Value::from("a1"))].into();
+
+    group.bench_function("1000_steps", |b| {
+        b.iter(|| black_box(executor.execute("tag_v1", inputs.clone()).unwrap()))
+    });
+    group.finish();
+}
+
+/// 20-step flow binding a variable per step; compare runs with and without
+/// the `arena` feature to see the effect of arena-allocated variables
+fn variable_allocation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("variable_allocation");
+    group.sample_size(10);
+    let executor = executor_for(&arithmetic_node(20));
+    let inputs: HashMap<String, Value> = [("a", 2), ("b", 3)]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), Value::Int(value)))
+        .collect();
+
+    group.bench_function("20_steps", |b| {
+        b.iter(|| {
+            for _ in 0..10_000 {
+                black_box(executor.execute("add_v1", inputs.clone()).unwrap());
+            }
+        })
+    });
+
+    // The variables alone, as each execution of the flow binds them
+    let names: Vec<String> = (0..20).map(|i| format!("sum{i}")).collect();
+    group.bench_function("20_variables_heap", |b| {
+        b.iter(|| {
+            for _ in 0..10_000 {
+                let mut ctx = ExecutionContext::new(HashMap::new());
+                for (i, name) in names.iter().enumerate() {
+                    ctx.set(name.clone(), Value::Int(i as i64));
+                }
+                black_box(&ctx);
+            }
+        })
+    });
+    #[cfg(feature = "arena")]
+    group.bench_function("20_variables_arena", |b| {
+        let mut arena = vesper_core::executor::ExecutionArena::new();
+        b.iter(|| {
+            for _ in 0..10_000 {
+                let mut ctx = ExecutionContext::new_in(&arena, HashMap::new());
+                for (i, name) in names.iter().enumerate() {
+                    ctx.set(name.clone(), Value::Int(i as i64));
+                }
+                black_box(&ctx);
+                drop(ctx);
+                arena.reset();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    arithmetic_simple,
+    template_substitution,
+    contract_check,
+    load_and_parse,
+    short_strings,
+    variable_allocation
+);
+criterion_main!(benches);
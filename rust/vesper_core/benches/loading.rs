@@ -0,0 +1,64 @@
+//! Benchmarks comparing YAML and binary node loading
+//!
+//! Run with `cargo bench --features binary-format --bench loading`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use vesper_core::VesperLoader;
+
+const FLOW_SIZES: [usize; 3] = [10, 50, 100];
+
+/// Node with `steps` chained additions over inputs `a` and `b`
+fn arithmetic_node(steps: usize) -> String {
+    let mut yaml = String::from(
+        "node_id: add_v1\ntype: function\nintent: add numbers\n\n\
+         inputs:\n  a:\n    type: integer\n  b:\n    type: integer\n\nflow:\n",
+    );
+    for i in 0..steps {
+        let left = if i == 0 {
+            "a".to_string()
+        } else {
+            format!("sum{}", i - 1)
+        };
+        yaml.push_str(&format!(
+            "  - step: add{i}\n    operation: arithmetic\n    expression: \"{left} + b\"\n    output: sum{i}\n"
+        ));
+    }
+    yaml
+}
+
+fn yaml_vs_binary(c: &mut Criterion) {
+    let mut group = c.benchmark_group("node_loading");
+    group.sample_size(10);
+    let loader = VesperLoader::new();
+    let dir = std::env::temp_dir().join(format!("vesper-loading-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for steps in FLOW_SIZES {
+        let yaml = dir.join(format!("add{steps}.yaml"));
+        let binary = dir.join(format!("add{steps}.vspb"));
+        std::fs::write(&yaml, arithmetic_node(steps)).unwrap();
+        loader
+            .save_binary_file(&loader.load_file(&yaml).unwrap(), &binary)
+            .unwrap();
+
+        group.bench_with_input(BenchmarkId::new("yaml", steps), &yaml, |b, yaml| {
+            b.iter(|| {
+                for _ in 0..1_000 {
+                    black_box(loader.load_file(yaml).unwrap());
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("binary", steps), &binary, |b, binary| {
+            b.iter(|| {
+                for _ in 0..1_000 {
+                    black_box(loader.load_binary_file(binary).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+criterion_group!(benches, yaml_vs_binary);
+criterion_main!(benches);
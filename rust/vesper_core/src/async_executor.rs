@@ -0,0 +1,147 @@
+//! Running nodes from async code
+//!
+//! Node execution is synchronous and can be CPU-heavy, so calling
+//! `SemanticExecutor::execute` directly from a task stalls the tokio
+//! worker running it. `AsyncSemanticExecutor` moves each execution onto
+//! tokio's blocking thread pool instead.
+
+use crate::error::{Result, VesperError};
+use crate::executor::{ExecutionResult, SemanticExecutor};
+use crate::types::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Executes nodes on the blocking thread pool of the current runtime
+pub struct AsyncSemanticExecutor {
+    /// Executor running the nodes
+    executor: Arc<SemanticExecutor>,
+    /// Bound on concurrently offloaded executions, if any
+    permits: Option<Arc<Semaphore>>,
+}
+
+impl AsyncSemanticExecutor {
+    /// Wrap an executor
+    pub fn new(executor: Arc<SemanticExecutor>) -> Self {
+        Self {
+            executor,
+            permits: None,
+        }
+    }
+
+    /// Run at most `size` executions at once; further calls wait their turn
+    ///
+    /// Without a size, executions are limited only by tokio's blocking
+    /// pool (512 threads by default).
+    pub fn with_thread_pool_size(mut self, size: usize) -> Self {
+        self.permits = Some(Arc::new(Semaphore::new(size.max(1))));
+        self
+    }
+
+    /// The wrapped executor
+    pub fn executor(&self) -> &SemanticExecutor {
+        &self.executor
+    }
+
+    /// Execute a node via `tokio::task::spawn_blocking`
+    ///
+    /// Must be awaited within a tokio runtime.
+    pub fn execute_offloaded(
+        &self,
+        node_id: &str,
+        inputs: HashMap<String, Value>,
+    ) -> impl Future<Output = Result<ExecutionResult>> + Send + 'static {
+        let executor = self.executor.clone();
+        let permits = self.permits.clone();
+        let node_id = node_id.to_string();
+
+        async move {
+            let _permit = match permits {
+                Some(permits) => Some(permits.acquire_owned().await.map_err(|e| {
+                    VesperError::ExecutionError(format!("Thread pool closed: {}", e))
+                })?),
+                None => None,
+            };
+            tokio::task::spawn_blocking(move || executor.execute(&node_id, inputs))
+                .await
+                .map_err(|e| {
+                    VesperError::ExecutionError(format!("Offloaded execution failed: {}", e))
+                })?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseBackend;
+    use crate::loader::VesperLoader;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+
+    /// Database whose queries wait for a gate opened from async code
+    #[derive(Default)]
+    struct GateDatabase {
+        open: AtomicBool,
+    }
+
+    impl DatabaseBackend for GateDatabase {
+        fn query(
+            &self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while !self.open.load(Ordering::SeqCst) {
+                if Instant::now() > deadline {
+                    return Err("gate never opened".to_string());
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            Ok(0)
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_offloaded_execution_keeps_reactor_responsive() {
+        let mut yaml = String::from(
+            "node_id: crunch_v1\ntype: function\nintent: long arithmetic chain\n\n\
+             inputs:\n  n:\n    type: integer\n\nflow:\n",
+        );
+        for i in 0..1_000 {
+            let previous = if i == 0 {
+                "n".to_string()
+            } else {
+                format!("acc{}", i - 1)
+            };
+            yaml.push_str(&format!(
+                "  - step: step{i}\n    operation: arithmetic\n    expression: \"{previous} + 3\"\n    output: acc{i}\n"
+            ));
+        }
+        yaml.push_str("  - step: wait\n    operation: database_query\n    parameters:\n      sql: \"SELECT 1\"\n");
+
+        let database = Arc::new(GateDatabase::default());
+        let mut executor = SemanticExecutor::new().with_database(database.clone());
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        let executor = AsyncSemanticExecutor::new(Arc::new(executor)).with_thread_pool_size(2);
+
+        let mut inputs = HashMap::new();
+        inputs.insert("n".to_string(), Value::Int(7));
+        let execution = executor.execute_offloaded("crunch_v1", inputs);
+
+        // The gate opens from a task on the only runtime thread, which can
+        // only run if the execution is not blocking it
+        tokio::spawn(async move {
+            tokio::task::yield_now().await;
+            database.open.store(true, Ordering::SeqCst);
+        });
+
+        let result = execution.await;
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+}
@@ -0,0 +1,425 @@
+//! Programmatic construction of Vesper nodes
+//!
+//! `NodeBuilder` assembles a `VesperNode` without a YAML spec and runs the
+//! loader's validation when the node is built. Typed constructors such as
+//! `FlowStep::arithmetic` check each step as it is created.
+
+use crate::error::{Result, VesperError};
+use crate::loader::VesperLoader;
+use crate::types::{Contracts, FlowStep, InputSpec, NodeType, OutputField, Outputs, VesperNode};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Type of an input or output field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    Any,
+    Array,
+    Boolean,
+    Bytes,
+    Decimal,
+    Enum,
+    Float,
+    Integer,
+    Number,
+    Object,
+    String,
+    Timestamp,
+    /// `array<T>` of the element type
+    ArrayOf(Box<FieldType>),
+    /// Custom type declared in the node's `types`
+    Custom(String),
+}
+
+/// Type of an input field
+pub type InputType = FieldType;
+
+/// Type of an output field
+pub type OutputType = FieldType;
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Any => "any",
+            Self::Array => "array",
+            Self::Boolean => "boolean",
+            Self::Bytes => "bytes",
+            Self::Decimal => "decimal",
+            Self::Enum => "enum",
+            Self::Float => "float",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::Object => "object",
+            Self::String => "string",
+            Self::Timestamp => "timestamp",
+            Self::ArrayOf(element) => return write!(f, "array<{}>", element),
+            Self::Custom(name) => name,
+        };
+        f.write_str(name)
+    }
+}
+
+/// Fluent builder for a `VesperNode`
+pub struct NodeBuilder {
+    node: VesperNode,
+    /// First step that failed to construct, reported by `build`
+    step_error: Option<VesperError>,
+}
+
+impl NodeBuilder {
+    /// Start a node with no inputs, outputs or flow
+    pub fn new(node_id: &str, node_type: NodeType) -> Self {
+        Self {
+            node: VesperNode {
+                node_id: node_id.to_string(),
+                node_type,
+                intent: String::new(),
+                metadata: None,
+                imports: None,
+                inputs: HashMap::new(),
+                outputs: None,
+                types: HashMap::new(),
+                contracts: None,
+                input_transform: None,
+                flow: Vec::new(),
+                output_transform: None,
+                performance: None,
+                security: None,
+                cache: None,
+            },
+            step_error: None,
+        }
+    }
+
+    /// Set the high-level purpose
+    pub fn intent(mut self, intent: &str) -> Self {
+        self.node.intent = intent.to_string();
+        self
+    }
+
+    /// Add a required input
+    pub fn input(self, name: &str, input_type: InputType) -> Self {
+        self.input_spec(
+            name,
+            InputSpec {
+                input_type: input_type.to_string(),
+                required: true,
+                constraints: Vec::new(),
+                default: None,
+                description: None,
+            },
+        )
+    }
+
+    /// Add an optional input
+    pub fn optional_input(self, name: &str, input_type: InputType) -> Self {
+        self.input_spec(
+            name,
+            InputSpec {
+                input_type: input_type.to_string(),
+                required: false,
+                constraints: Vec::new(),
+                default: None,
+                description: None,
+            },
+        )
+    }
+
+    /// Add an input with a full specification
+    pub fn input_spec(mut self, name: &str, spec: InputSpec) -> Self {
+        self.node.inputs.insert(name.to_string(), spec);
+        self
+    }
+
+    /// Append a step to the flow
+    ///
+    /// Takes the result of a typed step constructor; wrap hand-built steps
+    /// in `Ok`. The first error is returned by `build`.
+    pub fn flow_step(mut self, step: Result<FlowStep>) -> Self {
+        match step {
+            Ok(step) => self.node.flow.push(step),
+            Err(e) => {
+                self.step_error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    /// Add a success output field
+    pub fn output_success(mut self, name: &str, output_type: OutputType) -> Self {
+        self.outputs()
+            .success
+            .insert(name.to_string(), field(output_type));
+        self
+    }
+
+    /// Add an error output field
+    pub fn output_error(mut self, name: &str, output_type: OutputType) -> Self {
+        self.outputs()
+            .error
+            .insert(name.to_string(), field(output_type));
+        self
+    }
+
+    /// Add a precondition
+    pub fn precondition(mut self, condition: &str) -> Self {
+        self.contracts().preconditions.push(condition.to_string());
+        self
+    }
+
+    /// Add a postcondition
+    pub fn postcondition(mut self, condition: &str) -> Self {
+        self.contracts().postconditions.push(condition.to_string());
+        self
+    }
+
+    /// Add an invariant
+    pub fn invariant(mut self, condition: &str) -> Self {
+        self.contracts().invariants.push(condition.to_string());
+        self
+    }
+
+    /// Validate and return the node
+    ///
+    /// Runs the same checks as `VesperLoader::load_string`.
+    pub fn build(self) -> Result<VesperNode> {
+        if let Some(e) = self.step_error {
+            return Err(e);
+        }
+        VesperLoader::new().validate(&self.node)?;
+        Ok(self.node)
+    }
+
+    fn outputs(&mut self) -> &mut Outputs {
+        self.node.outputs.get_or_insert_with(|| Outputs {
+            success: HashMap::new(),
+            error: HashMap::new(),
+        })
+    }
+
+    fn contracts(&mut self) -> &mut Contracts {
+        self.node.contracts.get_or_insert_with(|| Contracts {
+            preconditions: Vec::new(),
+            postconditions: Vec::new(),
+            invariants: Vec::new(),
+        })
+    }
+}
+
+/// Output field of the given type
+fn field(output_type: OutputType) -> OutputField {
+    OutputField {
+        output_type: Some(output_type.to_string()),
+        description: None,
+        values: Vec::new(),
+    }
+}
+
+impl FlowStep {
+    /// Arithmetic step evaluating `expression` into `output`, named after it
+    pub fn arithmetic(output: &str, expression: &str) -> Result<FlowStep> {
+        let step = FlowStep {
+            step: output.to_string(),
+            operation: "arithmetic".to_string(),
+            expression: Some(expression.to_string()),
+            output: Some(output.to_string()),
+            ..Default::default()
+        };
+        step.validate()?;
+        Ok(step)
+    }
+
+    /// `string_template` step rendering `template` into `output`, named
+    /// after it
+    pub fn template(output: &str, template: &str) -> Result<FlowStep> {
+        let step = FlowStep {
+            step: output.to_string(),
+            operation: "string_template".to_string(),
+            template: Some(template.to_string()),
+            output: Some(output.to_string()),
+            ..Default::default()
+        };
+        step.validate()?;
+        Ok(step)
+    }
+
+    /// Start a conditional step branching on `condition`
+    pub fn conditional(condition: &str) -> ConditionalStepBuilder {
+        ConditionalStepBuilder {
+            step: FlowStep {
+                operation: "conditional".to_string(),
+                condition: Some(condition.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Check that the fields the step's operation requires are present
+    ///
+    /// Covers `arithmetic`, `string_template` and `conditional`; other
+    /// operations only need a name.
+    pub fn validate(&self) -> Result<()> {
+        let missing = |field: &str| {
+            Err(VesperError::ValidationError {
+                path: format!("flow.{}", self.step),
+                message: format!("{} step missing {}", self.operation, field),
+            })
+        };
+        let is_blank =
+            |field: &Option<String>| field.as_deref().is_none_or(|f| f.trim().is_empty());
+
+        if self.operation.is_empty() {
+            return missing("operation");
+        }
+        match self.operation.as_str() {
+            "arithmetic" if is_blank(&self.expression) => missing("expression"),
+            "string_template" if is_blank(&self.template) => missing("template"),
+            "conditional" if is_blank(&self.condition) => missing("condition"),
+            "conditional" if self.then_steps.is_empty() && self.else_steps.is_empty() => {
+                missing("then or else steps")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Builder for a `conditional` step, from `FlowStep::conditional`
+pub struct ConditionalStepBuilder {
+    step: FlowStep,
+}
+
+impl ConditionalStepBuilder {
+    /// Name the step
+    pub fn named(mut self, name: &str) -> Self {
+        self.step.step = name.to_string();
+        self
+    }
+
+    /// Store the result of the branch taken in `output`
+    pub fn output(mut self, output: &str) -> Self {
+        self.step.output = Some(output.to_string());
+        self
+    }
+
+    /// Steps run when the condition holds (the `then` branch)
+    pub fn on_success(mut self, steps: Vec<FlowStep>) -> Self {
+        self.step.then_steps = steps;
+        self
+    }
+
+    /// Steps run when the condition does not hold (the `else` branch)
+    pub fn on_failure(mut self, steps: Vec<FlowStep>) -> Self {
+        self.step.else_steps = steps;
+        self
+    }
+
+    /// Validate and return the step
+    ///
+    /// Fails unless at least one branch has steps.
+    pub fn build(self) -> Result<FlowStep> {
+        self.step.validate()?;
+        Ok(self.step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::SemanticExecutor;
+    use crate::types::Value;
+
+    #[test]
+    fn test_builder_matches_yaml() {
+        let built = NodeBuilder::new("add_v1", NodeType::Function)
+            .intent("add two numbers")
+            .input("a", InputType::Integer)
+            .input("b", InputType::Integer)
+            .flow_step(FlowStep::arithmetic("result", "a + b"))
+            .output_success("result", OutputType::Integer)
+            .precondition("a >= 0")
+            .build()
+            .unwrap();
+        let loaded = VesperLoader::new()
+            .load_string(
+                r#"
+node_id: add_v1
+type: function
+intent: add two numbers
+
+inputs:
+  a:
+    type: integer
+  b:
+    type: integer
+
+outputs:
+  success:
+    result:
+      type: integer
+
+contracts:
+  preconditions:
+    - a >= 0
+
+flow:
+  - step: result
+    operation: arithmetic
+    expression: a + b
+    output: result
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&built).unwrap(),
+            serde_json::to_value(&loaded).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_validates() {
+        let result = NodeBuilder::new("add", NodeType::Function)
+            .intent("add two numbers")
+            .build();
+        assert!(matches!(result, Err(VesperError::ValidationError { .. })));
+
+        let result = NodeBuilder::new("add_v1", NodeType::Function)
+            .input("a", InputType::ArrayOf(Box::new(InputType::Integer)))
+            .flow_step(FlowStep::arithmetic("result", "a + b"))
+            .build();
+        assert!(matches!(result, Err(VesperError::ValidationError { .. })));
+    }
+
+    #[test]
+    fn test_step_constructors_validate() {
+        assert!(FlowStep::arithmetic("result", " ").is_err());
+        assert!(FlowStep::template("greeting", "").is_err());
+        assert!(FlowStep::conditional("a > 0")
+            .named("check")
+            .build()
+            .is_err());
+
+        let node = NodeBuilder::new("sign_v1", NodeType::Function)
+            .intent("describe the sign of a number")
+            .input("a", InputType::Integer)
+            .flow_step(
+                FlowStep::conditional("a >= 0")
+                    .named("sign")
+                    .on_success(vec![FlowStep::template("sign", "non-negative").unwrap()])
+                    .on_failure(vec![FlowStep::template("sign", "negative").unwrap()])
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+        let inputs = HashMap::from([("a".to_string(), Value::Int(-4))]);
+        let result = executor.execute("sign_v1", inputs).unwrap();
+        assert_eq!(result.data, Some(Value::from("negative")));
+
+        let result = NodeBuilder::new("sign_v1", NodeType::Function)
+            .flow_step(FlowStep::template("sign", ""))
+            .build();
+        assert!(matches!(result, Err(VesperError::ValidationError { .. })));
+    }
+}
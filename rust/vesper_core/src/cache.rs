@@ -0,0 +1,113 @@
+//! Pluggable key-value caching for flow operations
+
+use crate::types::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Storage backend for the `cache_get` / `cache_set` operations
+pub trait CacheBackend: Send + Sync {
+    /// Get a cached value, or `None` on a miss or expired entry
+    fn get(&self, key: &str) -> Option<Value>;
+
+    /// Store a value, optionally expiring after `ttl`
+    fn set(&self, key: &str, value: Value, ttl: Option<Duration>);
+
+    /// Remove a value
+    fn delete(&self, key: &str);
+}
+
+/// Hit and miss counts of an executor's parse cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of cached entries
+    pub entries: usize,
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that had to parse
+    pub misses: u64,
+}
+
+/// Process-local cache backed by a `HashMap` with per-entry TTL
+pub struct InMemoryCacheBackend {
+    /// Cached values with their optional expiry deadline
+    entries: Mutex<HashMap<String, (Value, Option<Instant>)>>,
+}
+
+impl InMemoryCacheBackend {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of entries, including expired ones not yet evicted
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Check if the cache holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for InMemoryCacheBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CacheBackend for InMemoryCacheBackend {
+    fn get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((_, Some(deadline))) if Instant::now() >= *deadline => {
+                entries.remove(key);
+                None
+            }
+            Some((value, _)) => Some(value.clone()),
+            None => None,
+        }
+    }
+
+    fn set(&self, key: &str, value: Value, ttl: Option<Duration>) {
+        let deadline = ttl.map(|ttl| Instant::now() + ttl);
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (value, deadline));
+    }
+
+    fn delete(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ttl_expiry() {
+        let cache = InMemoryCacheBackend::new();
+        cache.set("short", Value::Int(1), Some(Duration::from_millis(10)));
+        cache.set("forever", Value::Int(2), None);
+
+        assert_eq!(cache.get("short"), Some(Value::Int(1)));
+        std::thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(cache.get("short"), None);
+        assert_eq!(cache.get("forever"), Some(Value::Int(2)));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_delete() {
+        let cache = InMemoryCacheBackend::new();
+        cache.set("key", Value::from("value"), None);
+        cache.delete("key");
+        assert!(cache.get("key").is_none());
+    }
+}
@@ -0,0 +1,88 @@
+//! Saved state of executions paused by an `approval_gate`
+
+use crate::types::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Everything needed to continue a paused execution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Execution to continue, as reported in its `ExecutionResult`
+    pub execution_id: String,
+    /// Node being executed
+    pub node_id: String,
+    /// Gate waiting for a decision
+    pub gate_id: String,
+    /// Index of the flow step to continue from
+    pub resume_at: usize,
+    /// Variables set before the gate
+    pub variables: HashMap<String, Value>,
+    /// Inputs of the execution
+    pub inputs: HashMap<String, Value>,
+    /// Capabilities granted to the node
+    pub capabilities: Vec<String>,
+    /// Time after which the gate can no longer be approved
+    pub expires_at: Option<SystemTime>,
+}
+
+impl Checkpoint {
+    /// Whether the approval window has passed
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| SystemTime::now() >= expires_at)
+    }
+}
+
+/// Storage for paused executions
+///
+/// Checkpoints are serializable, so a store shared by several processes
+/// lets an execution paused in one be resumed in another.
+pub trait CheckpointStore: Send + Sync {
+    /// Save a checkpoint, replacing any with the same execution ID
+    fn save(&self, checkpoint: Checkpoint) -> Result<(), String>;
+
+    /// Remove and return the checkpoint of an execution, if any
+    fn take(&self, execution_id: &str) -> Result<Option<Checkpoint>, String>;
+}
+
+/// Process-local checkpoint store
+pub struct InMemoryCheckpointStore {
+    /// Checkpoints by execution ID
+    checkpoints: Mutex<HashMap<String, Checkpoint>>,
+}
+
+impl InMemoryCheckpointStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self {
+            checkpoints: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Execution IDs of the paused executions, in no particular order
+    pub fn pending(&self) -> Vec<String> {
+        self.checkpoints.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+impl Default for InMemoryCheckpointStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CheckpointStore for InMemoryCheckpointStore {
+    fn save(&self, checkpoint: Checkpoint) -> Result<(), String> {
+        self.checkpoints
+            .lock()
+            .unwrap()
+            .insert(checkpoint.execution_id.clone(), checkpoint);
+        Ok(())
+    }
+
+    fn take(&self, execution_id: &str) -> Result<Option<Checkpoint>, String> {
+        Ok(self.checkpoints.lock().unwrap().remove(execution_id))
+    }
+}
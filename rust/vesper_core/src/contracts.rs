@@ -1,8 +1,8 @@
-//! Contract validation for Vesper nodes
+//! Contract validation and synthesis for Vesper nodes
 
 use crate::error::{Result, VesperError};
 use crate::types::{Contracts, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 /// Contract validator
 pub struct ContractValidator {
@@ -73,6 +73,16 @@ impl ContractValidator {
         Ok(())
     }
 
+    /// Evaluate a condition against a set of variable bindings
+    ///
+    /// A bare variable name evaluates to its truthiness.
+    pub fn evaluate(&self, condition: &str, bindings: &HashMap<String, Value>) -> Result<bool> {
+        if let Some(value) = bindings.get(condition.trim()) {
+            return Ok(value.is_truthy());
+        }
+        self.evaluate_condition(condition, bindings, &HashMap::new())
+    }
+
     /// Evaluate a condition expression
     fn evaluate_condition(
         &self,
@@ -179,7 +189,7 @@ impl ContractValidator {
         }
 
         // Return as string
-        Value::String(s.to_string())
+        Value::from(s)
     }
 
     fn eval_eq(left: &Value, right: &Value) -> bool {
@@ -225,6 +235,84 @@ impl Default for ContractValidator {
     }
 }
 
+/// Example execution for contract synthesis: inputs and output fields
+pub type ContractExample = (HashMap<String, Value>, HashMap<String, Value>);
+
+/// Candidate contracts holding for every example `(inputs, output)`
+///
+/// Preconditions bound numeric inputs below by zero and require string
+/// inputs to be non-empty. Postconditions do the same for output fields
+/// and relate each numeric output field to each input (`==`, `>=` or
+/// `<=`). Only candidates holding for all examples are kept, so the result
+/// is a heuristic to review, not a proof. No examples yield no contracts.
+pub fn synthesize_contracts(examples: &[ContractExample]) -> Contracts {
+    let validator = ContractValidator::new();
+    let no_outputs = HashMap::new();
+    let holds_for_all = |condition: &str, with_outputs: bool| {
+        !examples.is_empty()
+            && examples.iter().all(|(inputs, output)| {
+                let outputs = if with_outputs { output } else { &no_outputs };
+                validator
+                    .evaluate_condition(condition, inputs, outputs)
+                    .unwrap_or(false)
+            })
+    };
+    // Bound `name` by zero, or require it to be non-empty if a string
+    let bounds = |name: &str, sample: Option<&Value>| -> Vec<String> {
+        match sample {
+            Some(Value::String(_)) => vec![format!("{} != ''", name)],
+            _ => vec![format!("{} > 0", name), format!("{} >= 0", name)],
+        }
+    };
+
+    let input_names: BTreeSet<&String> = examples.iter().flat_map(|(i, _)| i.keys()).collect();
+    let output_names: BTreeSet<&String> = examples.iter().flat_map(|(_, o)| o.keys()).collect();
+    let first = examples.first();
+
+    let mut preconditions = Vec::new();
+    for name in &input_names {
+        let sample = first.and_then(|(inputs, _)| inputs.get(*name));
+        if let Some(condition) = bounds(name, sample)
+            .into_iter()
+            .find(|c| holds_for_all(c, false))
+        {
+            preconditions.push(condition);
+        }
+    }
+
+    let mut postconditions = Vec::new();
+    for name in &output_names {
+        let sample = first.and_then(|(_, output)| output.get(*name));
+        if let Some(condition) = bounds(name, sample)
+            .into_iter()
+            .find(|c| holds_for_all(c, true))
+        {
+            postconditions.push(condition);
+        }
+        if matches!(sample, Some(Value::String(_))) {
+            continue;
+        }
+        for input in input_names
+            .iter()
+            .filter(|input| !output_names.contains(*input))
+        {
+            if let Some(condition) = ["==", ">=", "<="]
+                .iter()
+                .map(|op| format!("{} {} {}", name, op, input))
+                .find(|c| holds_for_all(c, true))
+            {
+                postconditions.push(condition);
+            }
+        }
+    }
+
+    Contracts {
+        preconditions,
+        postconditions,
+        invariants: Vec::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,4 +348,23 @@ mod tests {
 
         assert!(validator.check_preconditions(&contracts, &inputs).is_err());
     }
+
+    #[test]
+    fn test_synthesize_contracts() {
+        let example = |x: i64, y: i64| {
+            let inputs = HashMap::from([("x".to_string(), Value::Int(x))]);
+            let output = HashMap::from([("y".to_string(), Value::Int(y))]);
+            (inputs, output)
+        };
+
+        let contracts = synthesize_contracts(&[example(0, 0), example(3, 3)]);
+        assert_eq!(contracts.preconditions, vec!["x >= 0"]);
+        assert_eq!(contracts.postconditions, vec!["y >= 0", "y == x"]);
+
+        let contracts = synthesize_contracts(&[example(-2, 1), example(4, 1)]);
+        assert!(contracts.preconditions.is_empty());
+        assert_eq!(contracts.postconditions, vec!["y > 0"]);
+
+        assert!(synthesize_contracts(&[]).postconditions.is_empty());
+    }
 }
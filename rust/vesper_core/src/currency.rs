@@ -0,0 +1,77 @@
+//! Exchange rate providers for the `currency_convert` operation
+
+use std::collections::HashMap;
+
+/// Source of currency exchange rates
+pub trait ExchangeRateProvider: Send + Sync {
+    /// Get the rate converting one unit of `from` into `to`
+    /// (ISO 4217 codes)
+    fn get_rate(&self, from: &str, to: &str) -> Result<f64, String>;
+}
+
+/// Fixed exchange rate table for testing and offline use
+///
+/// Missing pairs fall back to the inverse of the reverse pair, and
+/// converting a currency to itself always has rate 1.
+pub struct StaticRateProvider {
+    /// Rates keyed by `(from, to)`
+    rates: HashMap<(String, String), f64>,
+}
+
+impl StaticRateProvider {
+    /// Create an empty rate table
+    pub fn new() -> Self {
+        Self {
+            rates: HashMap::new(),
+        }
+    }
+
+    /// Add a rate for one unit of `from` in `to`
+    pub fn with_rate(mut self, from: &str, to: &str, rate: f64) -> Self {
+        self.rates
+            .insert((from.to_uppercase(), to.to_uppercase()), rate);
+        self
+    }
+}
+
+impl Default for StaticRateProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<HashMap<(String, String), f64>> for StaticRateProvider {
+    fn from(rates: HashMap<(String, String), f64>) -> Self {
+        Self { rates }
+    }
+}
+
+impl ExchangeRateProvider for StaticRateProvider {
+    fn get_rate(&self, from: &str, to: &str) -> Result<f64, String> {
+        if from == to {
+            return Ok(1.0);
+        }
+        if let Some(rate) = self.rates.get(&(from.to_string(), to.to_string())) {
+            return Ok(*rate);
+        }
+        match self.rates.get(&(to.to_string(), from.to_string())) {
+            Some(rate) if *rate != 0.0 => Ok(1.0 / rate),
+            _ => Err(format!("No exchange rate for {} to {}", from, to)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_rates() {
+        let provider = StaticRateProvider::new().with_rate("usd", "eur", 0.5);
+
+        assert_eq!(provider.get_rate("USD", "EUR"), Ok(0.5));
+        assert_eq!(provider.get_rate("EUR", "USD"), Ok(2.0));
+        assert_eq!(provider.get_rate("GBP", "GBP"), Ok(1.0));
+        assert!(provider.get_rate("USD", "JPY").is_err());
+    }
+}
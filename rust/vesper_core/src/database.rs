@@ -0,0 +1,17 @@
+//! Database backends for the `database_query` / `database_execute` operations
+
+use crate::types::Value;
+use std::collections::HashMap;
+
+/// Connection to a SQL database
+///
+/// Statements use the backend's native placeholder syntax and receive
+/// `params` positionally; implementations must bind them rather than
+/// interpolate them into the SQL text.
+pub trait DatabaseBackend: Send + Sync {
+    /// Run a query and return its rows keyed by column name
+    fn query(&self, sql: &str, params: Vec<Value>) -> Result<Vec<HashMap<String, Value>>, String>;
+
+    /// Run a statement and return the number of rows affected
+    fn execute(&self, sql: &str, params: Vec<Value>) -> Result<u64, String>;
+}
@@ -44,6 +44,18 @@ pub enum VesperError {
     #[error("Execution error: {0}")]
     ExecutionError(String),
 
+    /// Several errors collected from a single operation
+    #[error("Multiple errors: {}", .0.join("; "))]
+    MultipleErrors(Vec<String>),
+
+    /// Capability check rejected the operation
+    #[error("Security violation: {0}")]
+    SecurityViolation(String),
+
+    /// Rate limit exceeded
+    #[error("Rate limit exceeded: {0}")]
+    RateLimitExceeded(String),
+
     /// IO error
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -56,3 +68,86 @@ pub enum VesperError {
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
 }
+
+impl VesperError {
+    /// HTTP status code for reporting this error from an `HttpHandler` node
+    pub fn http_status(&self) -> u16 {
+        match self {
+            VesperError::MissingInput(_) => 400,
+            VesperError::SecurityViolation(_) => 403,
+            VesperError::TypeError { .. }
+            | VesperError::PreconditionFailed(_)
+            | VesperError::MultipleErrors(_) => 422,
+            VesperError::RateLimitExceeded(_) => 429,
+            _ => 500,
+        }
+    }
+
+    /// Stable machine-readable code for this error
+    pub fn code(&self) -> &'static str {
+        match self {
+            VesperError::ParseError(_) => "parse_error",
+            VesperError::ValidationError { .. } => "validation_error",
+            VesperError::PreconditionFailed(_) => "precondition_failed",
+            VesperError::PostconditionFailed(_) => "postcondition_failed",
+            VesperError::InvariantViolated(_) => "invariant_violated",
+            VesperError::TypeError { .. } => "type_error",
+            VesperError::UnknownOperation(_) => "unknown_operation",
+            VesperError::MissingInput(_) => "missing_input",
+            VesperError::ExecutionError(_) => "execution_error",
+            VesperError::MultipleErrors(_) => "multiple_errors",
+            VesperError::SecurityViolation(_) => "security_violation",
+            VesperError::RateLimitExceeded(_) => "rate_limit_exceeded",
+            VesperError::IoError(_) => "io_error",
+            VesperError::YamlError(_) => "yaml_error",
+            VesperError::JsonError(_) => "json_error",
+        }
+    }
+
+    /// JSON error body `{"error": code, "message": msg}` for HTTP responses
+    pub fn to_json_error(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": self.code(),
+            "message": self.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_status() {
+        let cases = [
+            (VesperError::MissingInput("id".into()), 400),
+            (
+                VesperError::TypeError {
+                    expected: "int".into(),
+                    actual: "string".into(),
+                },
+                422,
+            ),
+            (VesperError::PreconditionFailed("x > 0".into()), 422),
+            (VesperError::PostconditionFailed("y > 0".into()), 500),
+            (VesperError::ExecutionError("boom".into()), 500),
+            (VesperError::SecurityViolation("net.write".into()), 403),
+            (VesperError::RateLimitExceeded("10/min".into()), 429),
+        ];
+        for (error, status) in cases {
+            assert_eq!(error.http_status(), status, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_to_json_error() {
+        let error = VesperError::MissingInput("amount".into());
+        assert_eq!(
+            error.to_json_error(),
+            serde_json::json!({
+                "error": "missing_input",
+                "message": "Missing required input: amount",
+            })
+        );
+    }
+}
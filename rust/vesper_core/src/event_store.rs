@@ -0,0 +1,92 @@
+//! Append-only event logs for the event sourcing operations
+
+use crate::types::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Event recorded against an entity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredEvent {
+    /// Entity the event belongs to
+    pub entity_id: String,
+    /// Kind of change, e.g. `deposited`
+    pub event_type: String,
+    /// Details of the change
+    pub payload: Value,
+    /// RFC 3339 time the event was appended
+    pub timestamp: String,
+}
+
+impl StoredEvent {
+    /// Event as an object with one field per attribute
+    pub fn to_value(&self) -> Value {
+        Value::Object(HashMap::from([
+            (
+                "entity_id".to_string(),
+                Value::from(self.entity_id.as_str()),
+            ),
+            (
+                "event_type".to_string(),
+                Value::from(self.event_type.as_str()),
+            ),
+            ("payload".to_string(), self.payload.clone()),
+            (
+                "timestamp".to_string(),
+                Value::from(self.timestamp.as_str()),
+            ),
+        ]))
+    }
+}
+
+/// Durable log of events per entity
+pub trait EventStore: Send + Sync {
+    /// Append an event to the end of its entity's log
+    fn append(&self, event: StoredEvent) -> Result<(), String>;
+
+    /// Every event of an entity, oldest first
+    fn events(&self, entity_id: &str) -> Result<Vec<StoredEvent>, String>;
+}
+
+/// Process-local event store, for tests and single instances
+pub struct InMemoryEventStore {
+    /// Event logs by entity
+    logs: Mutex<HashMap<String, Vec<StoredEvent>>>,
+}
+
+impl InMemoryEventStore {
+    /// Create a store with no events
+    pub fn new() -> Self {
+        Self {
+            logs: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryEventStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventStore for InMemoryEventStore {
+    fn append(&self, event: StoredEvent) -> Result<(), String> {
+        self.logs
+            .lock()
+            .unwrap()
+            .entry(event.entity_id.clone())
+            .or_default()
+            .push(event);
+        Ok(())
+    }
+
+    fn events(&self, entity_id: &str) -> Result<Vec<StoredEvent>, String> {
+        Ok(self
+            .logs
+            .lock()
+            .unwrap()
+            .get(entity_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,209 @@
+//! Event subscriptions for `EventHandler` nodes
+//!
+//! Nodes subscribe to event types on an `EventBroker`. Published events are
+//! queued on a channel and an `EventHandlerExecutor` drains it on a
+//! background thread, running every subscribed node with the event
+//! payload as its inputs.
+
+use crate::error::Result;
+use crate::executor::{ExecutionResult, SemanticExecutor};
+use crate::types::Value;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Event waiting to be handled
+struct Event {
+    event_type: String,
+    payload: Value,
+}
+
+/// Node subscribed to an event type
+#[derive(Clone)]
+struct Subscription {
+    node_id: String,
+    executor: Arc<SemanticExecutor>,
+}
+
+/// Outcome of one subscribed node handling one event
+pub struct EventOutcome {
+    /// Type of the handled event
+    pub event_type: String,
+    /// Node that handled it
+    pub node_id: String,
+    /// Result of executing the node
+    pub result: Result<ExecutionResult>,
+}
+
+/// Routes published events to subscribed nodes
+pub struct EventBroker {
+    /// Subscriptions by event type
+    subscriptions: Mutex<HashMap<String, Vec<Subscription>>>,
+    /// Queue of published events; `None` once closed
+    sender: Mutex<Option<Sender<Event>>>,
+    /// Receiving end, taken by the listener
+    receiver: Mutex<Option<Receiver<Event>>>,
+}
+
+impl EventBroker {
+    /// Create a broker with no subscriptions
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            subscriptions: Mutex::new(HashMap::new()),
+            sender: Mutex::new(Some(sender)),
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+
+    /// Run `node_id` on `executor` whenever `event_type` is published
+    pub fn subscribe(&self, node_id: &str, event_type: &str, executor: Arc<SemanticExecutor>) {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .entry(event_type.to_string())
+            .or_default()
+            .push(Subscription {
+                node_id: node_id.to_string(),
+                executor,
+            });
+    }
+
+    /// Publish an event to its subscribers
+    ///
+    /// The event is queued and handled asynchronously by the listener.
+    /// Events published after `close` are dropped.
+    pub fn publish(&self, event_type: &str, payload: Value) {
+        if let Some(sender) = self.sender.lock().unwrap().as_ref() {
+            let _ = sender.send(Event {
+                event_type: event_type.to_string(),
+                payload,
+            });
+        }
+    }
+
+    /// Stop accepting events; the listener finishes once the queue drains
+    pub fn close(&self) {
+        self.sender.lock().unwrap().take();
+    }
+
+    fn subscribers(&self, event_type: &str) -> Vec<Subscription> {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .get(event_type)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl Default for EventBroker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Background listener executing `EventHandler` nodes for a broker
+pub struct EventHandlerExecutor {
+    /// Outcomes of handled events, in handling order
+    outcomes: Receiver<EventOutcome>,
+    /// Listener thread
+    handle: JoinHandle<()>,
+}
+
+impl EventHandlerExecutor {
+    /// Start handling the broker's events on a background thread
+    ///
+    /// Object payloads are passed to nodes field by field; any other
+    /// payload is passed as the `payload` input. Only one listener can
+    /// take a broker's events, so this returns `None` if one already has.
+    pub fn listen(broker: Arc<EventBroker>) -> Option<Self> {
+        let events = broker.receiver.lock().unwrap().take()?;
+        let (sender, outcomes) = mpsc::channel();
+
+        let handle = std::thread::spawn(move || {
+            for event in events {
+                let inputs = match &event.payload {
+                    Value::Object(fields) => fields.clone(),
+                    other => HashMap::from([("payload".to_string(), other.clone())]),
+                };
+                for subscription in broker.subscribers(&event.event_type) {
+                    tracing::debug!(
+                        "Handling {} with {}",
+                        event.event_type,
+                        subscription.node_id
+                    );
+                    let result = subscription
+                        .executor
+                        .execute(&subscription.node_id, inputs.clone());
+                    let _ = sender.send(EventOutcome {
+                        event_type: event.event_type.clone(),
+                        node_id: subscription.node_id,
+                        result,
+                    });
+                }
+            }
+        });
+
+        Some(Self { outcomes, handle })
+    }
+
+    /// Wait up to `timeout` for the next handled event
+    pub fn next_outcome(&self, timeout: Duration) -> Option<EventOutcome> {
+        self.outcomes.recv_timeout(timeout).ok()
+    }
+
+    /// Wait for the listener to finish after the broker is closed
+    pub fn join(self) -> Vec<EventOutcome> {
+        let _ = self.handle.join();
+        self.outcomes.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    #[test]
+    fn test_published_event_runs_handler() {
+        let yaml = r#"
+node_id: welcome_v1
+type: event_handler
+intent: greet newly registered users
+
+inputs:
+  name:
+    type: string
+
+flow:
+  - step: greet
+    operation: string_template
+    template: "Welcome, {name}!"
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let broker = Arc::new(EventBroker::new());
+        broker.subscribe("welcome_v1", "user.registered", Arc::new(executor));
+        let listener = EventHandlerExecutor::listen(broker.clone()).unwrap();
+        assert!(EventHandlerExecutor::listen(broker.clone()).is_none());
+
+        let payload = HashMap::from([("name".to_string(), Value::from("Ada"))]);
+        broker.publish("user.deleted", Value::Object(HashMap::new()));
+        broker.publish("user.registered", Value::Object(payload));
+
+        let outcome = listener.next_outcome(Duration::from_secs(5)).unwrap();
+        assert_eq!(outcome.node_id, "welcome_v1");
+        assert_eq!(outcome.event_type, "user.registered");
+        assert_eq!(
+            outcome.result.unwrap().data,
+            Some(Value::from("Welcome, Ada!"))
+        );
+
+        broker.close();
+        assert!(listener.join().is_empty());
+    }
+}
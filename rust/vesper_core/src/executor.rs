@@ -1,20 +1,333 @@
 //! Semantic executor for Vesper nodes
 
+mod approval;
+mod arena;
+mod benchmark;
+mod caching;
+mod coalescing;
+mod compensation;
+mod cqrs;
+mod crypto;
+mod currency;
+mod database;
+mod diff;
+mod distribution;
+mod event_sourcing;
+mod experiments;
+mod explain;
+mod expressions;
+mod files;
+mod flags;
+mod fuzzing;
+mod graphql;
+mod grpc;
+mod idempotency;
+mod jsonpath;
+mod jwt;
+mod kubernetes;
+mod linalg;
+mod locks;
+mod logging;
+mod messaging;
+mod migration;
+mod models;
+mod network;
+mod notify;
+mod oauth2;
+mod otel;
+mod pagination;
+mod parallel;
+mod phone;
+mod prometheus;
+mod reflection;
+mod resilience;
+mod responses;
+mod s3;
+mod saga;
+mod schema;
+mod secrets;
+mod state_machine;
+mod statistics;
+mod synthesis;
+mod tabular;
+mod templating;
+mod try_catch;
+mod versioning;
+mod workflow;
+mod xml;
+
+use crate::cache::{CacheBackend, CacheStats};
+use crate::checkpoints::CheckpointStore;
+use crate::contracts::ContractValidator;
+use crate::currency::ExchangeRateProvider;
+use crate::database::DatabaseBackend;
 use crate::error::{Result, VesperError};
-use crate::types::{FlowStep, Value, VesperNode};
-use std::collections::HashMap;
+use crate::event_store::EventStore;
+use crate::events::EventBroker;
+use crate::experiments::ResultComparator;
+use crate::feature_flags::FeatureFlagStore;
+use crate::idempotency::IdempotencyStore;
+use crate::locks::DistributedLockBackend;
+use crate::models::{ModelProvider, StructuredExtractor};
+use crate::queue::MessageQueueBackend;
+use crate::remote::RemoteExecutorClient;
+use crate::secrets::{SecretStore, SecretsManager};
+use crate::types::{FlowStep, IntoString, Value, VesperNode};
+use crate::workflow_state::WorkflowStateBackend;
+#[cfg(feature = "arena")]
+pub use arena::ExecutionArena;
+#[cfg(feature = "arena")]
+use arena::Variables;
+use coalescing::InFlightExecutions;
+pub use explain::{ConditionExplanation, FlowExplanation, StepExplanation};
+use expressions::ExpressionCache;
+use resilience::{Bulkheads, CircuitBreakers, RateLimiters};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use templating::TemplateCache;
+
+/// Operations a flow step can name, in dispatch order; some only run with
+/// their feature enabled
+pub const BUILTIN_OPERATIONS: &[&str] = &[
+    "validation",
+    "string_template",
+    "arithmetic",
+    "return",
+    "conditional",
+    "statistics",
+    "dot_product",
+    "matrix_multiply",
+    "jsonpath",
+    "schema_validate",
+    "lint_step",
+    "assert_schema",
+    "diff_values",
+    "benchmark_step",
+    "fuzzer",
+    "contract_synthesis",
+    "migration_guide",
+    "node_metadata",
+    "search_nodes",
+    "xml_parse",
+    "xml_stringify",
+    "csv_parse",
+    "csv_stringify",
+    "template_render",
+    "handlebars_render",
+    "notify",
+    "cache_get",
+    "cache_set",
+    "feature_flag",
+    "version_compare",
+    "version_parse",
+    "ip_validate",
+    "ip_parse",
+    "ip_in_cidr",
+    "phone_validate",
+    "phone_format",
+    "currency_convert",
+    "encrypt",
+    "decrypt",
+    "sign",
+    "verify",
+    "jwt_decode",
+    "jwt_verify",
+    "oauth2_token_exchange",
+    "http_response_builder",
+    "http_json_response",
+    "graphql_query",
+    "grpc_call",
+    "kubernetes_api",
+    "s3_get",
+    "s3_put",
+    "secrets_manager_get",
+    "prometheus_push",
+    "structured_log",
+    "opentelemetry_baggage_get",
+    "opentelemetry_baggage_set",
+    "trace_context_inject",
+    "trace_span_start",
+    "trace_span_end",
+    "paginate",
+    "parallel",
+    "race",
+    "saga",
+    "compensating_transaction",
+    "approval_gate",
+    "scatter_gather",
+    "workflow_orchestrator",
+    "model_invoke",
+    "text_embed",
+    "vector_similarity",
+    "structured_extract",
+    "try_catch",
+    "with_timeout",
+    "circuit_breaker_step",
+    "rate_limit_step",
+    "bulkhead",
+    "distributed_lock",
+    "idempotency_key",
+    "event_sourcing_append",
+    "event_sourcing_replay",
+    "cqrs_command",
+    "cqrs_query",
+    "load_balance",
+    "a_b_test",
+    "shadow_mode",
+    "message_queue_publish",
+    "message_queue_consume",
+    "database_query",
+    "database_execute",
+    "read_file",
+    "write_file",
+    "list_files",
+];
+
+/// Variable holding the flow result during an output transform
+const OUTPUT_VARIABLE: &str = "_output";
 
 /// Result of executing a Vesper node
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
     /// Whether execution succeeded
     pub success: bool,
+    /// Whether the flow ran to completion or is paused
+    pub status: ExecutionStatus,
     /// Output data on success
     pub data: Option<Value>,
     /// Error information on failure
     pub error: Option<ExecutionError>,
     /// Execution duration in milliseconds
     pub duration_ms: f64,
+    /// Identifier correlating this execution's spans and events
+    pub execution_id: String,
+    /// Whether the data was served from the node result cache
+    pub cache_hit: bool,
+    /// Non-fatal problems detected during execution
+    pub warnings: Vec<ExecutionWarning>,
+    /// Growth of the process's allocated memory during the execution in
+    /// kilobytes, with `memory-tracking`
+    pub memory_delta_kb: Option<u64>,
+}
+
+impl ExecutionResult {
+    /// Whether the execution stayed within every latency threshold of `node`
+    pub fn met_latency_sla(&self, node: &VesperNode) -> bool {
+        latency_warnings(node, self.duration_ms).is_empty()
+    }
+}
+
+/// Stage an execution reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    /// The flow ran to completion, or failed
+    Completed,
+    /// The flow is paused at an `approval_gate` until `resume` is called
+    PendingApproval,
+    /// The flow was stopped by a rejected `approval_gate`
+    Rejected,
+}
+
+impl ExecutionStatus {
+    /// Status name, e.g. `pending_approval`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionStatus::Completed => "completed",
+            ExecutionStatus::PendingApproval => "pending_approval",
+            ExecutionStatus::Rejected => "rejected",
+        }
+    }
+}
+
+impl std::fmt::Display for ExecutionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Non-fatal problem detected during an execution
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionWarning {
+    /// The execution took longer than a `performance` threshold
+    LatencyThresholdExceeded {
+        /// Name of the exceeded threshold, e.g. `p99_latency_ms`
+        threshold: String,
+        /// Configured threshold in milliseconds
+        limit_ms: u64,
+        /// Measured duration in milliseconds
+        duration_ms: f64,
+        /// `"critical"` for `max_latency_ms`, `"warning"` otherwise
+        severity: String,
+    },
+    /// The execution allocated more than `performance.memory_limit_mb`
+    MemoryLimitExceeded {
+        /// Configured limit in megabytes
+        limit_mb: u64,
+        /// Allocated kilobytes
+        delta_kb: u64,
+    },
+    /// A `shadow_mode` candidate returned a different result than the
+    /// primary node
+    ShadowDivergence {
+        /// Node whose result was served
+        primary: String,
+        /// Candidate run in its shadow
+        shadow: String,
+        /// Paths where the results differ, as reported by `diff_values`
+        differences: Vec<Value>,
+    },
+}
+
+/// Latency thresholds of `node` exceeded by an execution of `duration_ms`
+fn latency_warnings(node: &VesperNode, duration_ms: f64) -> Vec<ExecutionWarning> {
+    let Some(performance) = &node.performance else {
+        return Vec::new();
+    };
+    [
+        (
+            "expected_latency_ms",
+            performance.expected_latency_ms,
+            "warning",
+        ),
+        ("p99_latency_ms", performance.p99_latency_ms, "warning"),
+        ("max_latency_ms", performance.max_latency_ms, "critical"),
+    ]
+    .into_iter()
+    .filter_map(|(threshold, limit_ms, severity)| {
+        limit_ms
+            .filter(|&limit_ms| duration_ms > limit_ms as f64)
+            .map(|limit_ms| ExecutionWarning::LatencyThresholdExceeded {
+                threshold: threshold.to_string(),
+                limit_ms,
+                duration_ms,
+                severity: severity.to_string(),
+            })
+    })
+    .collect()
+}
+
+/// Bytes currently allocated by the whole process
+///
+/// jemalloc only refreshes its statistics when the epoch is advanced.
+#[cfg(feature = "memory-tracking")]
+fn allocated_bytes() -> Option<u64> {
+    tikv_jemalloc_ctl::epoch::advance().ok()?;
+    tikv_jemalloc_ctl::stats::allocated::read()
+        .ok()
+        .map(|allocated| allocated as u64)
+}
+
+/// Bytes currently allocated by the whole process
+#[cfg(not(feature = "memory-tracking"))]
+fn allocated_bytes() -> Option<u64> {
+    None
+}
+
+/// Options for a single execution
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionOptions {
+    /// Existing trace ID to use as the execution ID, for distributed tracing
+    pub trace_id: Option<String>,
 }
 
 /// Error information
@@ -27,22 +340,93 @@ pub struct ExecutionError {
 }
 
 /// Execution context containing variables
+#[derive(Clone)]
 pub struct ExecutionContext {
     /// Variable bindings
-    variables: HashMap<String, Value>,
+    variables: Variables,
     /// Input values
     inputs: HashMap<String, Value>,
+    /// Capabilities granted to the executing node
+    capabilities: Vec<String>,
+    /// Variables holding secrets, masked when the context is printed
+    secret_variables: HashSet<String>,
+    /// Node being executed, empty for standalone contexts
+    node_id: String,
+    /// Unique identifier of this execution
+    execution_id: String,
+    /// Non-fatal problems recorded by operations
+    warnings: Vec<ExecutionWarning>,
+    /// Approval gate the flow stopped at, if any
+    suspension: Option<approval::Suspension>,
+    /// Compensations to run if the flow fails, latest last
+    compensations: Vec<compensation::Compensation>,
+    /// Cancelled when nothing waits for the steps running in this context
+    /// anymore, such as the losing branches of a `race`
+    cancellation: tokio_util::sync::CancellationToken,
+    /// OpenTelemetry context current when the execution started, with
+    /// any baggage added by the flow
+    #[cfg(feature = "otel")]
+    otel_context: opentelemetry::Context,
+    /// Spans started by `trace_span_start` steps and not yet ended, by
+    /// span ID
+    #[cfg(feature = "otel")]
+    open_spans: HashMap<String, otel::OpenSpan>,
 }
 
 impl ExecutionContext {
     /// Create a new context with inputs
     pub fn new(inputs: HashMap<String, Value>) -> Self {
         Self {
-            variables: HashMap::new(),
+            variables: Variables::default(),
             inputs,
+            capabilities: Vec::new(),
+            secret_variables: HashSet::new(),
+            node_id: String::new(),
+            execution_id: uuid::Uuid::new_v4().to_string(),
+            warnings: Vec::new(),
+            suspension: None,
+            compensations: Vec::new(),
+            cancellation: tokio_util::sync::CancellationToken::new(),
+            #[cfg(feature = "otel")]
+            otel_context: opentelemetry::Context::current(),
+            #[cfg(feature = "otel")]
+            open_spans: HashMap::new(),
         }
     }
 
+    /// Record the node this context executes
+    pub fn with_node_id(mut self, node_id: &str) -> Self {
+        self.node_id = node_id.to_string();
+        self
+    }
+
+    /// Use an existing identifier, such as a propagated trace ID
+    pub fn with_execution_id(mut self, execution_id: &str) -> Self {
+        self.execution_id = execution_id.to_string();
+        self
+    }
+
+    /// Node being executed
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Unique identifier of this execution
+    pub fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
+
+    /// Grant capabilities to operations running in this context
+    pub fn with_capabilities(mut self, capabilities: Vec<String>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Check whether a capability has been granted
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
     /// Get a variable or input value
     pub fn get(&self, name: &str) -> Option<&Value> {
         self.variables.get(name).or_else(|| self.inputs.get(name))
@@ -50,32 +434,387 @@ impl ExecutionContext {
 
     /// Set a variable
     pub fn set(&mut self, name: String, value: Value) {
+        #[cfg(feature = "arena")]
+        let name = arena::VariableName::new_in(&name, self.variables.allocator());
         self.variables.insert(name, value);
     }
 
+    /// Mark a variable as holding a secret so it is never printed
+    pub fn mark_secret(&mut self, name: &str) {
+        self.secret_variables.insert(name.to_string());
+    }
+
+    /// Check whether a variable holds a secret
+    pub fn is_secret(&self, name: &str) -> bool {
+        self.secret_variables.contains(name)
+    }
+
+    /// Record a non-fatal problem, reported in the `ExecutionResult`
+    pub fn add_warning(&mut self, warning: ExecutionWarning) {
+        self.warnings.push(warning);
+    }
+
+    /// Non-fatal problems recorded so far
+    pub fn warnings(&self) -> &[ExecutionWarning] {
+        &self.warnings
+    }
+
     /// Get an input value
     pub fn get_input(&self, name: &str) -> Option<&Value> {
         self.inputs.get(name)
     }
+
+    /// Copy of this context for a step running alongside others, starting
+    /// without warnings or compensations of its own
+    #[cfg(feature = "parallel")]
+    fn fork(&self) -> ExecutionContext {
+        let mut branch = self.clone();
+        branch.warnings.clear();
+        branch.compensations.clear();
+        branch
+    }
+
+    /// Take over the variables a fork of this context set or changed, and
+    /// the warnings and compensations it recorded
+    #[cfg(feature = "parallel")]
+    fn merge(&mut self, branch: ExecutionContext) {
+        for (name, value) in branch.variables {
+            if self.variables.get(&name) != Some(&value) {
+                self.variables.insert(name, value);
+            }
+        }
+        self.secret_variables.extend(branch.secret_variables);
+        self.warnings.extend(branch.warnings);
+        self.compensations.extend(branch.compensations);
+    }
+
+    /// All visible bindings, with variables shadowing inputs of the same name
+    pub fn bindings(&self) -> HashMap<String, Value> {
+        let mut bindings = self.inputs.clone();
+        bindings.extend(
+            self.variables
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone())),
+        );
+        bindings
+    }
 }
 
+impl std::fmt::Debug for ExecutionContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let masked: HashMap<String, Value> = self
+            .variables
+            .iter()
+            .map(|(k, v)| {
+                if self.is_secret(k) {
+                    (k.to_string(), Value::from("***"))
+                } else {
+                    (k.to_string(), v.clone())
+                }
+            })
+            .collect();
+        f.debug_struct("ExecutionContext")
+            .field("variables", &masked)
+            .field("inputs", &self.inputs)
+            .field("capabilities", &self.capabilities)
+            .field("node_id", &self.node_id)
+            .field("execution_id", &self.execution_id)
+            .field("warnings", &self.warnings)
+            .finish()
+    }
+}
+
+/// Variable bindings of an execution context
+#[cfg(not(feature = "arena"))]
+type Variables = HashMap<String, Value>;
+
 /// Semantic executor for Vesper nodes
 pub struct SemanticExecutor {
+    /// Configuration, loaded nodes and state kept across executions,
+    /// shared with steps left running by `race` and `with_timeout`
+    state: Arc<ExecutorState>,
+}
+
+/// Everything an executor knows, behind the `Arc` it shares with steps it
+/// stopped waiting for
+///
+/// Cloning copies the configuration and loaded nodes but shares the
+/// caches and the state kept across executions.
+#[derive(Clone)]
+struct ExecutorState {
     /// Loaded nodes
     nodes: HashMap<String, VesperNode>,
+    /// Backend for the cache operations
+    cache: Option<Arc<dyn CacheBackend>>,
+    /// Store for the `feature_flag` operation
+    feature_flags: Option<Arc<dyn FeatureFlagStore>>,
+    /// Rate source for the `currency_convert` operation
+    exchange_rates: Option<Arc<dyn ExchangeRateProvider>>,
+    /// Backend for the `database_*` operations
+    database: Option<Arc<dyn DatabaseBackend>>,
+    /// Backend for the `message_queue_*` operations
+    message_queue: Option<Arc<dyn MessageQueueBackend>>,
+    /// Store of event logs for the `event_sourcing_*` operations
+    event_store: Option<Arc<dyn EventStore>>,
+    /// Store of results for the `idempotency_key` operation
+    idempotency: Option<Arc<dyn IdempotencyStore>>,
+    /// Store of executions paused by `approval_gate` steps
+    checkpoints: Option<Arc<dyn CheckpointStore>>,
+    /// Backend for the `distributed_lock` operation
+    lock_backend: Option<Arc<dyn DistributedLockBackend>>,
+    /// Saved states of `state_machine` workflows
+    workflow_state: Option<Arc<dyn WorkflowStateBackend>>,
+    /// Root directory confining the file operations, if any
+    base_path: Option<std::path::PathBuf>,
+    /// Credentials for operations that authenticate to external services
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    secrets: Option<Arc<dyn SecretStore>>,
+    /// Manager for the `secrets_manager_get` operation
+    secrets_manager: Option<Arc<dyn SecretsManager>>,
+    /// Providers for the model operations, by name
+    model_providers: HashMap<String, Arc<dyn ModelProvider>>,
+    /// Extractor for the `structured_extract` operation
+    extractor: Option<Arc<dyn StructuredExtractor>>,
+    /// Parsed arithmetic expressions
+    expressions: Arc<ExpressionCache>,
+    /// Compiled `string_template` templates
+    templates: TemplateCache,
+    /// Circuit breaker state of `circuit_breaker_step` steps
+    circuit_breakers: Arc<CircuitBreakers>,
+    /// Token buckets of `rate_limit_step` steps
+    rate_limiters: Arc<RateLimiters>,
+    /// Concurrency limits of `bulkhead` steps
+    bulkheads: Arc<Bulkheads>,
+    /// Executors for the `load_balance` operation, by name
+    remote_executors: HashMap<String, Arc<dyn RemoteExecutorClient>>,
+    /// Next round-robin position of each `load_balance` step
+    balancer_cursors: Arc<parking_lot::Mutex<HashMap<String, usize>>>,
+    /// Broker receiving events published by operations
+    event_broker: Option<Arc<EventBroker>>,
+    /// Receiver of `a_b_test` comparisons
+    result_comparator: Option<Arc<dyn ResultComparator>>,
+    /// Whether identical concurrent executions share one run
+    request_coalescing: bool,
+    /// Executions other identical requests can wait for
+    in_flight: Arc<InFlightExecutions>,
+    /// Arenas for the variables of executions
+    #[cfg(feature = "arena")]
+    arenas: Arc<arena::ArenaPool>,
+    /// Pre-compiled Handlebars templates, partials and helpers
+    #[cfg(feature = "handlebars")]
+    handlebars: handlebars::Handlebars<'static>,
+    /// Shared HTTP client, created on first use
+    #[cfg(feature = "http")]
+    http_client: std::sync::OnceLock<reqwest::blocking::Client>,
 }
 
 impl SemanticExecutor {
     /// Create a new executor
     pub fn new() -> Self {
-        Self {
+        let state = ExecutorState {
             nodes: HashMap::new(),
+            cache: None,
+            feature_flags: None,
+            exchange_rates: None,
+            database: None,
+            message_queue: None,
+            event_store: None,
+            idempotency: None,
+            checkpoints: None,
+            lock_backend: None,
+            workflow_state: None,
+            base_path: None,
+            secrets: None,
+            secrets_manager: None,
+            model_providers: HashMap::new(),
+            extractor: None,
+            expressions: Default::default(),
+            templates: TemplateCache::default(),
+            circuit_breakers: Default::default(),
+            rate_limiters: Default::default(),
+            bulkheads: Default::default(),
+            remote_executors: HashMap::new(),
+            balancer_cursors: Default::default(),
+            event_broker: None,
+            result_comparator: None,
+            request_coalescing: false,
+            in_flight: Default::default(),
+            #[cfg(feature = "arena")]
+            arenas: Default::default(),
+            #[cfg(feature = "handlebars")]
+            handlebars: handlebars::Handlebars::new(),
+            #[cfg(feature = "http")]
+            http_client: std::sync::OnceLock::new(),
+        };
+        Self {
+            state: Arc::new(state),
+        }
+    }
+
+    /// Another handle on this executor, for a step that may outlive the
+    /// one that started it
+    fn share(&self) -> SemanticExecutor {
+        SemanticExecutor {
+            state: Arc::clone(&self.state),
         }
     }
 
+    /// State to configure
+    ///
+    /// Steps left running by `race` and `with_timeout` keep the state they
+    /// started with, so while any runs the configuration is copied rather
+    /// than waiting for them; the copy still shares caches and breakers.
+    fn state_mut(&mut self) -> &mut ExecutorState {
+        Arc::make_mut(&mut self.state)
+    }
+
+    /// Install a cache backend for the `cache_get` / `cache_set` operations
+    pub fn with_cache(mut self, cache: Arc<dyn CacheBackend>) -> Self {
+        self.state_mut().cache = Some(cache);
+        self
+    }
+
+    /// Install a feature flag store for the `feature_flag` operation
+    pub fn with_feature_flags(mut self, store: Arc<dyn FeatureFlagStore>) -> Self {
+        self.state_mut().feature_flags = Some(store);
+        self
+    }
+
+    /// Install an exchange rate provider for the `currency_convert` operation
+    pub fn with_exchange_rates(mut self, provider: Arc<dyn ExchangeRateProvider>) -> Self {
+        self.state_mut().exchange_rates = Some(provider);
+        self
+    }
+
+    /// Install a database backend for the `database_*` operations
+    pub fn with_database(mut self, backend: Arc<dyn DatabaseBackend>) -> Self {
+        self.state_mut().database = Some(backend);
+        self
+    }
+
+    /// Install a message queue backend for the `message_queue_*` operations
+    pub fn with_message_queue(mut self, backend: Arc<dyn MessageQueueBackend>) -> Self {
+        self.state_mut().message_queue = Some(backend);
+        self
+    }
+
+    /// Install an event store for the `event_sourcing_*` operations
+    pub fn with_event_store(mut self, store: Arc<dyn EventStore>) -> Self {
+        self.state_mut().event_store = Some(store);
+        self
+    }
+
+    /// Install a result store for the `idempotency_key` operation
+    pub fn with_idempotency_store(mut self, store: Arc<dyn IdempotencyStore>) -> Self {
+        self.state_mut().idempotency = Some(store);
+        self
+    }
+
+    /// Install a checkpoint store for the `approval_gate` operation
+    pub fn with_checkpoint_store(mut self, store: Arc<dyn CheckpointStore>) -> Self {
+        self.state_mut().checkpoints = Some(store);
+        self
+    }
+
+    /// Install a lock backend for the `distributed_lock` operation
+    pub fn with_lock_backend(mut self, backend: Arc<dyn DistributedLockBackend>) -> Self {
+        self.state_mut().lock_backend = Some(backend);
+        self
+    }
+
+    /// Persist the state of `state_machine` nodes between executions
+    ///
+    /// Executions of a state machine must then have a `workflow_id` input.
+    /// They start from the state and variables the workflow was last saved
+    /// with, and save the `state` variable and the other variables once
+    /// they complete.
+    pub fn with_workflow_state(mut self, backend: Arc<dyn WorkflowStateBackend>) -> Self {
+        self.state_mut().workflow_state = Some(backend);
+        self
+    }
+
+    /// Confine the file operations to paths within `base_path`
+    pub fn with_base_path(mut self, base_path: impl Into<std::path::PathBuf>) -> Self {
+        self.state_mut().base_path = Some(base_path.into());
+        self
+    }
+
+    /// Install a secret store for operations that need credentials
+    pub fn with_secrets(mut self, store: Arc<dyn SecretStore>) -> Self {
+        self.state_mut().secrets = Some(store);
+        self
+    }
+
+    /// Install a secrets manager for the `secrets_manager_get` operation
+    pub fn with_secrets_manager(mut self, manager: Arc<dyn SecretsManager>) -> Self {
+        self.state_mut().secrets_manager = Some(manager);
+        self
+    }
+
+    /// Register a model provider, selected by `parameters["provider"]`
+    pub fn with_model_provider(mut self, name: &str, provider: Arc<dyn ModelProvider>) -> Self {
+        self.state_mut()
+            .model_providers
+            .insert(name.to_string(), provider);
+        self
+    }
+
+    /// Install an extractor for the `structured_extract` operation
+    pub fn with_extractor(mut self, extractor: Arc<dyn StructuredExtractor>) -> Self {
+        self.state_mut().extractor = Some(extractor);
+        self
+    }
+
+    /// Register executors for the `load_balance` operation, by name
+    pub fn with_remote_executors(
+        mut self,
+        clients: HashMap<String, Box<dyn RemoteExecutorClient>>,
+    ) -> Self {
+        self.state_mut().remote_executors.extend(
+            clients
+                .into_iter()
+                .map(|(name, client)| (name, Arc::from(client))),
+        );
+        self
+    }
+
+    /// Install a broker for events published by operations, e.g. by
+    /// `shadow_mode`
+    pub fn with_event_broker(mut self, broker: Arc<EventBroker>) -> Self {
+        self.state_mut().event_broker = Some(broker);
+        self
+    }
+
+    /// Install a comparator receiving the results of `a_b_test` steps
+    pub fn with_result_comparator(mut self, comparator: Arc<dyn ResultComparator>) -> Self {
+        self.state_mut().result_comparator = Some(comparator);
+        self
+    }
+
+    /// Coalesce concurrent executions of a node with identical inputs
+    ///
+    /// While an execution is in progress, identical requests wait for it
+    /// and receive a copy of its result instead of running the flow again.
+    pub fn with_request_coalescing(mut self, enabled: bool) -> Self {
+        self.state_mut().request_coalescing = enabled;
+        self
+    }
+
     /// Register a node with the executor
     pub fn register(&mut self, node: VesperNode) {
-        self.nodes.insert(node.node_id.clone(), node);
+        #[cfg(feature = "handlebars")]
+        self.compile_handlebars_templates(&node);
+        self.state_mut().nodes.insert(node.node_id.clone(), node);
+    }
+
+    /// Get a registered node
+    pub fn node(&self, node_id: &str) -> Option<&VesperNode> {
+        self.state.nodes.get(node_id)
+    }
+
+    /// Hit and miss counts of the parsed expression cache
+    pub fn expression_cache_stats(&self) -> CacheStats {
+        self.state.expressions.stats()
     }
 
     /// Execute a node with given inputs
@@ -83,17 +822,76 @@ impl SemanticExecutor {
         &self,
         node_id: &str,
         inputs: HashMap<String, Value>,
+    ) -> Result<ExecutionResult> {
+        self.execute_with_options(node_id, inputs, ExecutionOptions::default())
+    }
+
+    /// Execute a node with given inputs and options
+    ///
+    /// The execution ID is the provided trace ID, or a fresh UUID, and is
+    /// recorded on every span the executor opens.
+    pub fn execute_with_options(
+        &self,
+        node_id: &str,
+        inputs: HashMap<String, Value>,
+        options: ExecutionOptions,
+    ) -> Result<ExecutionResult> {
+        if self.state.request_coalescing {
+            self.execute_coalesced(node_id, inputs, options)
+        } else {
+            self.execute_once(node_id, inputs, options)
+        }
+    }
+
+    /// Run a single execution of a node
+    fn execute_once(
+        &self,
+        node_id: &str,
+        inputs: HashMap<String, Value>,
+        options: ExecutionOptions,
     ) -> Result<ExecutionResult> {
         let start = std::time::Instant::now();
+        let allocated_before = allocated_bytes();
+        let execution_id = options
+            .trace_id
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let _span = tracing::info_span!("execute", node_id, execution_id = %execution_id).entered();
 
-        let node = self
-            .nodes
-            .get(node_id)
-            .ok_or_else(|| VesperError::ExecutionError(format!("Node not found: {}", node_id)))?;
+        let node =
+            self.state.nodes.get(node_id).ok_or_else(|| {
+                VesperError::ExecutionError(format!("Node not found: {}", node_id))
+            })?;
 
         // Validate inputs
         self.validate_inputs(node, &inputs)?;
 
+        // Serve cached results
+        let cache = node.cache.as_ref().filter(|spec| spec.enabled).map(|spec| {
+            (
+                caching::node_cache_key(node_id, spec, &inputs),
+                spec.ttl_seconds,
+            )
+        });
+        if let Some((key, _)) = &cache {
+            if let Some(data) = self.cache_backend()?.get(key) {
+                tracing::debug!("Node cache hit: {}", node_id);
+                let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+                let mut warnings = self.check_latency(node, duration_ms);
+                let memory_delta_kb = self.check_memory(node, allocated_before, &mut warnings);
+                return Ok(ExecutionResult {
+                    success: true,
+                    status: ExecutionStatus::Completed,
+                    data: Some(data),
+                    error: None,
+                    duration_ms,
+                    execution_id,
+                    cache_hit: true,
+                    warnings,
+                    memory_delta_kb,
+                });
+            }
+        }
+
         // Check preconditions
         if let Some(contracts) = &node.contracts {
             for precondition in &contracts.preconditions {
@@ -103,35 +901,189 @@ impl SemanticExecutor {
         }
 
         // Execute flow
-        let mut ctx = ExecutionContext::new(inputs);
-        let result = self.execute_flow(node, &mut ctx)?;
+        #[cfg(feature = "arena")]
+        let arena = self.state.arenas.checkout();
+        #[cfg(feature = "arena")]
+        let ctx = ExecutionContext::new_in(&arena, inputs);
+        #[cfg(not(feature = "arena"))]
+        let ctx = ExecutionContext::new(inputs);
+        let mut ctx = ctx.with_node_id(node_id).with_execution_id(&execution_id);
+        if let Some(security) = &node.security {
+            let granted = security
+                .capabilities_required
+                .iter()
+                .filter(|c| !security.denied_capabilities.contains(c))
+                .cloned()
+                .collect();
+            ctx = ctx.with_capabilities(granted);
+        }
+        if let Some(transform) = &node.input_transform {
+            ctx = self.execute_input_transform(transform, ctx)?;
+        }
+        let workflow_id = self.restore_workflow_state(node, &mut ctx)?;
+        let mut result = match self.execute_flow(node, 0, &mut ctx) {
+            Ok(result) => result,
+            Err(error) => {
+                self.run_compensations(&mut ctx);
+                return Err(error);
+            }
+        };
+        if let Some(suspension) = ctx.suspension.take() {
+            return self.suspend(node, suspension, ctx, start);
+        }
+        if let Some(workflow_id) = &workflow_id {
+            self.save_workflow_state(workflow_id, &ctx)?;
+        }
+        if let Some(transform) = &node.output_transform {
+            result = self.execute_output_transform(transform, result, &mut ctx)?;
+        }
+        if let Some((key, ttl_seconds)) = &cache {
+            self.cache_backend()?.set(
+                key,
+                result.clone(),
+                Some(std::time::Duration::from_secs(*ttl_seconds)),
+            );
+        }
 
         let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let mut warnings = std::mem::take(&mut ctx.warnings);
+        warnings.extend(self.check_latency(node, duration_ms));
+        let memory_delta_kb = self.check_memory(node, allocated_before, &mut warnings);
 
         Ok(ExecutionResult {
             success: true,
+            status: ExecutionStatus::Completed,
             data: Some(result),
             error: None,
             duration_ms,
+            execution_id,
+            cache_hit: false,
+            warnings,
+            memory_delta_kb,
         })
     }
 
-    /// Validate inputs against node specification
-    fn validate_inputs(&self, node: &VesperNode, inputs: &HashMap<String, Value>) -> Result<()> {
-        for (name, spec) in &node.inputs {
-            if spec.required && !inputs.contains_key(name) {
-                return Err(VesperError::MissingInput(name.clone()));
+    /// Compare a duration against the node's `performance` thresholds
+    fn check_latency(&self, node: &VesperNode, duration_ms: f64) -> Vec<ExecutionWarning> {
+        let warnings = latency_warnings(node, duration_ms);
+        for warning in &warnings {
+            if let ExecutionWarning::LatencyThresholdExceeded {
+                threshold,
+                limit_ms,
+                severity,
+                ..
+            } = warning
+            {
+                tracing::warn!(
+                    "Node {} took {:.2}ms, exceeding {} of {}ms ({})",
+                    node.node_id,
+                    duration_ms,
+                    threshold,
+                    limit_ms,
+                    severity
+                );
             }
         }
-        Ok(())
+        warnings
+    }
+
+    /// Measure the growth of allocated memory since `allocated_before`
+    /// against the node's `performance.memory_limit_mb`
+    ///
+    /// The whole process is measured, so allocations made by steps running
+    /// on other threads are counted, and so are those of concurrent
+    /// executions.
+    fn check_memory(
+        &self,
+        node: &VesperNode,
+        allocated_before: Option<u64>,
+        warnings: &mut Vec<ExecutionWarning>,
+    ) -> Option<u64> {
+        let delta_kb = allocated_bytes()
+            .zip(allocated_before)
+            .map(|(after, before)| after.saturating_sub(before) / 1024)?;
+        let limit_mb = node.performance.as_ref()?.memory_limit_mb?;
+        if delta_kb > limit_mb * 1024 {
+            tracing::warn!(
+                "Node {} allocated {}KB, exceeding memory_limit_mb of {}MB",
+                node.node_id,
+                delta_kb,
+                limit_mb
+            );
+            warnings.push(ExecutionWarning::MemoryLimitExceeded { limit_mb, delta_kb });
+        }
+        Some(delta_kb)
+    }
+
+    /// Validate inputs against node specification
+    ///
+    /// A single missing input fails with `MissingInput`; several fail with
+    /// `MultipleErrors` naming each, in name order.
+    pub fn validate_inputs(
+        &self,
+        node: &VesperNode,
+        inputs: &HashMap<String, Value>,
+    ) -> Result<()> {
+        let mut missing: Vec<&String> = node
+            .inputs
+            .iter()
+            .filter(|(name, spec)| spec.required && !inputs.contains_key(*name))
+            .map(|(name, _)| name)
+            .collect();
+        missing.sort();
+
+        match missing.as_slice() {
+            [] => Ok(()),
+            [name] => Err(VesperError::MissingInput((*name).clone())),
+            names => Err(VesperError::MultipleErrors(
+                names
+                    .iter()
+                    .map(|name| VesperError::MissingInput((*name).clone()).to_string())
+                    .collect(),
+            )),
+        }
     }
 
-    /// Execute the flow steps
-    fn execute_flow(&self, node: &VesperNode, ctx: &mut ExecutionContext) -> Result<Value> {
+    /// Execute the flow steps from index `start`
+    ///
+    /// Steps named as the `fetch_step` of a `paginate` step only run
+    /// through it, not in sequence. The flow stops after a step that
+    /// reached an `approval_gate`, recording where to resume.
+    fn execute_flow(
+        &self,
+        node: &VesperNode,
+        start: usize,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
         let mut last_result = Value::Null;
+        let fetch_steps: HashSet<&str> = node
+            .flow
+            .iter()
+            .filter(|step| step.operation == "paginate")
+            .filter_map(|step| step.parameters.get("fetch_step")?.as_str())
+            .collect();
+
+        #[cfg(feature = "parallel")]
+        if start == 0
+            && !node.flow.iter().any(|step| {
+                step.return_success.is_some()
+                    || step.return_error.is_some()
+                    || step.operation == "approval_gate"
+                    || step.operation == "compensating_transaction"
+            })
+        {
+            return self.execute_flow_levels(node, &fetch_steps, ctx);
+        }
 
-        for step in &node.flow {
+        for (index, step) in node.flow.iter().enumerate().skip(start) {
+            if fetch_steps.contains(step.step.as_str()) {
+                continue;
+            }
             last_result = self.execute_step(step, ctx)?;
+            if let Some(suspension) = &mut ctx.suspension {
+                suspension.resume_at = index + 1;
+                break;
+            }
 
             // Check for early return
             if step.return_success.is_some() || step.return_error.is_some() {
@@ -142,16 +1094,244 @@ impl SemanticExecutor {
         Ok(last_result)
     }
 
+    /// Execute the flow steps level by level, in parallel within a level
+    ///
+    /// Levels come from the use-def analysis, so steps in the same level
+    /// share no variables and have no side effects. Each runs against a
+    /// copy of the context and the variables it sets are merged back, in
+    /// flow order, before the next level starts. A step alone in its level,
+    /// such as one with side effects, runs against the context itself. The
+    /// result is that of the last step in the flow.
+    #[cfg(feature = "parallel")]
+    fn execute_flow_levels(
+        &self,
+        node: &VesperNode,
+        fetch_steps: &HashSet<&str>,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use rayon::prelude::*;
+
+        // Result of the latest step in flow order, by index
+        let mut last: Option<(usize, Value)> = None;
+        for level in crate::loader::analysis::dependency_levels(&node.flow) {
+            let steps: Vec<(usize, &FlowStep)> = level
+                .into_iter()
+                .map(|index| (index, &node.flow[index]))
+                .filter(|(_, step)| !fetch_steps.contains(step.step.as_str()))
+                .collect();
+
+            if let [(index, step)] = steps[..] {
+                last = Some((index, self.execute_step(step, ctx)?));
+                continue;
+            }
+
+            let shared: &ExecutionContext = ctx;
+            let outcomes: Vec<(usize, Result<Value>, ExecutionContext)> = steps
+                .par_iter()
+                .map(|&(index, step)| {
+                    let mut branch = shared.fork();
+                    (index, self.execute_step(step, &mut branch), branch)
+                })
+                .collect();
+            // Every branch is merged before failing, so the compensations
+            // of the ones that succeeded still run
+            let mut failure = None;
+            for (index, outcome, branch) in outcomes {
+                ctx.merge(branch);
+                match outcome {
+                    Ok(value) => {
+                        if last.as_ref().is_none_or(|(latest, _)| index > *latest) {
+                            last = Some((index, value));
+                        }
+                    }
+                    Err(e) => {
+                        failure.get_or_insert(e);
+                    }
+                }
+            }
+            if let Some(e) = failure {
+                return Err(e);
+            }
+        }
+        Ok(last.map(|(_, value)| value).unwrap_or(Value::Null))
+    }
+
+    /// Run the node's input transform steps over the raw inputs
+    ///
+    /// Every binding left after the steps, with variables shadowing the
+    /// inputs they were derived from, becomes an input of the main flow.
+    fn execute_input_transform(
+        &self,
+        steps: &[FlowStep],
+        mut ctx: ExecutionContext,
+    ) -> Result<ExecutionContext> {
+        for step in steps {
+            self.execute_step(step, &mut ctx)?;
+        }
+        ctx.inputs = ctx.bindings();
+        ctx.variables.clear();
+        Ok(ctx)
+    }
+
+    /// Run the node's output transform steps over the flow result
+    ///
+    /// The result is bound to `_output`; whatever the steps leave there is
+    /// the node's final result.
+    fn execute_output_transform(
+        &self,
+        steps: &[FlowStep],
+        result: Value,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        ctx.set(OUTPUT_VARIABLE.to_string(), result);
+        for step in steps {
+            self.execute_step(step, ctx)?;
+        }
+        Ok(ctx.get(OUTPUT_VARIABLE).cloned().unwrap_or(Value::Null))
+    }
+
     /// Execute a single flow step
     fn execute_step(&self, step: &FlowStep, ctx: &mut ExecutionContext) -> Result<Value> {
+        let _span = tracing::debug_span!(
+            "step",
+            step = %step.step,
+            operation = %step.operation,
+            execution_id = %ctx.execution_id(),
+        )
+        .entered();
+        if ctx.cancellation.is_cancelled() {
+            return Err(VesperError::ExecutionError(format!(
+                "Step {} cancelled",
+                step.step
+            )));
+        }
         tracing::debug!("Executing step: {} ({})", step.step, step.operation);
 
+        let result = if step.memoize {
+            self.execute_memoized(step, ctx)
+        } else {
+            self.execute_operation(step, ctx)
+        };
+        #[cfg(feature = "otel")]
+        self.record_span_event(step, &result, ctx);
+
+        match (result, &step.on_error) {
+            (Ok(value), _) => {
+                if let Some(handler) = &step.on_success {
+                    let mut child = ctx.clone();
+                    for sub_step in handler {
+                        self.execute_step(sub_step, &mut child)?;
+                    }
+                }
+                Ok(value)
+            }
+            (Err(e), Some(handler)) => {
+                tracing::debug!("Step {} failed, running on_error: {}", step.step, e);
+                let mut last_result = Value::Null;
+                for sub_step in handler {
+                    last_result = self.execute_step(sub_step, ctx)?;
+                }
+                Ok(last_result)
+            }
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    /// Run the operation of a step
+    fn execute_operation(&self, step: &FlowStep, ctx: &mut ExecutionContext) -> Result<Value> {
         match step.operation.as_str() {
             "validation" => self.execute_validation(step, ctx),
             "string_template" => self.execute_template(step, ctx),
             "arithmetic" => self.execute_arithmetic(step, ctx),
             "return" => self.execute_return(step, ctx),
             "conditional" => self.execute_conditional(step, ctx),
+            "statistics" => self.execute_statistics(step, ctx),
+            "dot_product" => self.execute_dot_product(step, ctx),
+            "matrix_multiply" => self.execute_matrix_multiply(step, ctx),
+            "jsonpath" => self.execute_jsonpath(step, ctx),
+            "schema_validate" => self.execute_schema_validate(step, ctx),
+            "lint_step" => self.execute_lint_step(step, ctx),
+            "assert_schema" => self.execute_assert_schema(step, ctx),
+            "diff_values" => self.execute_diff_values(step, ctx),
+            "benchmark_step" => self.execute_benchmark_step(step, ctx),
+            "fuzzer" => self.execute_fuzzer(step, ctx),
+            "contract_synthesis" => self.execute_contract_synthesis(step, ctx),
+            "migration_guide" => self.execute_migration_guide(step, ctx),
+            "node_metadata" => self.execute_node_metadata(step, ctx),
+            "search_nodes" => self.execute_search_nodes(step, ctx),
+            "xml_parse" => self.execute_xml_parse(step, ctx),
+            "xml_stringify" => self.execute_xml_stringify(step, ctx),
+            "csv_parse" => self.execute_csv_parse(step, ctx),
+            "csv_stringify" => self.execute_csv_stringify(step, ctx),
+            "template_render" => self.execute_template_render(step, ctx),
+            "handlebars_render" => self.execute_handlebars_render(step, ctx),
+            "notify" => self.execute_notify(step, ctx),
+            "cache_get" => self.execute_cache_get(step, ctx),
+            "cache_set" => self.execute_cache_set(step, ctx),
+            "feature_flag" => self.execute_feature_flag(step, ctx),
+            "version_compare" => self.execute_version_compare(step, ctx),
+            "version_parse" => self.execute_version_parse(step, ctx),
+            "ip_validate" => self.execute_ip_validate(step, ctx),
+            "ip_parse" => self.execute_ip_parse(step, ctx),
+            "ip_in_cidr" => self.execute_ip_in_cidr(step, ctx),
+            "phone_validate" => self.execute_phone_validate(step, ctx),
+            "phone_format" => self.execute_phone_format(step, ctx),
+            "currency_convert" => self.execute_currency_convert(step, ctx),
+            "encrypt" => self.execute_encrypt(step, ctx),
+            "decrypt" => self.execute_decrypt(step, ctx),
+            "sign" => self.execute_sign(step, ctx),
+            "verify" => self.execute_verify(step, ctx),
+            "jwt_decode" => self.execute_jwt_decode(step, ctx),
+            "jwt_verify" => self.execute_jwt_verify(step, ctx),
+            "oauth2_token_exchange" => self.execute_oauth2_token_exchange(step, ctx),
+            "http_response_builder" => self.execute_http_response_builder(step, ctx),
+            "http_json_response" => self.execute_http_json_response(step, ctx),
+            "graphql_query" => self.execute_graphql_query(step, ctx),
+            "grpc_call" => self.execute_grpc_call(step, ctx),
+            "kubernetes_api" => self.execute_kubernetes_api(step, ctx),
+            "s3_get" => self.execute_s3_get(step, ctx),
+            "s3_put" => self.execute_s3_put(step, ctx),
+            "secrets_manager_get" => self.execute_secrets_manager_get(step, ctx),
+            "prometheus_push" => self.execute_prometheus_push(step, ctx),
+            "structured_log" => self.execute_structured_log(step, ctx),
+            "opentelemetry_baggage_get" => self.execute_opentelemetry_baggage_get(step, ctx),
+            "opentelemetry_baggage_set" => self.execute_opentelemetry_baggage_set(step, ctx),
+            "trace_context_inject" => self.execute_trace_context_inject(step, ctx),
+            "trace_span_start" => self.execute_trace_span_start(step, ctx),
+            "trace_span_end" => self.execute_trace_span_end(step, ctx),
+            "paginate" => self.execute_paginate(step, ctx),
+            "parallel" => self.execute_parallel(step, ctx),
+            "race" => self.execute_race(step, ctx),
+            "saga" => self.execute_saga(step, ctx),
+            "compensating_transaction" => self.execute_compensating_transaction(step, ctx),
+            "approval_gate" => self.execute_approval_gate(step, ctx),
+            "scatter_gather" => self.execute_scatter_gather(step, ctx),
+            "workflow_orchestrator" => self.execute_workflow_orchestrator(step, ctx),
+            "model_invoke" => self.execute_model_invoke(step, ctx),
+            "text_embed" => self.execute_text_embed(step, ctx),
+            "vector_similarity" => self.execute_vector_similarity(step, ctx),
+            "structured_extract" => self.execute_structured_extract(step, ctx),
+            "try_catch" => self.execute_try_catch(step, ctx),
+            "with_timeout" => self.execute_with_timeout(step, ctx),
+            "circuit_breaker_step" => self.execute_circuit_breaker_step(step, ctx),
+            "rate_limit_step" => self.execute_rate_limit_step(step, ctx),
+            "bulkhead" => self.execute_bulkhead(step, ctx),
+            "distributed_lock" => self.execute_distributed_lock(step, ctx),
+            "idempotency_key" => self.execute_idempotency_key(step, ctx),
+            "event_sourcing_append" => self.execute_event_sourcing_append(step, ctx),
+            "event_sourcing_replay" => self.execute_event_sourcing_replay(step, ctx),
+            "cqrs_command" => self.execute_cqrs_command(step, ctx),
+            "cqrs_query" => self.execute_cqrs_query(step, ctx),
+            "load_balance" => self.execute_load_balance(step, ctx),
+            "a_b_test" => self.execute_a_b_test(step, ctx),
+            "shadow_mode" => self.execute_shadow_mode(step, ctx),
+            "message_queue_publish" => self.execute_message_queue_publish(step, ctx),
+            "message_queue_consume" => self.execute_message_queue_consume(step, ctx),
+            "database_query" => self.execute_database_query(step, ctx),
+            "database_execute" => self.execute_database_execute(step, ctx),
+            "read_file" => self.execute_read_file(step, ctx),
+            "write_file" => self.execute_write_file(step, ctx),
+            "list_files" => self.execute_list_files(step, ctx),
             _ => {
                 tracing::warn!("Unknown operation: {}", step.operation);
                 Ok(Value::Null)
@@ -174,30 +1354,15 @@ impl SemanticExecutor {
             VesperError::ExecutionError("Template step missing template".to_string())
         })?;
 
-        // Simple template substitution
-        let mut result = template.clone();
-
-        // Replace {variable} patterns
-        for (name, value) in ctx.inputs.iter() {
-            let placeholder = format!("{{{}}}", name);
-            if result.contains(&placeholder) {
-                let replacement = match value {
-                    Value::String(s) => s.clone(),
-                    Value::Int(i) => i.to_string(),
-                    Value::Float(f) => f.to_string(),
-                    Value::Bool(b) => b.to_string(),
-                    _ => format!("{:?}", value),
-                };
-                result = result.replace(&placeholder, &replacement);
-            }
-        }
+        // Simple template substitution of {input} placeholders
+        let result = self.render_template(template, &ctx.inputs);
 
         // Store result in output variable
         if let Some(output) = &step.output {
-            ctx.set(output.clone(), Value::String(result.clone()));
+            ctx.set(output.clone(), Value::from(result.clone()));
         }
 
-        Ok(Value::String(result))
+        Ok(Value::from(result))
     }
 
     /// Execute an arithmetic step
@@ -208,7 +1373,7 @@ impl SemanticExecutor {
 
         // Very simple expression evaluation (a + b, a - b, a * b, a / b)
         // TODO: Implement proper expression parser
-        let result = self.evaluate_simple_expression(expression, ctx)?;
+        let result = self.evaluate_expression(expression, ctx)?;
 
         if let Some(output) = &step.output {
             ctx.set(output.clone(), result.clone());
@@ -217,76 +1382,6 @@ impl SemanticExecutor {
         Ok(result)
     }
 
-    /// Evaluate a simple arithmetic expression
-    fn evaluate_simple_expression(
-        &self,
-        expression: &str,
-        ctx: &ExecutionContext,
-    ) -> Result<Value> {
-        let expr = expression.trim();
-
-        // Try to parse as a simple binary operation
-        for op in [" + ", " - ", " * ", " / "] {
-            if let Some(idx) = expr.find(op) {
-                let left = expr[..idx].trim();
-                let right = expr[idx + op.len()..].trim();
-
-                let left_val = self.get_numeric_value(left, ctx)?;
-                let right_val = self.get_numeric_value(right, ctx)?;
-
-                let result = match op.trim() {
-                    "+" => left_val + right_val,
-                    "-" => left_val - right_val,
-                    "*" => left_val * right_val,
-                    "/" => {
-                        if right_val == 0.0 {
-                            return Err(VesperError::ExecutionError(
-                                "Division by zero".to_string(),
-                            ));
-                        }
-                        left_val / right_val
-                    }
-                    _ => unreachable!(),
-                };
-
-                return Ok(if result.fract() == 0.0 {
-                    Value::Int(result as i64)
-                } else {
-                    Value::Float(result)
-                });
-            }
-        }
-
-        // Try as a single value
-        let val = self.get_numeric_value(expr, ctx)?;
-        Ok(if val.fract() == 0.0 {
-            Value::Int(val as i64)
-        } else {
-            Value::Float(val)
-        })
-    }
-
-    /// Get a numeric value from a string (variable name or literal)
-    fn get_numeric_value(&self, s: &str, ctx: &ExecutionContext) -> Result<f64> {
-        // Try as a number literal
-        if let Ok(n) = s.parse::<f64>() {
-            return Ok(n);
-        }
-
-        // Try as a variable
-        if let Some(value) = ctx.get(s) {
-            return value.as_float().ok_or_else(|| VesperError::TypeError {
-                expected: "number".to_string(),
-                actual: format!("{:?}", value),
-            });
-        }
-
-        Err(VesperError::ExecutionError(format!(
-            "Unknown variable or invalid number: {}",
-            s
-        )))
-    }
-
     /// Execute a return step
     fn execute_return(&self, step: &FlowStep, ctx: &ExecutionContext) -> Result<Value> {
         if let Some(success_data) = &step.return_success {
@@ -321,16 +1416,110 @@ impl SemanticExecutor {
     }
 
     /// Execute a conditional step
-    fn execute_conditional(&self, step: &FlowStep, _ctx: &mut ExecutionContext) -> Result<Value> {
-        // TODO: Implement proper condition evaluation
+    ///
+    /// Runs the `then` steps when the condition holds and the `else` steps
+    /// otherwise, returning the result of the last step executed.
+    fn execute_conditional(&self, step: &FlowStep, ctx: &mut ExecutionContext) -> Result<Value> {
         let condition = step.condition.as_ref().ok_or_else(|| {
             VesperError::ExecutionError("Conditional step missing condition".to_string())
         })?;
 
         tracing::debug!("Evaluating condition: {}", condition);
 
-        // For now, just return null
-        Ok(Value::Null)
+        let holds = ContractValidator::new().evaluate(condition, &ctx.bindings())?;
+        let branch = if holds {
+            &step.then_steps
+        } else {
+            &step.else_steps
+        };
+
+        let mut result = Value::Null;
+        for nested in branch {
+            result = self.execute_step(nested, ctx)?;
+        }
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Resolve the variable named by a step parameter
+    ///
+    /// A string parameter is treated as a variable name (with or without
+    /// surrounding braces); any other parameter is resolved as a literal.
+    fn resolve_parameter_variable(
+        &self,
+        step: &FlowStep,
+        key: &str,
+        ctx: &ExecutionContext,
+    ) -> Result<Value> {
+        let param = step.parameters.get(key).ok_or_else(|| {
+            VesperError::ExecutionError(format!("Step {} missing parameter: {}", step.step, key))
+        })?;
+
+        match param {
+            serde_yaml::Value::String(s) => {
+                let name = s.trim_start_matches('{').trim_end_matches('}');
+                ctx.get(name).cloned().ok_or_else(|| {
+                    VesperError::ExecutionError(format!("Unknown variable: {}", name))
+                })
+            }
+            other => Ok(self.resolve_value(other, ctx)),
+        }
+    }
+
+    /// Resolve a step parameter to a string, substituting a `{var}` reference
+    fn resolve_string_parameter(
+        &self,
+        step: &FlowStep,
+        key: &str,
+        ctx: &ExecutionContext,
+    ) -> Result<String> {
+        let param = step.parameters.get(key).ok_or_else(|| {
+            VesperError::ExecutionError(format!("Step {} missing parameter: {}", step.step, key))
+        })?;
+
+        match self.resolve_value(param, ctx) {
+            Value::String(s) => Ok(s.into_string()),
+            Value::Int(i) => Ok(i.to_string()),
+            Value::Float(f) => Ok(f.to_string()),
+            Value::Bool(b) => Ok(b.to_string()),
+            other => Err(VesperError::TypeError {
+                expected: "string".to_string(),
+                actual: format!("{:?}", other),
+            }),
+        }
+    }
+
+    /// Shared HTTP client for operations calling external services
+    #[cfg(feature = "http")]
+    fn http_client(&self) -> &reqwest::blocking::Client {
+        self.state
+            .http_client
+            .get_or_init(reqwest::blocking::Client::new)
+    }
+
+    /// Fail unless the executing node has been granted `capability`
+    fn require_capability(
+        &self,
+        step: &FlowStep,
+        ctx: &ExecutionContext,
+        capability: &str,
+    ) -> Result<()> {
+        if ctx.has_capability(capability) {
+            Ok(())
+        } else {
+            Err(VesperError::SecurityViolation(format!(
+                "{} operation requires capability {}",
+                step.operation, capability
+            )))
+        }
+    }
+
+    /// Store a step result in its output variable, if one is declared
+    fn store_output(&self, step: &FlowStep, ctx: &mut ExecutionContext, value: &Value) {
+        if let Some(output) = &step.output {
+            ctx.set(output.clone(), value.clone());
+        }
     }
 
     /// Resolve a YAML value, substituting variable references
@@ -345,7 +1534,7 @@ impl SemanticExecutor {
                         return val.clone();
                     }
                 }
-                Value::String(s.clone())
+                Value::from(s.as_str())
             }
             serde_yaml::Value::Number(n) => {
                 if let Some(i) = n.as_i64() {
@@ -385,6 +1574,7 @@ impl Default for SemanticExecutor {
 mod tests {
     use super::*;
     use crate::loader::VesperLoader;
+    use crate::types::Performance;
 
     #[test]
     fn test_execute_arithmetic() {
@@ -428,6 +1618,118 @@ flow:
     }
 
     #[test]
+    fn test_expression_parsed_once() {
+        let yaml = r#"
+node_id: double_v1
+type: function
+intent: double a number
+
+inputs:
+  x:
+    type: integer
+
+flow:
+  - step: double
+    operation: arithmetic
+    expression: "x * 2"
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        for x in 0..10 {
+            let mut inputs = HashMap::new();
+            inputs.insert("x".to_string(), Value::Int(x));
+            let result = executor.execute("double_v1", inputs).unwrap();
+            assert_eq!(result.data, Some(Value::Int(x * 2)));
+        }
+
+        let stats = executor.expression_cache_stats();
+        assert_eq!(
+            stats,
+            CacheStats {
+                entries: 1,
+                hits: 9,
+                misses: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_on_error_sets_fallback() {
+        let yaml = r#"
+node_id: ratio_v1
+type: function
+intent: divide with a fallback
+
+inputs:
+  a:
+    type: integer
+  b:
+    type: integer
+
+flow:
+  - step: divide
+    operation: arithmetic
+    expression: "a / b"
+    output: ratio
+    on_success:
+      - operation: arithmetic
+        expression: "ratio * 100"
+        output: percent
+    on_error:
+      - operation: arithmetic
+        expression: "0"
+        output: ratio
+  - step: scale
+    operation: arithmetic
+    expression: "ratio + 1"
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let run = |b: i64| {
+            let mut inputs = HashMap::new();
+            inputs.insert("a".to_string(), Value::Int(6));
+            inputs.insert("b".to_string(), Value::Int(b));
+            executor.execute("ratio_v1", inputs).unwrap().data
+        };
+        assert_eq!(run(3), Some(Value::Int(3)));
+        assert_eq!(run(0), Some(Value::Int(1)));
+    }
+
+    #[test]
+    fn test_failing_on_error_propagates() {
+        let yaml = r#"
+node_id: strict_ratio_v1
+type: function
+intent: divide or fail
+
+inputs:
+  b:
+    type: integer
+
+flow:
+  - step: divide
+    operation: arithmetic
+    expression: "1 / b"
+    on_error:
+      - operation: return
+        return_error:
+          error_code: bad_divisor
+          message: "Divisor must not be zero"
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("b".to_string(), Value::Int(0));
+        let error = executor.execute("strict_ratio_v1", inputs).unwrap_err();
+        assert!(error.to_string().contains("bad_divisor"), "{}", error);
+    }
+
+    #[test]
+    // `Value::String` holds a `String` unless `sso` is enabled
+    #[cfg(not(feature = "sso"))]
     fn test_execute_template() {
         let yaml = r#"
 node_id: greet_v1
@@ -467,4 +1769,446 @@ flow:
             Some(Value::String("Hello, World!".to_string()))
         );
     }
+
+    #[test]
+    fn test_trace_id_propagates_to_result_and_spans() {
+        use std::io::Write;
+        use std::sync::Mutex;
+
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let yaml = r#"
+node_id: double_v1
+type: function
+intent: double a number
+
+inputs:
+  a:
+    type: integer
+
+flow:
+  - step: double
+    operation: arithmetic
+    expression: "a * 2"
+    output: result
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), Value::Int(21));
+
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_span_events(tracing_subscriber::fmt::format::FmtSpan::NEW)
+            .with_writer(move || writer.clone())
+            .finish();
+        let options = ExecutionOptions {
+            trace_id: Some("4bf92f3577b34da6a3ce929d0e0e4736".to_string()),
+        };
+        let result = tracing::subscriber::with_default(subscriber, || {
+            executor
+                .execute_with_options("double_v1", inputs, options)
+                .unwrap()
+        });
+        assert_eq!(result.execution_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let spans: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter_map(|event| event.get("span").cloned())
+            .collect();
+        let names: Vec<&str> = spans.iter().filter_map(|s| s["name"].as_str()).collect();
+        assert!(
+            names.contains(&"execute") && names.contains(&"step"),
+            "{}",
+            output
+        );
+        for span in &spans {
+            assert_eq!(span["execution_id"], "4bf92f3577b34da6a3ce929d0e0e4736");
+        }
+    }
+
+    #[test]
+    fn test_execution_id_is_generated() {
+        let yaml = r#"
+node_id: noop_v1
+type: function
+intent: do nothing
+
+inputs: {}
+
+flow:
+  - step: done
+    operation: return
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let first = executor.execute("noop_v1", HashMap::new()).unwrap();
+        let second = executor.execute("noop_v1", HashMap::new()).unwrap();
+        assert_eq!(first.execution_id.len(), 36);
+        assert_ne!(first.execution_id, second.execution_id);
+    }
+
+    #[test]
+    fn test_output_transform() {
+        let yaml = r#"
+node_id: price_v1
+type: function
+intent: quote a price
+
+inputs:
+  amount:
+    type: integer
+
+flow:
+  - step: total
+    operation: arithmetic
+    expression: "amount * 3"
+    output: total
+
+output_transform:
+  - step: add_fee
+    operation: arithmetic
+    expression: "_output + 2"
+    output: _output
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("amount".to_string(), Value::Int(7));
+        let result = executor.execute("price_v1", inputs).unwrap();
+        assert_eq!(result.data, Some(Value::Int(23)));
+    }
+
+    #[test]
+    fn test_latency_thresholds() {
+        let yaml = r#"
+node_id: slow_v1
+type: function
+intent: exceed every latency threshold
+
+inputs: {}
+
+flow:
+  - step: done
+    operation: return
+
+performance:
+  expected_latency_ms: 0
+  p99_latency_ms: 0
+  max_latency_ms: 0
+"#;
+        let mut executor = SemanticExecutor::new();
+        let node = VesperLoader::new().load_string(yaml).unwrap();
+        executor.register(node.clone());
+
+        let result = executor.execute("slow_v1", HashMap::new()).unwrap();
+        let severities: Vec<_> = result
+            .warnings
+            .iter()
+            .filter_map(|warning| match warning {
+                ExecutionWarning::LatencyThresholdExceeded {
+                    threshold,
+                    severity,
+                    ..
+                } => Some((threshold.as_str(), severity.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            severities,
+            vec![
+                ("expected_latency_ms", "warning"),
+                ("p99_latency_ms", "warning"),
+                ("max_latency_ms", "critical"),
+            ]
+        );
+        assert!(!result.met_latency_sla(&node));
+
+        let mut relaxed = node;
+        relaxed.performance = Some(Performance {
+            expected_latency_ms: Some(60_000),
+            p99_latency_ms: None,
+            max_latency_ms: None,
+            memory_limit_mb: None,
+            timeout_seconds: None,
+        });
+        assert!(result.met_latency_sla(&relaxed));
+    }
+
+    #[test]
+    #[cfg(feature = "memory-tracking")]
+    fn test_memory_delta_is_recorded() {
+        let yaml = r#"
+node_id: copy_v1
+type: function
+intent: copy a large array
+
+inputs:
+  items:
+    type: array
+
+flow:
+  - step: copy
+    operation: return
+    return_success:
+      items: "{items}"
+
+performance:
+  memory_limit_mb: 1
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        // 200k values of at least 32 bytes each, copied by the return step
+        let items = Value::Array((0..200_000).map(Value::Int).collect());
+        let mut inputs = HashMap::new();
+        inputs.insert("items".to_string(), items);
+        let result = executor.execute("copy_v1", inputs).unwrap();
+
+        let delta_kb = result.memory_delta_kb.unwrap();
+        assert!(delta_kb >= 200_000 * 32 / 1024, "{}KB", delta_kb);
+        assert!(result
+            .warnings
+            .contains(&ExecutionWarning::MemoryLimitExceeded {
+                limit_mb: 1,
+                delta_kb
+            }));
+    }
+
+    #[test]
+    #[cfg(feature = "tera")]
+    fn test_input_transform() {
+        let yaml = r#"
+node_id: greet_v1
+type: function
+intent: greet a user
+
+inputs:
+  name:
+    type: string
+
+input_transform:
+  - step: normalize
+    operation: template_render
+    parameters:
+      template: "{{ name | lower }}"
+    output: name
+
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello, {name}"
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("name".to_string(), Value::from("ADA"));
+        let result = executor.execute("greet_v1", inputs).unwrap();
+        assert_eq!(result.data, Some(Value::from("Hello, ada")));
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_independent_steps_run_in_parallel() {
+        use crate::database::DatabaseBackend;
+        use std::time::{Duration, Instant};
+
+        /// Database sleeping for the number of milliseconds in the query
+        struct SlowDatabase;
+
+        impl DatabaseBackend for SlowDatabase {
+            fn query(
+                &self,
+                sql: &str,
+                params: Vec<Value>,
+            ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+                let millis: u64 = sql.trim_start_matches("SELECT ").parse().unwrap();
+                std::thread::sleep(Duration::from_millis(millis));
+                let mut row = HashMap::new();
+                row.insert("params".to_string(), Value::Int(params.len() as i64));
+                Ok(vec![row])
+            }
+
+            fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+                Ok(0)
+            }
+        }
+
+        let yaml = r#"
+node_id: pairs_v1
+type: function
+intent: two independent lookups with follow-ups
+
+inputs: {}
+
+flow:
+  - step: first_user
+    operation: database_query
+    parameters:
+      sql: "SELECT 200"
+    output: user
+  - step: first_order
+    operation: database_query
+    parameters:
+      sql: "SELECT 200"
+    output: order
+  - step: user_detail
+    operation: database_query
+    parameters:
+      sql: "SELECT 200"
+      params: user
+    output: detail
+  - step: order_detail
+    operation: database_query
+    parameters:
+      sql: "SELECT 200"
+      params: order
+    output: lines
+"#;
+        let mut executor = SemanticExecutor::new().with_database(Arc::new(SlowDatabase));
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        // The queries sleep rather than compute, so they overlap even on one core
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let start = Instant::now();
+        let result = pool
+            .install(|| executor.execute("pairs_v1", HashMap::new()))
+            .unwrap();
+        let elapsed = start.elapsed();
+
+        // Sequentially the four queries take 800ms, in two levels 400ms
+        assert!(elapsed < Duration::from_millis(700), "took {:?}", elapsed);
+        let Some(Value::Array(rows)) = result.data else {
+            panic!("expected rows");
+        };
+        let Value::Object(row) = &rows[0] else {
+            panic!("expected object row");
+        };
+        assert_eq!(row["params"], Value::Int(1));
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_merge_takes_over_branch_warnings_and_compensations() {
+        let yaml = r#"
+node_id: undo_v1
+type: function
+intent: register an undo step
+
+inputs: {}
+
+flow:
+  - step: undo
+    operation: compensating_transaction
+    parameters:
+      compensate:
+        - operation: return
+"#;
+        let node = VesperLoader::new().load_string(yaml).unwrap();
+        let executor = SemanticExecutor::new();
+        let warning = |delta_kb| ExecutionWarning::MemoryLimitExceeded {
+            limit_mb: 1,
+            delta_kb,
+        };
+
+        let mut ctx = ExecutionContext::new(HashMap::new());
+        ctx.add_warning(warning(2048));
+        let mut branch = ctx.fork();
+        branch.add_warning(warning(4096));
+        executor.execute_step(&node.flow[0], &mut branch).unwrap();
+        ctx.merge(branch);
+
+        assert_eq!(ctx.warnings(), [warning(2048), warning(4096)]);
+        assert_eq!(ctx.compensations.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_side_effects_keep_flow_order_in_parallel() {
+        use crate::database::DatabaseBackend;
+        use std::sync::Mutex;
+
+        /// Table of rows, counted by queries
+        #[derive(Default)]
+        struct Table {
+            rows: Mutex<i64>,
+        }
+
+        impl DatabaseBackend for Table {
+            fn query(
+                &self,
+                _sql: &str,
+                _params: Vec<Value>,
+            ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+                let count = *self.rows.lock().unwrap();
+                Ok(vec![HashMap::from([("n".to_string(), Value::Int(count))])])
+            }
+
+            fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+                // Give a concurrent count the chance to overtake the insert
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                *self.rows.lock().unwrap() += 1;
+                Ok(1)
+            }
+        }
+
+        let yaml = r#"
+node_id: insert_count_v1
+type: function
+intent: insert a row and count the rows
+
+inputs: {}
+
+flow:
+  - step: insert
+    operation: database_execute
+    parameters:
+      sql: "INSERT INTO items VALUES (1)"
+  - step: count
+    operation: database_query
+    parameters:
+      sql: "SELECT COUNT(*) AS n FROM items"
+    output: rows
+"#;
+        let mut executor = SemanticExecutor::new().with_database(Arc::new(Table::default()));
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let result = pool
+            .install(|| executor.execute("insert_count_v1", HashMap::new()))
+            .unwrap();
+        let Some(Value::Array(rows)) = result.data else {
+            panic!("expected rows");
+        };
+        let Value::Object(row) = &rows[0] else {
+            panic!("expected object row");
+        };
+        assert_eq!(row["n"], Value::Int(1));
+    }
 }
@@ -0,0 +1,285 @@
+//! Human approval of paused executions
+
+use super::{
+    allocated_bytes, ExecutionContext, ExecutionError, ExecutionResult, ExecutionStatus,
+    SemanticExecutor,
+};
+use crate::checkpoints::{Checkpoint, CheckpointStore};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value, VesperNode};
+use std::time::{Duration, SystemTime};
+
+/// Approval gate reached by a flow
+#[derive(Debug, Clone)]
+pub(super) struct Suspension {
+    /// Gate waiting for a decision
+    gate_id: String,
+    /// Time after which the gate can no longer be approved
+    expires_at: Option<SystemTime>,
+    /// Index of the flow step to continue from, set by `execute_flow`
+    pub(super) resume_at: usize,
+}
+
+impl SemanticExecutor {
+    /// Execute an approval gate step
+    ///
+    /// Pauses the flow after this step: the execution is saved to the
+    /// installed `CheckpointStore` and returns at once with status
+    /// `pending_approval`. `parameters["gate_id"]` names the gate, and
+    /// `parameters["timeout_ms"]`, if set, bounds how long it can wait for
+    /// `resume`. Gates only pause at the top level of the main flow; a gate
+    /// nested in another step pauses once that step finishes.
+    pub(super) fn execute_approval_gate(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        self.checkpoint_store()?;
+        let gate_id = self.resolve_string_parameter(step, "gate_id", ctx)?;
+        let expires_at = match step.parameters.get("timeout_ms") {
+            Some(timeout) => {
+                let millis = timeout.as_u64().ok_or_else(|| {
+                    VesperError::ExecutionError(format!(
+                        "Step {} timeout_ms must be a non-negative integer",
+                        step.step
+                    ))
+                })?;
+                Some(SystemTime::now() + Duration::from_millis(millis))
+            }
+            None => None,
+        };
+
+        tracing::info!(
+            "Execution {} waiting for approval at gate {}",
+            ctx.execution_id(),
+            gate_id
+        );
+        ctx.suspension = Some(Suspension {
+            gate_id,
+            expires_at,
+            resume_at: 0,
+        });
+        Ok(Value::Null)
+    }
+
+    /// Continue an execution paused at an approval gate
+    ///
+    /// If `approved`, the flow runs on from the step after the gate, with
+    /// the variables it had when it paused; it may pause again at a later
+    /// gate. Otherwise the execution ends with status `rejected`. Either
+    /// way the checkpoint is consumed. Fails if the execution is not
+    /// paused or its gate timed out.
+    pub fn resume(&self, execution_id: &str, approved: bool) -> Result<ExecutionResult> {
+        let start = std::time::Instant::now();
+        let allocated_before = allocated_bytes();
+        let _span = tracing::info_span!("resume", execution_id).entered();
+
+        let checkpoint = self
+            .checkpoint_store()?
+            .take(execution_id)
+            .map_err(VesperError::ExecutionError)?
+            .ok_or_else(|| {
+                VesperError::ExecutionError(format!(
+                    "Execution {} is not pending approval",
+                    execution_id
+                ))
+            })?;
+        let node = self.state.nodes.get(&checkpoint.node_id).ok_or_else(|| {
+            VesperError::ExecutionError(format!("Node not found: {}", checkpoint.node_id))
+        })?;
+        if checkpoint.is_expired() {
+            return Err(VesperError::ExecutionError(format!(
+                "Approval gate {} of execution {} timed out",
+                checkpoint.gate_id, execution_id
+            )));
+        }
+
+        if !approved {
+            tracing::info!(
+                "Execution {} rejected at gate {}",
+                execution_id,
+                checkpoint.gate_id
+            );
+            return Ok(ExecutionResult {
+                success: false,
+                status: ExecutionStatus::Rejected,
+                data: None,
+                error: Some(ExecutionError {
+                    code: "approval_rejected".to_string(),
+                    message: format!("Approval gate {} was rejected", checkpoint.gate_id),
+                }),
+                duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+                execution_id: execution_id.to_string(),
+                cache_hit: false,
+                warnings: Vec::new(),
+                memory_delta_kb: None,
+            });
+        }
+
+        let mut ctx = ExecutionContext::new(checkpoint.inputs)
+            .with_node_id(&checkpoint.node_id)
+            .with_execution_id(execution_id)
+            .with_capabilities(checkpoint.capabilities);
+        for (name, value) in checkpoint.variables {
+            ctx.set(name, value);
+        }
+        let mut result = self.execute_flow(node, checkpoint.resume_at, &mut ctx)?;
+        if let Some(suspension) = ctx.suspension.take() {
+            return self.suspend(node, suspension, ctx, start);
+        }
+        if let Some(transform) = &node.output_transform {
+            result = self.execute_output_transform(transform, result, &mut ctx)?;
+        }
+
+        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let mut warnings = std::mem::take(&mut ctx.warnings);
+        warnings.extend(self.check_latency(node, duration_ms));
+        let memory_delta_kb = self.check_memory(node, allocated_before, &mut warnings);
+
+        Ok(ExecutionResult {
+            success: true,
+            status: ExecutionStatus::Completed,
+            data: Some(result),
+            error: None,
+            duration_ms,
+            execution_id: execution_id.to_string(),
+            cache_hit: false,
+            warnings,
+            memory_delta_kb,
+        })
+    }
+
+    /// Save a paused execution and report it as pending approval
+    pub(super) fn suspend(
+        &self,
+        node: &VesperNode,
+        suspension: Suspension,
+        ctx: ExecutionContext,
+        start: std::time::Instant,
+    ) -> Result<ExecutionResult> {
+        let execution_id = ctx.execution_id.clone();
+        self.checkpoint_store()?
+            .save(Checkpoint {
+                execution_id: execution_id.clone(),
+                node_id: node.node_id.clone(),
+                gate_id: suspension.gate_id,
+                resume_at: suspension.resume_at,
+                variables: ctx
+                    .variables
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+                inputs: ctx.inputs,
+                capabilities: ctx.capabilities,
+                expires_at: suspension.expires_at,
+            })
+            .map_err(VesperError::ExecutionError)?;
+
+        Ok(ExecutionResult {
+            success: true,
+            status: ExecutionStatus::PendingApproval,
+            data: None,
+            error: None,
+            duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+            execution_id,
+            cache_hit: false,
+            warnings: ctx.warnings,
+            memory_delta_kb: None,
+        })
+    }
+
+    /// The installed checkpoint store, or an error if there is none
+    fn checkpoint_store(&self) -> Result<&dyn CheckpointStore> {
+        self.state.checkpoints.as_deref().ok_or_else(|| {
+            VesperError::ExecutionError("No checkpoint store configured".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoints::InMemoryCheckpointStore;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn transfer_executor(timeout_ms: u64) -> (SemanticExecutor, Arc<InMemoryCheckpointStore>) {
+        let yaml = format!(
+            r#"
+node_id: transfer_v1
+type: function
+intent: transfer funds once a reviewer approves
+
+inputs:
+  amount:
+    type: integer
+
+flow:
+  - step: fee
+    operation: arithmetic
+    expression: "amount / 100"
+    output: fee
+  - step: review
+    operation: approval_gate
+    parameters:
+      gate_id: large_transfer
+      timeout_ms: {}
+  - step: done
+    operation: return
+    return_success:
+      amount: "{{amount}}"
+      fee: "{{fee}}"
+"#,
+            timeout_ms
+        );
+        let store = Arc::new(InMemoryCheckpointStore::new());
+        let mut executor = SemanticExecutor::new().with_checkpoint_store(store.clone());
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        (executor, store)
+    }
+
+    fn start(executor: &SemanticExecutor) -> ExecutionResult {
+        let inputs = HashMap::from([("amount".to_string(), Value::Int(5000))]);
+        executor.execute("transfer_v1", inputs).unwrap()
+    }
+
+    #[test]
+    fn test_approval_gate_pauses_until_approved() {
+        let (executor, store) = transfer_executor(60_000);
+        let paused = start(&executor);
+        assert_eq!(paused.status, ExecutionStatus::PendingApproval);
+        assert_eq!(paused.data, None);
+        assert_eq!(store.pending(), vec![paused.execution_id.clone()]);
+
+        let resumed = executor.resume(&paused.execution_id, true).unwrap();
+        assert_eq!(resumed.status, ExecutionStatus::Completed);
+        assert_eq!(resumed.execution_id, paused.execution_id);
+        let Some(Value::Object(fields)) = resumed.data else {
+            panic!("expected object result");
+        };
+        assert_eq!(fields["amount"], Value::Int(5000));
+        assert_eq!(fields["fee"], Value::Int(50));
+        assert!(store.pending().is_empty());
+        assert!(executor.resume(&paused.execution_id, true).is_err());
+    }
+
+    #[test]
+    fn test_approval_gate_rejected_and_timed_out() {
+        let (executor, _) = transfer_executor(60_000);
+        let paused = start(&executor);
+        let rejected = executor.resume(&paused.execution_id, false).unwrap();
+        assert!(!rejected.success);
+        assert_eq!(rejected.status, ExecutionStatus::Rejected);
+        assert_eq!(rejected.error.unwrap().code, "approval_rejected");
+
+        let (executor, _) = transfer_executor(10);
+        let paused = start(&executor);
+        std::thread::sleep(Duration::from_millis(20));
+        let Err(VesperError::ExecutionError(message)) = executor.resume(&paused.execution_id, true)
+        else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("timed out"));
+    }
+}
@@ -0,0 +1,278 @@
+//! Arena allocation of execution context variables
+//!
+//! An execution sets many short-lived variables that are all dropped when
+//! it ends. Contexts created with `ExecutionContext::new_in` allocate their
+//! variable table and variable names from an `ExecutionArena` instead of
+//! the heap, and the arena is reset between executions.
+#![cfg(feature = "arena")]
+
+use super::ExecutionContext;
+use crate::types::Value;
+use allocator_api2::alloc::{AllocError, Allocator, Global};
+use bumpalo::Bump;
+use parking_lot::Mutex;
+use std::alloc::Layout;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher, RandomState};
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+/// Variable bindings of an execution context
+pub(super) type Variables = hashbrown::HashMap<VariableName, Value, RandomState, VariableAlloc>;
+
+/// Bump arena that execution contexts allocate their variables from
+///
+/// Every allocation holds on to the arena, so memory is only reused once
+/// every context allocated from it, and every copy of one, is gone.
+pub struct ExecutionArena {
+    bump: Arc<Mutex<Bump>>,
+}
+
+impl ExecutionArena {
+    /// Create an empty arena
+    pub fn new() -> Self {
+        Self {
+            bump: Arc::new(Mutex::new(Bump::new())),
+        }
+    }
+
+    /// Free everything allocated so far, keeping the largest chunk for the
+    /// next execution
+    ///
+    /// Contexts still allocated from the arena keep their memory; the
+    /// arena starts over with a fresh chunk instead.
+    pub fn reset(&mut self) {
+        match Arc::get_mut(&mut self.bump) {
+            Some(bump) => bump.get_mut().reset(),
+            None => *self = Self::new(),
+        }
+    }
+
+    /// Bytes of chunk capacity the arena holds
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.lock().allocated_bytes()
+    }
+}
+
+impl Default for ExecutionArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Arenas of finished executions, ready for the next ones
+#[derive(Default)]
+pub(super) struct ArenaPool {
+    idle: Mutex<Vec<ExecutionArena>>,
+}
+
+impl ArenaPool {
+    /// Arena for one execution, reset and put back when dropped
+    pub(super) fn checkout(&self) -> PooledArena<'_> {
+        let arena = self.idle.lock().pop().unwrap_or_default();
+        PooledArena {
+            pool: self,
+            arena: Some(arena),
+        }
+    }
+}
+
+/// Arena taken from an `ArenaPool` for the length of an execution
+pub(super) struct PooledArena<'a> {
+    pool: &'a ArenaPool,
+    arena: Option<ExecutionArena>,
+}
+
+impl std::ops::Deref for PooledArena<'_> {
+    type Target = ExecutionArena;
+
+    fn deref(&self) -> &ExecutionArena {
+        self.arena.as_ref().expect("arena is only taken on drop")
+    }
+}
+
+impl Drop for PooledArena<'_> {
+    fn drop(&mut self) {
+        if let Some(mut arena) = self.arena.take() {
+            arena.reset();
+            self.pool.idle.lock().push(arena);
+        }
+    }
+}
+
+impl ExecutionContext {
+    /// Create a new context with inputs whose variables are allocated
+    /// from `arena`
+    pub fn new_in(arena: &ExecutionArena, inputs: HashMap<String, Value>) -> Self {
+        let mut ctx = Self::new(inputs);
+        ctx.variables =
+            Variables::with_hasher_in(RandomState::new(), VariableAlloc::Arena(arena.bump.clone()));
+        ctx
+    }
+}
+
+/// Allocator of a context's variables: the heap, or an arena
+#[derive(Clone, Default)]
+pub(super) enum VariableAlloc {
+    #[default]
+    Global,
+    Arena(Arc<Mutex<Bump>>),
+}
+
+unsafe impl Allocator for VariableAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match self {
+            Self::Global => Global.allocate(layout),
+            Self::Arena(bump) => (&*bump.lock()).allocate(layout),
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        match self {
+            Self::Global => Global.deallocate(ptr, layout),
+            Self::Arena(bump) => (&*bump.lock()).deallocate(ptr, layout),
+        }
+    }
+}
+
+/// Name of a variable, allocated alongside the table it is a key of
+#[derive(Clone)]
+pub(super) struct VariableName(allocator_api2::vec::Vec<u8, VariableAlloc>);
+
+impl VariableName {
+    /// Copy `name` into memory from `alloc`
+    pub(super) fn new_in(name: &str, alloc: &VariableAlloc) -> Self {
+        let mut bytes = allocator_api2::vec::Vec::with_capacity_in(name.len(), alloc.clone());
+        bytes.extend_from_slice(name.as_bytes());
+        Self(bytes)
+    }
+
+    pub(super) fn as_str(&self) -> &str {
+        // Only ever built from a `&str`
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+}
+
+impl std::ops::Deref for VariableName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for VariableName {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for VariableName {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for VariableName {}
+
+impl Hash for VariableName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Must hash like the `str` it borrows as
+        self.as_str().hash(state)
+    }
+}
+
+impl std::fmt::Debug for VariableName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl std::fmt::Display for VariableName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::SemanticExecutor;
+    use crate::loader::VesperLoader;
+
+    #[test]
+    fn test_variables_allocated_from_arena() {
+        let arena = ExecutionArena::new();
+        let mut ctx = ExecutionContext::new_in(&arena, HashMap::new());
+        assert_eq!(arena.allocated_bytes(), 0);
+
+        for i in 0..20 {
+            ctx.set(format!("sum{}", i), Value::Int(i));
+        }
+        assert!(arena.allocated_bytes() > 0);
+        assert_eq!(ctx.get("sum7"), Some(&Value::Int(7)));
+        assert_eq!(ctx.bindings().len(), 20);
+    }
+
+    #[test]
+    fn test_reset_keeps_live_contexts_intact() {
+        let mut arena = ExecutionArena::new();
+        let mut ctx = ExecutionContext::new_in(&arena, HashMap::new());
+        ctx.set("total".to_string(), Value::Int(42));
+        let copy = ctx.clone();
+
+        arena.reset();
+        let mut next = ExecutionContext::new_in(&arena, HashMap::new());
+        next.set("total".to_string(), Value::Int(0));
+
+        assert_eq!(ctx.get("total"), Some(&Value::Int(42)));
+        assert_eq!(copy.get("total"), Some(&Value::Int(42)));
+    }
+
+    #[test]
+    fn test_reset_reuses_memory_once_contexts_are_gone() {
+        let mut arena = ExecutionArena::new();
+        let mut ctx = ExecutionContext::new_in(&arena, HashMap::new());
+        ctx.set("total".to_string(), Value::Int(42));
+        drop(ctx);
+        let capacity = arena.allocated_bytes();
+
+        arena.reset();
+        let mut next = ExecutionContext::new_in(&arena, HashMap::new());
+        next.set("total".to_string(), Value::Int(0));
+        assert_eq!(arena.allocated_bytes(), capacity);
+    }
+
+    #[test]
+    fn test_executions_reuse_arenas() {
+        let yaml = r#"
+node_id: add_v1
+type: function
+intent: add numbers
+
+inputs:
+  a:
+    type: integer
+
+flow:
+  - step: double
+    operation: arithmetic
+    expression: "a + a"
+    output: doubled
+  - step: quadruple
+    operation: arithmetic
+    expression: "doubled + doubled"
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        for a in 0..3 {
+            let mut inputs = HashMap::new();
+            inputs.insert("a".to_string(), Value::Int(a));
+            let result = executor.execute("add_v1", inputs).unwrap();
+            assert_eq!(result.data, Some(Value::Int(4 * a)));
+        }
+        assert_eq!(executor.state.arenas.idle.lock().len(), 1);
+    }
+}
@@ -0,0 +1,128 @@
+//! Timing of flow steps
+
+use super::parallel::sub_steps;
+use super::statistics::Summary;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Warmup iterations run when a step does not set `warmup`
+const DEFAULT_WARMUP: u64 = 10;
+
+impl SemanticExecutor {
+    /// Execute a benchmark step
+    ///
+    /// Runs `parameters["steps"]` `parameters["warmup"]` (default 10) times
+    /// and then `parameters["iterations"]` times, timing each measured run.
+    /// Every run starts from a copy of the current context, so the steps
+    /// leave no variables behind. Returns the `mean_ms`, `median_ms`,
+    /// `p99_ms`, `min_ms`, `max_ms` and `stddev_ms` of the measured runs
+    /// and the number of `iterations`.
+    pub(super) fn execute_benchmark_step(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let iterations = step
+            .parameters
+            .get("iterations")
+            .and_then(|v| v.as_u64())
+            .filter(|&n| n > 0)
+            .ok_or_else(|| {
+                VesperError::ExecutionError(format!(
+                    "Step {} needs a positive number of iterations",
+                    step.step
+                ))
+            })?;
+        let warmup = step
+            .parameters
+            .get("warmup")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_WARMUP);
+
+        let mut timings = Vec::with_capacity(iterations as usize);
+        for iteration in 0..warmup + iterations {
+            let mut run = ctx.clone();
+            let start = Instant::now();
+            for sub_step in &steps {
+                self.execute_step(sub_step, &mut run)?;
+            }
+            let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+            if iteration >= warmup {
+                timings.push(elapsed_ms);
+            }
+        }
+
+        let summary = Summary::new(timings);
+        let mut result = HashMap::new();
+        for (name, metric) in [
+            ("mean_ms", "mean"),
+            ("median_ms", "median"),
+            ("p99_ms", "p99"),
+            ("min_ms", "min"),
+            ("max_ms", "max"),
+            ("stddev_ms", "stddev"),
+        ] {
+            result.insert(name.to_string(), Value::Float(summary.compute(metric)?));
+        }
+        result.insert("iterations".to_string(), Value::Int(iterations as i64));
+
+        let result = Value::Object(result);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    const ADD: &str = r#"
+node_id: bench_add_v1
+type: function
+intent: time an addition
+
+inputs:
+  a:
+    type: integer
+  b:
+    type: integer
+
+flow:
+  - step: timing
+    operation: benchmark_step
+    parameters:
+      iterations: 200
+      warmup: 5
+      steps:
+        - step: add
+          operation: arithmetic
+          expression: "a + b"
+          output: sum
+"#;
+
+    #[test]
+    fn test_benchmark_step() {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(ADD).unwrap());
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), Value::Int(2));
+        inputs.insert("b".to_string(), Value::Int(3));
+
+        let Some(Value::Object(result)) = executor.execute("bench_add_v1", inputs).unwrap().data
+        else {
+            panic!("expected a timing object");
+        };
+        assert_eq!(result["iterations"], Value::Int(200));
+        let metric = |name: &str| result[name].as_float().unwrap();
+        assert!(metric("mean_ms") < 1.0, "mean {}ms", metric("mean_ms"));
+        assert!(metric("min_ms") <= metric("median_ms"));
+        assert!(metric("median_ms") <= metric("p99_ms"));
+        assert!(metric("p99_ms") <= metric("max_ms"));
+        assert!(metric("stddev_ms") >= 0.0);
+    }
+}
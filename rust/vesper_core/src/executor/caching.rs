@@ -0,0 +1,422 @@
+//! Cache operations backed by the executor's `CacheBackend`
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::cache::CacheBackend;
+use crate::error::{Result, VesperError};
+use crate::loader::analysis::step_references;
+use crate::types::{CacheSpec, FlowStep, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+impl SemanticExecutor {
+    /// Execute a cache lookup step
+    ///
+    /// Returns the value cached under `parameters["key"]`. On a miss the
+    /// step falls through to `parameters["default"]` (or null) so later
+    /// steps can compute and store the value.
+    pub(super) fn execute_cache_get(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let key = self.resolve_string_parameter(step, "key", ctx)?;
+
+        let result = match self.cache_backend()?.get(&key) {
+            Some(value) => value,
+            None => {
+                tracing::debug!("Cache miss: {}", key);
+                step.parameters
+                    .get("default")
+                    .map(|d| self.resolve_value(d, ctx))
+                    .unwrap_or(Value::Null)
+            }
+        };
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a cache store step
+    ///
+    /// Caches the value named by `parameters["value"]` under
+    /// `parameters["key"]`, expiring after `parameters["ttl_seconds"]`.
+    pub(super) fn execute_cache_set(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let key = self.resolve_string_parameter(step, "key", ctx)?;
+        let value = self.resolve_parameter_variable(step, "value", ctx)?;
+        let ttl = ttl_parameter(step)?;
+
+        self.cache_backend()?.set(&key, value.clone(), ttl);
+
+        self.store_output(step, ctx, &value);
+        Ok(value)
+    }
+
+    /// Execute a step with `memoize: true`
+    ///
+    /// The result is cached under the node ID, step name, expression,
+    /// template and the current values of every variable the step reads,
+    /// so repeating the step with the same values skips the operation.
+    pub(super) fn execute_memoized(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let key = step_memo_key(step, ctx);
+        if let Some(value) = self.cache_backend()?.get(&key) {
+            tracing::debug!("Memoized result for step: {}", step.step);
+            self.store_output(step, ctx, &value);
+            return Ok(value);
+        }
+
+        let value = self.execute_operation(step, ctx)?;
+        let ttl = step.memo_ttl_seconds.map(Duration::from_secs);
+        self.cache_backend()?.set(&key, value.clone(), ttl);
+        Ok(value)
+    }
+
+    pub(super) fn cache_backend(&self) -> Result<&dyn CacheBackend> {
+        self.state
+            .cache
+            .as_deref()
+            .ok_or_else(|| VesperError::ExecutionError("No cache backend configured".to_string()))
+    }
+}
+
+/// Expiry from a step's `parameters["ttl_seconds"]`, if given
+///
+/// Negative, infinite and NaN durations are rejected rather than passed to
+/// the store.
+pub(super) fn ttl_parameter(step: &FlowStep) -> Result<Option<Duration>> {
+    let Some(seconds) = step.parameters.get("ttl_seconds").and_then(|t| t.as_f64()) else {
+        return Ok(None);
+    };
+    Duration::try_from_secs_f64(seconds).map(Some).map_err(|_| {
+        VesperError::ExecutionError(format!(
+            "Step {} ttl_seconds must be a non-negative number of seconds, got {}",
+            step.step, seconds
+        ))
+    })
+}
+
+/// Cache key for a node result
+///
+/// Hex SHA-256 over the node ID and the `key_inputs` values in name order;
+/// absent inputs hash as null.
+pub(super) fn node_cache_key(
+    node_id: &str,
+    spec: &CacheSpec,
+    inputs: &HashMap<String, Value>,
+) -> String {
+    let mut names: Vec<&String> = spec.key_inputs.iter().collect();
+    names.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(node_id.as_bytes());
+    for name in names {
+        hash_binding(&mut hasher, name, inputs.get(name));
+    }
+    hex_digest(hasher)
+}
+
+/// Key identifying a node execution by node ID and every input value
+pub(super) fn execution_key(node_id: &str, inputs: &HashMap<String, Value>) -> String {
+    let mut names: Vec<&String> = inputs.keys().collect();
+    names.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(node_id.as_bytes());
+    for name in names {
+        hash_binding(&mut hasher, name, inputs.get(name));
+    }
+    hex_digest(hasher)
+}
+
+/// Cache key for a memoized step result
+///
+/// Hex SHA-256 over the node ID, step name, operation, expression,
+/// template, parameters in name order and the values of the referenced
+/// variables in name order; unbound variables hash as null.
+fn step_memo_key(step: &FlowStep, ctx: &ExecutionContext) -> String {
+    let mut names: Vec<String> = step_references(step).into_iter().collect();
+    names.sort();
+    let parameters: BTreeMap<&String, &serde_yaml::Value> = step.parameters.iter().collect();
+    let parameters = serde_yaml::to_string(&parameters).unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    for text in [
+        ctx.node_id(),
+        &step.step,
+        &step.operation,
+        step.expression.as_deref().unwrap_or_default(),
+        step.template.as_deref().unwrap_or_default(),
+        &parameters,
+    ] {
+        hasher.update(text.as_bytes());
+        hasher.update([0]);
+    }
+    for name in &names {
+        hash_binding(&mut hasher, name, ctx.get(name));
+    }
+    hex_digest(hasher)
+}
+
+fn hash_binding(hasher: &mut Sha256, name: &str, value: Option<&Value>) {
+    let value = value
+        .map(serde_json::Value::from)
+        .unwrap_or(serde_json::Value::Null);
+    hasher.update([0]);
+    hasher.update(name.as_bytes());
+    hasher.update([0]);
+    hasher.update(value.to_string().as_bytes());
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCacheBackend;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const LOOKUP: &str = r#"
+node_id: lookup_v1
+type: function
+intent: read a cached profile
+
+inputs:
+  user_id:
+    type: string
+
+flow:
+  - step: read
+    operation: cache_get
+    parameters:
+      key: "{user_id}"
+      default: missing
+"#;
+
+    const STORE: &str = r#"
+node_id: store_v1
+type: function
+intent: cache a profile
+
+inputs:
+  user_id:
+    type: string
+  profile:
+    type: string
+
+flow:
+  - step: write
+    operation: cache_set
+    parameters:
+      key: "{user_id}"
+      value: profile
+      ttl_seconds: 0.05
+"#;
+
+    fn executor(cache: Arc<InMemoryCacheBackend>) -> SemanticExecutor {
+        let loader = VesperLoader::new();
+        let mut executor = SemanticExecutor::new().with_cache(cache);
+        executor.register(loader.load_string(LOOKUP).unwrap());
+        executor.register(loader.load_string(STORE).unwrap());
+        executor
+    }
+
+    fn inputs(profile: Option<&str>) -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        inputs.insert("user_id".to_string(), Value::from("u1"));
+        if let Some(profile) = profile {
+            inputs.insert("profile".to_string(), Value::from(profile));
+        }
+        inputs
+    }
+
+    #[test]
+    fn test_cache_miss_set_and_expiry() {
+        let executor = executor(Arc::new(InMemoryCacheBackend::new()));
+
+        let miss = executor.execute("lookup_v1", inputs(None)).unwrap();
+        assert_eq!(miss.data, Some(Value::from("missing")));
+
+        executor.execute("store_v1", inputs(Some("ada"))).unwrap();
+        let hit = executor.execute("lookup_v1", inputs(None)).unwrap();
+        assert_eq!(hit.data, Some(Value::from("ada")));
+
+        std::thread::sleep(Duration::from_millis(80));
+        let expired = executor.execute("lookup_v1", inputs(None)).unwrap();
+        assert_eq!(expired.data, Some(Value::from("missing")));
+    }
+
+    const QUOTE: &str = r#"
+node_id: quote_v1
+type: function
+intent: quote a price
+
+inputs:
+  sku:
+    type: integer
+  quantity:
+    type: integer
+
+flow:
+  - step: total
+    operation: arithmetic
+    expression: "sku * quantity"
+
+cache:
+  enabled: true
+  ttl_seconds: 60
+  key_inputs: [sku]
+"#;
+
+    fn quote(sku: i64, quantity: i64) -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        inputs.insert("sku".to_string(), Value::Int(sku));
+        inputs.insert("quantity".to_string(), Value::Int(quantity));
+        inputs
+    }
+
+    #[test]
+    fn test_cache_set_rejects_invalid_ttl() {
+        for ttl in ["-1", ".nan", ".inf", "1.0e300"] {
+            let mut executor =
+                SemanticExecutor::new().with_cache(Arc::new(InMemoryCacheBackend::new()));
+            executor.register(
+                VesperLoader::new()
+                    .load_string(&STORE.replace("0.05", ttl))
+                    .unwrap(),
+            );
+            let Err(VesperError::ExecutionError(message)) =
+                executor.execute("store_v1", inputs(Some("ada")))
+            else {
+                panic!("expected execution error for ttl {}", ttl);
+            };
+            assert!(message.contains("ttl_seconds"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_node_result_cache() {
+        let mut executor =
+            SemanticExecutor::new().with_cache(Arc::new(InMemoryCacheBackend::new()));
+        executor.register(VesperLoader::new().load_string(QUOTE).unwrap());
+
+        let miss = executor.execute("quote_v1", quote(3, 2)).unwrap();
+        assert!(!miss.cache_hit);
+        assert_eq!(miss.data, Some(Value::Int(6)));
+
+        // Quantity is not a key input, so the cached result is served
+        let hit = executor.execute("quote_v1", quote(3, 5)).unwrap();
+        assert!(hit.cache_hit);
+        assert_eq!(hit.data, Some(Value::Int(6)));
+
+        let other = executor.execute("quote_v1", quote(4, 5)).unwrap();
+        assert!(!other.cache_hit);
+        assert_eq!(other.data, Some(Value::Int(20)));
+    }
+
+    #[test]
+    fn test_cache_requires_backend() {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(LOOKUP).unwrap());
+        assert!(executor.execute("lookup_v1", inputs(None)).is_err());
+    }
+
+    const MEMO: &str = r#"
+node_id: user_name_v1
+type: function
+intent: look up a user name
+
+inputs:
+  ids:
+    type: array
+
+flow:
+  - step: lookup
+    operation: database_query
+    parameters:
+      sql: "SELECT name FROM users WHERE id = ?"
+      params: ids
+    memoize: true
+    memo_ttl_seconds: 60
+"#;
+
+    /// Database counting the queries it answers
+    #[derive(Default)]
+    struct CountingDatabase {
+        queries: AtomicUsize,
+    }
+
+    impl crate::database::DatabaseBackend for CountingDatabase {
+        fn query(
+            &self,
+            _sql: &str,
+            params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            self.queries.fetch_add(1, Ordering::SeqCst);
+            let mut row = HashMap::new();
+            row.insert("id".to_string(), params[0].clone());
+            Ok(vec![row])
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn test_memo_key_covers_operation_and_parameters() {
+        let step = |yaml: &str| serde_yaml::from_str::<FlowStep>(yaml).unwrap();
+        let ctx = ExecutionContext::new(HashMap::new()).with_node_id("user_name_v1");
+        let key = |yaml: &str| step_memo_key(&step(yaml), &ctx);
+
+        let query = "step: lookup\noperation: database_query\nparameters:\n  sql: SELECT name FROM users\n  params: ids\n";
+        assert_eq!(key(query), key(query));
+        assert_ne!(
+            key(query),
+            key(&query.replace("database_query", "database_execute"))
+        );
+        assert_ne!(
+            key(query),
+            key(&query.replace("SELECT name", "SELECT email"))
+        );
+    }
+
+    #[test]
+    fn test_memoized_step() {
+        let database = Arc::new(CountingDatabase::default());
+        let mut executor = SemanticExecutor::new()
+            .with_cache(Arc::new(InMemoryCacheBackend::new()))
+            .with_database(database.clone());
+        executor.register(VesperLoader::new().load_string(MEMO).unwrap());
+
+        let run = |id: i64| {
+            let mut inputs = HashMap::new();
+            inputs.insert("ids".to_string(), Value::Array(vec![Value::Int(id)]));
+            executor.execute("user_name_v1", inputs).unwrap().data
+        };
+
+        let first = run(1);
+        assert_eq!(run(1), first);
+        assert_eq!(run(1), first);
+        assert_eq!(database.queries.load(Ordering::SeqCst), 1);
+
+        assert_ne!(run(2), first);
+        assert_eq!(database.queries.load(Ordering::SeqCst), 2);
+    }
+}
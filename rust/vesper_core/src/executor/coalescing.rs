@@ -0,0 +1,165 @@
+//! Coalescing of identical concurrent executions
+
+use super::caching::execution_key;
+use super::{ExecutionOptions, ExecutionResult, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::Value;
+use parking_lot::{Condvar, Mutex};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Execution in progress that identical requests wait for
+#[derive(Default)]
+pub(super) struct InFlight {
+    /// Result of the execution once finished; errors as their message
+    outcome: Mutex<Option<std::result::Result<ExecutionResult, String>>>,
+    /// Signalled when `outcome` is set
+    done: Condvar,
+}
+
+/// Executions in progress keyed by node ID and input hash
+pub(super) type InFlightExecutions = Mutex<HashMap<String, Arc<InFlight>>>;
+
+/// The request running an execution on behalf of its waiters
+///
+/// Dropping it releases the waiters, also if the execution panicked.
+struct Leader<'a> {
+    executor: &'a SemanticExecutor,
+    key: String,
+    flight: Arc<InFlight>,
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        self.flight
+            .outcome
+            .lock()
+            .get_or_insert_with(|| Err("Coalesced execution did not complete".to_string()));
+        self.flight.done.notify_all();
+        self.executor.state.in_flight.lock().remove(&self.key);
+    }
+}
+
+impl SemanticExecutor {
+    /// Execute a node, sharing the run of an identical execution already
+    /// in progress
+    ///
+    /// Waiters receive a copy of the leader's `ExecutionResult`, including
+    /// its execution ID; errors reach them as `ExecutionError`s carrying
+    /// the original message.
+    pub(super) fn execute_coalesced(
+        &self,
+        node_id: &str,
+        inputs: HashMap<String, Value>,
+        options: ExecutionOptions,
+    ) -> Result<ExecutionResult> {
+        let key = execution_key(node_id, &inputs);
+        let (flight, leading) = {
+            let mut in_flight = self.state.in_flight.lock();
+            match in_flight.get(&key) {
+                Some(flight) => (flight.clone(), false),
+                None => {
+                    let flight = Arc::new(InFlight::default());
+                    in_flight.insert(key.clone(), flight.clone());
+                    (flight, true)
+                }
+            }
+        };
+
+        if !leading {
+            tracing::debug!("Coalescing execution of {}", node_id);
+            let mut outcome = flight.outcome.lock();
+            loop {
+                if let Some(outcome) = &*outcome {
+                    return outcome.clone().map_err(VesperError::ExecutionError);
+                }
+                flight.done.wait(&mut outcome);
+            }
+        }
+
+        let _leader = Leader {
+            executor: self,
+            key,
+            flight: flight.clone(),
+        };
+        let result = self.execute_once(node_id, inputs, options);
+        *flight.outcome.lock() = Some(result.as_ref().cloned().map_err(|e| e.to_string()));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseBackend;
+    use crate::loader::VesperLoader;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Sleeps 100ms per query, counting queries
+    #[derive(Default)]
+    struct SlowDatabase {
+        queries: AtomicUsize,
+    }
+
+    impl DatabaseBackend for SlowDatabase {
+        fn query(
+            &self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            self.queries.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(100));
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn test_identical_executions_run_once() {
+        let yaml = r#"
+node_id: report_v1
+type: function
+intent: build an expensive report
+
+inputs:
+  month:
+    type: string
+
+flow:
+  - step: load
+    operation: database_query
+    parameters:
+      sql: "SELECT * FROM sales"
+"#;
+        let database = Arc::new(SlowDatabase::default());
+        let mut executor = SemanticExecutor::new()
+            .with_database(database.clone())
+            .with_request_coalescing(true);
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let barrier = std::sync::Barrier::new(10);
+        let results: Vec<ExecutionResult> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..10)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut inputs = HashMap::new();
+                        inputs.insert("month".to_string(), Value::from("2026-09"));
+                        barrier.wait();
+                        executor.execute("report_v1", inputs).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(database.queries.load(Ordering::SeqCst), 1);
+        assert!(results
+            .iter()
+            .all(|r| r.execution_id == results[0].execution_id));
+        assert!(executor.state.in_flight.lock().is_empty());
+    }
+}
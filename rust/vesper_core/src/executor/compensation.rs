@@ -0,0 +1,166 @@
+//! Compensations registered mid-flow and run when the flow fails
+
+use super::parallel::sub_steps;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::Result;
+use crate::types::{FlowStep, Value};
+
+/// Steps undoing the effect of earlier steps
+#[derive(Debug, Clone)]
+pub(super) struct Compensation {
+    /// Step that registered the compensation
+    step: String,
+    /// Steps to run if the flow fails
+    steps: Vec<FlowStep>,
+}
+
+impl SemanticExecutor {
+    /// Execute a compensating transaction step
+    ///
+    /// Registers the steps in `parameters["compensate"]` without running
+    /// them. If a later step fails the flow, every compensation registered
+    /// so far runs, the latest first, before the execution fails with that
+    /// step's error. Registering inside a `conditional` compensates only
+    /// what actually happened. Returns the number of registered
+    /// compensations.
+    pub(super) fn execute_compensating_transaction(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "compensate")?;
+        ctx.compensations.push(Compensation {
+            step: step.step.clone(),
+            steps,
+        });
+
+        let result = Value::Int(ctx.compensations.len() as i64);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Run the compensations registered by a failed flow, latest first
+    ///
+    /// A failing compensation is logged and the others still run.
+    pub(super) fn run_compensations(&self, ctx: &mut ExecutionContext) {
+        while let Some(compensation) = ctx.compensations.pop() {
+            let outcome = compensation
+                .steps
+                .iter()
+                .try_fold(Value::Null, |_, sub_step| self.execute_step(sub_step, ctx));
+            if let Err(e) = outcome {
+                tracing::warn!(
+                    "Compensation registered by step {} failed: {}",
+                    compensation.step,
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseBackend;
+    use crate::error::VesperError;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Records statements, failing those containing `FAIL`
+    #[derive(Default)]
+    struct Ledger {
+        statements: Mutex<Vec<String>>,
+    }
+
+    impl DatabaseBackend for Ledger {
+        fn query(
+            &self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            if sql.contains("FAIL") {
+                return Err(format!("rejected: {}", sql));
+            }
+            self.statements.lock().unwrap().push(sql.to_string());
+            Ok(1)
+        }
+    }
+
+    fn run(ship_sql: &str) -> (Result<Value>, Vec<String>) {
+        let yaml = format!(
+            r#"
+node_id: order_v1
+type: function
+intent: place an order across services
+
+inputs:
+  gift:
+    type: boolean
+
+flow:
+  - step: reserve
+    operation: database_execute
+    parameters:
+      sql: reserve stock
+  - step: undo_reserve
+    operation: compensating_transaction
+    parameters:
+      compensate:
+        - operation: database_execute
+          parameters:
+            sql: release stock
+  - step: wrap
+    operation: conditional
+    condition: "gift == true"
+    then:
+      - operation: database_execute
+        parameters:
+          sql: wrap gift
+      - operation: compensating_transaction
+        parameters:
+          compensate:
+            - operation: database_execute
+              parameters:
+                sql: unwrap gift
+  - step: ship
+    operation: database_execute
+    parameters:
+      sql: "{}"
+"#,
+            ship_sql
+        );
+        let ledger = Arc::new(Ledger::default());
+        let mut executor = SemanticExecutor::new().with_database(ledger.clone());
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let inputs = HashMap::from([("gift".to_string(), Value::Bool(true))]);
+        let result = executor
+            .execute("order_v1", inputs)
+            .map(|r| r.data.unwrap());
+        let statements = ledger.statements.lock().unwrap().clone();
+        (result, statements)
+    }
+
+    #[test]
+    fn test_compensations_run_in_reverse_on_failure() {
+        let (result, statements) = run("ship order");
+        assert!(result.is_ok());
+        assert_eq!(statements, vec!["reserve stock", "wrap gift", "ship order"]);
+
+        let (result, statements) = run("FAIL ship order");
+        let Err(VesperError::ExecutionError(message)) = result else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("FAIL ship order"));
+        assert_eq!(
+            statements,
+            vec!["reserve stock", "wrap gift", "unwrap gift", "release stock"]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Command / query separation checks
+
+use super::parallel::sub_steps;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+/// Fields a command result may contain
+const COMMAND_STATUS_FIELDS: [&str; 2] = ["success", "error"];
+
+impl SemanticExecutor {
+    /// Execute a CQRS command step
+    ///
+    /// Runs the steps in `parameters["steps"]`, whose last result must
+    /// only say whether the command worked: null, a boolean, or an object
+    /// with just `success` and `error` fields. Anything else fails the
+    /// step, since data belongs in a query. On success the command is
+    /// announced on the installed `EventBroker` as `parameters["event_type"]`,
+    /// with the value named by `parameters["payload"]` or, by default, an
+    /// object of the flow inputs.
+    pub(super) fn execute_cqrs_command(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let event_type = self.resolve_string_parameter(step, "event_type", ctx)?;
+        let broker =
+            self.state.event_broker.as_ref().ok_or_else(|| {
+                VesperError::ExecutionError("No event broker configured".to_string())
+            })?;
+
+        let result = steps
+            .iter()
+            .try_fold(Value::Null, |_, sub_step| self.execute_step(sub_step, ctx))?;
+        let is_status = match &result {
+            Value::Null | Value::Bool(_) => true,
+            Value::Object(fields) => fields
+                .keys()
+                .all(|field| COMMAND_STATUS_FIELDS.contains(&field.as_str())),
+            _ => false,
+        };
+        if !is_status {
+            return Err(VesperError::ExecutionError(format!(
+                "Command {} returned data; commands may only report success or error",
+                step.step
+            )));
+        }
+
+        let payload = match step.parameters.get("payload") {
+            Some(_) => self.resolve_parameter_variable(step, "payload", ctx)?,
+            None => Value::Object(ctx.inputs.clone()),
+        };
+        broker.publish(&event_type, payload);
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a CQRS query step
+    ///
+    /// Runs the steps in `parameters["steps"]` against a copy of the
+    /// context and returns the last result. The step fails if they changed
+    /// or removed any variable that existed before; variables they
+    /// introduce are discarded.
+    pub(super) fn execute_cqrs_query(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+
+        let mut query_ctx = ctx.clone();
+        let result = steps.iter().try_fold(Value::Null, |_, sub_step| {
+            self.execute_step(sub_step, &mut query_ctx)
+        })?;
+        let mut mutated: Vec<&str> = ctx
+            .variables
+            .iter()
+            .filter(|(name, value)| query_ctx.variables.get(*name) != Some(*value))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !mutated.is_empty() {
+            mutated.sort_unstable();
+            return Err(VesperError::ExecutionError(format!(
+                "Query {} modified variables: {}",
+                step.step,
+                mutated.join(", ")
+            )));
+        }
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventBroker, EventHandlerExecutor};
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn run(operation: &str, steps: &str) -> (Result<Value>, EventHandlerExecutor) {
+        let yaml = format!(
+            r#"
+node_id: orders_v1
+type: function
+intent: handle an order request
+
+inputs:
+  order_id:
+    type: string
+
+flow:
+  - step: status
+    operation: string_template
+    template: "open"
+    output: status
+  - step: handle
+    operation: {}
+    parameters:
+      event_type: order.placed
+      steps:
+{}
+"#,
+            operation, steps
+        );
+        let notify = r#"
+node_id: notify_v1
+type: event_handler
+intent: confirm placed orders
+
+inputs:
+  order_id:
+    type: string
+
+flow:
+  - step: confirm
+    operation: string_template
+    template: "order {order_id} placed"
+"#;
+        let mut notifier = SemanticExecutor::new();
+        notifier.register(VesperLoader::new().load_string(notify).unwrap());
+        let broker = Arc::new(EventBroker::new());
+        broker.subscribe("notify_v1", "order.placed", Arc::new(notifier));
+        let listener = EventHandlerExecutor::listen(broker.clone()).unwrap();
+
+        let mut executor = SemanticExecutor::new().with_event_broker(broker.clone());
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        let inputs = HashMap::from([("order_id".to_string(), Value::from("o-17"))]);
+        let result = executor
+            .execute("orders_v1", inputs)
+            .map(|r| r.data.unwrap());
+        broker.close();
+        (result, listener)
+    }
+
+    #[test]
+    fn test_cqrs_command_publishes_and_rejects_data() {
+        let (result, listener) = run(
+            "cqrs_command",
+            r#"
+        - step: place
+          operation: string_template
+          template: "placed"
+          output: status
+        - step: done
+          operation: return
+          return_success:
+            success: true
+"#,
+        );
+        let Ok(Value::Object(fields)) = result else {
+            panic!("expected status object");
+        };
+        assert_eq!(fields["success"], Value::Bool(true));
+        let outcome = listener.next_outcome(Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            outcome.result.unwrap().data,
+            Some(Value::from("order o-17 placed"))
+        );
+
+        let (result, listener) = run(
+            "cqrs_command",
+            r#"
+        - step: place
+          operation: string_template
+          template: "order {order_id}"
+"#,
+        );
+        let Err(VesperError::ExecutionError(message)) = result else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("returned data"));
+        assert!(listener.join().is_empty());
+    }
+
+    #[test]
+    fn test_cqrs_query_rejects_mutation() {
+        let (result, _) = run(
+            "cqrs_query",
+            r#"
+        - step: greet
+          operation: string_template
+          template: "order {order_id}"
+          output: title
+        - step: describe
+          operation: return
+          return_success:
+            title: "{title}"
+            status: "{status}"
+"#,
+        );
+        let Ok(Value::Object(fields)) = result else {
+            panic!("expected object result");
+        };
+        assert_eq!(fields["title"], Value::from("order o-17"));
+        assert_eq!(fields["status"], Value::from("open"));
+
+        let (result, _) = run(
+            "cqrs_query",
+            r#"
+        - step: close
+          operation: string_template
+          template: "closed"
+          output: status
+"#,
+        );
+        let Err(VesperError::ExecutionError(message)) = result else {
+            panic!("expected execution error");
+        };
+        assert_eq!(message, "Query handle modified variables: status");
+    }
+}
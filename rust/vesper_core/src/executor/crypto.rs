@@ -0,0 +1,380 @@
+//! Cryptographic operations
+//!
+//! Binary payloads are accepted either as strings (UTF-8 bytes) or as
+//! arrays of byte integers; keys and ciphertexts travel as base64 strings.
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+/// Length of the AES-GCM nonce prepended to ciphertexts
+#[cfg(feature = "crypto")]
+const NONCE_LEN: usize = 12;
+
+impl SemanticExecutor {
+    /// Execute an encryption step
+    ///
+    /// Encrypts `parameters["on"]` with AES-256-GCM under the base64 key
+    /// named by `parameters["key"]`, returning base64 of
+    /// `nonce || ciphertext || tag`.
+    #[cfg(feature = "crypto")]
+    pub(super) fn execute_encrypt(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use aes_gcm::aead::{Aead, AeadCore, OsRng};
+        use base64::Engine;
+
+        let cipher = self.aes_cipher(step, ctx)?;
+        let plaintext = payload_bytes(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+
+        let nonce = aes_gcm::Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| VesperError::ExecutionError("Encryption failed".to_string()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+
+        let result = Value::from(base64::engine::general_purpose::STANDARD.encode(sealed));
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a decryption step
+    ///
+    /// Inverse of `encrypt`. The plaintext is returned as a string when it
+    /// is valid UTF-8 and as an array of bytes otherwise. Tampered or
+    /// truncated input fails authentication.
+    #[cfg(feature = "crypto")]
+    pub(super) fn execute_decrypt(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use aes_gcm::aead::Aead;
+
+        let cipher = self.aes_cipher(step, ctx)?;
+        let sealed = decode_base64(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(VesperError::ExecutionError(
+                "Ciphertext is too short".to_string(),
+            ));
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = cipher
+            .decrypt(aes_gcm::Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                VesperError::ExecutionError("Decryption failed: authentication error".to_string())
+            })?;
+
+        let result = match String::from_utf8(plaintext) {
+            Ok(text) => Value::from(text),
+            Err(e) => Value::Array(
+                e.into_bytes()
+                    .into_iter()
+                    .map(|b| Value::Int(i64::from(b)))
+                    .collect(),
+            ),
+        };
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Build the AES-256-GCM cipher for the key named by `parameters["key"]`
+    #[cfg(feature = "crypto")]
+    fn aes_cipher(&self, step: &FlowStep, ctx: &ExecutionContext) -> Result<aes_gcm::Aes256Gcm> {
+        use aes_gcm::KeyInit;
+
+        let key = decode_base64(&self.resolve_parameter_variable(step, "key", ctx)?)?;
+        aes_gcm::Aes256Gcm::new_from_slice(&key).map_err(|_| {
+            VesperError::ExecutionError(format!(
+                "Encryption key must be 32 bytes, got {}",
+                key.len()
+            ))
+        })
+    }
+
+    /// Execute a signing step
+    ///
+    /// Computes the HMAC-SHA256 of `parameters["message"]` under
+    /// `parameters["key"]` and returns it as a lowercase hex digest.
+    #[cfg(feature = "crypto")]
+    pub(super) fn execute_sign(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use hmac::Mac;
+
+        let mac = self.hmac_sha256(step, ctx)?;
+        let digest: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        let result = Value::from(digest);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a signature verification step
+    ///
+    /// Returns whether the hex `parameters["signature"]` is the HMAC-SHA256
+    /// of `parameters["message"]` under `parameters["key"]`, compared in
+    /// constant time.
+    #[cfg(feature = "crypto")]
+    pub(super) fn execute_verify(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use hmac::Mac;
+
+        let mac = self.hmac_sha256(step, ctx)?;
+        let signature = self.resolve_string_parameter(step, "signature", ctx)?;
+        let valid = decode_hex(signature.trim())
+            .map(|expected| mac.verify_slice(&expected).is_ok())
+            .unwrap_or(false);
+
+        let result = Value::Bool(valid);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Key an HMAC-SHA256 with `parameters["key"]` and feed it
+    /// `parameters["message"]`
+    #[cfg(feature = "crypto")]
+    fn hmac_sha256(
+        &self,
+        step: &FlowStep,
+        ctx: &ExecutionContext,
+    ) -> Result<hmac::Hmac<sha2::Sha256>> {
+        use hmac::Mac;
+
+        let key = self.resolve_string_parameter(step, "key", ctx)?;
+        let message = self.resolve_string_parameter(step, "message", ctx)?;
+
+        // HMAC accepts keys of any length, so this cannot fail
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(key.as_bytes())
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid HMAC key: {}", e)))?;
+        mac.update(message.as_bytes());
+        Ok(mac)
+    }
+
+    /// Execute an encryption step (feature disabled)
+    #[cfg(not(feature = "crypto"))]
+    pub(super) fn execute_encrypt(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "encrypt operation requires the `crypto` feature".to_string(),
+        ))
+    }
+
+    /// Execute a decryption step (feature disabled)
+    #[cfg(not(feature = "crypto"))]
+    pub(super) fn execute_decrypt(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "decrypt operation requires the `crypto` feature".to_string(),
+        ))
+    }
+
+    /// Execute a signing step (feature disabled)
+    #[cfg(not(feature = "crypto"))]
+    pub(super) fn execute_sign(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "sign operation requires the `crypto` feature".to_string(),
+        ))
+    }
+
+    /// Execute a signature verification step (feature disabled)
+    #[cfg(not(feature = "crypto"))]
+    pub(super) fn execute_verify(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "verify operation requires the `crypto` feature".to_string(),
+        ))
+    }
+}
+
+/// Raw bytes of a string or byte-array value
+#[cfg(feature = "crypto")]
+fn payload_bytes(value: &Value) -> Result<Vec<u8>> {
+    match value {
+        Value::String(s) => Ok(s.as_bytes().to_vec()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_int()
+                    .and_then(|i| u8::try_from(i).ok())
+                    .ok_or_else(|| VesperError::TypeError {
+                        expected: "byte".to_string(),
+                        actual: format!("{:?}", item),
+                    })
+            })
+            .collect(),
+        other => Err(VesperError::TypeError {
+            expected: "string or bytes".to_string(),
+            actual: format!("{:?}", other),
+        }),
+    }
+}
+
+#[cfg(feature = "crypto")]
+fn decode_base64(value: &Value) -> Result<Vec<u8>> {
+    use base64::Engine;
+
+    let text = value.as_str().ok_or_else(|| VesperError::TypeError {
+        expected: "base64 string".to_string(),
+        actual: format!("{:?}", value),
+    })?;
+    base64::engine::general_purpose::STANDARD
+        .decode(text.trim())
+        .map_err(|e| VesperError::ExecutionError(format!("Invalid base64: {}", e)))
+}
+
+/// Decode a hex string, or `None` if it is malformed
+#[cfg(feature = "crypto")]
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(all(test, feature = "crypto"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use base64::Engine;
+    use std::collections::HashMap;
+
+    const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+    fn run(operation: &str, on: Value) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: crypt_v1
+type: function
+intent: protect data
+
+inputs:
+  data:
+    type: string
+  key:
+    type: string
+
+flow:
+  - step: transform
+    operation: {}
+    parameters:
+      on: data
+      key: key
+"#,
+            operation
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("data".to_string(), on);
+        inputs.insert("key".to_string(), Value::from(KEY));
+        executor
+            .execute("crypt_v1", inputs)
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let sealed = run("encrypt", Value::from("card 4242")).unwrap();
+        assert_ne!(sealed, Value::from("card 4242"));
+
+        let opened = run("decrypt", sealed).unwrap();
+        assert_eq!(opened, Value::from("card 4242"));
+    }
+
+    #[test]
+    fn test_decrypt_detects_tampering() {
+        let sealed = run("encrypt", Value::from("transfer 100")).unwrap();
+        let engine = base64::engine::general_purpose::STANDARD;
+        let mut bytes = engine.decode(sealed.as_str().unwrap()).unwrap();
+        bytes[NONCE_LEN] ^= 0x01;
+
+        let result = run("decrypt", Value::from(engine.encode(bytes)));
+        assert!(matches!(result, Err(VesperError::ExecutionError(_))));
+    }
+
+    fn run_hmac(operation: &str, signature: &str) -> Value {
+        let yaml = format!(
+            r#"
+node_id: hmac_v1
+type: function
+intent: authenticate a payload
+
+inputs:
+  payload:
+    type: string
+  secret:
+    type: string
+
+flow:
+  - step: authenticate
+    operation: {}
+    parameters:
+      message: "{{payload}}"
+      key: "{{secret}}"
+      signature: "{}"
+"#,
+            operation, signature
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        // RFC 4231 test case 2
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "payload".to_string(),
+            Value::from("what do ya want for nothing?"),
+        );
+        inputs.insert("secret".to_string(), Value::from("Jefe"));
+        executor.execute("hmac_v1", inputs).unwrap().data.unwrap()
+    }
+
+    const RFC4231_DIGEST: &str = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+    #[test]
+    fn test_sign_matches_test_vector() {
+        assert_eq!(run_hmac("sign", ""), Value::from(RFC4231_DIGEST));
+    }
+
+    #[test]
+    fn test_verify() {
+        assert_eq!(run_hmac("verify", RFC4231_DIGEST), Value::Bool(true));
+
+        let mut forged = RFC4231_DIGEST.to_string();
+        forged.replace_range(0..1, "6");
+        assert_eq!(run_hmac("verify", &forged), Value::Bool(false));
+        assert_eq!(run_hmac("verify", "not hex"), Value::Bool(false));
+    }
+}
@@ -0,0 +1,95 @@
+//! Currency conversion operation
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a currency conversion step
+    ///
+    /// Converts `parameters["amount"]` from the `from` currency to the `to`
+    /// currency using the installed `ExchangeRateProvider`.
+    pub(super) fn execute_currency_convert(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let provider = self.state.exchange_rates.as_deref().ok_or_else(|| {
+            VesperError::ExecutionError("No exchange rate provider configured".to_string())
+        })?;
+
+        let amount = self.resolve_parameter_variable(step, "amount", ctx)?;
+        let amount = amount.as_float().ok_or_else(|| VesperError::TypeError {
+            expected: "number".to_string(),
+            actual: format!("{:?}", amount),
+        })?;
+        let from = currency_code(&self.resolve_string_parameter(step, "from", ctx)?)?;
+        let to = currency_code(&self.resolve_string_parameter(step, "to", ctx)?)?;
+
+        let rate = provider
+            .get_rate(&from, &to)
+            .map_err(VesperError::ExecutionError)?;
+
+        let result = Value::Float(amount * rate);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Normalize and check an ISO 4217 currency code
+fn currency_code(code: &str) -> Result<String> {
+    let code = code.trim().to_uppercase();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(VesperError::ExecutionError(format!(
+            "Invalid currency code: {}",
+            code
+        )));
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::StaticRateProvider;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_currency_convert() {
+        let yaml = r#"
+node_id: convert_v1
+type: function
+intent: convert a price
+
+inputs:
+  price:
+    type: decimal
+  target:
+    type: string
+
+flow:
+  - step: convert
+    operation: currency_convert
+    parameters:
+      amount: price
+      from: USD
+      to: "{target}"
+"#;
+        let rates = StaticRateProvider::new().with_rate("USD", "EUR", 0.92);
+        let mut executor = SemanticExecutor::new().with_exchange_rates(Arc::new(rates));
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("price".to_string(), Value::Int(100));
+        inputs.insert("target".to_string(), Value::from("eur"));
+        let result = executor.execute("convert_v1", inputs.clone()).unwrap();
+
+        let converted = result.data.unwrap().as_float().unwrap();
+        assert!((converted - 92.0).abs() < 1e-9);
+
+        inputs.insert("target".to_string(), Value::from("JPY"));
+        assert!(executor.execute("convert_v1", inputs).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Database operations backed by the executor's `DatabaseBackend`
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::database::DatabaseBackend;
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a database query step
+    ///
+    /// Runs `parameters["sql"]` with the array named by
+    /// `parameters["params"]` bound positionally and returns the rows as
+    /// an array of objects.
+    pub(super) fn execute_database_query(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let sql = self.resolve_string_parameter(step, "sql", ctx)?;
+        let params = self.statement_params(step, ctx)?;
+
+        let rows = self
+            .database()?
+            .query(&sql, params)
+            .map_err(|e| VesperError::ExecutionError(format!("Database query failed: {}", e)))?;
+
+        let result = Value::Array(rows.into_iter().map(Value::Object).collect());
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a database statement step
+    ///
+    /// Runs `parameters["sql"]` like `database_query` and returns the
+    /// number of rows affected.
+    pub(super) fn execute_database_execute(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let sql = self.resolve_string_parameter(step, "sql", ctx)?;
+        let params = self.statement_params(step, ctx)?;
+
+        let affected = self.database()?.execute(&sql, params).map_err(|e| {
+            VesperError::ExecutionError(format!("Database statement failed: {}", e))
+        })?;
+
+        let result = Value::Int(affected as i64);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Resolve the optional `params` array of a statement
+    fn statement_params(&self, step: &FlowStep, ctx: &ExecutionContext) -> Result<Vec<Value>> {
+        if !step.parameters.contains_key("params") {
+            return Ok(Vec::new());
+        }
+        match self.resolve_parameter_variable(step, "params", ctx)? {
+            Value::Array(params) => Ok(params),
+            other => Err(VesperError::TypeError {
+                expected: "array".to_string(),
+                actual: format!("{:?}", other),
+            }),
+        }
+    }
+
+    fn database(&self) -> Result<&dyn DatabaseBackend> {
+        self.state.database.as_deref().ok_or_else(|| {
+            VesperError::ExecutionError("No database backend configured".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Records statements and answers queries with one fixed row
+    #[derive(Default)]
+    struct MockDatabase {
+        statements: Mutex<Vec<(String, Vec<Value>)>>,
+    }
+
+    impl DatabaseBackend for MockDatabase {
+        fn query(
+            &self,
+            sql: &str,
+            params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            self.statements
+                .lock()
+                .unwrap()
+                .push((sql.to_string(), params.clone()));
+            let mut row = HashMap::new();
+            row.insert("id".to_string(), params[0].clone());
+            row.insert("name".to_string(), Value::from("Ada"));
+            Ok(vec![row])
+        }
+
+        fn execute(&self, sql: &str, params: Vec<Value>) -> std::result::Result<u64, String> {
+            self.statements
+                .lock()
+                .unwrap()
+                .push((sql.to_string(), params));
+            Ok(3)
+        }
+    }
+
+    #[test]
+    fn test_database_query_and_execute() {
+        let yaml = r#"
+node_id: users_v1
+type: function
+intent: look up and touch a user
+
+inputs:
+  args:
+    type: array
+
+flow:
+  - step: touch
+    operation: database_execute
+    parameters:
+      sql: UPDATE users SET seen = now() WHERE id = $1
+      params: args
+  - step: load
+    operation: database_query
+    parameters:
+      sql: SELECT id, name FROM users WHERE id = $1
+      params: args
+"#;
+        let database = Arc::new(MockDatabase::default());
+        let mut executor = SemanticExecutor::new().with_database(database.clone());
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("args".to_string(), Value::Array(vec![Value::Int(7)]));
+        let result = executor.execute("users_v1", inputs).unwrap();
+
+        let Some(Value::Array(rows)) = result.data else {
+            panic!("expected array result");
+        };
+        let Value::Object(row) = &rows[0] else {
+            panic!("expected object row");
+        };
+        assert_eq!(row["id"], Value::Int(7));
+        assert_eq!(row["name"], Value::from("Ada"));
+
+        let statements = database.statements.lock().unwrap();
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].0.starts_with("UPDATE"));
+        assert_eq!(statements[0].1, vec![Value::Int(7)]);
+    }
+}
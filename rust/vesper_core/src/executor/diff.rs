@@ -0,0 +1,164 @@
+//! Structured comparison of values
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::Result;
+use crate::types::{FlowStep, Value};
+use std::collections::{BTreeSet, HashMap};
+
+impl SemanticExecutor {
+    /// Execute a value diff step
+    ///
+    /// Compares the values named by `parameters["expected"]` and
+    /// `parameters["actual"]`. Returns null if they are equal, and otherwise
+    /// an object with both values and the `differences`: one entry per
+    /// dot-separated `path` (empty for the values themselves) where they
+    /// differ, with the `expected_value` and `actual_value` there. Objects
+    /// are compared key by key and arrays element by element; a value
+    /// missing on one side is reported as null.
+    pub(super) fn execute_diff_values(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let expected = self.resolve_parameter_variable(step, "expected", ctx)?;
+        let actual = self.resolve_parameter_variable(step, "actual", ctx)?;
+
+        let result = if expected == actual {
+            Value::Null
+        } else {
+            let mut differences = Vec::new();
+            collect_differences(
+                String::new(),
+                Some(&expected),
+                Some(&actual),
+                &mut differences,
+            );
+            let mut diff = HashMap::new();
+            diff.insert("expected".to_string(), expected);
+            diff.insert("actual".to_string(), actual);
+            diff.insert("differences".to_string(), Value::Array(differences));
+            Value::Object(diff)
+        };
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Append an entry for every path under `path` where the values differ
+pub(super) fn collect_differences(
+    path: String,
+    expected: Option<&Value>,
+    actual: Option<&Value>,
+    out: &mut Vec<Value>,
+) {
+    if expected == actual {
+        return;
+    }
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (expected, actual) {
+        (Some(Value::Object(expected)), Some(Value::Object(actual))) => {
+            let keys: BTreeSet<&String> = expected.keys().chain(actual.keys()).collect();
+            for key in keys {
+                collect_differences(child(key), expected.get(key), actual.get(key), out);
+            }
+        }
+        (Some(Value::Array(expected)), Some(Value::Array(actual))) => {
+            for i in 0..expected.len().max(actual.len()) {
+                collect_differences(child(&i.to_string()), expected.get(i), actual.get(i), out);
+            }
+        }
+        _ => {
+            let mut difference = HashMap::new();
+            difference.insert("path".to_string(), Value::from(path));
+            difference.insert(
+                "expected_value".to_string(),
+                expected.cloned().unwrap_or(Value::Null),
+            );
+            difference.insert(
+                "actual_value".to_string(),
+                actual.cloned().unwrap_or(Value::Null),
+            );
+            out.push(Value::Object(difference));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    const COMPARE: &str = r#"
+node_id: compare_v1
+type: function
+intent: compare a response with a fixture
+
+inputs:
+  expected:
+    type: object
+  actual:
+    type: object
+
+flow:
+  - step: diff
+    operation: diff_values
+    parameters:
+      expected: expected
+      actual: actual
+"#;
+
+    fn diff(expected: serde_json::Value, actual: serde_json::Value) -> Value {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(COMPARE).unwrap());
+        let mut inputs = HashMap::new();
+        inputs.insert("expected".to_string(), Value::from(expected));
+        inputs.insert("actual".to_string(), Value::from(actual));
+        executor
+            .execute("compare_v1", inputs)
+            .unwrap()
+            .data
+            .unwrap()
+    }
+
+    #[test]
+    fn test_equal_values() {
+        let value = serde_json::json!({"id": 1, "tags": ["a", "b"]});
+        assert_eq!(diff(value.clone(), value), Value::Null);
+    }
+
+    #[test]
+    fn test_differences() {
+        let expected = serde_json::json!({
+            "id": 1,
+            "user": {"name": "ada", "roles": ["admin", "dev"]},
+            "active": true,
+        });
+        let actual = serde_json::json!({
+            "id": 1,
+            "user": {"name": "ada", "roles": ["admin"], "email": "a@b.c"},
+            "active": false,
+        });
+        let Value::Object(result) = diff(expected.clone(), actual.clone()) else {
+            panic!("expected a diff object");
+        };
+        assert_eq!(result["expected"], Value::from(expected));
+        assert_eq!(result["actual"], Value::from(actual));
+
+        let differences = serde_json::Value::from(&result["differences"]);
+        assert_eq!(
+            differences,
+            serde_json::json!([
+                {"path": "active", "expected_value": true, "actual_value": false},
+                {"path": "user.email", "expected_value": null, "actual_value": "a@b.c"},
+                {"path": "user.roles.1", "expected_value": "dev", "actual_value": null},
+            ])
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Distribution of steps over remote executors
+
+use super::parallel::sub_steps;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::remote::RemoteExecutorClient;
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+
+impl SemanticExecutor {
+    /// Execute a load-balanced step
+    ///
+    /// Sends each step in `parameters["steps"]` to one of the remote
+    /// executors named in `parameters["executors"]`, chosen by
+    /// `parameters["strategy"]`:
+    ///
+    /// - `round_robin` (default): in turn, continuing across executions
+    /// - `random`: uniformly at random
+    /// - `least_loaded`: the one reporting the fewest running steps
+    ///
+    /// Each step receives the current bindings and its result is stored
+    /// under its output variable. Returns the result of the last step.
+    pub(super) fn execute_load_balance(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let names: Vec<&str> = match step.parameters.get("executors") {
+            Some(serde_yaml::Value::Sequence(names)) => {
+                names.iter().filter_map(|name| name.as_str()).collect()
+            }
+            _ => Vec::new(),
+        };
+        let clients: Vec<(&str, &dyn RemoteExecutorClient)> = names
+            .iter()
+            .map(|name| {
+                self.state
+                    .remote_executors
+                    .get(*name)
+                    .map(|client| (*name, client.as_ref()))
+                    .ok_or_else(|| {
+                        VesperError::ExecutionError(format!("Unknown remote executor: {}", name))
+                    })
+            })
+            .collect::<Result<_>>()?;
+        if clients.is_empty() {
+            return Err(VesperError::ExecutionError(format!(
+                "Step {} needs a list of executors",
+                step.step
+            )));
+        }
+        let strategy = step
+            .parameters
+            .get("strategy")
+            .and_then(|v| v.as_str())
+            .unwrap_or("round_robin");
+        let cursor_key = format!("{}/{}", ctx.node_id(), step.step);
+
+        let mut result = Value::Null;
+        for sub_step in &steps {
+            let index = match strategy {
+                "round_robin" => {
+                    let mut cursors = self.state.balancer_cursors.lock();
+                    let cursor = cursors.entry(cursor_key.clone()).or_insert(0);
+                    let index = *cursor % clients.len();
+                    *cursor = cursor.wrapping_add(1);
+                    index
+                }
+                "random" => (uuid::Uuid::new_v4().as_u128() % clients.len() as u128) as usize,
+                "least_loaded" => (0..clients.len())
+                    .min_by_key(|&i| clients[i].1.load())
+                    .unwrap_or(0),
+                other => {
+                    return Err(VesperError::ExecutionError(format!(
+                        "Unknown load balancing strategy: {}",
+                        other
+                    )))
+                }
+            };
+
+            let (name, client) = clients[index];
+            tracing::debug!("Sending step {} to executor {}", sub_step.step, name);
+            result = client
+                .execute_step(sub_step, &ctx.bindings())
+                .map_err(|e| {
+                    VesperError::ExecutionError(format!(
+                        "Remote executor {} failed step {}: {}",
+                        name, sub_step.step, e
+                    ))
+                })?;
+            self.store_output(sub_step, ctx, &result);
+        }
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Run one step against `bindings` outside of a node execution
+    pub(crate) fn execute_standalone_step(
+        &self,
+        step: &FlowStep,
+        bindings: HashMap<String, Value>,
+    ) -> Result<Value> {
+        let mut ctx = ExecutionContext::new(bindings);
+        self.execute_step(step, &mut ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use crate::remote::InProcessExecutorClient;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Client recording which steps it ran, with a fixed reported load
+    struct RecordingClient {
+        inner: InProcessExecutorClient,
+        steps: Arc<Mutex<Vec<String>>>,
+        load: AtomicUsize,
+    }
+
+    impl RemoteExecutorClient for RecordingClient {
+        fn execute_step(
+            &self,
+            step: &FlowStep,
+            bindings: &HashMap<String, Value>,
+        ) -> std::result::Result<Value, String> {
+            self.steps.lock().unwrap().push(step.step.clone());
+            self.inner.execute_step(step, bindings)
+        }
+
+        fn load(&self) -> usize {
+            self.load.load(Ordering::SeqCst)
+        }
+    }
+
+    fn run(strategy: &str, loads: [usize; 3]) -> (Value, Vec<Vec<String>>) {
+        let yaml = format!(
+            r#"
+node_id: spread_v1
+type: function
+intent: spread work over workers
+
+inputs:
+  x:
+    type: integer
+
+flow:
+  - step: spread
+    operation: load_balance
+    parameters:
+      executors: [a, b, c]
+      strategy: {strategy}
+      steps:
+        - step: one
+          operation: arithmetic
+          expression: "x + 1"
+          output: y
+        - step: two
+          operation: arithmetic
+          expression: "y * 2"
+          output: z
+        - step: three
+          operation: arithmetic
+          expression: "z + 1"
+          output: w
+        - step: four
+          operation: arithmetic
+          expression: "w * 10"
+"#
+        );
+        let worker = Arc::new(SemanticExecutor::new());
+        let records: Vec<Arc<Mutex<Vec<String>>>> = (0..3).map(|_| Arc::default()).collect();
+        let clients: HashMap<String, Box<dyn RemoteExecutorClient>> = ["a", "b", "c"]
+            .into_iter()
+            .zip(&records)
+            .zip(loads)
+            .map(|((name, steps), load)| {
+                let client: Box<dyn RemoteExecutorClient> = Box::new(RecordingClient {
+                    inner: InProcessExecutorClient::new(worker.clone()),
+                    steps: steps.clone(),
+                    load: AtomicUsize::new(load),
+                });
+                (name.to_string(), client)
+            })
+            .collect();
+
+        let mut executor = SemanticExecutor::new().with_remote_executors(clients);
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        let mut inputs = HashMap::new();
+        inputs.insert("x".to_string(), Value::Int(2));
+        let result = executor.execute("spread_v1", inputs).unwrap().data.unwrap();
+        let records = records.iter().map(|r| r.lock().unwrap().clone()).collect();
+        (result, records)
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let (result, records) = run("round_robin", [0, 0, 0]);
+        assert_eq!(result, Value::Int(70));
+        assert_eq!(
+            records,
+            vec![vec!["one", "four"], vec!["two"], vec!["three"]]
+        );
+    }
+
+    #[test]
+    fn test_least_loaded() {
+        let (result, records) = run("least_loaded", [3, 1, 2]);
+        assert_eq!(result, Value::Int(70));
+        assert_eq!(records[1].len(), 4);
+    }
+
+    #[test]
+    fn test_random() {
+        let (result, records) = run("random", [0, 0, 0]);
+        assert_eq!(result, Value::Int(70));
+        assert_eq!(records.iter().map(Vec::len).sum::<usize>(), 4);
+    }
+}
@@ -0,0 +1,241 @@
+//! Event sourcing: appending events and folding them into state
+
+use super::parallel::sub_steps;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::event_store::{EventStore, StoredEvent};
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+
+impl SemanticExecutor {
+    /// Execute an event append step
+    ///
+    /// Appends an event of type `parameters["event_type"]` with the value
+    /// named by `parameters["payload"]` to the log of
+    /// `parameters["entity_id"]` in the installed `EventStore`, and
+    /// returns the stored event.
+    pub(super) fn execute_event_sourcing_append(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let entity_id = self.resolve_string_parameter(step, "entity_id", ctx)?;
+        let event_type = self.resolve_string_parameter(step, "event_type", ctx)?;
+        let payload = self.resolve_parameter_variable(step, "payload", ctx)?;
+
+        let event = StoredEvent {
+            entity_id,
+            event_type,
+            payload,
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        };
+        let result = event.to_value();
+        self.event_store()?
+            .append(event)
+            .map_err(VesperError::ExecutionError)?;
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute an event replay step
+    ///
+    /// Folds the events of `parameters["entity_id"]`, oldest first, into a
+    /// state object starting from `parameters["initial"]` (default empty).
+    /// For each event the steps in `parameters["reducer"]` run against a
+    /// copy of the context with the state's fields bound as variables, then
+    /// `event_type`, `timestamp`, `payload` and the payload's fields, which
+    /// shadow state fields of the same name. `state` and `event` hold the
+    /// whole state and event. The reducer's last result, which must be an
+    /// object, becomes the next state. Returns the final state.
+    pub(super) fn execute_event_sourcing_replay(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let reducer = sub_steps(step, "reducer")?;
+        let entity_id = self.resolve_string_parameter(step, "entity_id", ctx)?;
+        let mut state = match step.parameters.get("initial") {
+            None => HashMap::new(),
+            Some(_) => match self.resolve_parameter_variable(step, "initial", ctx)? {
+                Value::Object(fields) => fields,
+                other => {
+                    return Err(VesperError::TypeError {
+                        expected: "object".to_string(),
+                        actual: format!("{:?}", other),
+                    })
+                }
+            },
+        };
+        let events = self
+            .event_store()?
+            .events(&entity_id)
+            .map_err(VesperError::ExecutionError)?;
+
+        for event in &events {
+            let mut reducer_ctx = ctx.clone();
+            for (name, value) in &state {
+                reducer_ctx.set(name.clone(), value.clone());
+            }
+            reducer_ctx.set(
+                "event_type".to_string(),
+                Value::from(event.event_type.as_str()),
+            );
+            reducer_ctx.set(
+                "timestamp".to_string(),
+                Value::from(event.timestamp.as_str()),
+            );
+            reducer_ctx.set("payload".to_string(), event.payload.clone());
+            if let Value::Object(fields) = &event.payload {
+                for (name, value) in fields {
+                    reducer_ctx.set(name.clone(), value.clone());
+                }
+            }
+            reducer_ctx.set("state".to_string(), Value::Object(state.clone()));
+            reducer_ctx.set("event".to_string(), event.to_value());
+
+            state = match reducer.iter().try_fold(Value::Null, |_, sub_step| {
+                self.execute_step(sub_step, &mut reducer_ctx)
+            })? {
+                Value::Object(fields) => fields,
+                other => {
+                    return Err(VesperError::TypeError {
+                        expected: "object".to_string(),
+                        actual: format!("{:?}", other),
+                    })
+                }
+            };
+        }
+        tracing::debug!("Replayed {} events of {}", events.len(), entity_id);
+
+        let result = Value::Object(state);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// The installed event store, or an error if there is none
+    fn event_store(&self) -> Result<&dyn EventStore> {
+        self.state
+            .event_store
+            .as_deref()
+            .ok_or_else(|| VesperError::ExecutionError("No event store configured".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_store::InMemoryEventStore;
+    use crate::loader::VesperLoader;
+    use std::sync::Arc;
+
+    const ACCOUNT: &str = r#"
+node_id: account_v1
+type: state_machine
+intent: record account movements and rebuild the balance
+
+inputs:
+  account:
+    type: string
+  kind:
+    type: string
+    required: false
+  movement:
+    type: object
+    required: false
+
+flow:
+  - step: record
+    operation: event_sourcing_append
+    parameters:
+      entity_id: "{account}"
+      event_type: "{kind}"
+      payload: movement
+"#;
+
+    const BALANCE: &str = r#"
+node_id: balance_v1
+type: function
+intent: rebuild an account balance from its movements
+
+inputs:
+  account:
+    type: string
+
+flow:
+  - step: replay
+    operation: event_sourcing_replay
+    parameters:
+      entity_id: "{account}"
+      initial: { balance: 0, movements: 0 }
+      reducer:
+        - step: apply
+          operation: conditional
+          condition: "event_type == 'deposited'"
+          then:
+            - operation: arithmetic
+              expression: "balance + amount"
+              output: balance
+          else:
+            - operation: arithmetic
+              expression: "balance - amount"
+              output: balance
+        - step: count
+          operation: arithmetic
+          expression: "movements + 1"
+          output: movements
+        - step: next
+          operation: return
+          return_success:
+            balance: "{balance}"
+            movements: "{movements}"
+"#;
+
+    #[test]
+    fn test_replay_accumulates_appended_events() {
+        let store = Arc::new(InMemoryEventStore::new());
+        let mut executor = SemanticExecutor::new().with_event_store(store.clone());
+        let loader = VesperLoader::new();
+        executor.register(loader.load_string(ACCOUNT).unwrap());
+        executor.register(loader.load_string(BALANCE).unwrap());
+
+        let account = |id: &str| ("account".to_string(), Value::from(id));
+        for (kind, amount) in [
+            ("deposited", 100),
+            ("deposited", 50),
+            ("withdrawn", 30),
+            ("deposited", 5),
+            ("withdrawn", 25),
+        ] {
+            let movement = HashMap::from([("amount".to_string(), Value::Int(amount))]);
+            let inputs = HashMap::from([
+                account("acc-1"),
+                ("kind".to_string(), Value::from(kind)),
+                ("movement".to_string(), Value::Object(movement)),
+            ]);
+            executor.execute("account_v1", inputs).unwrap();
+        }
+        let events = store.events("acc-1").unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[2].event_type, "withdrawn");
+
+        let balance = |id: &str| {
+            let inputs = HashMap::from([account(id)]);
+            executor
+                .execute("balance_v1", inputs)
+                .unwrap()
+                .data
+                .unwrap()
+        };
+        let Value::Object(state) = balance("acc-1") else {
+            panic!("expected object state");
+        };
+        assert_eq!(state["balance"], Value::Int(100));
+        assert_eq!(state["movements"], Value::Int(5));
+
+        let Value::Object(empty) = balance("acc-2") else {
+            panic!("expected object state");
+        };
+        assert_eq!(empty["balance"], Value::Int(0));
+    }
+}
@@ -0,0 +1,394 @@
+//! Side-by-side execution of node variants
+
+use super::diff::collect_differences;
+use super::{ExecutionContext, ExecutionWarning, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::experiments::VariantComparison;
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+
+impl SemanticExecutor {
+    /// Execute an A/B test step
+    ///
+    /// Runs the node `parameters["control"]` with the object named by
+    /// `parameters["inputs"]` and returns its result. A fraction
+    /// `parameters["sample_rate"]` (default 1.0) of executions also runs
+    /// `parameters["treatment"]` on the same inputs, concurrently with the
+    /// control. Its result never reaches the caller and its errors are
+    /// ignored; differences are logged and passed to the installed
+    /// `ResultComparator`. The comparison is reported from a background
+    /// thread unless `parameters["synchronous"]` is true.
+    pub(super) fn execute_a_b_test(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let control = self.resolve_string_parameter(step, "control", ctx)?;
+        let treatment = self.resolve_string_parameter(step, "treatment", ctx)?;
+        let inputs = self.variant_inputs(step, ctx)?;
+        let sample_rate = match step.parameters.get("sample_rate") {
+            Some(rate) => match rate.as_f64() {
+                Some(rate) if (0.0..=1.0).contains(&rate) => rate,
+                _ => {
+                    return Err(VesperError::ExecutionError(format!(
+                        "Step {} sample_rate must be between 0.0 and 1.0",
+                        step.step
+                    )))
+                }
+            },
+            None => 1.0,
+        };
+        let synchronous = step
+            .parameters
+            .get("synchronous")
+            .and_then(|s| s.as_bool())
+            .unwrap_or(false);
+
+        if rand::random::<f64>() >= sample_rate {
+            let result = self.run_variant(&control, inputs)?;
+            self.store_output(step, ctx, &result);
+            return Ok(result);
+        }
+
+        let (control_result, treatment_result) = self.run_alongside(&control, &treatment, inputs);
+        let control_result = control_result?;
+        let differences = match &treatment_result {
+            Ok(treatment_value) => result_differences(&control_result, treatment_value),
+            Err(_) => Vec::new(),
+        };
+        let comparison = VariantComparison {
+            control,
+            treatment,
+            control_result: control_result.clone(),
+            treatment_result: treatment_result.map_err(|e| e.to_string()),
+            differences,
+        };
+        let comparator = self.state.result_comparator.clone();
+        let report = move || {
+            match &comparison.treatment_result {
+                Err(e) => tracing::warn!("A/B treatment {} failed: {}", comparison.treatment, e),
+                Ok(_) if !comparison.matches() => tracing::warn!(
+                    "A/B treatment {} differs from control {} at {} paths",
+                    comparison.treatment,
+                    comparison.control,
+                    comparison.differences.len()
+                ),
+                Ok(_) => {}
+            }
+            if let Some(comparator) = comparator {
+                comparator.record(&comparison);
+            }
+        };
+        if synchronous {
+            report();
+        } else {
+            std::thread::spawn(report);
+        }
+
+        self.store_output(step, ctx, &control_result);
+        Ok(control_result)
+    }
+
+    /// Execute a shadow mode step
+    ///
+    /// Runs the node `parameters["primary"]` with the object named by
+    /// `parameters["inputs"]` and returns its result, while running
+    /// `parameters["shadow"]` on the same inputs concurrently. Shadow
+    /// errors are logged and otherwise ignored. Unless
+    /// `parameters["compare"]` is false, a differing shadow result is
+    /// recorded as an `ExecutionWarning::ShadowDivergence` and, if
+    /// `parameters["event_type"]` is set, published to the installed
+    /// `EventBroker` as an object with `primary`, `shadow` and
+    /// `differences`.
+    pub(super) fn execute_shadow_mode(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let primary = self.resolve_string_parameter(step, "primary", ctx)?;
+        let shadow = self.resolve_string_parameter(step, "shadow", ctx)?;
+        let inputs = self.variant_inputs(step, ctx)?;
+        let compare = step
+            .parameters
+            .get("compare")
+            .and_then(|c| c.as_bool())
+            .unwrap_or(true);
+        let event_type = match step.parameters.get("event_type") {
+            Some(_) => Some(self.resolve_string_parameter(step, "event_type", ctx)?),
+            None => None,
+        };
+
+        let (primary_result, shadow_result) = self.run_alongside(&primary, &shadow, inputs);
+        let primary_result = primary_result?;
+        match shadow_result {
+            Err(e) => tracing::debug!("Shadow {} of {} failed: {}", shadow, primary, e),
+            Ok(shadow_value) if compare => {
+                let differences = result_differences(&primary_result, &shadow_value);
+                if !differences.is_empty() {
+                    tracing::info!(
+                        "Shadow {} differs from {} at {} paths",
+                        shadow,
+                        primary,
+                        differences.len()
+                    );
+                    if let (Some(broker), Some(event_type)) =
+                        (&self.state.event_broker, &event_type)
+                    {
+                        let mut payload = HashMap::new();
+                        payload.insert("primary".to_string(), Value::from(primary.as_str()));
+                        payload.insert("shadow".to_string(), Value::from(shadow.as_str()));
+                        payload
+                            .insert("differences".to_string(), Value::Array(differences.clone()));
+                        broker.publish(event_type, Value::Object(payload));
+                    }
+                    ctx.add_warning(ExecutionWarning::ShadowDivergence {
+                        primary,
+                        shadow,
+                        differences,
+                    });
+                }
+            }
+            Ok(_) => {}
+        }
+
+        self.store_output(step, ctx, &primary_result);
+        Ok(primary_result)
+    }
+
+    /// Inputs object named by `parameters["inputs"]`
+    fn variant_inputs(
+        &self,
+        step: &FlowStep,
+        ctx: &ExecutionContext,
+    ) -> Result<HashMap<String, Value>> {
+        match self.resolve_parameter_variable(step, "inputs", ctx)? {
+            Value::Object(inputs) => Ok(inputs),
+            other => Err(VesperError::TypeError {
+                expected: "object".to_string(),
+                actual: format!("{:?}", other),
+            }),
+        }
+    }
+
+    /// Result data of executing `node_id`
+    fn run_variant(&self, node_id: &str, inputs: HashMap<String, Value>) -> Result<Value> {
+        self.execute(node_id, inputs)
+            .map(|r| r.data.unwrap_or(Value::Null))
+    }
+
+    /// Run `candidate` on a scoped thread while `current` runs on this one
+    fn run_alongside(
+        &self,
+        current: &str,
+        candidate: &str,
+        inputs: HashMap<String, Value>,
+    ) -> (Result<Value>, Result<Value>) {
+        std::thread::scope(|scope| {
+            let candidate_inputs = inputs.clone();
+            let handle = scope.spawn(move || self.run_variant(candidate, candidate_inputs));
+            let current_result = self.run_variant(current, inputs);
+            let candidate_result = handle
+                .join()
+                .unwrap_or_else(|_| Err(VesperError::ExecutionError("Node panicked".to_string())));
+            (current_result, candidate_result)
+        })
+    }
+}
+
+/// Paths where `candidate` differs from `current`, as in `diff_values`
+fn result_differences(current: &Value, candidate: &Value) -> Vec<Value> {
+    let mut differences = Vec::new();
+    collect_differences(
+        String::new(),
+        Some(current),
+        Some(candidate),
+        &mut differences,
+    );
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventBroker, EventHandlerExecutor};
+    use crate::executor::ExecutionResult;
+    use crate::experiments::InMemoryResultComparator;
+    use crate::loader::VesperLoader;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Register the experiment node and pricing variants computing
+    /// `qty * 2` (v1), `qty * 3` (v2) and `qty + qty` (v3)
+    fn register_pricers(executor: &mut SemanticExecutor, experiment: &str) {
+        let pricer = |node_id: &str, expression: &str| {
+            format!(
+                r#"
+node_id: {}
+type: function
+intent: quote a price
+
+inputs:
+  qty:
+    type: integer
+
+flow:
+  - step: price
+    operation: arithmetic
+    expression: "{}"
+"#,
+                node_id, expression
+            )
+        };
+        let loader = VesperLoader::new();
+        for node in [
+            experiment.to_string(),
+            pricer("price_v1", "qty * 2"),
+            pricer("price_v2", "qty * 3"),
+            pricer("price_v3", "qty + qty"),
+        ] {
+            executor.register(loader.load_string(&node).unwrap());
+        }
+    }
+
+    fn request(qty: i64) -> HashMap<String, Value> {
+        let request = HashMap::from([("qty".to_string(), Value::Int(qty))]);
+        HashMap::from([("request".to_string(), Value::Object(request))])
+    }
+
+    fn a_b_test(treatment: &str, sample_rate: f64) -> (Value, Arc<InMemoryResultComparator>) {
+        let yaml = format!(
+            r#"
+node_id: experiment_v1
+type: function
+intent: compare pricing variants
+
+inputs:
+  request:
+    type: object
+
+flow:
+  - step: compare
+    operation: a_b_test
+    parameters:
+      control: price_v1
+      treatment: {}
+      inputs: request
+      sample_rate: {}
+      synchronous: true
+"#,
+            treatment, sample_rate
+        );
+
+        let comparator = Arc::new(InMemoryResultComparator::new());
+        let mut executor = SemanticExecutor::new().with_result_comparator(comparator.clone());
+        register_pricers(&mut executor, &yaml);
+        let result = executor.execute("experiment_v1", request(3)).unwrap();
+        (result.data.unwrap(), comparator)
+    }
+
+    #[test]
+    fn test_a_b_test_reports_differences() {
+        let (result, comparator) = a_b_test("price_v2", 1.0);
+        assert_eq!(result, Value::Int(6));
+
+        let comparisons = comparator.comparisons();
+        assert_eq!(comparisons.len(), 1);
+        assert_eq!(comparisons[0].treatment_result, Ok(Value::Int(9)));
+        assert_eq!(comparisons[0].differences.len(), 1);
+        assert!(!comparisons[0].matches());
+
+        let (_, comparator) = a_b_test("price_v3", 1.0);
+        assert!(comparator.comparisons()[0].matches());
+    }
+
+    #[test]
+    fn test_a_b_test_sample_rate() {
+        let (result, comparator) = a_b_test("price_v2", 0.0);
+        assert_eq!(result, Value::Int(6));
+        assert!(comparator.comparisons().is_empty());
+    }
+
+    fn shadow(candidate: &str) -> (ExecutionResult, EventHandlerExecutor) {
+        let yaml = format!(
+            r#"
+node_id: experiment_v1
+type: function
+intent: validate a pricing rewrite in shadow
+
+inputs:
+  request:
+    type: object
+
+flow:
+  - step: price
+    operation: shadow_mode
+    parameters:
+      primary: price_v1
+      shadow: {}
+      inputs: request
+      event_type: pricing.diverged
+"#,
+            candidate
+        );
+        let audit = r#"
+node_id: audit_v1
+type: event_handler
+intent: report diverging shadows
+
+inputs:
+  shadow:
+    type: string
+
+flow:
+  - step: report
+    operation: string_template
+    template: "{shadow} diverged"
+"#;
+        let mut auditor = SemanticExecutor::new();
+        auditor.register(VesperLoader::new().load_string(audit).unwrap());
+        let broker = Arc::new(EventBroker::new());
+        broker.subscribe("audit_v1", "pricing.diverged", Arc::new(auditor));
+        let listener = EventHandlerExecutor::listen(broker.clone()).unwrap();
+
+        let mut executor = SemanticExecutor::new().with_event_broker(broker.clone());
+        register_pricers(&mut executor, &yaml);
+        let result = executor.execute("experiment_v1", request(3)).unwrap();
+        broker.close();
+        (result, listener)
+    }
+
+    #[test]
+    fn test_shadow_mode_records_divergence() {
+        let (result, listener) = shadow("price_v2");
+        assert_eq!(result.data, Some(Value::Int(6)));
+        let [ExecutionWarning::ShadowDivergence {
+            primary,
+            shadow,
+            differences,
+        }] = result.warnings.as_slice()
+        else {
+            panic!("expected one shadow divergence");
+        };
+        assert_eq!(
+            (primary.as_str(), shadow.as_str()),
+            ("price_v1", "price_v2")
+        );
+        assert_eq!(differences.len(), 1);
+
+        let outcome = listener.next_outcome(Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            outcome.result.unwrap().data,
+            Some(Value::from("price_v2 diverged"))
+        );
+    }
+
+    #[test]
+    fn test_shadow_mode_ignores_matches_and_failures() {
+        for candidate in ["price_v3", "missing_v1"] {
+            let (result, listener) = shadow(candidate);
+            assert_eq!(result.data, Some(Value::Int(6)));
+            assert!(result.warnings.is_empty());
+            assert!(listener.join().is_empty());
+        }
+    }
+}
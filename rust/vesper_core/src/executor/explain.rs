@@ -0,0 +1,311 @@
+//! Dry runs describing what an execution would do
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::contracts::ContractValidator;
+use crate::error::{Result, VesperError};
+use crate::loader::analysis::{defined_by, expression_identifiers};
+use crate::types::{FlowStep, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Description of what executing a node with given inputs would do
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowExplanation {
+    /// Node being explained
+    pub node_id: String,
+    /// Required inputs that were not provided
+    pub missing_inputs: Vec<String>,
+    /// Contract preconditions
+    pub preconditions: Vec<ConditionExplanation>,
+    /// Input transform steps, in execution order
+    pub input_transform: Vec<StepExplanation>,
+    /// Flow steps that would execute, in execution order
+    pub steps: Vec<StepExplanation>,
+}
+
+/// A condition and, if every variable it reads is known, its outcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionExplanation {
+    /// Condition source text
+    pub condition: String,
+    /// Whether the condition holds, if it could be evaluated
+    pub holds: Option<bool>,
+}
+
+/// A step that would execute
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepExplanation {
+    /// Step name
+    pub step: String,
+    /// Operation name
+    pub operation: String,
+    /// Step description from the spec
+    pub description: Option<String>,
+    /// Arithmetic expression the step evaluates
+    pub expression: Option<String>,
+    /// Condition of a conditional step
+    pub condition: Option<ConditionExplanation>,
+    /// Result of the expression, if every variable it reads is known
+    pub result: Option<Value>,
+    /// Variable the result is stored in
+    pub output: Option<String>,
+    /// Nested steps of a conditional: the branch taken, or both branches
+    /// (`then` first) if the condition could not be evaluated
+    pub steps: Vec<StepExplanation>,
+}
+
+impl SemanticExecutor {
+    /// Describe what executing a node with `inputs` would do, without
+    /// running any step
+    ///
+    /// Arithmetic expressions and conditions are evaluated when every
+    /// variable they read is an input or the known result of an earlier
+    /// expression; other operations are listed but their results are
+    /// unknown.
+    pub fn explain(
+        &self,
+        node_id: &str,
+        inputs: &HashMap<String, Value>,
+    ) -> Result<FlowExplanation> {
+        let node =
+            self.state.nodes.get(node_id).ok_or_else(|| {
+                VesperError::ExecutionError(format!("Node not found: {}", node_id))
+            })?;
+
+        let mut missing_inputs: Vec<String> = node
+            .inputs
+            .iter()
+            .filter(|(name, spec)| spec.required && !inputs.contains_key(*name))
+            .map(|(name, _)| name.clone())
+            .collect();
+        missing_inputs.sort();
+
+        let preconditions = node
+            .contracts
+            .iter()
+            .flat_map(|contracts| &contracts.preconditions)
+            .map(|condition| explain_condition(condition, inputs))
+            .collect();
+
+        let mut known = inputs.clone();
+        let input_transform = node
+            .input_transform
+            .iter()
+            .flatten()
+            .map(|step| self.explain_step(step, &mut known))
+            .collect();
+
+        let fetch_steps: HashSet<&str> = node
+            .flow
+            .iter()
+            .filter(|step| step.operation == "paginate")
+            .filter_map(|step| step.parameters.get("fetch_step")?.as_str())
+            .collect();
+        let mut steps = Vec::new();
+        for step in &node.flow {
+            if fetch_steps.contains(step.step.as_str()) {
+                continue;
+            }
+            steps.push(self.explain_step(step, &mut known));
+            if step.return_success.is_some() || step.return_error.is_some() {
+                break;
+            }
+        }
+
+        Ok(FlowExplanation {
+            node_id: node_id.to_string(),
+            missing_inputs,
+            preconditions,
+            input_transform,
+            steps,
+        })
+    }
+
+    /// Describe a step, updating `known` with the variables it would set
+    fn explain_step(&self, step: &FlowStep, known: &mut HashMap<String, Value>) -> StepExplanation {
+        let mut explanation = StepExplanation {
+            step: step.step.clone(),
+            operation: step.operation.clone(),
+            description: step.description.clone(),
+            expression: None,
+            condition: None,
+            result: None,
+            output: step.output.clone(),
+            steps: Vec::new(),
+        };
+
+        match step.operation.as_str() {
+            "arithmetic" => {
+                explanation.expression = step.expression.clone();
+                explanation.result = step.expression.as_ref().and_then(|expression| {
+                    let ctx = ExecutionContext::new(known.clone());
+                    self.evaluate_expression(expression, &ctx).ok()
+                });
+            }
+            "conditional" => {
+                let condition = step
+                    .condition
+                    .as_ref()
+                    .map(|condition| explain_condition(condition, known));
+                let explain_branch = |branch: &[FlowStep], known: &mut HashMap<String, Value>| {
+                    branch
+                        .iter()
+                        .map(|nested| self.explain_step(nested, known))
+                        .collect::<Vec<_>>()
+                };
+                match condition.as_ref().and_then(|c| c.holds) {
+                    Some(holds) => {
+                        let branch = if holds {
+                            &step.then_steps
+                        } else {
+                            &step.else_steps
+                        };
+                        explanation.steps = explain_branch(branch, known);
+                        explanation.result = explanation
+                            .steps
+                            .last()
+                            .map_or(Some(Value::Null), |last| last.result.clone());
+                    }
+                    None => {
+                        // Only values both branches agree on stay known
+                        let mut then_known = known.clone();
+                        let mut else_known = known.clone();
+                        explanation.steps = explain_branch(&step.then_steps, &mut then_known);
+                        explanation
+                            .steps
+                            .extend(explain_branch(&step.else_steps, &mut else_known));
+                        then_known.retain(|name, value| else_known.get(name) == Some(value));
+                        *known = then_known;
+                    }
+                }
+                explanation.condition = condition;
+            }
+            _ => {}
+        }
+
+        for name in defined_by(step) {
+            known.remove(&name);
+        }
+        if let (Some(output), Some(result)) = (&step.output, &explanation.result) {
+            known.insert(output.clone(), result.clone());
+        }
+        explanation
+    }
+}
+
+/// Evaluate a condition if every variable it reads is in `known`
+fn explain_condition(condition: &str, known: &HashMap<String, Value>) -> ConditionExplanation {
+    let evaluable = expression_identifiers(condition)
+        .iter()
+        .all(|name| known.contains_key(name));
+    ConditionExplanation {
+        condition: condition.to_string(),
+        holds: evaluable
+            .then(|| ContractValidator::new().evaluate(condition, known).ok())
+            .flatten(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    const SHIPPING: &str = r#"
+node_id: shipping_v1
+type: function
+intent: quote shipping
+
+inputs:
+  weight:
+    type: integer
+  rate:
+    type: integer
+  zone:
+    type: string
+    required: false
+
+contracts:
+  preconditions:
+    - "weight > 0"
+    - "zone != ''"
+
+flow:
+  - step: base
+    operation: arithmetic
+    expression: "weight * rate"
+    output: cost
+  - step: heavy
+    operation: conditional
+    condition: "cost > 100"
+    then:
+      - step: surcharge
+        operation: arithmetic
+        expression: "cost + 25"
+        output: total
+    else:
+      - step: flat
+        operation: arithmetic
+        expression: "cost + 5"
+        output: total
+  - step: lookup
+    operation: cache_get
+    parameters:
+      key: "{zone}"
+    output: discount
+  - step: discounted
+    operation: arithmetic
+    expression: "total - discount"
+    output: final
+"#;
+
+    fn inputs(weight: i64) -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        inputs.insert("weight".to_string(), Value::Int(weight));
+        inputs.insert("rate".to_string(), Value::Int(10));
+        inputs
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(SHIPPING).unwrap());
+
+        let explanation = executor.explain("shipping_v1", &inputs(20)).unwrap();
+        assert!(explanation.missing_inputs.is_empty());
+        let holds: Vec<Option<bool>> = explanation.preconditions.iter().map(|p| p.holds).collect();
+        assert_eq!(holds, vec![Some(true), None]);
+
+        let steps = &explanation.steps;
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[0].result, Some(Value::Int(200)));
+        assert_eq!(steps[1].condition.as_ref().unwrap().holds, Some(true));
+        assert_eq!(steps[1].steps.len(), 1);
+        assert_eq!(steps[1].steps[0].step, "surcharge");
+        assert_eq!(steps[1].steps[0].result, Some(Value::Int(225)));
+        assert_eq!(steps[2].result, None);
+        // The discount is only known once the cache is read
+        assert_eq!(steps[3].expression.as_deref(), Some("total - discount"));
+        assert_eq!(steps[3].result, None);
+
+        let light = executor.explain("shipping_v1", &inputs(5)).unwrap();
+        assert_eq!(light.steps[1].steps[0].step, "flat");
+        assert_eq!(light.steps[1].steps[0].result, Some(Value::Int(55)));
+    }
+
+    #[test]
+    fn test_explain_with_unknown_inputs() {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(SHIPPING).unwrap());
+
+        let explanation = executor.explain("shipping_v1", &HashMap::new()).unwrap();
+        assert_eq!(explanation.missing_inputs, vec!["rate", "weight"]);
+        assert_eq!(explanation.steps[0].result, None);
+        // Both branches are listed when the condition cannot be evaluated
+        let branches: Vec<&str> = explanation.steps[1]
+            .steps
+            .iter()
+            .map(|s| s.step.as_str())
+            .collect();
+        assert_eq!(branches, vec!["surcharge", "flat"]);
+    }
+}
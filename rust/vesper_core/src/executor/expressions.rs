@@ -0,0 +1,144 @@
+//! Arithmetic expressions, parsed once and cached by source text
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::cache::CacheStats;
+use crate::error::{Result, VesperError};
+use crate::types::Value;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Number literal or variable name in an expression
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Operand {
+    Number(f64),
+    Variable(String),
+}
+
+impl Operand {
+    fn parse(text: &str) -> Self {
+        match text.parse::<f64>() {
+            Ok(n) => Operand::Number(n),
+            Err(_) => Operand::Variable(text.to_string()),
+        }
+    }
+}
+
+/// Parsed arithmetic expression: a single operand or `a <op> b`
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum ExprAst {
+    Operand(Operand),
+    Binary {
+        op: char,
+        left: Operand,
+        right: Operand,
+    },
+}
+
+impl ExprAst {
+    /// Parse an expression, splitting on the first of ` + `, ` - `, ` * `,
+    /// ` / ` it contains
+    pub(super) fn parse(expression: &str) -> Self {
+        let expr = expression.trim();
+        for op in [" + ", " - ", " * ", " / "] {
+            if let Some(idx) = expr.find(op) {
+                return ExprAst::Binary {
+                    op: op.trim().chars().next().unwrap_or('+'),
+                    left: Operand::parse(expr[..idx].trim()),
+                    right: Operand::parse(expr[idx + op.len()..].trim()),
+                };
+            }
+        }
+        ExprAst::Operand(Operand::parse(expr))
+    }
+}
+
+/// Parsed expressions keyed by source text, shared by all executions
+#[derive(Default)]
+pub(super) struct ExpressionCache {
+    /// Parsed expressions
+    entries: RwLock<HashMap<String, Arc<ExprAst>>>,
+    /// Lookups served from the cache
+    hits: AtomicU64,
+    /// Lookups that had to parse
+    misses: AtomicU64,
+}
+
+impl ExpressionCache {
+    /// Get the parsed form of `expression`, parsing it on first use
+    pub(super) fn get_or_parse(&self, expression: &str) -> Arc<ExprAst> {
+        if let Some(ast) = self.entries.read().get(expression) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return ast.clone();
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let ast = Arc::new(ExprAst::parse(expression));
+        self.entries
+            .write()
+            .entry(expression.to_string())
+            .or_insert(ast)
+            .clone()
+    }
+
+    pub(super) fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.read().len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl SemanticExecutor {
+    /// Evaluate an arithmetic expression against the context
+    pub(super) fn evaluate_expression(
+        &self,
+        expression: &str,
+        ctx: &ExecutionContext,
+    ) -> Result<Value> {
+        let result = match &*self.state.expressions.get_or_parse(expression) {
+            ExprAst::Operand(operand) => operand_value(operand, ctx)?,
+            ExprAst::Binary { op, left, right } => {
+                let left_val = operand_value(left, ctx)?;
+                let right_val = operand_value(right, ctx)?;
+                match op {
+                    '+' => left_val + right_val,
+                    '-' => left_val - right_val,
+                    '*' => left_val * right_val,
+                    _ => {
+                        if right_val == 0.0 {
+                            return Err(VesperError::ExecutionError(
+                                "Division by zero".to_string(),
+                            ));
+                        }
+                        left_val / right_val
+                    }
+                }
+            }
+        };
+
+        Ok(if result.fract() == 0.0 {
+            Value::Int(result as i64)
+        } else {
+            Value::Float(result)
+        })
+    }
+}
+
+/// Numeric value of an operand (number literal or variable)
+fn operand_value(operand: &Operand, ctx: &ExecutionContext) -> Result<f64> {
+    match operand {
+        Operand::Number(n) => Ok(*n),
+        Operand::Variable(name) => match ctx.get(name) {
+            Some(value) => value.as_float().ok_or_else(|| VesperError::TypeError {
+                expected: "number".to_string(),
+                actual: format!("{:?}", value),
+            }),
+            None => Err(VesperError::ExecutionError(format!(
+                "Unknown variable or invalid number: {}",
+                name
+            ))),
+        },
+    }
+}
@@ -0,0 +1,353 @@
+//! File system operations
+//!
+//! All operations require the `capability:file_io` capability. When the
+//! executor has a base path, every path is resolved relative to it and
+//! must stay inside it after canonicalization.
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, IntoString, Value};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Capability a node must declare to use the file operations
+const FILE_IO_CAPABILITY: &str = "capability:file_io";
+
+impl SemanticExecutor {
+    /// Execute a file read step
+    ///
+    /// Reads `parameters["path"]` as a string (`encoding: utf8`, the
+    /// default) or as an array of bytes (`encoding: bytes`).
+    pub(super) fn execute_read_file(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        self.require_capability(step, ctx, FILE_IO_CAPABILITY)?;
+        let path = self.confined_path(&self.resolve_string_parameter(step, "path", ctx)?)?;
+
+        let encoding = step
+            .parameters
+            .get("encoding")
+            .and_then(|e| e.as_str())
+            .unwrap_or("utf8");
+        let result = match encoding {
+            "utf8" | "utf-8" => Value::from(std::fs::read_to_string(&path)?),
+            "bytes" => Value::Array(
+                std::fs::read(&path)?
+                    .into_iter()
+                    .map(|b| Value::Int(i64::from(b)))
+                    .collect(),
+            ),
+            other => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Unknown file encoding: {}",
+                    other
+                )))
+            }
+        };
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a file write step
+    ///
+    /// Writes the string or byte array named by `parameters["content"]` to
+    /// `parameters["path"]`, replacing the file unless `append: true`.
+    /// Returns the number of bytes written.
+    pub(super) fn execute_write_file(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        self.require_capability(step, ctx, FILE_IO_CAPABILITY)?;
+        let path = self.confined_path(&self.resolve_string_parameter(step, "path", ctx)?)?;
+
+        let bytes = match self.resolve_parameter_variable(step, "content", ctx)? {
+            Value::String(s) => s.into_string().into_bytes(),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| item.as_int().and_then(|i| u8::try_from(i).ok()))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(|| VesperError::TypeError {
+                    expected: "byte array".to_string(),
+                    actual: format!("{:?}", items),
+                })?,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string or bytes".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+        let append = step
+            .parameters
+            .get("append")
+            .and_then(|a| a.as_bool())
+            .unwrap_or(false);
+
+        if append {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?
+                .write_all(&bytes)?;
+        } else {
+            std::fs::write(&path, &bytes)?;
+        }
+
+        let result = Value::Int(bytes.len() as i64);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a directory listing step
+    ///
+    /// Lists files in `parameters["directory"]` whose names match the glob
+    /// `parameters["pattern"]` (default `*`), descending into
+    /// subdirectories when `recursive: true`. Returns sorted paths.
+    #[cfg(feature = "glob")]
+    pub(super) fn execute_list_files(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        self.require_capability(step, ctx, FILE_IO_CAPABILITY)?;
+        let directory =
+            self.confined_path(&self.resolve_string_parameter(step, "directory", ctx)?)?;
+        let pattern = match step.parameters.get("pattern") {
+            Some(_) => self.resolve_string_parameter(step, "pattern", ctx)?,
+            None => "*".to_string(),
+        };
+        let pattern = glob::Pattern::new(&pattern)
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid pattern: {}", e)))?;
+        let recursive = step
+            .parameters
+            .get("recursive")
+            .and_then(|r| r.as_bool())
+            .unwrap_or(false);
+
+        let mut files = Vec::new();
+        let mut pending = vec![directory];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    if recursive {
+                        pending.push(entry.path());
+                    }
+                } else if pattern.matches(&entry.file_name().to_string_lossy()) {
+                    files.push(entry.path().to_string_lossy().into_owned());
+                }
+            }
+        }
+        files.sort();
+
+        let result = Value::Array(files.into_iter().map(Value::from).collect());
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a directory listing step (feature disabled)
+    #[cfg(not(feature = "glob"))]
+    pub(super) fn execute_list_files(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "list_files operation requires the `glob` feature".to_string(),
+        ))
+    }
+
+    /// Resolve a path against the base path, rejecting anything outside it
+    ///
+    /// The deepest existing ancestor is canonicalized so that symlinks and
+    /// `..` components cannot escape, while still allowing paths to files
+    /// that do not exist yet.
+    fn confined_path(&self, path: &str) -> Result<PathBuf> {
+        let Some(base) = &self.state.base_path else {
+            return Ok(PathBuf::from(path));
+        };
+        let base = base.canonicalize()?;
+        let joined = base.join(path);
+
+        let mut existing = joined.as_path();
+        let mut missing = Vec::new();
+        while !existing.exists() {
+            match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name.to_owned());
+                    existing = parent;
+                }
+                _ => break,
+            }
+        }
+
+        let mut resolved = existing.canonicalize()?;
+        for name in missing.iter().rev() {
+            resolved.push(name);
+        }
+        if !resolved.starts_with(&base) {
+            return Err(VesperError::SecurityViolation(format!(
+                "Path {} is outside the allowed directory",
+                path
+            )));
+        }
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn executor(base: &std::path::Path, flow: &str) -> SemanticExecutor {
+        let yaml = format!(
+            r#"
+node_id: files_v1
+type: function
+intent: read and write report files
+
+inputs:
+  name:
+    type: string
+  report:
+    type: string
+
+security:
+  capabilities_required:
+    - capability:file_io
+
+flow:
+{}
+"#,
+            flow
+        );
+        let mut executor = SemanticExecutor::new().with_base_path(base);
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        executor
+    }
+
+    fn inputs(name: &str) -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        inputs.insert("name".to_string(), Value::from(name));
+        inputs.insert("report".to_string(), Value::from("line\n"));
+        inputs
+    }
+
+    const WRITE_TWICE_THEN_READ: &str = r#"
+  - step: write
+    operation: write_file
+    parameters:
+      path: "{name}"
+      content: report
+  - step: append
+    operation: write_file
+    parameters:
+      path: "{name}"
+      content: report
+      append: true
+  - step: read
+    operation: read_file
+    parameters:
+      path: "{name}"
+"#;
+
+    #[test]
+    fn test_write_then_read_file() {
+        let dir = std::env::temp_dir().join(format!("vesper-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let executor = executor(&dir, WRITE_TWICE_THEN_READ);
+        let result = executor.execute("files_v1", inputs("report.txt")).unwrap();
+        assert_eq!(result.data, Some(Value::from("line\nline\n")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_path_traversal_is_rejected() {
+        let dir = std::env::temp_dir().join(format!("vesper-jail-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let executor = executor(&dir, WRITE_TWICE_THEN_READ);
+        let result = executor.execute("files_v1", inputs("../escaped.txt"));
+        assert!(matches!(result, Err(VesperError::SecurityViolation(_))));
+        assert!(!dir.parent().unwrap().join("escaped.txt").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_io_requires_capability() {
+        let yaml = r#"
+node_id: sneaky_v1
+type: function
+intent: read without asking
+
+inputs: {}
+
+flow:
+  - step: read
+    operation: read_file
+    parameters:
+      path: /etc/hostname
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let result = executor.execute("sneaky_v1", HashMap::new());
+        assert!(matches!(result, Err(VesperError::SecurityViolation(_))));
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    fn test_list_files() {
+        let dir = std::env::temp_dir().join(format!("vesper-list-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in ["a.yaml", "b.txt", "nested/c.yaml"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let list = |recursive: bool| {
+            let flow = format!(
+                r#"
+  - step: list
+    operation: list_files
+    parameters:
+      directory: "."
+      pattern: "*.yaml"
+      recursive: {}
+"#,
+                recursive
+            );
+            let result = executor(&dir, &flow)
+                .execute("files_v1", inputs("unused"))
+                .unwrap();
+            let Some(Value::Array(paths)) = result.data else {
+                panic!("expected array result");
+            };
+            paths
+                .iter()
+                .map(|p| {
+                    let path = std::path::Path::new(p.as_str().unwrap());
+                    let base = dir.canonicalize().unwrap();
+                    path.strip_prefix(base)
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(list(false), vec!["a.yaml"]);
+        assert_eq!(list(true), vec!["a.yaml", "nested/c.yaml"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
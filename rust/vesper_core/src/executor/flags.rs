@@ -0,0 +1,83 @@
+//! Feature flag operation
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a feature flag step
+    ///
+    /// Looks up `parameters["flag"]` in the installed `FeatureFlagStore`,
+    /// passing the current bindings as evaluation context.
+    pub(super) fn execute_feature_flag(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let store = self.state.feature_flags.as_deref().ok_or_else(|| {
+            VesperError::ExecutionError("No feature flag store configured".to_string())
+        })?;
+
+        let flag = self.resolve_string_parameter(step, "flag", ctx)?;
+        let result = Value::Bool(store.is_enabled(&flag, &ctx.bindings()));
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature_flags::StaticFeatureFlagStore;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    const CHECKOUT: &str = r#"
+node_id: checkout_v1
+type: function
+intent: pick a checkout flow
+
+inputs:
+  user:
+    type: string
+
+flow:
+  - step: check_flag
+    operation: feature_flag
+    parameters:
+      flag: new_checkout
+    output: use_new_checkout
+
+  - step: choose
+    operation: conditional
+    condition: use_new_checkout
+    then:
+      - operation: string_template
+        template: "new checkout for {user}"
+    else:
+      - operation: string_template
+        template: "classic checkout for {user}"
+"#;
+
+    fn run(enabled: bool) -> Value {
+        let store = StaticFeatureFlagStore::new().with_flag("new_checkout", enabled);
+        let mut executor = SemanticExecutor::new().with_feature_flags(Arc::new(store));
+        executor.register(VesperLoader::new().load_string(CHECKOUT).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("user".to_string(), Value::from("ada"));
+        executor
+            .execute("checkout_v1", inputs)
+            .unwrap()
+            .data
+            .unwrap()
+    }
+
+    #[test]
+    fn test_feature_flag_branching() {
+        assert_eq!(run(true), Value::from("new checkout for ada"));
+        assert_eq!(run(false), Value::from("classic checkout for ada"));
+    }
+}
@@ -0,0 +1,306 @@
+//! Property-based testing of nodes against their contracts
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::contracts::ContractValidator;
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// Bounds of generated numbers, and of string and array lengths, when an
+/// input schema sets none
+const DEFAULT_NUMBER_RANGE: (f64, f64) = (-1000.0, 1000.0);
+const DEFAULT_LENGTH_RANGE: (f64, f64) = (0.0, 8.0);
+
+/// Characters of generated strings
+const STRING_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+
+impl SemanticExecutor {
+    /// Execute a fuzzing step
+    ///
+    /// Executes node `parameters["node_id"]` `parameters["iterations"]`
+    /// times with random inputs and checks its postconditions after each
+    /// run. Inputs follow `parameters["input_schema"]`, which maps input
+    /// names to a type name or to an object with a `type` and optional
+    /// `min`/`max` (values, or lengths of strings and arrays) or `values`
+    /// to pick from; without it, the node's own input types are used.
+    /// Generated inputs violating a precondition are discarded.
+    ///
+    /// The generator is seeded with `parameters["seed"]` (default 0), so
+    /// runs are reproducible. Returns the number of `passes` and the
+    /// `failures`, each with the `inputs`, the violated `contract` (null
+    /// if the execution failed) and a `message`.
+    pub(super) fn execute_fuzzer(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let node_id = self.resolve_string_parameter(step, "node_id", ctx)?;
+        let node =
+            self.state.nodes.get(&node_id).ok_or_else(|| {
+                VesperError::ExecutionError(format!("Node not found: {}", node_id))
+            })?;
+        let iterations = step
+            .parameters
+            .get("iterations")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| {
+                VesperError::ExecutionError(format!("Step {} needs iterations", step.step))
+            })?;
+        let seed = step
+            .parameters
+            .get("seed")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        let mut schema: Vec<(String, serde_yaml::Value)> = match step.parameters.get("input_schema")
+        {
+            Some(serde_yaml::Value::Mapping(fields)) => fields
+                .iter()
+                .filter_map(|(name, spec)| Some((name.as_str()?.to_string(), spec.clone())))
+                .collect(),
+            Some(_) => {
+                return Err(VesperError::ExecutionError(
+                    "Fuzzer input_schema must be a mapping".to_string(),
+                ))
+            }
+            None => node
+                .inputs
+                .iter()
+                .map(|(name, spec)| {
+                    (
+                        name.clone(),
+                        serde_yaml::Value::String(spec.input_type.clone()),
+                    )
+                })
+                .collect(),
+        };
+        schema.sort_by(|a, b| a.0.cmp(&b.0));
+        let (preconditions, postconditions) = node
+            .contracts
+            .as_ref()
+            .map(|c| (c.preconditions.as_slice(), c.postconditions.as_slice()))
+            .unwrap_or_default();
+
+        let validator = ContractValidator::new();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut passes = 0;
+        let mut failures = Vec::new();
+        for _ in 0..iterations {
+            let inputs: HashMap<String, Value> = schema
+                .iter()
+                .map(|(name, spec)| (name.clone(), random_value(&mut rng, spec)))
+                .collect();
+            if !preconditions
+                .iter()
+                .all(|condition| validator.evaluate(condition, &inputs).unwrap_or(false))
+            {
+                continue;
+            }
+
+            let violations: Vec<(Value, String)> = match self.execute(&node_id, inputs.clone()) {
+                Ok(result) => {
+                    let data = result.data.unwrap_or(Value::Null);
+                    let mut bindings = inputs.clone();
+                    if let Value::Object(fields) = &data {
+                        bindings.extend(fields.clone());
+                    }
+                    bindings.insert("result".to_string(), data);
+                    postconditions
+                        .iter()
+                        .filter(|condition| {
+                            !validator.evaluate(condition, &bindings).unwrap_or(false)
+                        })
+                        .map(|condition| {
+                            (
+                                Value::from(condition.as_str()),
+                                VesperError::PostconditionFailed(condition.clone()).to_string(),
+                            )
+                        })
+                        .collect()
+                }
+                Err(e) => vec![(Value::Null, e.to_string())],
+            };
+
+            if violations.is_empty() {
+                passes += 1;
+            }
+            for (contract, message) in violations {
+                let mut failure = HashMap::new();
+                failure.insert("inputs".to_string(), Value::Object(inputs.clone()));
+                failure.insert("contract".to_string(), contract);
+                failure.insert("message".to_string(), Value::from(message));
+                failures.push(Value::Object(failure));
+            }
+        }
+
+        let mut result = HashMap::new();
+        result.insert("failures".to_string(), Value::Array(failures));
+        result.insert("passes".to_string(), Value::Int(passes));
+        let result = Value::Object(result);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Random value for an input schema entry: a type name, or an object with
+/// a `type` and optional `min`, `max` and `values`
+fn random_value(rng: &mut StdRng, spec: &serde_yaml::Value) -> Value {
+    if let Some(serde_yaml::Value::Sequence(values)) = spec.get("values") {
+        if !values.is_empty() {
+            let picked = &values[rng.gen_range(0..values.len())];
+            return serde_json::to_value(picked)
+                .map(Value::from)
+                .unwrap_or(Value::Null);
+        }
+    }
+    let type_name = spec
+        .as_str()
+        .or_else(|| spec.get("type")?.as_str())
+        .unwrap_or("any");
+    let bound = |key: &str, default: f64| spec.get(key).and_then(|v| v.as_f64()).unwrap_or(default);
+    let (min, max) = match type_name {
+        "string" | "array" => (
+            bound("min", DEFAULT_LENGTH_RANGE.0),
+            bound("max", DEFAULT_LENGTH_RANGE.1),
+        ),
+        _ if type_name.starts_with("array<") => (
+            bound("min", DEFAULT_LENGTH_RANGE.0),
+            bound("max", DEFAULT_LENGTH_RANGE.1),
+        ),
+        _ => (
+            bound("min", DEFAULT_NUMBER_RANGE.0),
+            bound("max", DEFAULT_NUMBER_RANGE.1),
+        ),
+    };
+    let length = |rng: &mut StdRng| rng.gen_range(min as usize..=max.max(min) as usize);
+
+    if let Some(element) = type_name
+        .strip_prefix("array<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        let element = serde_yaml::Value::String(element.trim().to_string());
+        let len = length(rng);
+        return Value::Array((0..len).map(|_| random_value(rng, &element)).collect());
+    }
+    match type_name {
+        "integer" => Value::Int(rng.gen_range(min as i64..=max.max(min) as i64)),
+        "number" | "float" | "decimal" => Value::Float(if max > min {
+            rng.gen_range(min..max)
+        } else {
+            min
+        }),
+        "boolean" => Value::Bool(rng.gen()),
+        "string" => {
+            let len = length(rng);
+            Value::from(
+                (0..len)
+                    .map(|_| STRING_CHARS[rng.gen_range(0..STRING_CHARS.len())] as char)
+                    .collect::<String>(),
+            )
+        }
+        "array" => {
+            let integer = serde_yaml::Value::String("integer".to_string());
+            let len = length(rng);
+            Value::Array((0..len).map(|_| random_value(rng, &integer)).collect())
+        }
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    const ABS: &str = r#"
+node_id: distance_v1
+type: function
+intent: distance between two points on a line
+
+inputs:
+  a:
+    type: integer
+  b:
+    type: integer
+
+contracts:
+  postconditions:
+    - "distance >= 0"
+
+flow:
+  - step: diff
+    operation: arithmetic
+    expression: "a - b"
+    output: distance
+  - step: done
+    operation: return
+    return_success:
+      distance: "{distance}"
+"#;
+
+    fn fuzz(seed: u64, schema: &str) -> Value {
+        let yaml = format!(
+            r#"
+node_id: fuzz_v1
+type: function
+intent: fuzz the distance node
+
+flow:
+  - step: fuzz
+    operation: fuzzer
+    parameters:
+      node_id: distance_v1
+      iterations: 50
+      seed: {seed}
+      input_schema:
+{schema}
+"#
+        );
+        let loader = VesperLoader::new();
+        let mut executor = SemanticExecutor::new();
+        executor.register(loader.load_string(ABS).unwrap());
+        executor.register(loader.load_string(&yaml).unwrap());
+        executor
+            .execute("fuzz_v1", HashMap::new())
+            .unwrap()
+            .data
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fuzzer_finds_violations() {
+        let schema = "        a: integer\n        b: integer";
+        let Value::Object(result) = fuzz(7, schema) else {
+            panic!("expected a fuzzing report");
+        };
+        let Value::Array(failures) = &result["failures"] else {
+            panic!("expected failures");
+        };
+        assert!(!failures.is_empty());
+        assert_eq!(result["passes"], Value::Int(50 - failures.len() as i64));
+
+        let Value::Object(failure) = &failures[0] else {
+            panic!("expected a failure object");
+        };
+        assert_eq!(failure["contract"], Value::from("distance >= 0"));
+        let Value::Object(inputs) = &failure["inputs"] else {
+            panic!("expected the failing inputs");
+        };
+        assert!(inputs["a"].as_int().unwrap() < inputs["b"].as_int().unwrap());
+
+        // The same seed reproduces the same run
+        assert_eq!(fuzz(7, schema), Value::Object(result));
+    }
+
+    #[test]
+    fn test_fuzzer_respects_bounds() {
+        let schema = "        a: {type: integer, min: 100, max: 200}\n        \
+                      b: {type: integer, values: [0, 50]}";
+        let Value::Object(result) = fuzz(1, schema) else {
+            panic!("expected a fuzzing report");
+        };
+        assert_eq!(result["failures"], Value::Array(Vec::new()));
+        assert_eq!(result["passes"], Value::Int(50));
+    }
+}
@@ -0,0 +1,182 @@
+//! GraphQL client operation
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a GraphQL query step
+    ///
+    /// POSTs `{query, variables}` to `parameters["url"]` and returns the
+    /// response's `data`. `parameters["query"]` is either the query text
+    /// or the name of a variable holding it; `parameters["variables"]`
+    /// names an object of query variables. The first entry of a non-empty
+    /// `errors` array fails the step.
+    #[cfg(feature = "http")]
+    pub(super) fn execute_graphql_query(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let url = self.resolve_string_parameter(step, "url", ctx)?;
+        let query = match step.parameters.get("query").and_then(|q| q.as_str()) {
+            Some(name) if ctx.get(name).is_some() => {
+                self.resolve_parameter_variable(step, "query", ctx)?
+            }
+            _ => Value::from(self.resolve_string_parameter(step, "query", ctx)?),
+        };
+        let query = query.as_str().ok_or_else(|| VesperError::TypeError {
+            expected: "string".to_string(),
+            actual: format!("{:?}", query),
+        })?;
+        let variables = match step.parameters.get("variables") {
+            Some(_) => match self.resolve_parameter_variable(step, "variables", ctx)? {
+                variables @ Value::Object(_) => serde_json::Value::from(&variables),
+                other => {
+                    return Err(VesperError::TypeError {
+                        expected: "object".to_string(),
+                        actual: format!("{:?}", other),
+                    })
+                }
+            },
+            None => serde_json::Value::Object(serde_json::Map::new()),
+        };
+
+        let mut request = self
+            .http_client()
+            .post(&url)
+            .json(&serde_json::json!({ "query": query, "variables": variables }));
+        if let Some(serde_yaml::Value::Mapping(headers)) = step.parameters.get("headers") {
+            for (name, value) in headers {
+                if let (Some(name), Value::String(value)) =
+                    (name.as_str(), self.resolve_value(value, ctx))
+                {
+                    request = request.header(name, value.as_str());
+                }
+            }
+        }
+
+        let response = request
+            .send()
+            .map_err(|e| VesperError::ExecutionError(format!("GraphQL request failed: {}", e)))?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().map_err(|e| {
+            VesperError::ExecutionError(format!(
+                "Invalid GraphQL response (HTTP {}): {}",
+                status, e
+            ))
+        })?;
+
+        if let Some(error) = body["errors"].as_array().and_then(|errors| errors.first()) {
+            let message = error["message"].as_str().unwrap_or("unknown error");
+            return Err(VesperError::ExecutionError(format!(
+                "GraphQL error: {}",
+                message
+            )));
+        }
+        if !status.is_success() {
+            return Err(VesperError::ExecutionError(format!(
+                "GraphQL request failed: HTTP {}",
+                status
+            )));
+        }
+
+        let result = Value::from(body["data"].clone());
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a GraphQL query step (feature disabled)
+    #[cfg(not(feature = "http"))]
+    pub(super) fn execute_graphql_query(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "graphql_query operation requires the `http` feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use mockito::Matcher;
+    use std::collections::HashMap;
+
+    fn run(url: &str) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: lookup_v1
+type: function
+intent: fetch a user from the graph
+
+inputs:
+  filter:
+    type: object
+
+flow:
+  - step: fetch
+    operation: graphql_query
+    parameters:
+      url: "{}/graphql"
+      query: "query($id: ID!) {{ user(id: $id) {{ name }} }}"
+      variables: filter
+      headers:
+        authorization: Bearer t0ken
+"#,
+            url
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut filter = HashMap::new();
+        filter.insert("id".to_string(), Value::from("u1"));
+        let mut inputs = HashMap::new();
+        inputs.insert("filter".to_string(), Value::Object(filter));
+        executor
+            .execute("lookup_v1", inputs)
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_graphql_query() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/graphql")
+            .match_header("authorization", "Bearer t0ken")
+            .match_body(Matcher::PartialJson(
+                serde_json::json!({"variables": {"id": "u1"}}),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"user": {"name": "Ada"}}}"#)
+            .create();
+
+        let Value::Object(data) = run(&server.url()).unwrap() else {
+            panic!("expected object");
+        };
+        mock.assert();
+
+        let Value::Object(user) = &data["user"] else {
+            panic!("expected user object");
+        };
+        assert_eq!(user["name"], Value::from("Ada"));
+    }
+
+    #[test]
+    fn test_graphql_errors() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("POST", "/graphql")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": null, "errors": [{"message": "user not found"}]}"#)
+            .create();
+
+        let Err(VesperError::ExecutionError(message)) = run(&server.url()) else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("user not found"));
+    }
+}
@@ -0,0 +1,198 @@
+//! gRPC client operation
+//!
+//! Service schemas are not known at compile time, so messages travel as
+//! `google.protobuf.Struct`: the request object is encoded as a Struct and
+//! the response is decoded from one. This suits services that accept and
+//! return Struct messages (or gateways that bridge JSON to them).
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a gRPC call step
+    ///
+    /// Calls `/{service}/{method}` on `parameters["endpoint"]` with the
+    /// object named by `parameters["message"]` and returns the response
+    /// object. Connection failures and non-OK statuses fail the step.
+    #[cfg(feature = "grpc")]
+    pub(super) fn execute_grpc_call(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let endpoint = self.resolve_string_parameter(step, "endpoint", ctx)?;
+        let service = self.resolve_string_parameter(step, "service", ctx)?;
+        let method = self.resolve_string_parameter(step, "method", ctx)?;
+        let message = match self.resolve_parameter_variable(step, "message", ctx)? {
+            Value::Object(fields) => prost_types::Struct {
+                fields: fields
+                    .iter()
+                    .map(|(k, v)| (k.clone(), to_protobuf(v)))
+                    .collect(),
+            },
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "object".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        let path: tonic::codegen::http::uri::PathAndQuery = format!("/{}/{}", service, method)
+            .parse()
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid gRPC method: {}", e)))?;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let response = runtime.block_on(async {
+            let channel = tonic::transport::Endpoint::from_shared(endpoint.clone())
+                .map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?
+                .connect()
+                .await
+                .map_err(|e| format!("Connecting to {} failed: {}", endpoint, e))?;
+
+            let mut client = tonic::client::Grpc::new(channel);
+            client
+                .ready()
+                .await
+                .map_err(|e| format!("Channel not ready: {}", e))?;
+            client
+                .unary::<_, prost_types::Struct, _>(
+                    tonic::Request::new(message),
+                    path,
+                    tonic::codec::ProstCodec::default(),
+                )
+                .await
+                .map_err(|status| format!("{:?}: {}", status.code(), status.message()))
+        });
+        let response = response.map_err(|e| {
+            VesperError::ExecutionError(format!("gRPC call {}/{} failed: {}", service, method, e))
+        })?;
+
+        let result = Value::Object(
+            response
+                .into_inner()
+                .fields
+                .into_iter()
+                .map(|(k, v)| (k, from_protobuf(v)))
+                .collect(),
+        );
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a gRPC call step (feature disabled)
+    #[cfg(not(feature = "grpc"))]
+    pub(super) fn execute_grpc_call(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "grpc_call operation requires the `grpc` feature".to_string(),
+        ))
+    }
+}
+
+/// Convert a value to its `google.protobuf.Value` form
+#[cfg(feature = "grpc")]
+fn to_protobuf(value: &Value) -> prost_types::Value {
+    use prost_types::value::Kind;
+
+    let kind = match value {
+        Value::Null => Kind::NullValue(0),
+        Value::Bool(b) => Kind::BoolValue(*b),
+        Value::Int(i) => Kind::NumberValue(*i as f64),
+        Value::Float(f) => Kind::NumberValue(*f),
+        Value::String(s) => Kind::StringValue(s.to_string()),
+        Value::Array(items) => Kind::ListValue(prost_types::ListValue {
+            values: items.iter().map(to_protobuf).collect(),
+        }),
+        Value::Object(fields) => Kind::StructValue(prost_types::Struct {
+            fields: fields
+                .iter()
+                .map(|(k, v)| (k.clone(), to_protobuf(v)))
+                .collect(),
+        }),
+    };
+    prost_types::Value { kind: Some(kind) }
+}
+
+/// Convert a `google.protobuf.Value` back to a value
+///
+/// Protobuf has a single number type, so integral numbers become `Int`.
+#[cfg(feature = "grpc")]
+fn from_protobuf(value: prost_types::Value) -> Value {
+    use prost_types::value::Kind;
+
+    match value.kind {
+        None | Some(Kind::NullValue(_)) => Value::Null,
+        Some(Kind::BoolValue(b)) => Value::Bool(b),
+        Some(Kind::NumberValue(n)) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => {
+            Value::Int(n as i64)
+        }
+        Some(Kind::NumberValue(n)) => Value::Float(n),
+        Some(Kind::StringValue(s)) => Value::from(s),
+        Some(Kind::ListValue(list)) => {
+            Value::Array(list.values.into_iter().map(from_protobuf).collect())
+        }
+        Some(Kind::StructValue(s)) => Value::Object(
+            s.fields
+                .into_iter()
+                .map(|(k, v)| (k, from_protobuf(v)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(all(test, feature = "grpc"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let mut fields = HashMap::new();
+        fields.insert("id".to_string(), Value::Int(7));
+        fields.insert("score".to_string(), Value::Float(0.5));
+        fields.insert(
+            "tags".to_string(),
+            Value::Array(vec![Value::from("a"), Value::Null, Value::Bool(true)]),
+        );
+        let value = Value::Object(fields);
+
+        assert_eq!(from_protobuf(to_protobuf(&value)), value);
+    }
+
+    #[test]
+    fn test_grpc_call_connection_refused() {
+        let yaml = r#"
+node_id: rpc_v1
+type: function
+intent: call an inventory service
+
+inputs:
+  request:
+    type: object
+
+flow:
+  - step: call
+    operation: grpc_call
+    parameters:
+      endpoint: "http://127.0.0.1:1"
+      service: inventory.v1.Inventory
+      method: GetItem
+      message: request
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("request".to_string(), Value::Object(HashMap::new()));
+        let result = executor.execute("rpc_v1", inputs);
+        assert!(matches!(result, Err(VesperError::ExecutionError(_))));
+    }
+}
@@ -0,0 +1,169 @@
+//! Exactly-once execution of protected steps
+
+use super::caching::ttl_parameter;
+use super::parallel::sub_steps;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, IntoString, Value};
+
+impl SemanticExecutor {
+    /// Execute an idempotency key step
+    ///
+    /// Looks up the key held by the variable `parameters["key"]` in the
+    /// installed `IdempotencyStore`, scoped to the executing node. If a
+    /// result is stored, it is returned without running anything.
+    /// Otherwise the steps in `parameters["steps"]` run, and the last
+    /// result is stored for `parameters["ttl_seconds"]` (default forever)
+    /// and returned. Failures are not stored, so a failed request can be
+    /// retried with the same key.
+    pub(super) fn execute_idempotency_key(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let key = match self.resolve_parameter_variable(step, "key", ctx)? {
+            Value::String(s) => s.into_string(),
+            Value::Int(i) => i.to_string(),
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+        let ttl = ttl_parameter(step)?;
+        let store = self.state.idempotency.as_deref().ok_or_else(|| {
+            VesperError::ExecutionError("No idempotency store configured".to_string())
+        })?;
+        let scoped_key = format!("{}:{}", ctx.node_id(), key);
+
+        let result = match store.get(&scoped_key) {
+            Some(result) => {
+                tracing::debug!("Idempotency key {} already used, replaying result", key);
+                result
+            }
+            None => {
+                let result = steps
+                    .iter()
+                    .try_fold(Value::Null, |_, sub_step| self.execute_step(sub_step, ctx))?;
+                store.put(&scoped_key, result.clone(), ttl);
+                result
+            }
+        };
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseBackend;
+    use crate::idempotency::InMemoryIdempotencyStore;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Counts charges, returning the running total as the row count
+    #[derive(Default)]
+    struct Payments {
+        charges: Mutex<u64>,
+    }
+
+    impl DatabaseBackend for Payments {
+        fn query(
+            &self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            let mut charges = self.charges.lock().unwrap();
+            *charges += 1;
+            Ok(*charges)
+        }
+    }
+
+    #[test]
+    fn test_idempotency_key_replays_stored_result() {
+        let yaml = r#"
+node_id: charge_v1
+type: function
+intent: charge a card once per request
+
+inputs:
+  request_id:
+    type: string
+
+flow:
+  - step: charge
+    operation: idempotency_key
+    parameters:
+      key: request_id
+      ttl_seconds: 3600
+      steps:
+        - operation: database_execute
+          parameters:
+            sql: "INSERT INTO charges (amount) VALUES (100)"
+"#;
+        let payments = Arc::new(Payments::default());
+        let store = Arc::new(InMemoryIdempotencyStore::new());
+        let mut executor = SemanticExecutor::new()
+            .with_database(payments.clone())
+            .with_idempotency_store(store.clone());
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let charge = |request_id: &str| {
+            let inputs = HashMap::from([("request_id".to_string(), Value::from(request_id))]);
+            executor.execute("charge_v1", inputs).unwrap().data.unwrap()
+        };
+        let first = charge("req-1");
+        assert_eq!(charge("req-1"), first);
+        assert_eq!(*payments.charges.lock().unwrap(), 1);
+
+        assert_ne!(charge("req-2"), first);
+        assert_eq!(*payments.charges.lock().unwrap(), 2);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_idempotency_key_rejects_negative_ttl() {
+        let yaml = r#"
+node_id: charge_v1
+type: function
+intent: charge a card once per request
+
+inputs:
+  request_id:
+    type: string
+
+flow:
+  - step: charge
+    operation: idempotency_key
+    parameters:
+      key: request_id
+      ttl_seconds: -60
+      steps:
+        - operation: database_execute
+          parameters:
+            sql: "INSERT INTO charges (amount) VALUES (100)"
+"#;
+        let payments = Arc::new(Payments::default());
+        let mut executor = SemanticExecutor::new()
+            .with_database(payments.clone())
+            .with_idempotency_store(Arc::new(InMemoryIdempotencyStore::new()));
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let inputs = HashMap::from([("request_id".to_string(), Value::from("req-1"))]);
+        let Err(VesperError::ExecutionError(message)) = executor.execute("charge_v1", inputs)
+        else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("ttl_seconds"));
+        assert_eq!(*payments.charges.lock().unwrap(), 0);
+    }
+}
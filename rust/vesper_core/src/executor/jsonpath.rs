@@ -0,0 +1,153 @@
+//! JSONPath query operation
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+#[cfg(feature = "jsonpath")]
+use jsonpath_rust::{JsonPath, JsonPtr};
+#[cfg(feature = "jsonpath")]
+use std::str::FromStr;
+
+impl SemanticExecutor {
+    /// Execute a JSONPath query step
+    ///
+    /// Evaluates `parameters["query"]` against the value named by
+    /// `parameters["on"]` and returns every match as an array.
+    #[cfg(feature = "jsonpath")]
+    pub(super) fn execute_jsonpath(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let query = step
+            .parameters
+            .get("query")
+            .and_then(|q| q.as_str())
+            .ok_or_else(|| {
+                VesperError::ExecutionError("JSONPath step missing query".to_string())
+            })?;
+
+        let target = self.resolve_parameter_variable(step, "on", ctx)?;
+
+        let result = Value::Array(jsonpath_matches(query, &target)?);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a JSONPath query step (feature disabled)
+    #[cfg(not(feature = "jsonpath"))]
+    pub(super) fn execute_jsonpath(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "jsonpath operation requires the `jsonpath` feature".to_string(),
+        ))
+    }
+}
+
+/// Evaluate `query` against `target`, returning every match
+#[cfg(feature = "jsonpath")]
+pub(super) fn jsonpath_matches(query: &str, target: &Value) -> Result<Vec<Value>> {
+    let path = JsonPath::<serde_json::Value>::from_str(query)
+        .map_err(|e| VesperError::ExecutionError(format!("Invalid JSONPath {}: {}", query, e)))?;
+
+    let target = serde_json::Value::from(target);
+    Ok(path
+        .find_slice_ptr(&target)
+        .into_iter()
+        .map(|found| match found {
+            JsonPtr::Slice(v) => Value::from(v.clone()),
+            JsonPtr::NewValue(v) => Value::from(v),
+        })
+        .collect())
+}
+
+#[cfg(all(test, feature = "jsonpath"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn query(query: &str, on: Value) -> Value {
+        let yaml = format!(
+            r#"
+node_id: query_v1
+type: function
+intent: query a document
+
+inputs:
+  doc:
+    type: object
+
+flow:
+  - step: select
+    operation: jsonpath
+    parameters:
+      on: doc
+      query: "{}"
+"#,
+            query
+        );
+        let node = VesperLoader::new().load_string(&yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+
+        let mut inputs = HashMap::new();
+        inputs.insert("doc".to_string(), on);
+        executor.execute("query_v1", inputs).unwrap().data.unwrap()
+    }
+
+    fn document() -> Value {
+        Value::from(serde_json::json!({
+            "name": "store",
+            "items": [
+                {"name": "pen", "price": 2},
+                {"name": "book", "price": 15},
+                {"name": "cup", "price": 7}
+            ]
+        }))
+    }
+
+    fn names(value: Value) -> Vec<String> {
+        let Value::Array(items) = value else {
+            panic!("expected array result");
+        };
+        let mut names: Vec<String> = items
+            .iter()
+            .filter_map(|v| match v {
+                Value::Object(o) => o.get("name").and_then(|n| n.as_str()).map(String::from),
+                Value::String(s) => Some(s.to_string()),
+                _ => None,
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_jsonpath_wildcard() {
+        let Value::Array(items) = query("$.*", document()) else {
+            panic!("expected array result");
+        };
+        assert_eq!(items.len(), 2);
+    }
+
+    #[test]
+    fn test_jsonpath_filter() {
+        let result = query("$.items[?(@.price < 10)]", document());
+        assert_eq!(names(result), vec!["cup", "pen"]);
+    }
+
+    #[test]
+    fn test_jsonpath_recursive_descent() {
+        let result = query("$..name", document());
+        assert_eq!(names(result), vec!["book", "cup", "pen", "store"]);
+    }
+
+    #[test]
+    fn test_jsonpath_no_match() {
+        assert_eq!(query("$.missing", document()), Value::Array(vec![]));
+    }
+}
@@ -0,0 +1,206 @@
+//! JSON Web Token operations
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a JWT decode step
+    ///
+    /// Decodes the token named by `parameters["on"]` into
+    /// `{header, payload}` without checking its signature or claims.
+    #[cfg(feature = "jwt")]
+    pub(super) fn execute_jwt_decode(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use jsonwebtoken::{DecodingKey, Validation};
+
+        let token = jwt_text(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+
+        let mut validation = Validation::default();
+        validation.insecure_disable_signature_validation();
+        validation.validate_exp = false;
+        validation.validate_aud = false;
+        validation.required_spec_claims.clear();
+
+        let decoded = jsonwebtoken::decode::<serde_json::Value>(
+            &token,
+            &DecodingKey::from_secret(&[]),
+            &validation,
+        )
+        .map_err(|e| VesperError::ExecutionError(format!("Invalid JWT: {}", e)))?;
+
+        let header = serde_json::to_value(&decoded.header)
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid JWT header: {}", e)))?;
+
+        let mut fields = std::collections::HashMap::new();
+        fields.insert("header".to_string(), Value::from(header));
+        fields.insert("payload".to_string(), Value::from(decoded.claims));
+
+        let result = Value::Object(fields);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a JWT verification step
+    ///
+    /// Verifies the token named by `parameters["on"]` against
+    /// `parameters["secret"]` (HMAC) or a PEM `parameters["public_key"]`
+    /// (RSA/EC), checks its `exp` and `nbf` claims and returns the payload.
+    #[cfg(feature = "jwt")]
+    pub(super) fn execute_jwt_verify(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+
+        let token = jwt_text(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+        let header = jsonwebtoken::decode_header(&token)
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid JWT: {}", e)))?;
+
+        let key = if step.parameters.contains_key("secret") {
+            let secret = self.resolve_string_parameter(step, "secret", ctx)?;
+            DecodingKey::from_secret(secret.as_bytes())
+        } else if step.parameters.contains_key("public_key") {
+            let pem = self.resolve_string_parameter(step, "public_key", ctx)?;
+            match header.alg {
+                Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem.as_bytes()),
+                _ => DecodingKey::from_rsa_pem(pem.as_bytes()),
+            }
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid public key: {}", e)))?
+        } else {
+            return Err(VesperError::ExecutionError(format!(
+                "Step {} needs a secret or public_key to verify a JWT",
+                step.step
+            )));
+        };
+
+        // Audience checks are left to the flow, which sees the full payload
+        let mut validation = Validation::new(header.alg);
+        validation.validate_nbf = true;
+        validation.validate_aud = false;
+
+        let decoded = jsonwebtoken::decode::<serde_json::Value>(&token, &key, &validation)
+            .map_err(|e| VesperError::ExecutionError(format!("JWT verification failed: {}", e)))?;
+
+        let result = Value::from(decoded.claims);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a JWT decode step (feature disabled)
+    #[cfg(not(feature = "jwt"))]
+    pub(super) fn execute_jwt_decode(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "jwt_decode operation requires the `jwt` feature".to_string(),
+        ))
+    }
+
+    /// Execute a JWT verification step (feature disabled)
+    #[cfg(not(feature = "jwt"))]
+    pub(super) fn execute_jwt_verify(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "jwt_verify operation requires the `jwt` feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(feature = "jwt")]
+fn jwt_text(value: &Value) -> Result<String> {
+    value
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| VesperError::TypeError {
+            expected: "string".to_string(),
+            actual: format!("{:?}", value),
+        })
+}
+
+#[cfg(all(test, feature = "jwt"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use jsonwebtoken::{EncodingKey, Header};
+    use std::collections::HashMap;
+
+    fn token(exp_offset: i64) -> String {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let claims = serde_json::json!({ "sub": "user-42", "exp": now + exp_offset });
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(b"s3cret"),
+        )
+        .unwrap()
+    }
+
+    fn run(operation: &str, extra: &str, jwt: String) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: jwt_v1
+type: function
+intent: inspect a bearer token
+
+inputs:
+  token:
+    type: string
+
+flow:
+  - step: inspect
+    operation: {}
+    parameters:
+      on: token
+      {}
+"#,
+            operation, extra
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("token".to_string(), Value::from(jwt));
+        executor.execute("jwt_v1", inputs).map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_jwt_decode() {
+        let Value::Object(decoded) = run("jwt_decode", "", token(-3600)).unwrap() else {
+            panic!("expected object");
+        };
+        let Value::Object(header) = &decoded["header"] else {
+            panic!("expected header object");
+        };
+        assert_eq!(header["alg"], Value::from("HS256"));
+        let Value::Object(payload) = &decoded["payload"] else {
+            panic!("expected payload object");
+        };
+        assert_eq!(payload["sub"], Value::from("user-42"));
+    }
+
+    #[test]
+    fn test_jwt_verify() {
+        let Value::Object(payload) = run("jwt_verify", "secret: s3cret", token(3600)).unwrap()
+        else {
+            panic!("expected object");
+        };
+        assert_eq!(payload["sub"], Value::from("user-42"));
+
+        assert!(run("jwt_verify", "secret: wrong", token(3600)).is_err());
+        assert!(run("jwt_verify", "secret: s3cret", token(-3600)).is_err());
+        assert!(run("jwt_verify", "", token(3600)).is_err());
+    }
+}
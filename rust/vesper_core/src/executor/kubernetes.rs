@@ -0,0 +1,181 @@
+//! Kubernetes API operation
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+/// Capability a node must declare to use the Kubernetes API
+#[cfg(feature = "kubernetes")]
+const KUBERNETES_CAPABILITY: &str = "capability:kubernetes";
+
+impl SemanticExecutor {
+    /// Execute a Kubernetes API step
+    ///
+    /// Runs `parameters["verb"]` (`get`, `list`, `apply` or `delete`) on
+    /// `parameters["resource"]` (plural name, e.g. `deployments`) in
+    /// `parameters["namespace"]`. `get` and `delete` take
+    /// `parameters["name"]`; `apply` server-side applies the object named
+    /// by `parameters["body"]`. The cluster is reached through the local
+    /// kubeconfig or in-cluster service account.
+    #[cfg(feature = "kubernetes")]
+    pub(super) fn execute_kubernetes_api(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use kube::api::{Api, DeleteParams, DynamicObject, ListParams, Patch, PatchParams};
+        use kube::discovery::{Discovery, Scope};
+
+        self.require_capability(step, ctx, KUBERNETES_CAPABILITY)?;
+
+        let verb = self.resolve_string_parameter(step, "verb", ctx)?;
+        if !matches!(verb.as_str(), "get" | "list" | "apply" | "delete") {
+            return Err(VesperError::ExecutionError(format!(
+                "Unsupported Kubernetes verb: {}",
+                verb
+            )));
+        }
+        let resource = self.resolve_string_parameter(step, "resource", ctx)?;
+        let namespace = match step.parameters.get("namespace") {
+            Some(_) => Some(self.resolve_string_parameter(step, "namespace", ctx)?),
+            None => None,
+        };
+        let name = match step.parameters.get("name") {
+            Some(_) => Some(self.resolve_string_parameter(step, "name", ctx)?),
+            None => None,
+        };
+        let body = match verb.as_str() {
+            "apply" => Some(serde_json::Value::from(
+                &self.resolve_parameter_variable(step, "body", ctx)?,
+            )),
+            _ => None,
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let result: std::result::Result<serde_json::Value, String> = runtime.block_on(async {
+            let client = kube::Client::try_default()
+                .await
+                .map_err(|e| format!("No Kubernetes client: {}", e))?;
+            let discovery = Discovery::new(client.clone())
+                .run()
+                .await
+                .map_err(|e| format!("API discovery failed: {}", e))?;
+            let (api_resource, capabilities) = discovery
+                .groups()
+                .flat_map(|group| group.recommended_resources())
+                .find(|(ar, _)| ar.plural == resource)
+                .ok_or_else(|| format!("Unknown resource: {}", resource))?;
+
+            let api: Api<DynamicObject> = match (&capabilities.scope, &namespace) {
+                (Scope::Namespaced, Some(ns)) => Api::namespaced_with(client, ns, &api_resource),
+                (Scope::Namespaced, None) if verb != "list" => {
+                    Api::default_namespaced_with(client, &api_resource)
+                }
+                _ => Api::all_with(client, &api_resource),
+            };
+            let required_name = || {
+                name.clone()
+                    .ok_or_else(|| format!("Kubernetes {} needs a name", verb))
+            };
+
+            let value = match verb.as_str() {
+                "get" => serde_json::to_value(
+                    api.get(&required_name()?)
+                        .await
+                        .map_err(|e| e.to_string())?,
+                ),
+                "list" => serde_json::to_value(
+                    api.list(&ListParams::default())
+                        .await
+                        .map_err(|e| e.to_string())?,
+                ),
+                "delete" => api
+                    .delete(&required_name()?, &DeleteParams::default())
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .either(serde_json::to_value, serde_json::to_value),
+                _ => {
+                    let object: DynamicObject = serde_json::from_value(body.unwrap_or_default())
+                        .map_err(|e| format!("Invalid resource body: {}", e))?;
+                    let object_name = match &object.metadata.name {
+                        Some(n) => n.clone(),
+                        None => required_name()?,
+                    };
+                    let params = PatchParams::apply("vesper").force();
+                    serde_json::to_value(
+                        api.patch(&object_name, &params, &Patch::Apply(&object))
+                            .await
+                            .map_err(|e| e.to_string())?,
+                    )
+                }
+            };
+            value.map_err(|e| e.to_string())
+        });
+
+        let result = Value::from(result.map_err(|e| {
+            VesperError::ExecutionError(format!("Kubernetes {} {} failed: {}", verb, resource, e))
+        })?);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a Kubernetes API step (feature disabled)
+    #[cfg(not(feature = "kubernetes"))]
+    pub(super) fn execute_kubernetes_api(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "kubernetes_api operation requires the `kubernetes` feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "kubernetes"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn run(security: &str, verb: &str) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: pods_v1
+type: function
+intent: inspect workloads
+
+inputs: {{}}
+{}
+flow:
+  - step: inspect
+    operation: kubernetes_api
+    parameters:
+      verb: {}
+      resource: pods
+      namespace: default
+"#,
+            security, verb
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        executor
+            .execute("pods_v1", HashMap::new())
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_kubernetes_requires_capability() {
+        let result = run("", "list");
+        assert!(matches!(result, Err(VesperError::SecurityViolation(_))));
+    }
+
+    #[test]
+    fn test_kubernetes_rejects_unknown_verb() {
+        let security = "security:\n  capabilities_required: [\"capability:kubernetes\"]\n";
+        let result = run(security, "scale");
+        assert!(matches!(result, Err(VesperError::ExecutionError(_))));
+    }
+}
@@ -0,0 +1,279 @@
+//! Linear algebra operations over numeric arrays
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a dot product step
+    ///
+    /// Multiplies the equal-length vectors named by `parameters["left"]`
+    /// and `parameters["right"]` and returns the scalar sum.
+    pub(super) fn execute_dot_product(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let left = to_vector(&self.resolve_parameter_variable(step, "left", ctx)?)?;
+        let right = to_vector(&self.resolve_parameter_variable(step, "right", ctx)?)?;
+
+        if left.len() != right.len() {
+            return Err(VesperError::ExecutionError(format!(
+                "Dot product dimension mismatch: {} vs {}",
+                left.len(),
+                right.len()
+            )));
+        }
+
+        let result = Value::Float(dot(&left, &right));
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a matrix multiplication step
+    ///
+    /// Treats `parameters["left"]` and `parameters["right"]` as row-major
+    /// matrices and returns their product as an array of float rows.
+    pub(super) fn execute_matrix_multiply(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let left = to_matrix(&self.resolve_parameter_variable(step, "left", ctx)?)?;
+        let right = to_matrix(&self.resolve_parameter_variable(step, "right", ctx)?)?;
+
+        let inner = left.first().map(Vec::len).unwrap_or(0);
+        if inner != right.len() {
+            return Err(VesperError::ExecutionError(format!(
+                "Matrix dimension mismatch: {}x{} times {}x{}",
+                left.len(),
+                inner,
+                right.len(),
+                right.first().map(Vec::len).unwrap_or(0)
+            )));
+        }
+
+        // Transpose once so every output cell is a contiguous dot product
+        let cols = right.first().map(Vec::len).unwrap_or(0);
+        let right_t: Vec<Vec<f64>> = (0..cols)
+            .map(|c| right.iter().map(|row| row[c]).collect())
+            .collect();
+
+        let product = left
+            .iter()
+            .map(|row| {
+                Value::Array(
+                    right_t
+                        .iter()
+                        .map(|col| Value::Float(dot(row, col)))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let result = Value::Array(product);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a vector similarity step
+    ///
+    /// Returns the cosine similarity of the equal-length vectors named by
+    /// `parameters["left"]` and `parameters["right"]`, such as embeddings.
+    pub(super) fn execute_vector_similarity(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let left = to_vector(&self.resolve_parameter_variable(step, "left", ctx)?)?;
+        let right = to_vector(&self.resolve_parameter_variable(step, "right", ctx)?)?;
+
+        if left.len() != right.len() {
+            return Err(VesperError::ExecutionError(format!(
+                "Vector similarity dimension mismatch: {} vs {}",
+                left.len(),
+                right.len()
+            )));
+        }
+
+        let norms = dot(&left, &left).sqrt() * dot(&right, &right).sqrt();
+        if norms == 0.0 {
+            return Err(VesperError::ExecutionError(
+                "Cosine similarity is undefined for zero vectors".to_string(),
+            ));
+        }
+
+        let result = Value::Float(dot(&left, &right) / norms);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Convert an array value to a numeric vector
+fn to_vector(value: &Value) -> Result<Vec<f64>> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_float().ok_or_else(|| VesperError::TypeError {
+                    expected: "number".to_string(),
+                    actual: format!("{:?}", item),
+                })
+            })
+            .collect(),
+        other => Err(VesperError::TypeError {
+            expected: "array".to_string(),
+            actual: format!("{:?}", other),
+        }),
+    }
+}
+
+/// Convert an array of arrays to a rectangular numeric matrix
+fn to_matrix(value: &Value) -> Result<Vec<Vec<f64>>> {
+    let rows = match value {
+        Value::Array(rows) => rows.iter().map(to_vector).collect::<Result<Vec<_>>>()?,
+        other => {
+            return Err(VesperError::TypeError {
+                expected: "matrix".to_string(),
+                actual: format!("{:?}", other),
+            })
+        }
+    };
+
+    if let Some(first) = rows.first() {
+        if rows.iter().any(|row| row.len() != first.len()) {
+            return Err(VesperError::ExecutionError(
+                "Matrix rows must all have the same length".to_string(),
+            ));
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Inner product of two equal-length slices
+#[cfg(not(feature = "simd"))]
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Inner product of two equal-length slices, four lanes at a time
+#[cfg(feature = "simd")]
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    use std::simd::{f64x4, num::SimdFloat};
+
+    let chunks = a.len() / 4;
+    let mut acc = f64x4::splat(0.0);
+    for i in 0..chunks {
+        let x = f64x4::from_slice(&a[i * 4..]);
+        let y = f64x4::from_slice(&b[i * 4..]);
+        acc += x * y;
+    }
+
+    let tail: f64 = a[chunks * 4..]
+        .iter()
+        .zip(&b[chunks * 4..])
+        .map(|(x, y)| x * y)
+        .sum();
+    acc.reduce_sum() + tail
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn vector(values: &[i64]) -> Value {
+        Value::Array(values.iter().map(|v| Value::Int(*v)).collect())
+    }
+
+    fn matrix(rows: &[&[i64]]) -> Value {
+        Value::Array(rows.iter().map(|row| vector(row)).collect())
+    }
+
+    fn executor_for(operation: &str) -> SemanticExecutor {
+        let yaml = format!(
+            r#"
+node_id: linalg_v1
+type: function
+intent: linear algebra
+
+inputs:
+  a:
+    type: array
+  b:
+    type: array
+
+flow:
+  - step: compute
+    operation: {}
+    parameters:
+      left: a
+      right: b
+"#,
+            operation
+        );
+        let node = VesperLoader::new().load_string(&yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+        executor
+    }
+
+    #[test]
+    fn test_dot_product() {
+        let executor = executor_for("dot_product");
+
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), vector(&[1, 2, 3, 4, 5]));
+        inputs.insert("b".to_string(), vector(&[6, 7, 8, 9, 10]));
+
+        let result = executor.execute("linalg_v1", inputs).unwrap();
+        assert_eq!(result.data, Some(Value::Float(130.0)));
+    }
+
+    #[test]
+    fn test_matrix_multiply() {
+        let executor = executor_for("matrix_multiply");
+
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), matrix(&[&[1, 2, 3], &[4, 5, 6]]));
+        inputs.insert("b".to_string(), matrix(&[&[7, 8], &[9, 10], &[11, 12]]));
+
+        let result = executor.execute("linalg_v1", inputs).unwrap();
+
+        let expected = Value::Array(vec![
+            Value::Array(vec![Value::Float(58.0), Value::Float(64.0)]),
+            Value::Array(vec![Value::Float(139.0), Value::Float(154.0)]),
+        ]);
+        assert_eq!(result.data, Some(expected));
+    }
+
+    #[test]
+    fn test_vector_similarity() {
+        let executor = executor_for("vector_similarity");
+        let similarity = |a: &[i64], b: &[i64]| {
+            let mut inputs = HashMap::new();
+            inputs.insert("a".to_string(), vector(a));
+            inputs.insert("b".to_string(), vector(b));
+            match executor.execute("linalg_v1", inputs).unwrap().data {
+                Some(Value::Float(f)) => f,
+                other => panic!("expected float, got {:?}", other),
+            }
+        };
+
+        assert!((similarity(&[1, 2, 3], &[1, 2, 3]) - 1.0).abs() < 1e-12);
+        assert!(similarity(&[1, 0, 2], &[0, 5, 0]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_matrix_dimension_mismatch() {
+        let executor = executor_for("matrix_multiply");
+
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), matrix(&[&[1, 2], &[3, 4]]));
+        inputs.insert("b".to_string(), matrix(&[&[1, 2, 3]]));
+
+        assert!(executor.execute("linalg_v1", inputs).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Mutual exclusion across executions
+
+use super::parallel::sub_steps;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::locks::LockError;
+use crate::types::{FlowStep, Value};
+use std::time::Duration;
+
+impl SemanticExecutor {
+    /// Execute a distributed lock step
+    ///
+    /// Acquires the lock on `parameters["resource"]` from the installed
+    /// `DistributedLockBackend`, waiting up to `parameters["timeout_ms"]`
+    /// (default 0), then runs the steps in `parameters["steps"]` and
+    /// returns the last result. The lock is released once the steps finish,
+    /// whether or not they succeed.
+    pub(super) fn execute_distributed_lock(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let resource = self.resolve_string_parameter(step, "resource", ctx)?;
+        let timeout = Duration::from_millis(
+            step.parameters
+                .get("timeout_ms")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0),
+        );
+        let backend =
+            self.state.lock_backend.as_deref().ok_or_else(|| {
+                VesperError::ExecutionError("No lock backend configured".to_string())
+            })?;
+
+        let guard = backend
+            .try_acquire(&resource, timeout)
+            .map_err(|e| match e {
+                LockError::Timeout(_) => VesperError::ExecutionError(format!(
+                    "Could not acquire lock {} within {}ms",
+                    resource,
+                    timeout.as_millis()
+                )),
+                other => VesperError::ExecutionError(other.to_string()),
+            })?;
+        tracing::debug!("Acquired lock {}", resource);
+
+        let result = steps
+            .iter()
+            .try_fold(Value::Null, |_, sub_step| self.execute_step(sub_step, ctx));
+        guard.release();
+
+        let result = result?;
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseBackend;
+    use crate::loader::VesperLoader;
+    use crate::locks::InMemoryLockBackend;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Sleeps 100ms per statement, recording the peak number running at once
+    #[derive(Default)]
+    struct LedgerDatabase {
+        active: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl DatabaseBackend for LedgerDatabase {
+        fn query(
+            &self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(active, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(100));
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(1)
+        }
+    }
+
+    fn run_concurrently(timeout_ms: u64) -> (Vec<Result<Value>>, usize) {
+        let yaml = format!(
+            r#"
+node_id: settle_v1
+type: function
+intent: settle the ledger one process at a time
+
+flow:
+  - step: settle
+    operation: distributed_lock
+    parameters:
+      resource: ledger
+      timeout_ms: {timeout_ms}
+      steps:
+        - operation: database_execute
+          parameters:
+            sql: "UPDATE ledger SET settled = true"
+"#
+        );
+        let database = Arc::new(LedgerDatabase::default());
+        let mut executor = SemanticExecutor::new()
+            .with_database(database.clone())
+            .with_lock_backend(Arc::new(InMemoryLockBackend::new()));
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let barrier = std::sync::Barrier::new(2);
+        let outcomes = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        executor
+                            .execute("settle_v1", HashMap::new())
+                            .map(|r| r.data.unwrap())
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        (outcomes, database.peak.load(Ordering::SeqCst))
+    }
+
+    #[test]
+    fn test_distributed_lock_serializes_executions() {
+        let (outcomes, peak) = run_concurrently(1000);
+        assert!(outcomes.iter().all(|o| o.is_ok()));
+        assert_eq!(peak, 1);
+    }
+
+    #[test]
+    fn test_distributed_lock_times_out() {
+        let (outcomes, peak) = run_concurrently(10);
+        let failures: Vec<_> = outcomes.iter().filter_map(|o| o.as_ref().err()).collect();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0]
+            .to_string()
+            .contains("Could not acquire lock ledger within 10ms"));
+        assert_eq!(peak, 1);
+    }
+}
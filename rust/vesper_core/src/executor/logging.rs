@@ -0,0 +1,315 @@
+//! Structured logging operation
+//!
+//! `tracing` events normally declare their field names at compile time,
+//! but flows choose theirs at runtime. Each distinct set of field names
+//! therefore gets a callsite built on first use and reused afterwards;
+//! these are leaked, which is bounded by the number of distinct sets the
+//! loaded flows use.
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tracing::callsite::{Callsite, Identifier};
+use tracing::field::{Field, FieldSet};
+use tracing::metadata::Kind;
+use tracing::subscriber::Interest;
+use tracing::{Level, Metadata};
+
+/// Fields every structured log event carries ahead of the flow's own
+const CONTEXT_FIELDS: [&str; 5] = [
+    "message",
+    "timestamp",
+    "node_id",
+    "step_name",
+    "execution_id",
+];
+
+/// Most fields a single event can carry
+const MAX_FIELDS: usize = 32;
+
+impl SemanticExecutor {
+    /// Execute a structured log step
+    ///
+    /// Emits a `tracing` event at `parameters["level"]` (default `info`)
+    /// whose fields are the timestamp, node, step and execution id plus
+    /// each entry of `parameters["fields"]`, a mapping of names to values
+    /// or variable references, or the name of an object variable.
+    /// `parameters["message"]` defaults to the step name. Returns the
+    /// logged record.
+    pub(super) fn execute_structured_log(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let level = match step
+            .parameters
+            .get("level")
+            .and_then(|l| l.as_str())
+            .unwrap_or("info")
+        {
+            "trace" => Level::TRACE,
+            "debug" => Level::DEBUG,
+            "info" => Level::INFO,
+            "warn" | "warning" => Level::WARN,
+            "error" => Level::ERROR,
+            other => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Unknown log level: {}",
+                    other
+                )))
+            }
+        };
+        let message = match step.parameters.get("message") {
+            Some(_) => self.resolve_string_parameter(step, "message", ctx)?,
+            None => step.step.clone(),
+        };
+
+        let mut fields: Vec<(String, Value)> = match step.parameters.get("fields") {
+            None => Vec::new(),
+            Some(serde_yaml::Value::Mapping(mapping)) => mapping
+                .iter()
+                .filter_map(|(k, v)| {
+                    k.as_str()
+                        .map(|k| (k.to_string(), self.resolve_value(v, ctx)))
+                })
+                .collect(),
+            Some(_) => match self.resolve_parameter_variable(step, "fields", ctx)? {
+                Value::Object(object) => object.into_iter().collect(),
+                other => {
+                    return Err(VesperError::TypeError {
+                        expected: "object".to_string(),
+                        actual: format!("{:?}", other),
+                    })
+                }
+            },
+        };
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        if let Some((name, _)) = fields
+            .iter()
+            .find(|(name, _)| CONTEXT_FIELDS.contains(&name.as_str()))
+        {
+            return Err(VesperError::ExecutionError(format!(
+                "Log field {} is reserved",
+                name
+            )));
+        }
+        if CONTEXT_FIELDS.len() + fields.len() > MAX_FIELDS {
+            return Err(VesperError::ExecutionError(format!(
+                "Too many log fields: at most {} allowed",
+                MAX_FIELDS - CONTEXT_FIELDS.len()
+            )));
+        }
+
+        let timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let mut record: Vec<(String, Value)> = vec![
+            ("message".to_string(), Value::from(message)),
+            ("timestamp".to_string(), Value::from(timestamp)),
+            ("node_id".to_string(), Value::from(ctx.node_id())),
+            ("step_name".to_string(), Value::from(step.step.as_str())),
+            ("execution_id".to_string(), Value::from(ctx.execution_id())),
+        ];
+        record.extend(fields);
+        emit_event(level, &record);
+
+        let result = Value::Object(record.into_iter().collect::<HashMap<_, _>>());
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Callsite for one level and set of field names
+struct DynamicCallsite {
+    metadata: OnceLock<Metadata<'static>>,
+}
+
+impl Callsite for DynamicCallsite {
+    fn set_interest(&self, _interest: Interest) {}
+
+    fn metadata(&self) -> &Metadata<'_> {
+        self.metadata
+            .get()
+            .expect("callsite metadata is set before registration")
+    }
+}
+
+/// Callsites built so far, by level and field names
+type CallsiteRegistry = Mutex<HashMap<(Level, Vec<String>), &'static DynamicCallsite>>;
+
+/// Find or build the callsite for `level` and `names`
+fn callsite(level: Level, names: Vec<String>) -> &'static DynamicCallsite {
+    static CALLSITES: OnceLock<CallsiteRegistry> = OnceLock::new();
+
+    let mut callsites = CALLSITES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(callsite) = callsites.get(&(level, names.clone())) {
+        return callsite;
+    }
+
+    let field_names: &'static [&'static str] = Box::leak(
+        names
+            .iter()
+            .map(|name| &*Box::leak(name.clone().into_boxed_str()))
+            .collect::<Vec<&'static str>>()
+            .into_boxed_slice(),
+    );
+    let callsite: &'static DynamicCallsite = Box::leak(Box::new(DynamicCallsite {
+        metadata: OnceLock::new(),
+    }));
+    let _ = callsite.metadata.set(Metadata::new(
+        "structured_log",
+        module_path!(),
+        level,
+        Some(file!()),
+        Some(line!()),
+        Some(module_path!()),
+        FieldSet::new(field_names, Identifier(callsite)),
+        Kind::EVENT,
+    ));
+    tracing::callsite::register(callsite);
+    callsites.insert((level, names), callsite);
+    callsite
+}
+
+/// Dispatch one event carrying `record` as separate fields
+fn emit_event(level: Level, record: &[(String, Value)]) {
+    let names = record.iter().map(|(name, _)| name.clone()).collect();
+    let metadata = callsite(level, names).metadata();
+
+    let values: Vec<Box<dyn tracing::Value>> = record
+        .iter()
+        .map(|(_, value)| -> Box<dyn tracing::Value> {
+            match value {
+                Value::Bool(b) => Box::new(*b),
+                Value::Int(i) => Box::new(*i),
+                Value::Float(f) => Box::new(*f),
+                Value::String(s) => Box::new(s.to_string()),
+                other => Box::new(serde_json::Value::from(other).to_string()),
+            }
+        })
+        .collect();
+    let fields: Vec<Field> = metadata.fields().iter().collect();
+    // Value sets are fixed-size arrays; unused slots carry no value
+    let entries: [(&Field, Option<&dyn tracing::Value>); MAX_FIELDS] =
+        std::array::from_fn(|i| match fields.get(i) {
+            Some(field) => (field, Some(values[i].as_ref())),
+            None => (&fields[0], None),
+        });
+
+    tracing::dispatcher::get_default(|dispatch| {
+        if dispatch.enabled(metadata) {
+            dispatch.event(&tracing::Event::new(
+                metadata,
+                &metadata.fields().value_set(&entries),
+            ));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    /// Writer collecting subscriber output in memory
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_structured_log_fields() {
+        let yaml = r#"
+node_id: checkout_v1
+type: function
+intent: record a completed checkout
+
+inputs:
+  order_id:
+    type: string
+  total:
+    type: number
+
+flow:
+  - step: audit
+    operation: structured_log
+    parameters:
+      level: warn
+      message: checkout completed
+      fields:
+        order_id: "{order_id}"
+        total: "{total}"
+        channel: web
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+        let mut inputs = HashMap::new();
+        inputs.insert("order_id".to_string(), Value::from("ord-42"));
+        inputs.insert("total".to_string(), Value::Float(19.5));
+
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            executor.execute("checkout_v1", inputs).unwrap();
+        });
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let line = output
+            .lines()
+            .find(|line| line.contains("checkout completed"))
+            .expect("structured log line");
+        let event: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(event["level"], "WARN");
+
+        let fields = &event["fields"];
+        assert_eq!(fields["message"], "checkout completed");
+        assert_eq!(fields["node_id"], "checkout_v1");
+        assert_eq!(fields["step_name"], "audit");
+        assert_eq!(fields["order_id"], "ord-42");
+        assert_eq!(fields["total"], 19.5);
+        assert_eq!(fields["channel"], "web");
+        assert_eq!(fields["execution_id"].as_str().unwrap().len(), 36);
+        assert!(fields["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+
+    #[test]
+    fn test_structured_log_rejects_reserved_field() {
+        let yaml = r#"
+node_id: audit_v1
+type: function
+intent: log with a clashing field
+
+inputs: {}
+
+flow:
+  - step: audit
+    operation: structured_log
+    parameters:
+      fields:
+        node_id: spoofed
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let result = executor.execute("audit_v1", HashMap::new());
+        assert!(matches!(result, Err(VesperError::ExecutionError(_))));
+    }
+}
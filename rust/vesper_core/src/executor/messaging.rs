@@ -0,0 +1,112 @@
+//! Message queue operations backed by the executor's `MessageQueueBackend`
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::queue::MessageQueueBackend;
+use crate::types::{FlowStep, Value};
+use std::time::Duration;
+
+impl SemanticExecutor {
+    /// Execute a message publish step
+    ///
+    /// Publishes the value named by `parameters["message"]` to
+    /// `parameters["topic"]` and returns it.
+    pub(super) fn execute_message_queue_publish(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let topic = self.resolve_string_parameter(step, "topic", ctx)?;
+        let message = self.resolve_parameter_variable(step, "message", ctx)?;
+
+        self.message_queue()?
+            .publish(&topic, message.clone())
+            .map_err(|e| {
+                VesperError::ExecutionError(format!("Publishing to {} failed: {}", topic, e))
+            })?;
+
+        self.store_output(step, ctx, &message);
+        Ok(message)
+    }
+
+    /// Execute a message consume step
+    ///
+    /// Takes the next message from `parameters["topic"]`, waiting up to
+    /// `parameters["timeout_ms"]` (default: no wait). Returns null when no
+    /// message arrives in time.
+    pub(super) fn execute_message_queue_consume(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let topic = self.resolve_string_parameter(step, "topic", ctx)?;
+        let timeout = step
+            .parameters
+            .get("timeout_ms")
+            .and_then(|t| t.as_u64())
+            .map(Duration::from_millis);
+
+        let result = self
+            .message_queue()?
+            .consume(&topic, timeout)
+            .map_err(|e| {
+                VesperError::ExecutionError(format!("Consuming from {} failed: {}", topic, e))
+            })?
+            .unwrap_or(Value::Null);
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    fn message_queue(&self) -> Result<&dyn MessageQueueBackend> {
+        self.state.message_queue.as_deref().ok_or_else(|| {
+            VesperError::ExecutionError("No message queue backend configured".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use crate::queue::InMemoryQueueBackend;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_publish_then_consume() {
+        let yaml = r#"
+node_id: relay_v1
+type: function
+intent: pass an event through a queue
+
+inputs:
+  event:
+    type: object
+
+flow:
+  - step: publish
+    operation: message_queue_publish
+    parameters:
+      topic: orders.created
+      message: event
+  - step: consume
+    operation: message_queue_consume
+    parameters:
+      topic: orders.created
+      timeout_ms: 100
+"#;
+        let queue = Arc::new(InMemoryQueueBackend::new());
+        let mut executor = SemanticExecutor::new().with_message_queue(queue.clone());
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut event = HashMap::new();
+        event.insert("order_id".to_string(), Value::from("ord-1"));
+        let mut inputs = HashMap::new();
+        inputs.insert("event".to_string(), Value::Object(event.clone()));
+
+        let result = executor.execute("relay_v1", inputs).unwrap();
+        assert_eq!(result.data, Some(Value::Object(event)));
+        assert_eq!(queue.consume("orders.created", None), Ok(None));
+    }
+}
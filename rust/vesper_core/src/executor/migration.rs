@@ -0,0 +1,117 @@
+//! Migration guides between registered node versions
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::node_diff::NodeDiff;
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a migration guide step
+    ///
+    /// Compares the registered nodes `parameters["from_version"]` and
+    /// `parameters["to_version"]` with `NodeDiff::compute` and returns its
+    /// Markdown `migration_guide`.
+    pub(super) fn execute_migration_guide(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let node = |key: &str| {
+            let node_id = self.resolve_string_parameter(step, key, ctx)?;
+            self.state
+                .nodes
+                .get(&node_id)
+                .ok_or_else(|| VesperError::ExecutionError(format!("Node not found: {}", node_id)))
+        };
+        let diff = NodeDiff::compute(node("from_version")?, node("to_version")?);
+
+        let result = Value::from(diff.migration_guide());
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_migration_guide() {
+        let loader = VesperLoader::new();
+        let mut executor = SemanticExecutor::new();
+        for yaml in [
+            r#"
+node_id: add_v1
+type: function
+intent: add two numbers
+
+inputs:
+  a:
+    type: integer
+  verbose:
+    type: boolean
+    required: false
+
+outputs:
+  success:
+    sum:
+      type: integer
+"#,
+            r#"
+node_id: add_v2
+type: function
+intent: add two numbers
+
+inputs:
+  operand_a:
+    type: integer
+  precision:
+    type: string
+
+outputs:
+  success:
+    sum:
+      type: integer
+    rounded:
+      type: boolean
+"#,
+            r#"
+node_id: guide_v1
+type: function
+intent: explain an upgrade
+
+flow:
+  - step: guide
+    operation: migration_guide
+    parameters:
+      from_version: add_v1
+      to_version: add_v2
+"#,
+        ] {
+            executor.register(loader.load_string(yaml).unwrap());
+        }
+
+        let result = executor.execute("guide_v1", HashMap::new()).unwrap();
+        assert_eq!(
+            result.data,
+            Some(Value::from(
+                "# Migrating from `add_v1` to `add_v2`\n\
+                 \n\
+                 To migrate from `add_v1` to `add_v2`, rename input `a` to `operand_a`, \
+                 add new required input `precision` of type `string`.\n\
+                 \n\
+                 ## Breaking changes\n\
+                 \n\
+                 - rename input `a` to `operand_a`\n\
+                 - add new required input `precision` of type `string`\n\
+                 \n\
+                 ## Non-breaking changes\n\
+                 \n\
+                 - stop passing removed input `verbose`\n\
+                 - optionally read new output `rounded`\n"
+            ))
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Machine learning model operations
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::models::{ModelProvider, ModelRequest, StructuredExtractor};
+use crate::types::{FlowStep, IntoString, Value};
+use std::collections::HashMap;
+
+impl SemanticExecutor {
+    /// Execute a model inference step
+    ///
+    /// Sends the prompt named by `parameters["prompt"]` to `parameters["model"]`
+    /// through the provider registered as `parameters["provider"]`, with
+    /// optional `max_tokens` and `temperature`. Returns
+    /// `{text, tokens_used, finish_reason}`.
+    pub(super) fn execute_model_invoke(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let provider = self.model_provider(step, ctx)?;
+        let prompt = match self.resolve_parameter_variable(step, "prompt", ctx)? {
+            Value::String(s) => s.into_string(),
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+        let max_tokens = match step.parameters.get("max_tokens") {
+            Some(value) => match self.resolve_value(value, ctx) {
+                Value::Int(n) if n > 0 => u32::try_from(n).ok(),
+                other => {
+                    return Err(VesperError::TypeError {
+                        expected: "positive integer".to_string(),
+                        actual: format!("{:?}", other),
+                    })
+                }
+            },
+            None => None,
+        };
+        let temperature = match step.parameters.get("temperature") {
+            Some(value) => {
+                let value = self.resolve_value(value, ctx);
+                Some(value.as_float().ok_or_else(|| VesperError::TypeError {
+                    expected: "number".to_string(),
+                    actual: format!("{:?}", value),
+                })?)
+            }
+            None => None,
+        };
+        let request = ModelRequest {
+            model: self.resolve_string_parameter(step, "model", ctx)?,
+            prompt,
+            max_tokens,
+            temperature,
+        };
+
+        let response = provider.complete(&request).map_err(|e| {
+            VesperError::ExecutionError(format!("Model {} failed: {}", request.model, e))
+        })?;
+
+        let mut fields = HashMap::new();
+        fields.insert("text".to_string(), Value::from(response.text));
+        fields.insert("tokens_used".to_string(), Value::Int(response.tokens_used));
+        fields.insert(
+            "finish_reason".to_string(),
+            Value::from(response.finish_reason),
+        );
+
+        let result = Value::Object(fields);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a text embedding step
+    ///
+    /// Embeds the string named by `parameters["text"]` with
+    /// `parameters["model"]` through the provider registered as
+    /// `parameters["provider"]`, returning the vector as floats.
+    pub(super) fn execute_text_embed(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let provider = self.model_provider(step, ctx)?;
+        let model = self.resolve_string_parameter(step, "model", ctx)?;
+        let text = match self.resolve_parameter_variable(step, "text", ctx)? {
+            Value::String(s) => s.into_string(),
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        let embedding = provider.embed(&model, &text).map_err(|e| {
+            VesperError::ExecutionError(format!("Embedding with {} failed: {}", model, e))
+        })?;
+
+        let result = Value::Array(embedding.into_iter().map(Value::Float).collect());
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a structured extraction step
+    ///
+    /// Extracts an object matching `parameters["schema"]` (field names to
+    /// type names) from the string named by `parameters["text"]` with the
+    /// installed `StructuredExtractor`. Output failing the schema is retried
+    /// up to `parameters["max_retries"]` times (default 2).
+    pub(super) fn execute_structured_extract(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let extractor: &dyn StructuredExtractor =
+            self.state.extractor.as_deref().ok_or_else(|| {
+                VesperError::ExecutionError("No structured extractor configured".to_string())
+            })?;
+        let text = match self.resolve_parameter_variable(step, "text", ctx)? {
+            Value::String(s) => s.into_string(),
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+        let schema = self.resolve_parameter_variable(step, "schema", ctx)?;
+        let Value::Object(fields) = &schema else {
+            return Err(VesperError::TypeError {
+                expected: "schema object".to_string(),
+                actual: format!("{:?}", schema),
+            });
+        };
+        let max_retries = step
+            .parameters
+            .get("max_retries")
+            .and_then(|r| r.as_u64())
+            .unwrap_or(2);
+
+        let mut attempt = 0;
+        let result = loop {
+            let problem = match extractor.extract(&text, &schema) {
+                Ok(value) => match schema_mismatch(fields, &value) {
+                    None => break value,
+                    Some(problem) => problem,
+                },
+                Err(e) => e,
+            };
+            if attempt >= max_retries {
+                return Err(VesperError::ExecutionError(format!(
+                    "Structured extraction failed after {} attempts: {}",
+                    attempt + 1,
+                    problem
+                )));
+            }
+            attempt += 1;
+            tracing::warn!(
+                "Structured extraction attempt {} rejected: {}",
+                attempt,
+                problem
+            );
+        };
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Look up the provider named by `parameters["provider"]`
+    fn model_provider(
+        &self,
+        step: &FlowStep,
+        ctx: &ExecutionContext,
+    ) -> Result<&dyn ModelProvider> {
+        let name = self.resolve_string_parameter(step, "provider", ctx)?;
+        self.state
+            .model_providers
+            .get(&name)
+            .map(|provider| provider.as_ref())
+            .ok_or_else(|| {
+                VesperError::ExecutionError(format!("No model provider registered: {}", name))
+            })
+    }
+}
+
+/// Describe how `value` fails the field types in `schema`, if it does
+fn schema_mismatch(schema: &HashMap<String, Value>, value: &Value) -> Option<String> {
+    let Value::Object(object) = value else {
+        return Some(format!("expected an object, got {:?}", value));
+    };
+    let mut names: Vec<&String> = schema.keys().collect();
+    names.sort();
+    for name in names {
+        let expected = schema[name].as_str().unwrap_or("any");
+        let matches = match (expected, object.get(name)) {
+            (_, None) => return Some(format!("missing field {}", name)),
+            ("string", Some(Value::String(_)))
+            | ("integer", Some(Value::Int(_)))
+            | ("number", Some(Value::Int(_) | Value::Float(_)))
+            | ("boolean", Some(Value::Bool(_)))
+            | ("array", Some(Value::Array(_)))
+            | ("object", Some(Value::Object(_)))
+            | ("any", Some(_)) => true,
+            _ => false,
+        };
+        if !matches {
+            return Some(format!("field {} is not {}", name, expected));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::ExecutionResult;
+    use crate::loader::VesperLoader;
+    use crate::models::{ModelExtractor, ModelResponse};
+    use std::sync::{Arc, Mutex};
+
+    /// Provider echoing the prompt and recording requests
+    struct EchoProvider {
+        requests: Mutex<Vec<ModelRequest>>,
+    }
+
+    impl ModelProvider for EchoProvider {
+        fn complete(&self, request: &ModelRequest) -> std::result::Result<ModelResponse, String> {
+            self.requests.lock().unwrap().push(request.clone());
+            Ok(ModelResponse {
+                text: format!("echo: {}", request.prompt),
+                tokens_used: 7,
+                finish_reason: "stop".to_string(),
+            })
+        }
+
+        fn embed(&self, _model: &str, text: &str) -> std::result::Result<Vec<f64>, String> {
+            Ok(vec![text.len() as f64, 1.0])
+        }
+    }
+
+    const SUMMARIZE: &str = r#"
+node_id: summarize_v1
+type: function
+intent: summarize a document
+
+inputs:
+  document:
+    type: string
+
+flow:
+  - step: summarize
+    operation: model_invoke
+    parameters:
+      provider: openai
+      model: gpt-4o-mini
+      prompt: document
+      max_tokens: 64
+      temperature: 0.2
+"#;
+
+    #[test]
+    fn test_model_invoke() {
+        let provider = Arc::new(EchoProvider {
+            requests: Mutex::new(Vec::new()),
+        });
+        let mut executor = SemanticExecutor::new().with_model_provider("openai", provider.clone());
+        executor.register(VesperLoader::new().load_string(SUMMARIZE).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("document".to_string(), Value::from("long text"));
+        let result = executor.execute("summarize_v1", inputs.clone()).unwrap();
+
+        let Some(Value::Object(fields)) = result.data else {
+            panic!("expected object");
+        };
+        assert_eq!(fields["text"], Value::from("echo: long text"));
+        assert_eq!(fields["tokens_used"], Value::Int(7));
+        assert_eq!(fields["finish_reason"], Value::from("stop"));
+        assert_eq!(
+            provider.requests.lock().unwrap()[0],
+            ModelRequest {
+                model: "gpt-4o-mini".to_string(),
+                prompt: "long text".to_string(),
+                max_tokens: Some(64),
+                temperature: Some(0.2),
+            }
+        );
+
+        let mut unregistered = SemanticExecutor::new();
+        unregistered.register(VesperLoader::new().load_string(SUMMARIZE).unwrap());
+        assert!(unregistered.execute("summarize_v1", inputs).is_err());
+    }
+
+    #[test]
+    fn test_text_embed() {
+        let yaml = r#"
+node_id: embed_v1
+type: function
+intent: embed a query
+
+inputs:
+  query:
+    type: string
+
+flow:
+  - step: embed
+    operation: text_embed
+    parameters:
+      provider: local
+      model: nomic-embed-text
+      text: query
+"#;
+        let provider = Arc::new(EchoProvider {
+            requests: Mutex::new(Vec::new()),
+        });
+        let mut executor = SemanticExecutor::new().with_model_provider("local", provider);
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("query".to_string(), Value::from("rust"));
+        let result = executor.execute("embed_v1", inputs).unwrap();
+        assert_eq!(
+            result.data,
+            Some(Value::Array(vec![Value::Float(4.0), Value::Float(1.0)]))
+        );
+    }
+
+    /// Provider replying with scripted completions in order
+    struct ScriptedProvider {
+        replies: Mutex<Vec<&'static str>>,
+    }
+
+    impl ModelProvider for ScriptedProvider {
+        fn complete(&self, _request: &ModelRequest) -> std::result::Result<ModelResponse, String> {
+            Ok(ModelResponse {
+                text: self.replies.lock().unwrap().remove(0).to_string(),
+                tokens_used: 1,
+                finish_reason: "stop".to_string(),
+            })
+        }
+    }
+
+    const EXTRACT: &str = r#"
+node_id: extract_v1
+type: function
+intent: extract a contact
+
+inputs:
+  message:
+    type: string
+
+flow:
+  - step: extract
+    operation: structured_extract
+    parameters:
+      text: message
+      schema:
+        name: string
+        age: integer
+      max_retries: 1
+"#;
+
+    fn extract(replies: Vec<&'static str>) -> Result<ExecutionResult> {
+        let provider = Arc::new(ScriptedProvider {
+            replies: Mutex::new(replies),
+        });
+        let mut executor = SemanticExecutor::new()
+            .with_extractor(Arc::new(ModelExtractor::new(provider, "gpt-4o-mini")));
+        executor.register(VesperLoader::new().load_string(EXTRACT).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "message".to_string(),
+            Value::from("Hi, I'm Ada and I turn 36 today"),
+        );
+        executor.execute("extract_v1", inputs)
+    }
+
+    #[test]
+    fn test_structured_extract_retries_invalid_output() {
+        let result = extract(vec![
+            r#"{"name": "Ada", "age": "thirty-six"}"#,
+            "```json\n{\"name\": \"Ada\", \"age\": 36}\n```",
+        ])
+        .unwrap();
+
+        let mut expected = HashMap::new();
+        expected.insert("name".to_string(), Value::from("Ada"));
+        expected.insert("age".to_string(), Value::Int(36));
+        assert_eq!(result.data, Some(Value::Object(expected)));
+
+        let exhausted = extract(vec!["no idea", r#"{"name": "Ada"}"#]);
+        assert!(matches!(
+            exhausted,
+            Err(VesperError::ExecutionError(message)) if message.contains("missing field age")
+        ));
+    }
+}
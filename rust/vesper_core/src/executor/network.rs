@@ -0,0 +1,183 @@
+//! IP address operations
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+impl SemanticExecutor {
+    /// Execute an IP validation step
+    ///
+    /// Returns whether the string named by `parameters["on"]` is a valid
+    /// IPv4 or IPv6 address.
+    pub(super) fn execute_ip_validate(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let value = self.resolve_parameter_variable(step, "on", ctx)?;
+        let valid = value
+            .as_str()
+            .map(|s| s.trim().parse::<IpAddr>().is_ok())
+            .unwrap_or(false);
+
+        let result = Value::Bool(valid);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute an IP parse step
+    ///
+    /// Describes the address named by `parameters["on"]` as
+    /// `{version, address, is_loopback, is_private}`.
+    pub(super) fn execute_ip_parse(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let addr = parse_ip(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+
+        let (version, is_private) = match addr {
+            IpAddr::V4(v4) => ("v4", v4.is_private()),
+            // Unique local addresses (fc00::/7) are the IPv6 private range
+            IpAddr::V6(v6) => ("v6", (v6.segments()[0] & 0xfe00) == 0xfc00),
+        };
+
+        let mut fields = HashMap::new();
+        fields.insert("version".to_string(), Value::from(version));
+        fields.insert("address".to_string(), Value::from(addr.to_string()));
+        fields.insert("is_loopback".to_string(), Value::Bool(addr.is_loopback()));
+        fields.insert("is_private".to_string(), Value::Bool(is_private));
+
+        let result = Value::Object(fields);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a CIDR membership step
+    ///
+    /// Returns whether the address named by `parameters["on"]` falls
+    /// within the block in `parameters["cidr"]`.
+    #[cfg(feature = "ipnet")]
+    pub(super) fn execute_ip_in_cidr(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let addr = parse_ip(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+        let cidr = self.resolve_string_parameter(step, "cidr", ctx)?;
+        let network: ipnet::IpNet = cidr
+            .trim()
+            .parse()
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid CIDR {}: {}", cidr, e)))?;
+
+        let result = Value::Bool(network.contains(&addr));
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a CIDR membership step (feature disabled)
+    #[cfg(not(feature = "ipnet"))]
+    pub(super) fn execute_ip_in_cidr(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "ip_in_cidr operation requires the `ipnet` feature".to_string(),
+        ))
+    }
+}
+
+fn parse_ip(value: &Value) -> Result<IpAddr> {
+    let text = value.as_str().ok_or_else(|| VesperError::TypeError {
+        expected: "string".to_string(),
+        actual: format!("{:?}", value),
+    })?;
+    text.trim()
+        .parse()
+        .map_err(|_| VesperError::ExecutionError(format!("Invalid IP address: {}", text)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    fn run(operation: &str, extra: &str, address: &str) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: ip_v1
+type: function
+intent: inspect an address
+
+inputs:
+  addr:
+    type: string
+
+flow:
+  - step: inspect
+    operation: {}
+    parameters:
+      on: addr
+      {}
+"#,
+            operation, extra
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("addr".to_string(), Value::from(address));
+        executor.execute("ip_v1", inputs).map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_ip_validate() {
+        assert_eq!(
+            run("ip_validate", "", "192.168.1.10").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(run("ip_validate", "", "::1").unwrap(), Value::Bool(true));
+        assert_eq!(
+            run("ip_validate", "", "300.1.1.1").unwrap(),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_ip_parse() {
+        let Value::Object(v4) = run("ip_parse", "", "10.0.0.1").unwrap() else {
+            panic!("expected object");
+        };
+        assert_eq!(v4["version"], Value::from("v4"));
+        assert_eq!(v4["is_private"], Value::Bool(true));
+        assert_eq!(v4["is_loopback"], Value::Bool(false));
+
+        let Value::Object(v6) = run("ip_parse", "", "::1").unwrap() else {
+            panic!("expected object");
+        };
+        assert_eq!(v6["version"], Value::from("v6"));
+        assert_eq!(v6["is_loopback"], Value::Bool(true));
+
+        assert!(run("ip_parse", "", "not-an-ip").is_err());
+    }
+
+    #[cfg(feature = "ipnet")]
+    #[test]
+    fn test_ip_in_cidr() {
+        assert_eq!(
+            run("ip_in_cidr", "cidr: 10.0.0.0/8", "10.20.30.40").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            run("ip_in_cidr", "cidr: 10.0.0.0/8", "11.0.0.1").unwrap(),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            run("ip_in_cidr", "cidr: \"fd00::/8\"", "fd12::1").unwrap(),
+            Value::Bool(true)
+        );
+    }
+}
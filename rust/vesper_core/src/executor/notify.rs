@@ -0,0 +1,234 @@
+//! Notification operation
+//!
+//! Supported channels:
+//! - `email`: posts `{to, subject, body}` as JSON to the mail relay at `url`
+//! - `slack_webhook`: posts `{text: message}` to a Slack incoming webhook `url`
+//! - `http_webhook`: sends `body` to `url` with the given `method` and `headers`
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a notification step
+    ///
+    /// Failed deliveries are retried up to `FlowStep::retry_count` times.
+    #[cfg(feature = "http")]
+    pub(super) fn execute_notify(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let channel = self.resolve_string_parameter(step, "channel", ctx)?;
+        let request = self.build_notification(&channel, step, ctx)?;
+
+        let attempts = step.retry_count.unwrap_or(0) + 1;
+        let mut last_error = String::new();
+        for attempt in 1..=attempts {
+            let request = request.try_clone().ok_or_else(|| {
+                VesperError::ExecutionError("Notification request is not retryable".to_string())
+            })?;
+
+            match request.send() {
+                Ok(response) if response.status().is_success() => {
+                    let mut result = std::collections::HashMap::new();
+                    result.insert("channel".to_string(), Value::from(channel));
+                    result.insert(
+                        "status".to_string(),
+                        Value::Int(i64::from(response.status().as_u16())),
+                    );
+                    result.insert("attempts".to_string(), Value::Int(i64::from(attempt)));
+
+                    let result = Value::Object(result);
+                    self.store_output(step, ctx, &result);
+                    return Ok(result);
+                }
+                Ok(response) => last_error = format!("HTTP {}", response.status()),
+                Err(e) => last_error = e.to_string(),
+            }
+
+            tracing::warn!(
+                "Notification via {} failed (attempt {}/{}): {}",
+                channel,
+                attempt,
+                attempts,
+                last_error
+            );
+        }
+
+        Err(VesperError::ExecutionError(format!(
+            "Notification via {} failed after {} attempt(s): {}",
+            channel, attempts, last_error
+        )))
+    }
+
+    /// Build the outbound request for a notification channel
+    #[cfg(feature = "http")]
+    fn build_notification(
+        &self,
+        channel: &str,
+        step: &FlowStep,
+        ctx: &ExecutionContext,
+    ) -> Result<reqwest::blocking::RequestBuilder> {
+        let url = self.resolve_string_parameter(step, "url", ctx)?;
+        let client = self.http_client();
+
+        match channel {
+            "email" => {
+                let payload = serde_json::json!({
+                    "to": self.resolve_string_parameter(step, "to", ctx)?,
+                    "subject": self.resolve_string_parameter(step, "subject", ctx)?,
+                    "body": self.resolve_string_parameter(step, "body", ctx)?,
+                });
+                Ok(client.post(url).json(&payload))
+            }
+            "slack_webhook" => {
+                let payload = serde_json::json!({
+                    "text": self.resolve_string_parameter(step, "message", ctx)?,
+                });
+                Ok(client.post(url).json(&payload))
+            }
+            "http_webhook" => {
+                let method = match step.parameters.get("method") {
+                    Some(_) => self.resolve_string_parameter(step, "method", ctx)?,
+                    None => "POST".to_string(),
+                };
+                let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+                    .map_err(|_| {
+                        VesperError::ExecutionError(format!("Invalid HTTP method: {}", method))
+                    })?;
+
+                let mut request = client.request(method, url);
+                if let Some(serde_yaml::Value::Mapping(headers)) = step.parameters.get("headers") {
+                    for (name, value) in headers {
+                        if let (Some(name), Value::String(value)) =
+                            (name.as_str(), self.resolve_value(value, ctx))
+                        {
+                            request = request.header(name, value.as_str());
+                        }
+                    }
+                }
+                if let Some(body) = step.parameters.get("body") {
+                    request =
+                        request.json(&serde_json::Value::from(&self.resolve_value(body, ctx)));
+                }
+                Ok(request)
+            }
+            other => Err(VesperError::ExecutionError(format!(
+                "Unknown notification channel: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Execute a notification step (feature disabled)
+    #[cfg(not(feature = "http"))]
+    pub(super) fn execute_notify(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "notify operation requires the `http` feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn executor(step: &str) -> SemanticExecutor {
+        let yaml = format!(
+            r#"
+node_id: alert_v1
+type: function
+intent: send an alert
+
+inputs:
+  order_id:
+    type: string
+
+flow:
+{}
+"#,
+            step
+        );
+        let node = VesperLoader::new().load_string(&yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+        executor
+    }
+
+    fn inputs() -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        inputs.insert("order_id".to_string(), Value::from("ord-42"));
+        inputs
+    }
+
+    #[test]
+    fn test_notify_slack_webhook() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"text": "ord-42"}),
+            ))
+            .with_status(200)
+            .create();
+
+        let executor = executor(&format!(
+            r#"
+  - step: alert
+    operation: notify
+    parameters:
+      channel: slack_webhook
+      url: "{}/hook"
+      message: "{{order_id}}"
+"#,
+            server.url()
+        ));
+
+        let result = executor.execute("alert_v1", inputs()).unwrap();
+        mock.assert();
+
+        let Some(Value::Object(data)) = result.data else {
+            panic!("expected object result");
+        };
+        assert_eq!(data["status"], Value::Int(200));
+    }
+
+    #[test]
+    fn test_notify_http_webhook_retries() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("PUT", "/events")
+            .match_header("x-token", "secret")
+            .with_status(503)
+            .expect(3)
+            .create();
+
+        let executor = executor(&format!(
+            r#"
+  - step: alert
+    operation: notify
+    retry_count: 2
+    parameters:
+      channel: http_webhook
+      url: "{}/events"
+      method: put
+      headers:
+        x-token: secret
+      body:
+        order: "{{order_id}}"
+"#,
+            server.url()
+        ));
+
+        let result = executor.execute("alert_v1", inputs());
+        mock.assert();
+        assert!(matches!(result, Err(VesperError::ExecutionError(_))));
+    }
+}
@@ -0,0 +1,245 @@
+//! OAuth2 token acquisition
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+/// Seconds before expiry at which a cached token is considered stale
+#[cfg(feature = "http")]
+const EXPIRY_MARGIN_SECS: u64 = 30;
+
+impl SemanticExecutor {
+    /// Execute an OAuth2 token exchange step
+    ///
+    /// Requests a token from `parameters["token_url"]` using the
+    /// `client_credentials`, `password` or `refresh_token` grant and
+    /// returns `{access_token, token_type, expires_in}`. The client secret
+    /// (and the password for the `password` grant) are names looked up in
+    /// the executor's `SecretStore`. When a cache backend is installed,
+    /// tokens are reused until shortly before they expire.
+    #[cfg(feature = "http")]
+    pub(super) fn execute_oauth2_token_exchange(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let grant_type = self.resolve_string_parameter(step, "grant_type", ctx)?;
+        let token_url = self.resolve_string_parameter(step, "token_url", ctx)?;
+        let client_id = self.resolve_string_parameter(step, "client_id", ctx)?;
+        let client_secret = self.secret_parameter(step, "client_secret", ctx)?;
+        let scope = match step.parameters.get("scope") {
+            Some(_) => Some(self.resolve_string_parameter(step, "scope", ctx)?),
+            None => None,
+        };
+
+        let mut form = vec![
+            ("grant_type", grant_type.clone()),
+            ("client_id", client_id.clone()),
+            ("client_secret", client_secret),
+        ];
+        if let Some(scope) = &scope {
+            form.push(("scope", scope.clone()));
+        }
+
+        // Refresh tokens are typically single-use, so those grants bypass the cache
+        let cache_key = match grant_type.as_str() {
+            "client_credentials" => Some(format!(
+                "oauth2:{}:{}:{}:{}",
+                grant_type,
+                token_url,
+                client_id,
+                scope.as_deref().unwrap_or("")
+            )),
+            "password" => {
+                let username = self.resolve_string_parameter(step, "username", ctx)?;
+                form.push(("password", self.secret_parameter(step, "password", ctx)?));
+                form.push(("username", username.clone()));
+                Some(format!(
+                    "oauth2:{}:{}:{}:{}:{}",
+                    grant_type,
+                    token_url,
+                    client_id,
+                    scope.as_deref().unwrap_or(""),
+                    username
+                ))
+            }
+            "refresh_token" => {
+                form.push((
+                    "refresh_token",
+                    self.resolve_string_parameter(step, "refresh_token", ctx)?,
+                ));
+                None
+            }
+            other => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Unsupported OAuth2 grant type: {}",
+                    other
+                )))
+            }
+        };
+
+        if let (Some(cache), Some(key)) = (self.state.cache.as_deref(), &cache_key) {
+            if let Some(token) = cache.get(key) {
+                tracing::debug!("Using cached OAuth2 token for {}", client_id);
+                self.store_output(step, ctx, &token);
+                return Ok(token);
+            }
+        }
+
+        let response = self
+            .http_client()
+            .post(&token_url)
+            .form(&form)
+            .send()
+            .map_err(|e| VesperError::ExecutionError(format!("Token request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(VesperError::ExecutionError(format!(
+                "Token request failed: HTTP {}",
+                response.status()
+            )));
+        }
+        let body: serde_json::Value = response
+            .json()
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid token response: {}", e)))?;
+
+        let access_token = body["access_token"].as_str().ok_or_else(|| {
+            VesperError::ExecutionError("Token response has no access_token".to_string())
+        })?;
+        let token_type = body["token_type"].as_str().unwrap_or("Bearer");
+        let expires_in = body["expires_in"].as_u64();
+
+        let mut fields = std::collections::HashMap::new();
+        fields.insert("access_token".to_string(), Value::from(access_token));
+        fields.insert("token_type".to_string(), Value::from(token_type));
+        fields.insert(
+            "expires_in".to_string(),
+            expires_in.map_or(Value::Null, |s| Value::Int(s as i64)),
+        );
+        let result = Value::Object(fields);
+
+        if let (Some(cache), Some(key), Some(expires_in)) =
+            (self.state.cache.as_deref(), &cache_key, expires_in)
+        {
+            let ttl = expires_in.saturating_sub(EXPIRY_MARGIN_SECS);
+            if ttl > 0 {
+                cache.set(
+                    key,
+                    result.clone(),
+                    Some(std::time::Duration::from_secs(ttl)),
+                );
+            }
+        }
+
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute an OAuth2 token exchange step (feature disabled)
+    #[cfg(not(feature = "http"))]
+    pub(super) fn execute_oauth2_token_exchange(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "oauth2_token_exchange operation requires the `http` feature".to_string(),
+        ))
+    }
+
+    /// Look up the secret named by a step parameter in the secret store
+    #[cfg(feature = "http")]
+    fn secret_parameter(
+        &self,
+        step: &FlowStep,
+        key: &str,
+        ctx: &ExecutionContext,
+    ) -> Result<String> {
+        let name = self.resolve_string_parameter(step, key, ctx)?;
+        let store =
+            self.state.secrets.as_deref().ok_or_else(|| {
+                VesperError::ExecutionError("No secret store configured".to_string())
+            })?;
+        store
+            .get_secret(&name)
+            .ok_or_else(|| VesperError::ExecutionError(format!("Unknown secret: {}", name)))
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCacheBackend;
+    use crate::loader::VesperLoader;
+    use crate::secrets::StaticSecretStore;
+    use mockito::Matcher;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn executor(token_url: &str) -> SemanticExecutor {
+        let yaml = format!(
+            r#"
+node_id: token_v1
+type: function
+intent: acquire an API token
+
+inputs: {{}}
+
+flow:
+  - step: authenticate
+    operation: oauth2_token_exchange
+    parameters:
+      grant_type: client_credentials
+      token_url: "{}"
+      client_id: vesper
+      client_secret: billing_client_secret
+      scope: invoices.read
+"#,
+            token_url
+        );
+        let secrets = StaticSecretStore::new().with_secret("billing_client_secret", "s3cret");
+        let mut executor = SemanticExecutor::new()
+            .with_secrets(Arc::new(secrets))
+            .with_cache(Arc::new(InMemoryCacheBackend::new()));
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        executor
+    }
+
+    #[test]
+    fn test_client_credentials_token_is_cached() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/token")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("grant_type".into(), "client_credentials".into()),
+                Matcher::UrlEncoded("client_secret".into(), "s3cret".into()),
+                Matcher::UrlEncoded("scope".into(), "invoices.read".into()),
+            ]))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"access_token": "abc", "token_type": "Bearer", "expires_in": 3600}"#)
+            .expect(1)
+            .create();
+
+        let executor = executor(&format!("{}/token", server.url()));
+        for _ in 0..2 {
+            let Some(Value::Object(token)) =
+                executor.execute("token_v1", HashMap::new()).unwrap().data
+            else {
+                panic!("expected object result");
+            };
+            assert_eq!(token["access_token"], Value::from("abc"));
+            assert_eq!(token["expires_in"], Value::Int(3600));
+        }
+        mock.assert();
+    }
+
+    #[test]
+    fn test_token_endpoint_error() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/token").with_status(401).create();
+
+        let executor = executor(&format!("{}/token", server.url()));
+        let result = executor.execute("token_v1", HashMap::new());
+        mock.assert();
+        assert!(matches!(result, Err(VesperError::ExecutionError(_))));
+    }
+}
@@ -0,0 +1,514 @@
+//! OpenTelemetry context propagation and manual span operations
+//!
+//! Each execution captures the OpenTelemetry context current when it
+//! starts. Baggage set and spans started by a flow only extend that
+//! captured context, so they are visible to later steps and injected
+//! headers but not to the caller.
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a baggage lookup step
+    ///
+    /// Returns the baggage entry `parameters["key"]` as a string, or null
+    /// if the context carries no such entry.
+    #[cfg(feature = "otel")]
+    pub(super) fn execute_opentelemetry_baggage_get(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use opentelemetry::baggage::BaggageExt;
+
+        let key = self.resolve_string_parameter(step, "key", ctx)?;
+        let result = ctx
+            .otel_context
+            .baggage()
+            .get(key.as_str())
+            .map(|value| Value::from(value.as_str().into_owned()))
+            .unwrap_or(Value::Null);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a baggage update step
+    ///
+    /// Adds the value named by `parameters["value"]` to the baggage under
+    /// `parameters["key"]`, replacing any existing entry. Strings are
+    /// stored as-is and other values as JSON.
+    #[cfg(feature = "otel")]
+    pub(super) fn execute_opentelemetry_baggage_set(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use opentelemetry::baggage::BaggageExt;
+        use opentelemetry::KeyValue;
+
+        let key = self.resolve_string_parameter(step, "key", ctx)?;
+        let value = self.resolve_parameter_variable(step, "value", ctx)?;
+        let text = match &value {
+            Value::String(s) => s.to_string(),
+            other => serde_json::Value::from(other).to_string(),
+        };
+        ctx.otel_context = ctx
+            .otel_context
+            .with_baggage(vec![KeyValue::new(key, text.clone())]);
+
+        let result = Value::from(text);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a trace context injection step
+    ///
+    /// Returns the W3C `traceparent` / `tracestate` and `baggage` headers
+    /// for the current context as an object, to pass to outgoing requests.
+    /// Headers without content are omitted.
+    #[cfg(feature = "otel")]
+    pub(super) fn execute_trace_context_inject(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use opentelemetry::propagation::{TextMapCompositePropagator, TextMapPropagator};
+        use opentelemetry_sdk::propagation::{BaggagePropagator, TraceContextPropagator};
+        use std::collections::HashMap;
+
+        let propagator = TextMapCompositePropagator::new(vec![
+            Box::new(TraceContextPropagator::new()),
+            Box::new(BaggagePropagator::new()),
+        ]);
+        let mut headers: HashMap<String, String> = HashMap::new();
+        propagator.inject_context(&ctx.otel_context, &mut headers);
+
+        let result = Value::Object(
+            headers
+                .into_iter()
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(name, value)| (name, Value::from(value)))
+                .collect(),
+        );
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a span start step
+    ///
+    /// Starts a span named `parameters["name"]` as a child of the current
+    /// span, with the fields of `parameters["attributes"]`, a mapping or
+    /// the name of an object variable, as attributes. The span becomes current,
+    /// so injected headers and spans started later descend from it, and
+    /// every step until `trace_span_end` is recorded as an event on it.
+    /// Returns the span's name and trace and span IDs, the handle that
+    /// `trace_span_end` takes.
+    #[cfg(feature = "otel")]
+    pub(super) fn execute_trace_span_start(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use opentelemetry::trace::{TraceContextExt, Tracer};
+        use opentelemetry::KeyValue;
+
+        let name = self.resolve_string_parameter(step, "name", ctx)?;
+        let attributes = match step.parameters.get("attributes") {
+            None => Vec::new(),
+            Some(_) => match self.resolve_parameter_variable(step, "attributes", ctx)? {
+                Value::Object(fields) => fields
+                    .into_iter()
+                    .map(|(key, value)| KeyValue::new(key, attribute_value(value)))
+                    .collect(),
+                other => {
+                    return Err(VesperError::TypeError {
+                        expected: "object".to_string(),
+                        actual: format!("{:?}", other),
+                    })
+                }
+            },
+        };
+
+        let tracer = opentelemetry::global::tracer("vesper");
+        let span = tracer
+            .span_builder(name.clone())
+            .with_attributes(attributes)
+            .start_with_context(&tracer, &ctx.otel_context);
+        let parent = std::mem::take(&mut ctx.otel_context);
+        ctx.otel_context = parent.with_span(span);
+        let span_context = ctx.otel_context.span().span_context().clone();
+        let span_id = span_context.span_id().to_string();
+        ctx.open_spans.insert(
+            span_id.clone(),
+            OpenSpan {
+                context: ctx.otel_context.clone(),
+                parent,
+            },
+        );
+
+        let result = Value::Object(std::collections::HashMap::from([
+            ("name".to_string(), Value::from(name)),
+            (
+                "trace_id".to_string(),
+                Value::from(span_context.trace_id().to_string()),
+            ),
+            ("span_id".to_string(), Value::from(span_id)),
+        ]));
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a span end step
+    ///
+    /// Ends the span whose handle is held by the variable
+    /// `parameters["span_variable"]`, with `parameters["status"]` `ok`
+    /// (the default) or `error`, described by `parameters["error_message"]`.
+    /// The span that was current when it started becomes current again, so
+    /// spans should end in the reverse order they started.
+    #[cfg(feature = "otel")]
+    pub(super) fn execute_trace_span_end(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use opentelemetry::trace::{Status, TraceContextExt};
+
+        let handle = self.resolve_parameter_variable(step, "span_variable", ctx)?;
+        let span_id = match &handle {
+            Value::Object(fields) => fields.get("span_id").and_then(|id| id.as_str()),
+            _ => None,
+        }
+        .ok_or_else(|| VesperError::TypeError {
+            expected: "span handle from trace_span_start".to_string(),
+            actual: format!("{:?}", handle),
+        })?;
+        let status = match step.parameters.get("status") {
+            None => Status::Ok,
+            Some(_) => match self.resolve_string_parameter(step, "status", ctx)?.as_str() {
+                "ok" => Status::Ok,
+                "error" => {
+                    let message = match step.parameters.get("error_message") {
+                        Some(_) => self.resolve_string_parameter(step, "error_message", ctx)?,
+                        None => String::new(),
+                    };
+                    Status::error(message)
+                }
+                other => {
+                    return Err(VesperError::ExecutionError(format!(
+                        "Unknown span status {}, expected ok or error",
+                        other
+                    )))
+                }
+            },
+        };
+        let open = ctx
+            .open_spans
+            .remove(span_id)
+            .ok_or_else(|| VesperError::ExecutionError(format!("Span {} is not open", span_id)))?;
+
+        let span = open.context.span();
+        span.set_status(status);
+        span.end();
+        ctx.otel_context = open.parent;
+
+        self.store_output(step, ctx, &handle);
+        Ok(handle)
+    }
+
+    /// Record a finished step as an event on the current span, while a
+    /// span started by `trace_span_start` is open
+    #[cfg(feature = "otel")]
+    pub(super) fn record_span_event(
+        &self,
+        step: &FlowStep,
+        result: &Result<Value>,
+        ctx: &ExecutionContext,
+    ) {
+        use opentelemetry::trace::TraceContextExt;
+        use opentelemetry::KeyValue;
+
+        if ctx.open_spans.is_empty() || step.operation.starts_with("trace_span_") {
+            return;
+        }
+        let name = match step.step.as_str() {
+            "" => step.operation.clone(),
+            name => name.to_string(),
+        };
+        let mut attributes = vec![KeyValue::new("operation", step.operation.clone())];
+        if let Err(e) = result {
+            attributes.push(KeyValue::new("error", e.to_string()));
+        }
+        ctx.otel_context.span().add_event(name, attributes);
+    }
+
+    /// Execute a baggage lookup step (feature disabled)
+    #[cfg(not(feature = "otel"))]
+    pub(super) fn execute_opentelemetry_baggage_get(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "opentelemetry_baggage_get operation requires the `otel` feature".to_string(),
+        ))
+    }
+
+    /// Execute a baggage update step (feature disabled)
+    #[cfg(not(feature = "otel"))]
+    pub(super) fn execute_opentelemetry_baggage_set(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "opentelemetry_baggage_set operation requires the `otel` feature".to_string(),
+        ))
+    }
+
+    /// Execute a trace context injection step (feature disabled)
+    #[cfg(not(feature = "otel"))]
+    pub(super) fn execute_trace_context_inject(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "trace_context_inject operation requires the `otel` feature".to_string(),
+        ))
+    }
+
+    /// Execute a span start step (feature disabled)
+    #[cfg(not(feature = "otel"))]
+    pub(super) fn execute_trace_span_start(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "trace_span_start operation requires the `otel` feature".to_string(),
+        ))
+    }
+
+    /// Execute a span end step (feature disabled)
+    #[cfg(not(feature = "otel"))]
+    pub(super) fn execute_trace_span_end(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "trace_span_end operation requires the `otel` feature".to_string(),
+        ))
+    }
+}
+
+/// Span started by a `trace_span_start` step and not yet ended
+#[cfg(feature = "otel")]
+#[derive(Clone)]
+pub(super) struct OpenSpan {
+    /// Context with the span current
+    context: opentelemetry::Context,
+    /// Context current before the span started
+    parent: opentelemetry::Context,
+}
+
+/// Span attribute holding `value`; arrays and objects are stored as JSON
+#[cfg(feature = "otel")]
+fn attribute_value(value: Value) -> opentelemetry::Value {
+    match value {
+        Value::String(s) => s.to_string().into(),
+        Value::Int(i) => i.into(),
+        Value::Float(f) => f.into(),
+        Value::Bool(b) => b.into(),
+        other => serde_json::Value::from(&other).to_string().into(),
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use opentelemetry::baggage::BaggageExt;
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+    use opentelemetry::{Context, KeyValue};
+    use opentelemetry_sdk::export::trace::SpanData;
+    use opentelemetry_sdk::trace::{SpanProcessor, TracerProvider};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Keeps every span that ends
+    #[derive(Debug, Clone, Default)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<SpanData>>>,
+    }
+
+    impl SpanProcessor for Recorder {
+        fn on_start(&self, _span: &mut opentelemetry_sdk::trace::Span, _cx: &Context) {}
+
+        fn on_end(&self, span: SpanData) {
+            self.spans.lock().unwrap().push(span);
+        }
+
+        fn force_flush(&self) -> opentelemetry::trace::TraceResult<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> opentelemetry::trace::TraceResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_baggage_and_trace_context_propagation() {
+        let yaml = r#"
+node_id: forward_v1
+type: function
+intent: forward a request with its tracing context
+
+inputs:
+  region:
+    type: string
+
+flow:
+  - step: tenant
+    operation: opentelemetry_baggage_get
+    parameters:
+      key: tenant
+    output: tenant
+  - step: tag_region
+    operation: opentelemetry_baggage_set
+    parameters:
+      key: region
+      value: region
+  - step: headers
+    operation: trace_context_inject
+    output: headers
+  - step: collect
+    operation: return
+    return_success:
+      tenant: "{tenant}"
+      headers: "{headers}"
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let parent = Context::new()
+            .with_remote_span_context(span_context)
+            .with_baggage(vec![KeyValue::new("tenant", "acme")]);
+        let _guard = parent.attach();
+
+        let inputs = HashMap::from([("region".to_string(), Value::from("eu-west"))]);
+        let result = executor.execute("forward_v1", inputs).unwrap();
+        let Some(Value::Object(fields)) = result.data else {
+            panic!("expected object result");
+        };
+        assert_eq!(fields["tenant"], Value::from("acme"));
+        let Value::Object(headers) = &fields["headers"] else {
+            panic!("expected header object");
+        };
+        assert_eq!(
+            headers["traceparent"],
+            Value::from("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        );
+        let mut baggage: Vec<&str> = headers["baggage"].as_str().unwrap().split(',').collect();
+        baggage.sort();
+        assert_eq!(baggage, vec!["region=eu-west", "tenant=acme"]);
+
+        // Baggage set by the flow does not leak into the caller's context
+        assert!(Context::current().baggage().get("region").is_none());
+    }
+
+    #[test]
+    fn test_manual_span_records_steps_as_events() {
+        let yaml = r#"
+node_id: checkout_v1
+type: function
+intent: charge a cart inside its own span
+
+inputs:
+  tier:
+    type: string
+
+flow:
+  - step: open
+    operation: trace_span_start
+    parameters:
+      name: checkout
+      attributes:
+        tier: "{tier}"
+        items: 3
+    output: span
+  - step: total
+    operation: arithmetic
+    expression: "2 + 3"
+    output: total
+  - step: headers
+    operation: trace_context_inject
+    output: headers
+  - step: close
+    operation: trace_span_end
+    parameters:
+      span_variable: span
+      status: error
+      error_message: card declined
+  - step: collect
+    operation: return
+    return_success:
+      span: "{span}"
+      headers: "{headers}"
+"#;
+        let recorder = Recorder::default();
+        let provider = TracerProvider::builder()
+            .with_span_processor(recorder.clone())
+            .build();
+        opentelemetry::global::set_tracer_provider(provider);
+
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+        let inputs = HashMap::from([("tier".to_string(), Value::from("gold"))]);
+        let result = executor.execute("checkout_v1", inputs).unwrap();
+        let Some(Value::Object(fields)) = result.data else {
+            panic!("expected object result");
+        };
+        let Value::Object(span) = &fields["span"] else {
+            panic!("expected span handle");
+        };
+        let Value::Object(headers) = &fields["headers"] else {
+            panic!("expected header object");
+        };
+        let span_id = span["span_id"].as_str().unwrap();
+        assert!(headers["traceparent"].as_str().unwrap().contains(span_id));
+
+        let spans = recorder.spans.lock().unwrap();
+        let checkout = spans.iter().find(|s| s.name == "checkout").unwrap();
+        assert_eq!(checkout.span_context.span_id().to_string(), span_id);
+        let mut attributes: Vec<String> = checkout
+            .attributes
+            .iter()
+            .map(|kv| format!("{}={}", kv.key, kv.value))
+            .collect();
+        attributes.sort();
+        assert_eq!(attributes, vec!["items=3", "tier=gold"]);
+        let events: Vec<&str> = checkout
+            .events
+            .events
+            .iter()
+            .map(|e| e.name.as_ref())
+            .collect();
+        assert_eq!(events, vec!["total", "headers"]);
+        assert_eq!(
+            checkout.status,
+            opentelemetry::trace::Status::error("card declined")
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Pagination over a fetch step
+
+#[cfg(feature = "jsonpath")]
+use super::jsonpath::jsonpath_matches;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+/// Page limit when `max_pages` is not given
+#[cfg(feature = "jsonpath")]
+const DEFAULT_MAX_PAGES: u64 = 100;
+
+impl SemanticExecutor {
+    /// Execute a pagination step
+    ///
+    /// Repeatedly runs the flow step named by `parameters["fetch_step"]`,
+    /// which the flow itself then skips. Before each fetch the cursor is
+    /// stored in `parameters["cursor_variable"]` (starting from
+    /// `parameters["initial_cursor"]`, default null). Items matched by the
+    /// JSONPath `parameters["collect_path"]` are collected from every page
+    /// and the next cursor is read with `parameters["next_cursor_path"]`;
+    /// a missing, null or empty cursor ends the loop, as does
+    /// `parameters["max_pages"]` (default 100).
+    #[cfg(feature = "jsonpath")]
+    pub(super) fn execute_paginate(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let fetch_step = self.resolve_string_parameter(step, "fetch_step", ctx)?;
+        let cursor_variable = self.resolve_string_parameter(step, "cursor_variable", ctx)?;
+        let next_cursor_path = self.resolve_string_parameter(step, "next_cursor_path", ctx)?;
+        let collect_path = self.resolve_string_parameter(step, "collect_path", ctx)?;
+        let max_pages = step
+            .parameters
+            .get("max_pages")
+            .and_then(|m| m.as_u64())
+            .unwrap_or(DEFAULT_MAX_PAGES);
+
+        let fetch = self
+            .state
+            .nodes
+            .get(ctx.node_id())
+            .and_then(|node| node.flow.iter().find(|s| s.step == fetch_step))
+            .ok_or_else(|| {
+                VesperError::ExecutionError(format!("Unknown fetch step: {}", fetch_step))
+            })?;
+
+        let mut cursor = step
+            .parameters
+            .get("initial_cursor")
+            .map(|c| self.resolve_value(c, ctx))
+            .unwrap_or(Value::Null);
+        let mut items = Vec::new();
+        for page_number in 1..=max_pages {
+            ctx.set(cursor_variable.clone(), cursor);
+            let page = self.execute_step(fetch, ctx)?;
+            items.extend(jsonpath_matches(&collect_path, &page)?);
+
+            cursor = jsonpath_matches(&next_cursor_path, &page)?
+                .into_iter()
+                .next()
+                .unwrap_or(Value::Null);
+            if matches!(&cursor, Value::Null) || cursor.as_str() == Some("") {
+                break;
+            }
+            if page_number == max_pages {
+                tracing::warn!(
+                    "Pagination in step {} stopped after {} pages",
+                    step.step,
+                    max_pages
+                );
+            }
+        }
+
+        let result = Value::Array(items);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a pagination step (feature disabled)
+    #[cfg(not(feature = "jsonpath"))]
+    pub(super) fn execute_paginate(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "paginate operation requires the `jsonpath` feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "jsonpath"))]
+mod tests {
+    use super::*;
+    use crate::cache::{CacheBackend, InMemoryCacheBackend};
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn page(items: &[i64], next: Option<&str>) -> Value {
+        let mut page = HashMap::new();
+        page.insert(
+            "items".to_string(),
+            Value::Array(items.iter().map(|i| Value::Int(*i)).collect()),
+        );
+        page.insert("next".to_string(), next.map_or(Value::Null, Value::from));
+        Value::Object(page)
+    }
+
+    fn run(max_pages: u64) -> Value {
+        let yaml = format!(
+            r#"
+node_id: orders_v1
+type: function
+intent: collect every order
+
+inputs: {{}}
+
+flow:
+  - step: collect
+    operation: paginate
+    parameters:
+      fetch_step: fetch_page
+      cursor_variable: cursor
+      initial_cursor: first
+      next_cursor_path: "$.next"
+      collect_path: "$.items[*]"
+      max_pages: {}
+  - step: fetch_page
+    operation: cache_get
+    parameters:
+      key: "{{cursor}}"
+"#,
+            max_pages
+        );
+        let cache = Arc::new(InMemoryCacheBackend::new());
+        cache.set("first", page(&[1, 2], Some("second")), None);
+        cache.set("second", page(&[3], Some("third")), None);
+        cache.set("third", page(&[4], None), None);
+
+        let mut executor = SemanticExecutor::new().with_cache(cache);
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        executor
+            .execute("orders_v1", HashMap::new())
+            .unwrap()
+            .data
+            .unwrap()
+    }
+
+    #[test]
+    fn test_paginate_collects_all_pages() {
+        let all = [1, 2, 3, 4].map(Value::Int).to_vec();
+        assert_eq!(run(10), Value::Array(all));
+    }
+
+    #[test]
+    fn test_paginate_stops_at_max_pages() {
+        let first_two = [1, 2, 3].map(Value::Int).to_vec();
+        assert_eq!(run(2), Value::Array(first_two));
+    }
+}
@@ -0,0 +1,634 @@
+//! Concurrent sub-steps and nodes
+//!
+//! Work runs on scoped threads, sub-steps against copies of the context.
+//! Steps are synchronous and cannot be interrupted, so every thread runs
+//! to completion before the operation returns, except for `race`, which
+//! leaves the losing steps behind.
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, IntoString, Value};
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+impl SemanticExecutor {
+    /// Execute a parallel step
+    ///
+    /// Runs every step in `parameters["steps"]` concurrently on its own
+    /// thread, each against a copy of the current context, and returns an
+    /// object of their results keyed by output variable (or step name).
+    /// The results are also stored as variables. The first failing step,
+    /// in declaration order, fails the whole step unless
+    /// `parameters["collect_errors"]` is true, in which case every failure
+    /// is reported together.
+    pub(super) fn execute_parallel(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let collect_errors = step
+            .parameters
+            .get("collect_errors")
+            .and_then(|c| c.as_bool())
+            .unwrap_or(false);
+
+        let outcomes: Vec<Result<Value>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = steps
+                .iter()
+                .map(|sub_step| {
+                    let mut sub_ctx = ctx.clone();
+                    scope.spawn(move || self.execute_step(sub_step, &mut sub_ctx))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        Err(VesperError::ExecutionError(
+                            "Parallel step panicked".to_string(),
+                        ))
+                    })
+                })
+                .collect()
+        });
+
+        let mut results = HashMap::new();
+        let mut errors = Vec::new();
+        for (sub_step, outcome) in steps.iter().zip(outcomes) {
+            match outcome {
+                Ok(value) => {
+                    let name = sub_step
+                        .output
+                        .clone()
+                        .unwrap_or_else(|| sub_step.step.clone());
+                    ctx.set(name.clone(), value.clone());
+                    results.insert(name, value);
+                }
+                Err(e) if !collect_errors => return Err(e),
+                Err(e) => errors.push(format!("{}: {}", sub_step.step, e)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(VesperError::MultipleErrors(errors));
+        }
+
+        let result = Value::Object(results);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a race step
+    ///
+    /// Starts every step in `parameters["steps"]` concurrently and returns
+    /// the result of the first to succeed, without waiting for the others.
+    /// They are cancelled, so steps nested in them no longer start, and
+    /// the results of any step already running are discarded. If every
+    /// step fails, the errors are combined into one.
+    pub(super) fn execute_race(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        if steps.is_empty() {
+            return Err(VesperError::ExecutionError(format!(
+                "Step {} needs at least one step to race",
+                step.step
+            )));
+        }
+
+        let token = ctx.cancellation.child_token();
+        let (sender, receiver) = mpsc::channel();
+        for (index, sub_step) in steps.iter().enumerate() {
+            let sender = sender.clone();
+            let executor = self.share();
+            let sub_step = sub_step.clone();
+            let mut sub_ctx = ctx.clone();
+            sub_ctx.cancellation = token.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send((index, executor.execute_step(&sub_step, &mut sub_ctx)));
+            });
+        }
+        drop(sender);
+
+        let mut winner = None;
+        let mut errors = Vec::new();
+        for (index, outcome) in receiver.iter() {
+            match outcome {
+                Ok(value) => {
+                    tracing::debug!("Race in step {} won by {}", step.step, steps[index].step);
+                    winner = Some(value);
+                    break;
+                }
+                Err(e) => errors.push(format!("{}: {}", steps[index].step, e)),
+            }
+        }
+        token.cancel();
+
+        let result = winner.ok_or_else(|| {
+            VesperError::ExecutionError(format!("All raced steps failed: {}", errors.join("; ")))
+        })?;
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a scatter-gather step
+    ///
+    /// Runs every node in `parameters["nodes"]` concurrently with the
+    /// object named by `parameters["inputs"]` as inputs, then combines the
+    /// results according to `parameters["aggregate"]`:
+    ///
+    /// - `collect` (default): object of results keyed by node ID
+    /// - `merge`: fields of all object results merged, later nodes winning
+    /// - `sum`: sum of all numeric results; an integer sum that overflows
+    ///   fails the step
+    ///
+    /// A failing node is recorded under its node ID as `{error, message}`
+    /// (for `sum` it is only logged) unless `parameters["fail_fast"]` is
+    /// true, in which case the first failure fails the step.
+    pub(super) fn execute_scatter_gather(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let node_ids: Vec<String> = match step.parameters.get("nodes") {
+            Some(serde_yaml::Value::Sequence(nodes)) => nodes
+                .iter()
+                .map(|n| match self.resolve_value(n, ctx) {
+                    Value::String(id) => Ok(id.into_string()),
+                    other => Err(VesperError::TypeError {
+                        expected: "node ID".to_string(),
+                        actual: format!("{:?}", other),
+                    }),
+                })
+                .collect::<Result<_>>()?,
+            _ => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Step {} needs a sequence of nodes",
+                    step.step
+                )))
+            }
+        };
+        let inputs = match self.resolve_parameter_variable(step, "inputs", ctx)? {
+            Value::Object(inputs) => inputs,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "object".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+        let aggregate = step
+            .parameters
+            .get("aggregate")
+            .and_then(|a| a.as_str())
+            .unwrap_or("collect");
+        if !matches!(aggregate, "collect" | "merge" | "sum") {
+            return Err(VesperError::ExecutionError(format!(
+                "Unknown aggregation: {}",
+                aggregate
+            )));
+        }
+        let fail_fast = step
+            .parameters
+            .get("fail_fast")
+            .and_then(|f| f.as_bool())
+            .unwrap_or(false);
+
+        let outcomes: Vec<Result<Value>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = node_ids
+                .iter()
+                .map(|node_id| {
+                    let inputs = inputs.clone();
+                    scope.spawn(move || {
+                        self.execute(node_id, inputs)
+                            .map(|r| r.data.unwrap_or(Value::Null))
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        Err(VesperError::ExecutionError("Node panicked".to_string()))
+                    })
+                })
+                .collect()
+        });
+
+        let mut fields = HashMap::new();
+        let mut total = Value::Int(0);
+        for (node_id, outcome) in node_ids.iter().zip(outcomes) {
+            let value = match outcome {
+                Ok(value) => value,
+                Err(e) if fail_fast => return Err(e),
+                Err(e) if aggregate == "sum" => {
+                    tracing::warn!("Node {} failed in scatter-gather: {}", node_id, e);
+                    continue;
+                }
+                Err(e) => {
+                    fields.insert(node_id.clone(), Value::from(e.to_json_error()));
+                    continue;
+                }
+            };
+            match (aggregate, value) {
+                ("merge", Value::Object(object)) => fields.extend(object),
+                ("merge", other) => {
+                    return Err(VesperError::TypeError {
+                        expected: "object".to_string(),
+                        actual: format!("{:?}", other),
+                    })
+                }
+                ("sum", value) => {
+                    total = match (total, value) {
+                        (Value::Int(a), Value::Int(b)) => {
+                            Value::Int(a.checked_add(b).ok_or_else(|| {
+                                VesperError::ExecutionError(format!(
+                                    "Sum of step {} overflows at node {}",
+                                    step.step, node_id
+                                ))
+                            })?)
+                        }
+                        (a, b) => match (a.as_float(), b.as_float()) {
+                            (Some(a), Some(b)) => Value::Float(a + b),
+                            (_, b) => {
+                                return Err(VesperError::TypeError {
+                                    expected: "number".to_string(),
+                                    actual: format!("{:?}", b),
+                                })
+                            }
+                        },
+                    }
+                }
+                (_, value) => {
+                    fields.insert(node_id.clone(), value);
+                }
+            }
+        }
+
+        let result = match aggregate {
+            "sum" => total,
+            _ => Value::Object(fields),
+        };
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Parse the sequence of step definitions in `parameters[key]`
+pub(super) fn sub_steps(step: &FlowStep, key: &str) -> Result<Vec<FlowStep>> {
+    match step.parameters.get(key) {
+        Some(steps @ serde_yaml::Value::Sequence(_)) => Ok(serde_yaml::from_value(steps.clone())?),
+        _ => Err(VesperError::ExecutionError(format!(
+            "Step {} needs a sequence of steps in {}",
+            step.step, key
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseBackend;
+    use crate::loader::VesperLoader;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    fn run(steps: &str, collect_errors: bool) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: fanout_v1
+type: function
+intent: compute several things at once
+
+inputs:
+  a:
+    type: integer
+  name:
+    type: string
+
+flow:
+  - step: fanout
+    operation: parallel
+    parameters:
+      collect_errors: {}
+      steps:
+{}
+"#,
+            collect_errors, steps
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), Value::Int(21));
+        inputs.insert("name".to_string(), Value::from("Ada"));
+        executor
+            .execute("fanout_v1", inputs)
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_parallel_merges_outputs() {
+        let steps = r#"
+        - step: double
+          operation: arithmetic
+          expression: "a * 2"
+          output: doubled
+        - step: greet
+          operation: string_template
+          template: "Hello, {name}!"
+          output: greeting
+"#;
+        let Value::Object(results) = run(steps, false).unwrap() else {
+            panic!("expected object result");
+        };
+        assert_eq!(results["doubled"], Value::Int(42));
+        assert_eq!(results["greeting"], Value::from("Hello, Ada!"));
+    }
+
+    #[test]
+    fn test_parallel_collects_errors() {
+        let steps = r#"
+        - step: first
+          operation: cache_get
+          parameters:
+            key: one
+        - step: second
+          operation: cache_get
+          parameters:
+            key: two
+"#;
+        assert!(matches!(
+            run(steps, false),
+            Err(VesperError::ExecutionError(_))
+        ));
+        let Err(VesperError::MultipleErrors(errors)) = run(steps, true) else {
+            panic!("expected collected errors");
+        };
+        assert_eq!(errors.len(), 2);
+    }
+
+    /// Answers `SELECT <ms>` after sleeping that long, failing on `FAIL`
+    #[derive(Default)]
+    struct SlowDatabase {
+        finished: Mutex<Vec<u64>>,
+    }
+
+    impl DatabaseBackend for SlowDatabase {
+        fn query(
+            &self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            let millis: u64 = sql
+                .trim_start_matches("SELECT ")
+                .parse()
+                .map_err(|_| format!("bad query: {}", sql))?;
+            std::thread::sleep(Duration::from_millis(millis));
+            self.finished.lock().unwrap().push(millis);
+            let mut row = HashMap::new();
+            row.insert("millis".to_string(), Value::Int(millis as i64));
+            Ok(vec![row])
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            Ok(0)
+        }
+    }
+
+    fn race(queries: &[&str]) -> (Result<Value>, Arc<SlowDatabase>, SemanticExecutor) {
+        let steps: String = queries
+            .iter()
+            .enumerate()
+            .map(|(i, sql)| {
+                format!(
+                    "        - step: source_{}\n          operation: database_query\n          parameters:\n            sql: \"{}\"\n",
+                    i, sql
+                )
+            })
+            .collect();
+        let yaml = format!(
+            r#"
+node_id: fastest_v1
+type: function
+intent: take the quickest source
+
+inputs: {{}}
+
+flow:
+  - step: fetch
+    operation: race
+    parameters:
+      steps:
+{}
+"#,
+            steps
+        );
+        let database = Arc::new(SlowDatabase::default());
+        let mut executor = SemanticExecutor::new().with_database(database.clone());
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        let result = executor
+            .execute("fastest_v1", HashMap::new())
+            .map(|r| r.data.unwrap());
+        (result, database, executor)
+    }
+
+    #[test]
+    fn test_race_fastest_step_wins() {
+        let start = Instant::now();
+        let (result, database, _) = race(&["SELECT 2000", "SELECT FAIL", "SELECT 10"]);
+
+        let Ok(Value::Array(rows)) = result else {
+            panic!("expected rows");
+        };
+        let Value::Object(row) = &rows[0] else {
+            panic!("expected object row");
+        };
+        assert_eq!(row["millis"], Value::Int(10));
+        assert_eq!(*database.finished.lock().unwrap(), vec![10]);
+        // The slow step is left running rather than waited for
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_race_all_failed() {
+        let (result, _, _) = race(&["SELECT FAIL", "SELECT NOPE"]);
+        let Err(VesperError::ExecutionError(message)) = result else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("source_0") && message.contains("source_1"));
+    }
+
+    #[test]
+    fn test_race_loser_does_not_block_configuration() {
+        let start = Instant::now();
+        let (result, database, mut executor) = race(&["SELECT 2000", "SELECT 10"]);
+        assert!(result.is_ok());
+
+        // The slow step still holds the state it started with
+        executor.register(
+            VesperLoader::new()
+                .load_string(
+                    r#"
+node_id: noop_v1
+type: function
+intent: do nothing
+
+inputs: {}
+
+flow:
+  - step: done
+    operation: return
+"#,
+                )
+                .unwrap(),
+        );
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert!(executor.execute("noop_v1", HashMap::new()).is_ok());
+        assert_eq!(*database.finished.lock().unwrap(), vec![10]);
+    }
+
+    fn scatter_gather(aggregate: &str, fail_fast: bool) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: quote_v1
+type: function
+intent: ask every pricing service
+
+inputs:
+  request:
+    type: object
+
+flow:
+  - step: quotes
+    operation: scatter_gather
+    parameters:
+      nodes: [cheap_v1, pricey_v1, broken_v1]
+      inputs: request
+      aggregate: {}
+      fail_fast: {}
+"#,
+            aggregate, fail_fast
+        );
+        let pricer = |node_id: &str, price: i64| {
+            format!(
+                r#"
+node_id: {}
+type: function
+intent: quote a price
+
+inputs:
+  qty:
+    type: integer
+
+flow:
+  - step: price
+    operation: arithmetic
+    expression: "qty * {}"
+"#,
+                node_id, price
+            )
+        };
+        let broken = r#"
+node_id: broken_v1
+type: function
+intent: always fail
+
+inputs:
+  qty:
+    type: integer
+
+flow:
+  - step: lookup
+    operation: cache_get
+    parameters:
+      key: price
+"#;
+        let mut executor = SemanticExecutor::new();
+        let loader = VesperLoader::new();
+        for node in [
+            yaml,
+            pricer("cheap_v1", 2),
+            pricer("pricey_v1", 5),
+            broken.to_string(),
+        ] {
+            executor.register(loader.load_string(&node).unwrap());
+        }
+
+        let mut request = HashMap::new();
+        request.insert("qty".to_string(), Value::Int(3));
+        let mut inputs = HashMap::new();
+        inputs.insert("request".to_string(), Value::Object(request));
+        executor
+            .execute("quote_v1", inputs)
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_scatter_gather_collect_captures_failures() {
+        let Value::Object(results) = scatter_gather("collect", false).unwrap() else {
+            panic!("expected object result");
+        };
+        assert_eq!(results["cheap_v1"], Value::Int(6));
+        assert_eq!(results["pricey_v1"], Value::Int(15));
+        let Value::Object(error) = &results["broken_v1"] else {
+            panic!("expected captured error");
+        };
+        assert_eq!(error["error"], Value::from("execution_error"));
+    }
+
+    #[test]
+    fn test_scatter_gather_sum_and_fail_fast() {
+        assert_eq!(scatter_gather("sum", false).unwrap(), Value::Int(21));
+        assert!(scatter_gather("sum", true).is_err());
+    }
+
+    #[test]
+    fn test_scatter_gather_sum_overflow() {
+        let gather = r#"
+node_id: total_v1
+type: function
+intent: add up large counts
+
+inputs:
+  request:
+    type: object
+
+flow:
+  - step: totals
+    operation: scatter_gather
+    parameters:
+      nodes: [big_v1, big_v1]
+      inputs: request
+      aggregate: sum
+"#;
+        let big = r#"
+node_id: big_v1
+type: function
+intent: report a large count
+
+inputs: {}
+
+flow:
+  - step: count
+    operation: arithmetic
+    expression: "4611686018427387904 * 1"
+"#;
+        let mut executor = SemanticExecutor::new();
+        let loader = VesperLoader::new();
+        executor.register(loader.load_string(gather).unwrap());
+        executor.register(loader.load_string(big).unwrap());
+        assert_eq!(
+            executor.execute("big_v1", HashMap::new()).unwrap().data,
+            Some(Value::Int(1 << 62))
+        );
+
+        let inputs = HashMap::from([("request".to_string(), Value::Object(HashMap::new()))]);
+        let Err(VesperError::ExecutionError(message)) = executor.execute("total_v1", inputs) else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("overflows"), "{}", message);
+    }
+}
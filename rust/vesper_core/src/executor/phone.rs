@@ -0,0 +1,205 @@
+//! Phone number operations
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a phone validation step
+    ///
+    /// Returns whether the number named by `parameters["on"]` is valid,
+    /// interpreting national numbers in `parameters["region"]`.
+    #[cfg(feature = "phone")]
+    pub(super) fn execute_phone_validate(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let text = phone_text(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+        let region = phone_region(step)?;
+
+        let valid = phonenumber::parse(region, &text)
+            .map(|number| phonenumber::is_valid(&number))
+            .unwrap_or(false);
+
+        let result = Value::Bool(valid);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a phone formatting step
+    ///
+    /// Formats the number named by `parameters["on"]` as `E164`,
+    /// `national` or `international` (`parameters["format"]`, default
+    /// E164). Invalid numbers are an error unless `strict: false`, in
+    /// which case the input is returned unchanged.
+    #[cfg(feature = "phone")]
+    pub(super) fn execute_phone_format(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        use phonenumber::Mode;
+
+        let text = phone_text(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+        let region = phone_region(step)?;
+        let mode = match step.parameters.get("format").and_then(|f| f.as_str()) {
+            None | Some("E164") | Some("e164") => Mode::E164,
+            Some("national") => Mode::National,
+            Some("international") => Mode::International,
+            Some(other) => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Unknown phone format: {}",
+                    other
+                )))
+            }
+        };
+        let strict = step
+            .parameters
+            .get("strict")
+            .and_then(|s| s.as_bool())
+            .unwrap_or(true);
+
+        let formatted = match phonenumber::parse(region, &text) {
+            Ok(number) if phonenumber::is_valid(&number) => number.format().mode(mode).to_string(),
+            _ if strict => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Invalid phone number: {}",
+                    text
+                )))
+            }
+            _ => {
+                tracing::warn!("Leaving invalid phone number unformatted: {}", text);
+                text
+            }
+        };
+
+        let result = Value::from(formatted);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a phone validation step (feature disabled)
+    #[cfg(not(feature = "phone"))]
+    pub(super) fn execute_phone_validate(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "phone_validate operation requires the `phone` feature".to_string(),
+        ))
+    }
+
+    /// Execute a phone formatting step (feature disabled)
+    #[cfg(not(feature = "phone"))]
+    pub(super) fn execute_phone_format(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "phone_format operation requires the `phone` feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(feature = "phone")]
+fn phone_text(value: &Value) -> Result<String> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| VesperError::TypeError {
+            expected: "string".to_string(),
+            actual: format!("{:?}", value),
+        })
+}
+
+/// Parse the optional ISO 3166-1 alpha-2 region of a step
+#[cfg(feature = "phone")]
+fn phone_region(step: &FlowStep) -> Result<Option<phonenumber::country::Id>> {
+    step.parameters
+        .get("region")
+        .and_then(|r| r.as_str())
+        .map(|r| {
+            r.to_uppercase()
+                .parse()
+                .map_err(|_| VesperError::ExecutionError(format!("Unknown region: {}", r)))
+        })
+        .transpose()
+}
+
+#[cfg(all(test, feature = "phone"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn run(operation: &str, extra: &str, number: &str) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: phone_v1
+type: function
+intent: handle a phone number
+
+inputs:
+  number:
+    type: string
+
+flow:
+  - step: handle
+    operation: {}
+    parameters:
+      on: number
+      {}
+"#,
+            operation, extra
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("number".to_string(), Value::from(number));
+        executor
+            .execute("phone_v1", inputs)
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_phone_validate() {
+        assert_eq!(
+            run("phone_validate", "region: NL", "020 794 0000").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            run("phone_validate", "", "+31 20 794 0000").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            run("phone_validate", "region: NL", "12").unwrap(),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_phone_format() {
+        assert_eq!(
+            run("phone_format", "region: NL", "020 794 0000").unwrap(),
+            Value::from("+31207940000")
+        );
+        assert_eq!(
+            run(
+                "phone_format",
+                "region: NL\n      format: international",
+                "020 794 0000"
+            )
+            .unwrap(),
+            Value::from("+31 20 794 0000")
+        );
+        assert!(run("phone_format", "region: NL", "12").is_err());
+        assert_eq!(
+            run("phone_format", "region: NL\n      strict: false", "12").unwrap(),
+            Value::from("12")
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Prometheus Pushgateway operation
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a Prometheus push step
+    ///
+    /// Renders the object named by `parameters["metrics"]` (metric name to
+    /// number) in the text exposition format and PUTs it to
+    /// `{gateway_url}/metrics/job/{job}`, replacing the job's metrics.
+    #[cfg(feature = "http")]
+    pub(super) fn execute_prometheus_push(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let gateway_url = self.resolve_string_parameter(step, "gateway_url", ctx)?;
+        let job = self.resolve_string_parameter(step, "job", ctx)?;
+        if job.is_empty() || job.contains('/') {
+            return Err(VesperError::ExecutionError(format!(
+                "Invalid Prometheus job name: {}",
+                job
+            )));
+        }
+        let metrics = match self.resolve_parameter_variable(step, "metrics", ctx)? {
+            Value::Object(metrics) => metrics,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "object".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        let url = format!("{}/metrics/job/{}", gateway_url.trim_end_matches('/'), job);
+        let response = self
+            .http_client()
+            .put(&url)
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .body(exposition_format(&metrics)?)
+            .send()
+            .map_err(|e| VesperError::ExecutionError(format!("Prometheus push failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(VesperError::ExecutionError(format!(
+                "Prometheus push failed: HTTP {}",
+                response.status()
+            )));
+        }
+
+        let result = Value::Bool(true);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a Prometheus push step (feature disabled)
+    #[cfg(not(feature = "http"))]
+    pub(super) fn execute_prometheus_push(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "prometheus_push operation requires the `http` feature".to_string(),
+        ))
+    }
+}
+
+/// Render metrics as `name value` lines, sorted by name
+#[cfg(feature = "http")]
+fn exposition_format(metrics: &std::collections::HashMap<String, Value>) -> Result<String> {
+    let mut names: Vec<&String> = metrics.keys().collect();
+    names.sort();
+
+    let mut body = String::new();
+    for name in names {
+        let valid_name = name.chars().enumerate().all(|(i, c)| {
+            c.is_ascii_alphabetic() || c == '_' || c == ':' || (i > 0 && c.is_ascii_digit())
+        });
+        if name.is_empty() || !valid_name {
+            return Err(VesperError::ExecutionError(format!(
+                "Invalid Prometheus metric name: {}",
+                name
+            )));
+        }
+        let value = match &metrics[name] {
+            Value::Int(i) => i.to_string(),
+            Value::Float(f) => f.to_string(),
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "number".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+        body.push_str(&format!("{} {}\n", name, value));
+    }
+    Ok(body)
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_prometheus_push() {
+        let mut server = mockito::Server::new();
+        let pushed = server
+            .mock("PUT", "/metrics/job/nightly_export")
+            .match_body("export_duration_seconds 12.5\nexport_rows_total 1042\n")
+            .with_status(200)
+            .create();
+
+        let yaml = format!(
+            r#"
+node_id: export_v1
+type: function
+intent: report batch export completion
+
+inputs:
+  stats:
+    type: object
+
+flow:
+  - step: push
+    operation: prometheus_push
+    parameters:
+      gateway_url: "{}"
+      job: nightly_export
+      metrics: stats
+"#,
+            server.url()
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut stats = HashMap::new();
+        stats.insert("export_rows_total".to_string(), Value::Int(1042));
+        stats.insert("export_duration_seconds".to_string(), Value::Float(12.5));
+        let mut inputs = HashMap::new();
+        inputs.insert("stats".to_string(), Value::Object(stats));
+
+        let result = executor.execute("export_v1", inputs).unwrap();
+        pushed.assert();
+        assert_eq!(result.data, Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_exposition_format_rejects_bad_names() {
+        let mut metrics = HashMap::new();
+        metrics.insert("2xx-count".to_string(), Value::Int(1));
+        assert!(exposition_format(&metrics).is_err());
+    }
+}
@@ -0,0 +1,300 @@
+//! Reflection over and discovery of the nodes registered with the executor
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, NodeType, Value, VesperNode};
+use std::collections::HashMap;
+
+impl SemanticExecutor {
+    /// Registered nodes whose intent or a metadata tag contains `query`,
+    /// ignoring case, ordered by node ID
+    pub fn search_by_intent(&self, query: &str) -> Vec<&VesperNode> {
+        let query = query.to_lowercase();
+        let mut nodes: Vec<&VesperNode> = self
+            .state
+            .nodes
+            .values()
+            .filter(|node| {
+                node.intent.to_lowercase().contains(&query)
+                    || node.metadata.iter().any(|metadata| {
+                        metadata
+                            .tags
+                            .iter()
+                            .any(|tag| tag.to_lowercase().contains(&query))
+                    })
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        nodes
+    }
+
+    /// Execute a node search step
+    ///
+    /// Finds registered nodes matching `parameters["query"]` as in
+    /// `search_by_intent`, optionally only those of type
+    /// `parameters["node_type"]`, and returns an array of objects with
+    /// their `node_id`, `intent` and `tags`.
+    pub(super) fn execute_search_nodes(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let query = self.resolve_string_parameter(step, "query", ctx)?;
+        let node_type: Option<NodeType> = match step.parameters.get("node_type") {
+            Some(_) => {
+                let name = self.resolve_string_parameter(step, "node_type", ctx)?;
+                Some(
+                    serde_json::from_value(serde_json::Value::String(name.clone())).map_err(
+                        |_| VesperError::ExecutionError(format!("Unknown node type: {}", name)),
+                    )?,
+                )
+            }
+            None => None,
+        };
+
+        let result = Value::Array(
+            self.search_by_intent(&query)
+                .into_iter()
+                .filter(|node| node_type.is_none_or(|t| node.node_type == t))
+                .map(|node| {
+                    let tags = node
+                        .metadata
+                        .iter()
+                        .flat_map(|metadata| &metadata.tags)
+                        .map(|tag| Value::from(tag.as_str()))
+                        .collect();
+                    let mut summary = HashMap::new();
+                    summary.insert("node_id".to_string(), Value::from(node.node_id.as_str()));
+                    summary.insert("intent".to_string(), Value::from(node.intent.as_str()));
+                    summary.insert("tags".to_string(), Value::Array(tags));
+                    Value::Object(summary)
+                })
+                .collect(),
+        );
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a node metadata step
+    ///
+    /// Describes the registered node `parameters["node_id"]` as an object
+    /// with its `node_id`, `node_type`, `intent`, sorted `input_names` and
+    /// `output_names` (success fields), and whether it `has_contracts`.
+    /// Input and output specs, flow steps and security settings are not
+    /// exposed.
+    pub(super) fn execute_node_metadata(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let node_id = self.resolve_string_parameter(step, "node_id", ctx)?;
+        let node =
+            self.state.nodes.get(&node_id).ok_or_else(|| {
+                VesperError::ExecutionError(format!("Node not found: {}", node_id))
+            })?;
+
+        let sorted_names = |names: Vec<&String>| {
+            let mut names: Vec<Value> =
+                names.into_iter().map(|n| Value::from(n.as_str())).collect();
+            names.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+            Value::Array(names)
+        };
+        let node_type = match serde_json::to_value(node.node_type)? {
+            serde_json::Value::String(name) => name,
+            other => other.to_string(),
+        };
+        let has_contracts = node.contracts.as_ref().is_some_and(|contracts| {
+            !(contracts.preconditions.is_empty()
+                && contracts.postconditions.is_empty()
+                && contracts.invariants.is_empty())
+        });
+
+        let mut metadata = HashMap::new();
+        metadata.insert("node_id".to_string(), Value::from(node.node_id.as_str()));
+        metadata.insert("node_type".to_string(), Value::from(node_type));
+        metadata.insert("intent".to_string(), Value::from(node.intent.as_str()));
+        metadata.insert(
+            "input_names".to_string(),
+            sorted_names(node.inputs.keys().collect()),
+        );
+        metadata.insert(
+            "output_names".to_string(),
+            sorted_names(
+                node.outputs
+                    .iter()
+                    .flat_map(|outputs| outputs.success.keys())
+                    .collect(),
+            ),
+        );
+        metadata.insert("has_contracts".to_string(), Value::Bool(has_contracts));
+
+        let result = Value::Object(metadata);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    const REFUND: &str = r#"
+node_id: refund_v1
+type: http_handler
+intent: refund a payment
+
+inputs:
+  payment_id:
+    type: string
+  amount:
+    type: number
+
+outputs:
+  success:
+    refund_id:
+      type: string
+    status:
+      type: string
+
+contracts:
+  preconditions:
+    - "amount > 0"
+
+flow: []
+"#;
+
+    const INSPECT: &str = r#"
+node_id: inspect_v1
+type: function
+intent: describe another node
+
+inputs:
+  target:
+    type: string
+
+flow:
+  - step: describe
+    operation: node_metadata
+    parameters:
+      node_id: "{target}"
+"#;
+
+    #[test]
+    fn test_node_metadata() {
+        let loader = VesperLoader::new();
+        let mut executor = SemanticExecutor::new();
+        executor.register(loader.load_string(REFUND).unwrap());
+        executor.register(loader.load_string(INSPECT).unwrap());
+        let describe = |target: &str| {
+            let mut inputs = HashMap::new();
+            inputs.insert("target".to_string(), Value::from(target));
+            executor.execute("inspect_v1", inputs)
+        };
+
+        let strings =
+            |names: &[&str]| Value::Array(names.iter().map(|&n| Value::from(n)).collect());
+        let mut expected = HashMap::new();
+        expected.insert("node_id".to_string(), Value::from("refund_v1"));
+        expected.insert("node_type".to_string(), Value::from("http_handler"));
+        expected.insert("intent".to_string(), Value::from("refund a payment"));
+        expected.insert(
+            "input_names".to_string(),
+            strings(&["amount", "payment_id"]),
+        );
+        expected.insert(
+            "output_names".to_string(),
+            strings(&["refund_id", "status"]),
+        );
+        expected.insert("has_contracts".to_string(), Value::Bool(true));
+        assert_eq!(
+            describe("refund_v1").unwrap().data,
+            Some(Value::Object(expected))
+        );
+
+        let own = describe("inspect_v1").unwrap().data.unwrap();
+        let Value::Object(own) = own else {
+            panic!("expected an object, got {:?}", own);
+        };
+        assert_eq!(own["output_names"], Value::Array(Vec::new()));
+        assert_eq!(own["has_contracts"], Value::Bool(false));
+
+        assert!(describe("missing_v1").is_err());
+    }
+
+    const CATALOG: [&str; 3] = [
+        r#"
+node_id: resize_image_v1
+type: function
+intent: Resize an uploaded image
+metadata:
+  tags: [media]
+flow: []
+"#,
+        r#"
+node_id: thumbnail_v1
+type: event_handler
+intent: Generate previews for new uploads
+metadata:
+  tags: [Media, images]
+flow: []
+"#,
+        r#"
+node_id: invoice_v1
+type: function
+intent: Render an invoice PDF
+flow: []
+"#,
+    ];
+
+    const DISCOVER: &str = r#"
+node_id: discover_v1
+type: function
+intent: find nodes for a capability
+
+inputs:
+  query:
+    type: string
+
+flow:
+  - step: find
+    operation: search_nodes
+    parameters:
+      query: "{query}"
+      node_type: function
+"#;
+
+    #[test]
+    fn test_search_nodes() {
+        let loader = VesperLoader::new();
+        let mut executor = SemanticExecutor::new();
+        for node in CATALOG.iter().chain([&DISCOVER]) {
+            executor.register(loader.load_string(node).unwrap());
+        }
+
+        let ids = |nodes: Vec<&VesperNode>| -> Vec<String> {
+            nodes.into_iter().map(|n| n.node_id.clone()).collect()
+        };
+        assert_eq!(
+            ids(executor.search_by_intent("MEDIA")),
+            vec!["resize_image_v1", "thumbnail_v1"]
+        );
+        assert_eq!(
+            ids(executor.search_by_intent("invoice")),
+            vec!["invoice_v1"]
+        );
+        assert!(executor.search_by_intent("audio").is_empty());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("query".to_string(), Value::from("image"));
+        let found = executor.execute("discover_v1", inputs).unwrap().data;
+        let mut expected = HashMap::new();
+        expected.insert("node_id".to_string(), Value::from("resize_image_v1"));
+        expected.insert(
+            "intent".to_string(),
+            Value::from("Resize an uploaded image"),
+        );
+        expected.insert("tags".to_string(), Value::Array(vec![Value::from("media")]));
+        assert_eq!(found, Some(Value::Array(vec![Value::Object(expected)])));
+    }
+}
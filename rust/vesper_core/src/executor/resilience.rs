@@ -0,0 +1,744 @@
+//! Resilience operations guarding blocks of sub-steps
+
+use super::parallel::sub_steps;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use parking_lot::{Condvar, Mutex};
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+/// State of a step's circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum CircuitState {
+    /// Calls pass through; counts consecutive failures
+    Closed { failures: u32 },
+    /// Calls are refused until the reset timeout has passed
+    Open { since: Instant },
+    /// One trial call is in flight to decide whether to close again
+    HalfOpen,
+}
+
+/// Circuit breakers keyed by node ID and step name
+pub(super) type CircuitBreakers = Mutex<HashMap<String, CircuitState>>;
+
+/// Token bucket refilled continuously at a fixed rate
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TokenBucket {
+    /// Tokens available, up to one second's worth
+    tokens: f64,
+    /// When `tokens` was last brought up to date
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    /// Take a token, or return how long until one is available
+    fn try_take(&mut self, per_second: f64) -> std::result::Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(per_second);
+        self.refilled = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
+        }
+    }
+}
+
+/// Rate limiters keyed by node ID and step name
+pub(super) type RateLimiters = Mutex<HashMap<String, TokenBucket>>;
+
+/// Counting semaphore bounding concurrent runs of a step group
+#[derive(Debug, Default)]
+pub(super) struct Bulkhead {
+    /// Runs in progress
+    in_use: Mutex<usize>,
+    /// Signalled whenever a run finishes
+    released: Condvar,
+}
+
+impl Bulkhead {
+    /// Enter the bulkhead, waiting up to `wait` (forever if `None`) for
+    /// one of the `capacity` slots to free up
+    fn acquire(&self, capacity: usize, wait: Option<Duration>) -> Option<BulkheadPermit<'_>> {
+        let deadline = wait.map(|wait| Instant::now() + wait);
+        let mut in_use = self.in_use.lock();
+        while *in_use >= capacity {
+            match deadline {
+                Some(deadline) => {
+                    if self.released.wait_until(&mut in_use, deadline).timed_out()
+                        && *in_use >= capacity
+                    {
+                        return None;
+                    }
+                }
+                None => self.released.wait(&mut in_use),
+            }
+        }
+        *in_use += 1;
+        Some(BulkheadPermit { bulkhead: self })
+    }
+}
+
+/// Slot in a bulkhead, freed on drop
+struct BulkheadPermit<'a> {
+    bulkhead: &'a Bulkhead,
+}
+
+impl Drop for BulkheadPermit<'_> {
+    fn drop(&mut self) {
+        *self.bulkhead.in_use.lock() -= 1;
+        self.bulkhead.released.notify_one();
+    }
+}
+
+/// Bulkheads keyed by node ID and step name
+pub(super) type Bulkheads = Mutex<HashMap<String, Arc<Bulkhead>>>;
+
+impl SemanticExecutor {
+    /// Execute a with-timeout step
+    ///
+    /// Runs the steps in `parameters["steps"]` on a background thread
+    /// against a copy of the context. If they finish within
+    /// `parameters["timeout_ms"]`, their variables are kept and the last
+    /// result is returned. Otherwise the block is cancelled before its next
+    /// sub-step, its variables are discarded, and the steps in
+    /// `parameters["on_timeout"]` run instead; the step then returns null
+    /// without setting its output.
+    ///
+    /// Sub-steps cannot be interrupted, so one in progress at the timeout
+    /// is left to finish in the background; this step does not wait for it.
+    pub(super) fn execute_with_timeout(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let on_timeout = match step.parameters.get("on_timeout") {
+            Some(_) => sub_steps(step, "on_timeout")?,
+            None => Vec::new(),
+        };
+        let timeout = step
+            .parameters
+            .get("timeout_ms")
+            .and_then(|t| t.as_u64())
+            .map(Duration::from_millis)
+            .ok_or_else(|| {
+                VesperError::ExecutionError(format!(
+                    "Step {} needs a timeout_ms parameter",
+                    step.step
+                ))
+            })?;
+
+        let token = ctx.cancellation.child_token();
+        let (sender, receiver) = mpsc::channel();
+        let executor = self.share();
+        let mut block_ctx = ctx.clone();
+        block_ctx.cancellation = token.clone();
+        std::thread::spawn(move || {
+            let mut result = Ok(Value::Null);
+            for sub_step in &steps {
+                // Nobody is waiting for the rest once the timeout has fired
+                if block_ctx.cancellation.is_cancelled() {
+                    return;
+                }
+                result = executor.execute_step(sub_step, &mut block_ctx);
+                if result.is_err() {
+                    break;
+                }
+            }
+            let _ = sender.send(result.map(|value| (value, block_ctx)));
+        });
+
+        let completed = match receiver.recv_timeout(timeout) {
+            Ok(outcome) => Some(outcome),
+            Err(_) => {
+                token.cancel();
+                None
+            }
+        };
+
+        match completed {
+            Some(outcome) => {
+                let (result, block_ctx) = outcome?;
+                *ctx = block_ctx;
+                self.store_output(step, ctx, &result);
+                Ok(result)
+            }
+            None => {
+                tracing::warn!("Step {} timed out after {:?}", step.step, timeout);
+                for timeout_step in &on_timeout {
+                    self.execute_step(timeout_step, ctx)?;
+                }
+                Ok(Value::Null)
+            }
+        }
+    }
+}
+
+impl SemanticExecutor {
+    /// Execute a circuit breaker step
+    ///
+    /// Runs the steps in `parameters["steps"]` in order while the breaker
+    /// is closed. After `parameters["failure_threshold"]` (default 5)
+    /// consecutive failed runs the breaker opens: for
+    /// `parameters["reset_timeout_ms"]` (default 30000) the steps are
+    /// skipped and `parameters["fallback"]` runs instead, or the step fails
+    /// if there is none. The first run after that is a trial, which closes
+    /// the breaker on success and reopens it on failure.
+    ///
+    /// Breaker state persists across executions, keyed by node ID and
+    /// step name. Failures of the protected steps propagate.
+    pub(super) fn execute_circuit_breaker_step(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let integer = |key, default| {
+            step.parameters
+                .get(key)
+                .and_then(|v| v.as_u64())
+                .unwrap_or(default)
+        };
+        let failure_threshold = integer("failure_threshold", 5).max(1) as u32;
+        let reset_timeout = Duration::from_millis(integer("reset_timeout_ms", 30_000));
+        let key = format!("{}/{}", ctx.node_id(), step.step);
+
+        let admitted = {
+            let mut breakers = self.state.circuit_breakers.lock();
+            let state = breakers
+                .entry(key.clone())
+                .or_insert(CircuitState::Closed { failures: 0 });
+            match *state {
+                CircuitState::Closed { .. } => true,
+                CircuitState::Open { since } if since.elapsed() >= reset_timeout => {
+                    tracing::debug!("Circuit breaker {} half-open", key);
+                    *state = CircuitState::HalfOpen;
+                    true
+                }
+                CircuitState::Open { .. } | CircuitState::HalfOpen => false,
+            }
+        };
+
+        if !admitted {
+            if !step.parameters.contains_key("fallback") {
+                return Err(VesperError::ExecutionError(format!(
+                    "Circuit breaker open for step {}",
+                    step.step
+                )));
+            }
+            let result = sub_steps(step, "fallback")?
+                .iter()
+                .try_fold(Value::Null, |_, fallback_step| {
+                    self.execute_step(fallback_step, ctx)
+                })?;
+            self.store_output(step, ctx, &result);
+            return Ok(result);
+        }
+
+        let outcome = steps
+            .iter()
+            .try_fold(Value::Null, |_, sub_step| self.execute_step(sub_step, ctx));
+
+        {
+            let mut breakers = self.state.circuit_breakers.lock();
+            let state = breakers
+                .entry(key.clone())
+                .or_insert(CircuitState::Closed { failures: 0 });
+            *state = match (outcome.is_ok(), *state) {
+                (true, _) => CircuitState::Closed { failures: 0 },
+                (false, CircuitState::Closed { failures }) if failures + 1 < failure_threshold => {
+                    CircuitState::Closed {
+                        failures: failures + 1,
+                    }
+                }
+                (false, _) => {
+                    tracing::warn!("Circuit breaker {} opened", key);
+                    CircuitState::Open {
+                        since: Instant::now(),
+                    }
+                }
+            };
+        }
+
+        let result = outcome?;
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a rate-limited step
+    ///
+    /// Runs the steps in `parameters["steps"]` at most
+    /// `parameters["max_per_second"]` times per second, allowing bursts of
+    /// up to one second's worth. Over the limit, `parameters["strategy"]`
+    /// decides: `fail` (default) rejects the run with a rate limit error,
+    /// `wait` blocks until the bucket has a token again.
+    ///
+    /// Buckets persist across executions, keyed by node ID and step name.
+    pub(super) fn execute_rate_limit_step(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let per_second = step
+            .parameters
+            .get("max_per_second")
+            .and_then(|v| v.as_f64())
+            .filter(|rate| *rate > 0.0)
+            .ok_or_else(|| {
+                VesperError::ExecutionError(format!(
+                    "Step {} needs a positive max_per_second parameter",
+                    step.step
+                ))
+            })?;
+        let wait = match step.parameters.get("strategy").and_then(|v| v.as_str()) {
+            None | Some("fail") => false,
+            Some("wait") => true,
+            Some(other) => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Unknown rate limit strategy: {}",
+                    other
+                )))
+            }
+        };
+        let key = format!("{}/{}", ctx.node_id(), step.step);
+
+        loop {
+            let taken = self
+                .state
+                .rate_limiters
+                .lock()
+                .entry(key.clone())
+                .or_insert(TokenBucket {
+                    tokens: per_second,
+                    refilled: Instant::now(),
+                })
+                .try_take(per_second);
+            match taken {
+                Ok(()) => break,
+                Err(delay) if wait => std::thread::sleep(delay),
+                Err(_) => {
+                    return Err(VesperError::RateLimitExceeded(format!(
+                        "{}/s for step {}",
+                        per_second, step.step
+                    )))
+                }
+            }
+        }
+
+        let result = steps
+            .iter()
+            .try_fold(Value::Null, |_, sub_step| self.execute_step(sub_step, ctx))?;
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a bulkhead step
+    ///
+    /// Runs the steps in `parameters["steps"]` with at most
+    /// `parameters["max_concurrent"]` runs in progress at once across all
+    /// executions. When the bulkhead is full, `parameters["strategy"]`
+    /// decides: `fail` (default) rejects the run, `wait` blocks until a run
+    /// finishes, giving up after `parameters["wait_timeout_ms"]` if set.
+    pub(super) fn execute_bulkhead(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let steps = sub_steps(step, "steps")?;
+        let capacity = step
+            .parameters
+            .get("max_concurrent")
+            .and_then(|v| v.as_u64())
+            .filter(|max| *max > 0)
+            .ok_or_else(|| {
+                VesperError::ExecutionError(format!(
+                    "Step {} needs a positive max_concurrent parameter",
+                    step.step
+                ))
+            })? as usize;
+        let wait = match step.parameters.get("strategy").and_then(|v| v.as_str()) {
+            None | Some("fail") => Some(Duration::ZERO),
+            Some("wait") => step
+                .parameters
+                .get("wait_timeout_ms")
+                .and_then(|v| v.as_u64())
+                .map(Duration::from_millis),
+            Some(other) => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Unknown bulkhead strategy: {}",
+                    other
+                )))
+            }
+        };
+
+        let bulkhead = self
+            .state
+            .bulkheads
+            .lock()
+            .entry(format!("{}/{}", ctx.node_id(), step.step))
+            .or_default()
+            .clone();
+        let Some(_permit) = bulkhead.acquire(capacity, wait) else {
+            return Err(VesperError::ExecutionError("Bulkhead full".to_string()));
+        };
+
+        let result = steps
+            .iter()
+            .try_fold(Value::Null, |_, sub_step| self.execute_step(sub_step, ctx))?;
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseBackend;
+    use crate::executor::ExecutionResult;
+    use crate::loader::VesperLoader;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Answers `SELECT <ms>` after sleeping that long
+    #[derive(Default)]
+    struct SleepingDatabase {
+        answered: Mutex<Vec<String>>,
+    }
+
+    impl DatabaseBackend for SleepingDatabase {
+        fn query(
+            &self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            let millis: u64 = sql
+                .trim_start_matches("SELECT ")
+                .parse()
+                .map_err(|_| format!("bad query: {}", sql))?;
+            std::thread::sleep(Duration::from_millis(millis));
+            self.answered.lock().push(sql.to_string());
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            Ok(0)
+        }
+    }
+
+    fn run_with_timeout(sleep_ms: u64) -> Value {
+        let yaml = format!(
+            r#"
+node_id: bounded_v1
+type: function
+intent: give up on slow lookups
+
+inputs:
+  x:
+    type: integer
+
+flow:
+  - step: bounded
+    operation: with_timeout
+    parameters:
+      timeout_ms: 100
+      steps:
+        - operation: database_query
+          parameters:
+            sql: "SELECT {sleep_ms}"
+        - operation: arithmetic
+          expression: "x + 1"
+          output: value
+      on_timeout:
+        - operation: arithmetic
+          expression: "0 - 1"
+          output: value
+  - step: report
+    operation: arithmetic
+    expression: "value * 1"
+"#
+        );
+        let mut executor =
+            SemanticExecutor::new().with_database(Arc::new(SleepingDatabase::default()));
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("x".to_string(), Value::Int(41));
+        executor
+            .execute("bounded_v1", inputs)
+            .unwrap()
+            .data
+            .unwrap()
+    }
+
+    /// Fails every query while `failing` is set, counting queries
+    #[derive(Default)]
+    struct FlakyDatabase {
+        failing: AtomicBool,
+        queries: AtomicUsize,
+    }
+
+    impl DatabaseBackend for FlakyDatabase {
+        fn query(
+            &self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            self.queries.fetch_add(1, Ordering::SeqCst);
+            if self.failing.load(Ordering::SeqCst) {
+                return Err("connection refused".to_string());
+            }
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            Ok(0)
+        }
+    }
+
+    const GUARDED: &str = r#"
+node_id: guarded_v1
+type: function
+intent: protect a flaky dependency
+
+flow:
+  - step: lookup
+    operation: circuit_breaker_step
+    parameters:
+      failure_threshold: 2
+      reset_timeout_ms: 50
+      steps:
+        - operation: database_query
+          parameters:
+            sql: "SELECT 1"
+      fallback:
+        - operation: arithmetic
+          expression: "0 - 1"
+"#;
+
+    #[test]
+    fn test_circuit_breaker_transitions() {
+        let database = Arc::new(FlakyDatabase::default());
+        let mut executor = SemanticExecutor::new().with_database(database.clone());
+        executor.register(VesperLoader::new().load_string(GUARDED).unwrap());
+        let state = |executor: &SemanticExecutor| {
+            executor.state.circuit_breakers.lock()["guarded_v1/lookup"]
+        };
+        let run = |executor: &SemanticExecutor| executor.execute("guarded_v1", HashMap::new());
+
+        // Closed: failures propagate until the threshold opens the breaker
+        database.failing.store(true, Ordering::SeqCst);
+        assert!(run(&executor).is_err());
+        assert_eq!(state(&executor), CircuitState::Closed { failures: 1 });
+        assert!(run(&executor).is_err());
+        assert!(matches!(state(&executor), CircuitState::Open { .. }));
+
+        // Open: the fallback answers without touching the database
+        let fallback = run(&executor).unwrap();
+        assert_eq!(fallback.data, Some(Value::Int(-1)));
+        assert_eq!(database.queries.load(Ordering::SeqCst), 2);
+
+        // Half-open: a failed trial reopens the breaker
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(run(&executor).is_err());
+        assert!(matches!(state(&executor), CircuitState::Open { .. }));
+        assert_eq!(database.queries.load(Ordering::SeqCst), 3);
+
+        // Half-open: a successful trial closes it
+        std::thread::sleep(Duration::from_millis(60));
+        database.failing.store(false, Ordering::SeqCst);
+        let recovered = run(&executor).unwrap();
+        assert_eq!(recovered.data, Some(Value::Array(Vec::new())));
+        assert_eq!(state(&executor), CircuitState::Closed { failures: 0 });
+    }
+
+    fn throttled(strategy: &str) -> SemanticExecutor {
+        let yaml = format!(
+            r#"
+node_id: throttled_v1
+type: function
+intent: call an API politely
+
+flow:
+  - step: call
+    operation: rate_limit_step
+    parameters:
+      max_per_second: 5
+      strategy: {strategy}
+      steps:
+        - operation: arithmetic
+          expression: "1 + 1"
+"#
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        executor
+    }
+
+    #[test]
+    fn test_rate_limit_fail() {
+        let executor = throttled("fail");
+        let outcomes: Vec<_> = (0..20)
+            .map(|_| executor.execute("throttled_v1", HashMap::new()))
+            .collect();
+
+        assert!(outcomes[..5].iter().all(|outcome| outcome.is_ok()));
+        assert!(outcomes[5..]
+            .iter()
+            .all(|outcome| matches!(outcome, Err(VesperError::RateLimitExceeded(_)))));
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        let executor = throttled("wait");
+        let start = Instant::now();
+        let elapsed: Vec<Duration> = (0..10)
+            .map(|_| {
+                executor.execute("throttled_v1", HashMap::new()).unwrap();
+                start.elapsed()
+            })
+            .collect();
+
+        // The burst passes at once, then one call per 200ms
+        assert!(elapsed[4] < Duration::from_millis(100), "{:?}", elapsed);
+        assert!(elapsed[5] >= Duration::from_millis(150), "{:?}", elapsed);
+        assert!(elapsed[9] >= Duration::from_millis(950), "{:?}", elapsed);
+    }
+
+    /// Sleeps 100ms per query, recording the peak number of concurrent queries
+    #[derive(Default)]
+    struct PooledDatabase {
+        active: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl DatabaseBackend for PooledDatabase {
+        fn query(
+            &self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(active, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(100));
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, _sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            Ok(0)
+        }
+    }
+
+    fn bulkhead(strategy: &str, callers: usize) -> (Vec<Result<ExecutionResult>>, usize) {
+        let yaml = format!(
+            r#"
+node_id: pooled_v1
+type: function
+intent: share a small connection pool
+
+flow:
+  - step: query
+    operation: bulkhead
+    parameters:
+      max_concurrent: 2
+      strategy: {strategy}
+      steps:
+        - operation: database_query
+          parameters:
+            sql: "SELECT 1"
+"#
+        );
+        let database = Arc::new(PooledDatabase::default());
+        let mut executor = SemanticExecutor::new().with_database(database.clone());
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let barrier = std::sync::Barrier::new(callers);
+        let outcomes = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..callers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        executor.execute("pooled_v1", HashMap::new())
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        (outcomes, database.peak.load(Ordering::SeqCst))
+    }
+
+    #[test]
+    fn test_bulkhead_fail() {
+        let (outcomes, peak) = bulkhead("fail", 4);
+        let rejected: Vec<_> = outcomes.iter().filter_map(|o| o.as_ref().err()).collect();
+        assert_eq!(rejected.len(), 2);
+        assert!(rejected
+            .iter()
+            .all(|e| e.to_string().contains("Bulkhead full")));
+        assert_eq!(peak, 2);
+    }
+
+    #[test]
+    fn test_bulkhead_wait() {
+        let (outcomes, peak) = bulkhead("wait", 6);
+        assert!(outcomes.iter().all(|o| o.is_ok()));
+        assert_eq!(peak, 2);
+    }
+
+    #[test]
+    fn test_with_timeout_completes() {
+        assert_eq!(run_with_timeout(1), Value::Int(42));
+    }
+
+    #[test]
+    fn test_with_timeout_runs_on_timeout() {
+        assert_eq!(run_with_timeout(300), Value::Int(-1));
+    }
+
+    #[test]
+    fn test_with_timeout_does_not_wait_for_sub_step() {
+        let start = std::time::Instant::now();
+        assert_eq!(run_with_timeout(2000), Value::Int(-1));
+        assert!(
+            start.elapsed() < Duration::from_millis(500),
+            "took {:?}",
+            start.elapsed()
+        );
+    }
+
+    #[test]
+    fn test_with_timeout_stops_remaining_sub_steps() {
+        let yaml = r#"
+node_id: bounded_v1
+type: function
+intent: give up on slow lookups
+
+inputs: {}
+
+flow:
+  - step: bounded
+    operation: with_timeout
+    parameters:
+      timeout_ms: 50
+      steps:
+        - operation: database_query
+          parameters:
+            sql: "SELECT 150"
+        - operation: database_query
+          parameters:
+            sql: "SELECT 0"
+"#;
+        let database = Arc::new(SleepingDatabase::default());
+        let mut executor = SemanticExecutor::new().with_database(database.clone());
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        executor.execute("bounded_v1", HashMap::new()).unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(*database.answered.lock(), vec!["SELECT 150".to_string()]);
+    }
+}
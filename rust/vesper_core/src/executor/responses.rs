@@ -0,0 +1,207 @@
+//! HTTP response construction for `HttpHandler` flows
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+#[cfg(feature = "http")]
+use crate::types::IntoString;
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute an HTTP response builder step
+    ///
+    /// Builds `{status, headers, body}` from `parameters["status"]`, the
+    /// body named by `parameters["body"]`, `parameters["content_type"]`
+    /// (default `application/json`) and extra `parameters["headers"]`.
+    /// JSON bodies are serialized; other content types are passed through.
+    #[cfg(feature = "http")]
+    pub(super) fn execute_http_response_builder(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        if !step.parameters.contains_key("status") {
+            return Err(VesperError::ExecutionError(format!(
+                "Step {} missing parameter: status",
+                step.step
+            )));
+        }
+        self.build_http_response(step, ctx)
+    }
+
+    /// Execute a JSON response step
+    ///
+    /// Shorthand for `http_response_builder` with status 200 and a JSON body.
+    #[cfg(feature = "http")]
+    pub(super) fn execute_http_json_response(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        self.build_http_response(step, ctx)
+    }
+
+    #[cfg(feature = "http")]
+    fn build_http_response(&self, step: &FlowStep, ctx: &mut ExecutionContext) -> Result<Value> {
+        use std::collections::HashMap;
+
+        let status = match step.parameters.get("status") {
+            Some(status) => match self.resolve_value(status, ctx) {
+                Value::Int(code) if (100..=599).contains(&code) => code,
+                other => {
+                    return Err(VesperError::ExecutionError(format!(
+                        "Invalid HTTP status: {:?}",
+                        other
+                    )))
+                }
+            },
+            None => 200,
+        };
+        let content_type = match step.parameters.get("content_type") {
+            Some(_) => self.resolve_string_parameter(step, "content_type", ctx)?,
+            None => "application/json".to_string(),
+        };
+        let body = match step.parameters.get("body") {
+            Some(_) => self.resolve_parameter_variable(step, "body", ctx)?,
+            None => Value::Null,
+        };
+
+        let mut headers = HashMap::new();
+        if let Some(serde_yaml::Value::Mapping(extra)) = step.parameters.get("headers") {
+            for (name, value) in extra {
+                if let Some(name) = name.as_str() {
+                    let value = match self.resolve_value(value, ctx) {
+                        Value::String(s) => s.into_string(),
+                        other => serde_json::Value::from(&other).to_string(),
+                    };
+                    headers.insert(name.to_string(), Value::from(value));
+                }
+            }
+        }
+        headers.insert(
+            "content-type".to_string(),
+            Value::from(content_type.as_str()),
+        );
+
+        let body = if is_json(&content_type) {
+            Value::from(serde_json::to_string(&serde_json::Value::from(&body))?)
+        } else {
+            body
+        };
+
+        let mut fields = HashMap::new();
+        fields.insert("status".to_string(), Value::Int(status));
+        fields.insert("headers".to_string(), Value::Object(headers));
+        fields.insert("body".to_string(), body);
+
+        let result = Value::Object(fields);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute an HTTP response builder step (feature disabled)
+    #[cfg(not(feature = "http"))]
+    pub(super) fn execute_http_response_builder(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "http_response_builder operation requires the `http` feature".to_string(),
+        ))
+    }
+
+    /// Execute a JSON response step (feature disabled)
+    #[cfg(not(feature = "http"))]
+    pub(super) fn execute_http_json_response(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "http_json_response operation requires the `http` feature".to_string(),
+        ))
+    }
+}
+
+/// Check whether a content type carries JSON, e.g. `application/problem+json`
+#[cfg(feature = "http")]
+fn is_json(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or("").trim();
+    mime == "application/json" || mime.ends_with("+json")
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn run(step: &str) -> Value {
+        let yaml = format!(
+            r#"
+node_id: respond_v1
+type: function
+intent: answer a request
+
+inputs:
+  user:
+    type: object
+
+flow:
+{}
+"#,
+            step
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut user = HashMap::new();
+        user.insert("id".to_string(), Value::Int(7));
+        let mut inputs = HashMap::new();
+        inputs.insert("user".to_string(), Value::Object(user));
+        executor
+            .execute("respond_v1", inputs)
+            .unwrap()
+            .data
+            .unwrap()
+    }
+
+    #[test]
+    fn test_http_response_builder() {
+        let Value::Object(response) = run(r#"
+  - step: respond
+    operation: http_response_builder
+    parameters:
+      status: 201
+      body: user
+      headers:
+        location: /users/7
+"#)
+        else {
+            panic!("expected object");
+        };
+        assert_eq!(response["status"], Value::Int(201));
+        assert_eq!(response["body"], Value::from(r#"{"id":7}"#));
+
+        let Value::Object(headers) = &response["headers"] else {
+            panic!("expected headers object");
+        };
+        assert_eq!(headers["location"], Value::from("/users/7"));
+        assert_eq!(headers["content-type"], Value::from("application/json"));
+    }
+
+    #[test]
+    fn test_http_json_response_defaults() {
+        let Value::Object(response) = run(r#"
+  - step: respond
+    operation: http_json_response
+    parameters:
+      body: user
+"#)
+        else {
+            panic!("expected object");
+        };
+        assert_eq!(response["status"], Value::Int(200));
+        assert_eq!(response["body"], Value::from(r#"{"id":7}"#));
+    }
+}
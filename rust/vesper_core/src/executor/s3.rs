@@ -0,0 +1,312 @@
+//! AWS S3 object storage operations
+//!
+//! Credentials and region come from the standard AWS environment
+//! (variables, profiles or instance metadata). An `endpoint_url` parameter
+//! points the client at S3-compatible stores such as MinIO.
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+#[cfg(feature = "aws")]
+use crate::types::IntoString;
+use crate::types::{FlowStep, Value};
+
+/// Capability a node must declare to use the S3 operations
+#[cfg(feature = "aws")]
+const S3_CAPABILITY: &str = "capability:s3";
+
+impl SemanticExecutor {
+    /// Execute an S3 download step
+    ///
+    /// Fetches `parameters["key"]` from `parameters["bucket"]` (optionally
+    /// at `parameters["version_id"]`) and returns the body as bytes.
+    #[cfg(feature = "aws")]
+    pub(super) fn execute_s3_get(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        self.require_capability(step, ctx, S3_CAPABILITY)?;
+        let bucket = self.resolve_string_parameter(step, "bucket", ctx)?;
+        let key = self.resolve_string_parameter(step, "key", ctx)?;
+        let version_id = self.optional_string_parameter(step, "version_id", ctx)?;
+        let endpoint_url = self.optional_string_parameter(step, "endpoint_url", ctx)?;
+
+        let body = block_on(async {
+            let client = s3_client(endpoint_url).await;
+            let object = client
+                .get_object()
+                .bucket(&bucket)
+                .key(&key)
+                .set_version_id(version_id)
+                .send()
+                .await
+                .map_err(s3_error)?;
+            object
+                .body
+                .collect()
+                .await
+                .map(|data| data.into_bytes().to_vec())
+                .map_err(|e| format!("reading body: {}", e))
+        })?
+        .map_err(|e| {
+            VesperError::ExecutionError(format!("S3 get s3://{}/{} failed: {}", bucket, key, e))
+        })?;
+
+        let result = Value::Array(body.into_iter().map(|b| Value::Int(i64::from(b))).collect());
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute an S3 upload step
+    ///
+    /// Stores the string or byte array named by `parameters["body"]` at
+    /// `parameters["key"]` in `parameters["bucket"]`, with an optional
+    /// `content_type` and `tags` mapping. Returns `{etag, version_id}`.
+    #[cfg(feature = "aws")]
+    pub(super) fn execute_s3_put(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        self.require_capability(step, ctx, S3_CAPABILITY)?;
+        let bucket = self.resolve_string_parameter(step, "bucket", ctx)?;
+        let key = self.resolve_string_parameter(step, "key", ctx)?;
+        let content_type = self.optional_string_parameter(step, "content_type", ctx)?;
+        let endpoint_url = self.optional_string_parameter(step, "endpoint_url", ctx)?;
+        let body = match self.resolve_parameter_variable(step, "body", ctx)? {
+            Value::String(s) => s.into_string().into_bytes(),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| item.as_int().and_then(|i| u8::try_from(i).ok()))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(|| VesperError::TypeError {
+                    expected: "byte array".to_string(),
+                    actual: format!("{:?}", items),
+                })?,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string or bytes".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+        let tagging = match step.parameters.get("tags") {
+            Some(serde_yaml::Value::Mapping(tags)) => {
+                let pairs: Vec<String> = tags
+                    .iter()
+                    .filter_map(|(k, v)| {
+                        let value = match self.resolve_value(v, ctx) {
+                            Value::String(s) => s.into_string(),
+                            other => serde_json::Value::from(&other).to_string(),
+                        };
+                        k.as_str()
+                            .map(|k| format!("{}={}", url_encode(k), url_encode(&value)))
+                    })
+                    .collect();
+                Some(pairs.join("&"))
+            }
+            _ => None,
+        };
+
+        let output = block_on(async {
+            let client = s3_client(endpoint_url).await;
+            client
+                .put_object()
+                .bucket(&bucket)
+                .key(&key)
+                .body(body.into())
+                .set_content_type(content_type)
+                .set_tagging(tagging)
+                .send()
+                .await
+                .map_err(s3_error)
+        })?
+        .map_err(|e| {
+            VesperError::ExecutionError(format!("S3 put s3://{}/{} failed: {}", bucket, key, e))
+        })?;
+
+        let mut fields = std::collections::HashMap::new();
+        fields.insert(
+            "etag".to_string(),
+            output.e_tag.map_or(Value::Null, Value::from),
+        );
+        fields.insert(
+            "version_id".to_string(),
+            output.version_id.map_or(Value::Null, Value::from),
+        );
+
+        let result = Value::Object(fields);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    #[cfg(feature = "aws")]
+    fn optional_string_parameter(
+        &self,
+        step: &FlowStep,
+        key: &str,
+        ctx: &ExecutionContext,
+    ) -> Result<Option<String>> {
+        match step.parameters.get(key) {
+            Some(_) => self.resolve_string_parameter(step, key, ctx).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Execute an S3 download step (feature disabled)
+    #[cfg(not(feature = "aws"))]
+    pub(super) fn execute_s3_get(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "s3_get operation requires the `aws` feature".to_string(),
+        ))
+    }
+
+    /// Execute an S3 upload step (feature disabled)
+    #[cfg(not(feature = "aws"))]
+    pub(super) fn execute_s3_put(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "s3_put operation requires the `aws` feature".to_string(),
+        ))
+    }
+}
+
+/// Run an SDK future to completion on a private runtime
+#[cfg(feature = "aws")]
+fn block_on<F: std::future::Future>(future: F) -> Result<F::Output> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    Ok(runtime.block_on(future))
+}
+
+#[cfg(feature = "aws")]
+async fn s3_client(endpoint_url: Option<String>) -> aws_sdk_s3::Client {
+    let mut loader = aws_config::from_env();
+    if let Some(url) = &endpoint_url {
+        loader = loader.endpoint_url(url);
+    }
+    let shared = loader.load().await;
+    let config = aws_sdk_s3::config::Builder::from(&shared)
+        .force_path_style(endpoint_url.is_some())
+        .build();
+    aws_sdk_s3::Client::from_conf(config)
+}
+
+/// Describe an SDK error by its S3 error code, e.g. `NoSuchKey`
+#[cfg(feature = "aws")]
+fn s3_error<E, R>(error: aws_sdk_s3::error::SdkError<E, R>) -> String
+where
+    E: aws_sdk_s3::error::ProvideErrorMetadata + std::error::Error + 'static,
+    R: std::fmt::Debug,
+{
+    use aws_sdk_s3::error::ProvideErrorMetadata;
+
+    match (error.code(), error.message()) {
+        (Some(code), Some(message)) => format!("{}: {}", code, message),
+        (Some(code), None) => code.to_string(),
+        _ => aws_sdk_s3::error::DisplayErrorContext(&error).to_string(),
+    }
+}
+
+/// Percent-encode a tag key or value
+#[cfg(feature = "aws")]
+fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "aws"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn run(security: &str, endpoint: &str) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: fetch_v1
+type: function
+intent: download a report
+
+inputs: {{}}
+{}
+flow:
+  - step: fetch
+    operation: s3_get
+    parameters:
+      bucket: reports
+      key: daily.csv
+      endpoint_url: "{}"
+"#,
+            security, endpoint
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        executor
+            .execute("fetch_v1", HashMap::new())
+            .map(|r| r.data.unwrap())
+    }
+
+    const S3_SECURITY: &str = "security:\n  capabilities_required: [\"capability:s3\"]\n";
+
+    #[test]
+    fn test_s3_get() {
+        std::env::set_var("AWS_ACCESS_KEY_ID", "test");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+        std::env::set_var("AWS_REGION", "us-east-1");
+
+        let mut server = mockito::Server::new();
+        let found = server
+            .mock("GET", "/reports/daily.csv")
+            .match_query(mockito::Matcher::Any)
+            .with_body("a,b")
+            .create();
+
+        let result = run(S3_SECURITY, &server.url()).unwrap();
+        found.assert();
+        assert_eq!(
+            result,
+            Value::Array(vec![
+                Value::Int(i64::from(b'a')),
+                Value::Int(i64::from(b',')),
+                Value::Int(i64::from(b'b')),
+            ])
+        );
+
+        server.reset();
+        server
+            .mock("GET", "/reports/daily.csv")
+            .match_query(mockito::Matcher::Any)
+            .with_status(404)
+            .with_header("content-type", "application/xml")
+            .with_body(
+                "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+            )
+            .create();
+
+        let Err(VesperError::ExecutionError(message)) = run(S3_SECURITY, &server.url()) else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("NoSuchKey"), "{}", message);
+    }
+
+    #[test]
+    fn test_s3_requires_capability() {
+        let result = run("", "http://127.0.0.1:1");
+        assert!(matches!(result, Err(VesperError::SecurityViolation(_))));
+    }
+}
@@ -0,0 +1,210 @@
+//! Sagas: sequential steps with compensating actions
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// One forward action of a saga and the action undoing it
+#[derive(Debug, Deserialize)]
+struct SagaStage {
+    /// Forward action
+    step: FlowStep,
+    /// Compensating action, if the forward action needs undoing
+    #[serde(default)]
+    compensate: Option<FlowStep>,
+}
+
+impl SemanticExecutor {
+    /// Execute a saga step
+    ///
+    /// Runs the `step` of each `{step, compensate}` entry in
+    /// `parameters["steps"]` in order and returns their results keyed by
+    /// output variable (or step name). If a step fails, the compensations
+    /// of the steps before it run in reverse order; a failing compensation
+    /// is logged and skipped. The saga then fails with the original error.
+    pub(super) fn execute_saga(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let stages: Vec<SagaStage> = match step.parameters.get("steps") {
+            Some(steps @ serde_yaml::Value::Sequence(_)) => serde_yaml::from_value(steps.clone())?,
+            _ => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Step {} needs a sequence of saga steps",
+                    step.step
+                )))
+            }
+        };
+
+        let mut results = HashMap::new();
+        for (index, stage) in stages.iter().enumerate() {
+            match self.execute_step(&stage.step, ctx) {
+                Ok(value) => {
+                    let name = stage
+                        .step
+                        .output
+                        .clone()
+                        .unwrap_or_else(|| stage.step.step.clone());
+                    results.insert(name, value);
+                }
+                Err(error) => {
+                    let compensations = self.compensate(&stages[..index], ctx);
+                    let failed = compensations.iter().filter(|c| c.is_err()).count();
+                    return Err(VesperError::ExecutionError(format!(
+                        "Saga step {} failed: {} (ran {} compensations, {} failed)",
+                        stage.step.step,
+                        error,
+                        compensations.len(),
+                        failed
+                    )));
+                }
+            }
+        }
+
+        let result = Value::Object(results);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Run the compensations of `completed` stages, last first
+    fn compensate(
+        &self,
+        completed: &[SagaStage],
+        ctx: &mut ExecutionContext,
+    ) -> Vec<Result<Value>> {
+        completed
+            .iter()
+            .rev()
+            .filter_map(|stage| stage.compensate.as_ref().map(|c| (stage, c)))
+            .map(|(stage, compensation)| {
+                let outcome = self.execute_step(compensation, ctx);
+                if let Err(e) = &outcome {
+                    tracing::warn!(
+                        "Compensation for saga step {} failed: {}",
+                        stage.step.step,
+                        e
+                    );
+                }
+                outcome
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseBackend;
+    use crate::loader::VesperLoader;
+    use std::sync::{Arc, Mutex};
+
+    /// Records statements, failing those containing `FAIL`
+    #[derive(Default)]
+    struct Ledger {
+        statements: Mutex<Vec<String>>,
+    }
+
+    impl DatabaseBackend for Ledger {
+        fn query(
+            &self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> std::result::Result<Vec<HashMap<String, Value>>, String> {
+            Ok(Vec::new())
+        }
+
+        fn execute(&self, sql: &str, _params: Vec<Value>) -> std::result::Result<u64, String> {
+            if sql.contains("FAIL") {
+                return Err(format!("rejected: {}", sql));
+            }
+            self.statements.lock().unwrap().push(sql.to_string());
+            Ok(1)
+        }
+    }
+
+    fn run(ship_sql: &str) -> (Result<Value>, Vec<String>) {
+        let yaml = format!(
+            r#"
+node_id: order_v1
+type: function
+intent: place an order across services
+
+inputs: {{}}
+
+flow:
+  - step: place
+    operation: saga
+    parameters:
+      steps:
+        - step:
+            step: reserve
+            operation: database_execute
+            parameters:
+              sql: reserve stock
+          compensate:
+            operation: database_execute
+            parameters:
+              sql: release stock
+        - step:
+            step: charge
+            operation: database_execute
+            parameters:
+              sql: charge card
+          compensate:
+            operation: database_execute
+            parameters:
+              sql: FAIL refund card
+        - step:
+            step: ship
+            operation: database_execute
+            parameters:
+              sql: "{}"
+          compensate:
+            operation: database_execute
+            parameters:
+              sql: cancel shipment
+"#,
+            ship_sql
+        );
+        let ledger = Arc::new(Ledger::default());
+        let mut executor = SemanticExecutor::new().with_database(ledger.clone());
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let result = executor
+            .execute("order_v1", HashMap::new())
+            .map(|r| r.data.unwrap());
+        let statements = ledger.statements.lock().unwrap().clone();
+        (result, statements)
+    }
+
+    #[test]
+    fn test_saga_completes() {
+        let (result, statements) = run("ship order");
+        let Ok(Value::Object(results)) = result else {
+            panic!("expected object result");
+        };
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            statements,
+            vec!["reserve stock", "charge card", "ship order"]
+        );
+    }
+
+    #[test]
+    fn test_saga_compensates_in_reverse() {
+        let (result, statements) = run("FAIL ship order");
+        let Err(VesperError::ExecutionError(message)) = result else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("ship"), "{}", message);
+        assert!(message.contains("1 failed"), "{}", message);
+        // The refund compensation fails but the release still runs
+        assert_eq!(
+            statements,
+            vec!["reserve stock", "charge card", "release stock"]
+        );
+    }
+}
@@ -0,0 +1,442 @@
+//! Validation operations: JSON Schema and node input specs
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, OutputField, Value, VesperNode, BUILTIN_TYPES};
+use jsonschema::JSONSchema;
+
+impl SemanticExecutor {
+    /// Execute a schema validation step
+    ///
+    /// Validates the value named by `parameters["value"]` against
+    /// `parameters["schema"]`, which is either an inline schema or a path
+    /// to a YAML/JSON schema file. With `collect_errors: true` every
+    /// violation is reported instead of only the first.
+    pub(super) fn execute_schema_validate(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let schema = match step.parameters.get("schema") {
+            Some(serde_yaml::Value::String(path)) => {
+                let content = std::fs::read_to_string(path)?;
+                serde_yaml::from_str::<serde_json::Value>(&content)?
+            }
+            Some(inline) => serde_json::to_value(inline)?,
+            None => {
+                return Err(VesperError::ExecutionError(
+                    "Schema validation step missing schema".to_string(),
+                ))
+            }
+        };
+
+        let compiled = JSONSchema::compile(&schema)
+            .map_err(|e| VesperError::ExecutionError(format!("Invalid schema: {}", e)))?;
+
+        let instance =
+            serde_json::Value::from(&self.resolve_parameter_variable(step, "value", ctx)?);
+        let collect_errors = step
+            .parameters
+            .get("collect_errors")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if let Err(errors) = compiled.validate(&instance) {
+            let mut messages = errors.map(|e| format!("{}: {}", e.instance_path, e));
+            if collect_errors {
+                return Err(VesperError::MultipleErrors(messages.collect()));
+            }
+            let first = messages.next().unwrap_or_default();
+            return Err(VesperError::ExecutionError(format!(
+                "Schema validation failed at {}",
+                first
+            )));
+        }
+
+        Ok(Value::Bool(true))
+    }
+
+    /// Execute a lint step
+    ///
+    /// Validates the object named by `parameters["inputs"]` against the
+    /// input spec of node `parameters["node_id"]` without executing it,
+    /// failing with every violation.
+    pub(super) fn execute_lint_step(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let node_id = self.resolve_string_parameter(step, "node_id", ctx)?;
+        let node =
+            self.state.nodes.get(&node_id).ok_or_else(|| {
+                VesperError::ExecutionError(format!("Node not found: {}", node_id))
+            })?;
+        let inputs = match self.resolve_parameter_variable(step, "inputs", ctx)? {
+            Value::Object(inputs) => inputs,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "object".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        self.validate_inputs(node, &inputs).map_err(|e| {
+            let messages = match e {
+                VesperError::MultipleErrors(messages) => messages.join("; "),
+                other => other.to_string(),
+            };
+            VesperError::ExecutionError(format!("Inputs for {} are invalid: {}", node_id, messages))
+        })?;
+
+        let result = Value::Bool(true);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a schema assertion step
+    ///
+    /// Checks the object named by `parameters["value"]` (by default the
+    /// `_output` of an output transform) against the executing node's
+    /// `outputs.success` field types, failing with every mismatch.
+    pub(super) fn execute_assert_schema(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let node = self.state.nodes.get(ctx.node_id()).ok_or_else(|| {
+            VesperError::ExecutionError(format!("Node not found: {}", ctx.node_id()))
+        })?;
+        let value = match step.parameters.get("value") {
+            Some(_) => self.resolve_parameter_variable(step, "value", ctx)?,
+            None => ctx.get(super::OUTPUT_VARIABLE).cloned().ok_or_else(|| {
+                VesperError::ExecutionError(format!(
+                    "Step {} has no value to check outside an output transform",
+                    step.step
+                ))
+            })?,
+        };
+        let Value::Object(fields) = &value else {
+            return Err(VesperError::TypeError {
+                expected: "object".to_string(),
+                actual: format!("{:?}", value),
+            });
+        };
+
+        let mut specs: Vec<(&String, &OutputField)> = node
+            .outputs
+            .iter()
+            .flat_map(|outputs| &outputs.success)
+            .collect();
+        specs.sort_by(|a, b| a.0.cmp(b.0));
+        let mismatches: Vec<String> = specs
+            .into_iter()
+            .filter_map(|(name, spec)| {
+                let output_type = spec.output_type.as_deref().unwrap_or("any");
+                match fields.get(name) {
+                    None => Some(format!("missing field {}", name)),
+                    Some(field) if !type_matches(node, output_type, &spec.values, field) => {
+                        Some(format!("field {} is not {}", name, output_type))
+                    }
+                    Some(_) => None,
+                }
+            })
+            .collect();
+        if !mismatches.is_empty() {
+            return Err(VesperError::ExecutionError(format!(
+                "Output of {} does not match its spec: {}",
+                node.node_id,
+                mismatches.join("; ")
+            )));
+        }
+
+        let result = Value::Bool(true);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Whether `value` is of spec type `type_name`, restricted to `values` for
+/// enums
+///
+/// Custom types of `node` match any object; unrecognized types match
+/// anything.
+fn type_matches(node: &VesperNode, type_name: &str, values: &[String], value: &Value) -> bool {
+    if let Some(element) = type_name
+        .strip_prefix("array<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        return matches!(value, Value::Array(items)
+            if items.iter().all(|item| type_matches(node, element.trim(), &[], item)));
+    }
+    match (type_name, value) {
+        ("any", _)
+        | ("string" | "bytes", Value::String(_))
+        | ("timestamp", Value::String(_) | Value::Int(_))
+        | ("integer", Value::Int(_))
+        | ("number" | "float" | "decimal", Value::Int(_) | Value::Float(_))
+        | ("boolean", Value::Bool(_))
+        | ("array", Value::Array(_))
+        | ("object", Value::Object(_)) => true,
+        ("decimal", Value::String(s)) => s.parse::<f64>().is_ok(),
+        ("enum", Value::String(s)) => values.is_empty() || values.iter().any(|v| v == s.as_str()),
+        (name, value) => {
+            !BUILTIN_TYPES.contains(&name)
+                && (!node.types.contains_key(name) || matches!(value, Value::Object(_)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn executor(collect_errors: bool) -> SemanticExecutor {
+        let yaml = format!(
+            r#"
+node_id: check_v1
+type: function
+intent: validate a payload
+
+inputs:
+  payload:
+    type: object
+
+flow:
+  - step: check
+    operation: schema_validate
+    parameters:
+      value: payload
+      collect_errors: {}
+      schema:
+        type: object
+        required: [id, email]
+        properties:
+          id:
+            type: integer
+          email:
+            type: string
+"#,
+            collect_errors
+        );
+        let node = VesperLoader::new().load_string(&yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+        executor
+    }
+
+    fn payload(json: serde_json::Value) -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        inputs.insert("payload".to_string(), Value::from(json));
+        inputs
+    }
+
+    #[test]
+    fn test_schema_validate_success() {
+        let result = executor(false)
+            .execute(
+                "check_v1",
+                payload(serde_json::json!({"id": 1, "email": "a@b.c"})),
+            )
+            .unwrap();
+        assert_eq!(result.data, Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_schema_validate_collects_errors() {
+        let invalid = serde_json::json!({"id": "one"});
+
+        let first = executor(false).execute("check_v1", payload(invalid.clone()));
+        assert!(matches!(first, Err(VesperError::ExecutionError(_))));
+
+        match executor(true).execute("check_v1", payload(invalid)) {
+            Err(VesperError::MultipleErrors(errors)) => assert_eq!(errors.len(), 2),
+            other => panic!("expected multiple errors, got {:?}", other),
+        }
+    }
+
+    const TRANSFER: &str = r#"
+node_id: transfer_v1
+type: function
+intent: move money between accounts
+
+inputs:
+  from:
+    type: string
+  to:
+    type: string
+  amount:
+    type: number
+  memo:
+    type: string
+    required: false
+
+flow: []
+"#;
+
+    const PREFLIGHT: &str = r#"
+node_id: preflight_v1
+type: function
+intent: check a transfer request before queueing it
+
+inputs:
+  request:
+    type: object
+
+flow:
+  - step: check
+    operation: lint_step
+    parameters:
+      node_id: transfer_v1
+      inputs: request
+    output: valid
+  - step: done
+    operation: return
+    return_success:
+      valid: "{valid}"
+"#;
+
+    #[test]
+    fn test_lint_step() {
+        let loader = VesperLoader::new();
+        let mut executor = SemanticExecutor::new();
+        executor.register(loader.load_string(TRANSFER).unwrap());
+        executor.register(loader.load_string(PREFLIGHT).unwrap());
+        let run = |request: serde_json::Value| {
+            let mut inputs = HashMap::new();
+            inputs.insert("request".to_string(), Value::from(request));
+            executor.execute("preflight_v1", inputs)
+        };
+
+        let valid = run(serde_json::json!({"from": "a", "to": "b", "amount": 5})).unwrap();
+        let mut expected = HashMap::new();
+        expected.insert("valid".to_string(), Value::Bool(true));
+        assert_eq!(valid.data, Some(Value::Object(expected)));
+
+        match run(serde_json::json!({"to": "b"})) {
+            Err(VesperError::ExecutionError(message)) => {
+                assert!(message.contains("amount"), "{}", message);
+                assert!(message.contains("from"), "{}", message);
+                assert!(!message.contains("memo"), "{}", message);
+            }
+            other => panic!("expected execution error, got {:?}", other),
+        }
+    }
+
+    const ORDER: &str = r#"
+node_id: order_v1
+type: function
+intent: place an order
+
+inputs:
+  order:
+    type: object
+
+outputs:
+  success:
+    order_id:
+      type: string
+    total:
+      type: decimal
+    status:
+      type: enum
+      values: [placed, held]
+    lines:
+      type: array<integer>
+    customer:
+      type: Customer
+
+types:
+  Customer:
+    fields:
+      id: string
+
+flow:
+  - step: check
+    operation: assert_schema
+    parameters:
+      value: order
+"#;
+
+    #[test]
+    fn test_assert_schema() {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(ORDER).unwrap());
+        let run = |order: serde_json::Value| {
+            let mut inputs = HashMap::new();
+            inputs.insert("order".to_string(), Value::from(order));
+            executor.execute("order_v1", inputs)
+        };
+
+        let valid = serde_json::json!({
+            "order_id": "o-1",
+            "total": 12.5,
+            "status": "placed",
+            "lines": [1, 2],
+            "customer": {"id": "c-1"},
+        });
+        assert_eq!(run(valid).unwrap().data, Some(Value::Bool(true)));
+
+        let invalid = serde_json::json!({
+            "order_id": 1,
+            "total": "12.50",
+            "status": "shipped",
+            "lines": [1, "two"],
+        });
+        match run(invalid) {
+            Err(VesperError::ExecutionError(message)) => assert!(
+                message.ends_with(
+                    "missing field customer; field lines is not array<integer>; \
+                     field order_id is not string; field status is not enum"
+                ),
+                "{}",
+                message
+            ),
+            other => panic!("expected execution error, got {:?}", other),
+        }
+    }
+
+    const STATUS: &str = r#"
+node_id: status_v1
+type: function
+intent: report a status
+
+inputs:
+  state:
+    type: string
+
+outputs:
+  success:
+    status:
+      type: enum
+      values: [up, down]
+
+flow:
+  - step: report
+    operation: return
+    return_success:
+      status: "{state}"
+
+output_transform:
+  - step: check
+    operation: assert_schema
+"#;
+
+    #[test]
+    fn test_assert_schema_checks_output() {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(STATUS).unwrap());
+        let run = |state: &str| {
+            let mut inputs = HashMap::new();
+            inputs.insert("state".to_string(), Value::from(state));
+            executor.execute("status_v1", inputs)
+        };
+
+        let mut expected = HashMap::new();
+        expected.insert("status".to_string(), Value::from("up"));
+        assert_eq!(run("up").unwrap().data, Some(Value::Object(expected)));
+        assert!(run("sideways").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! Secret retrieval backed by the executor's `SecretsManager`
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a secret lookup step
+    ///
+    /// Fetches the secret `parameters["name"]` and stores it in the step's
+    /// output variable, which is marked secret so the value is masked
+    /// whenever the context is printed. Errors name the secret, never its
+    /// value.
+    pub(super) fn execute_secrets_manager_get(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let name = self.resolve_string_parameter(step, "name", ctx)?;
+        let manager = self.state.secrets_manager.as_deref().ok_or_else(|| {
+            VesperError::ExecutionError("No secrets manager configured".to_string())
+        })?;
+
+        tracing::debug!("Fetching secret: {}", name);
+        let secret = manager.get(&name).map_err(|e| {
+            VesperError::ExecutionError(format!("Secret {} unavailable: {}", name, e))
+        })?;
+
+        if let Some(output) = &step.output {
+            ctx.mark_secret(output);
+        }
+        let result = Value::from(secret);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use crate::secrets::SecretsManager;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    struct FixedSecrets;
+
+    impl SecretsManager for FixedSecrets {
+        fn get(&self, name: &str) -> std::result::Result<String, String> {
+            match name {
+                "db_password" => Ok("hunter2".to_string()),
+                _ => Err("not found".to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_secrets_manager_get_masks_value() {
+        let yaml = r#"
+node_id: connect_v1
+type: function
+intent: fetch database credentials
+
+inputs: {}
+
+flow:
+  - step: password
+    operation: secrets_manager_get
+    parameters:
+      name: db_password
+    output: password
+"#;
+        let node = VesperLoader::new().load_string(yaml).unwrap();
+        let executor = SemanticExecutor::new().with_secrets_manager(Arc::new(FixedSecrets));
+
+        let mut ctx = ExecutionContext::new(HashMap::new());
+        let result = executor.execute_step(&node.flow[0], &mut ctx).unwrap();
+        assert_eq!(result, Value::from("hunter2"));
+        assert_eq!(ctx.get("password"), Some(&Value::from("hunter2")));
+
+        let printed = format!("{:?}", ctx);
+        assert!(!printed.contains("hunter2"), "{}", printed);
+        assert!(printed.contains("***"));
+    }
+
+    #[test]
+    fn test_secrets_manager_get_unknown_secret() {
+        let yaml = r#"
+node_id: connect_v1
+type: function
+intent: fetch database credentials
+
+inputs: {}
+
+flow:
+  - step: password
+    operation: secrets_manager_get
+    parameters:
+      name: api_token
+"#;
+        let mut executor = SemanticExecutor::new().with_secrets_manager(Arc::new(FixedSecrets));
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let result = executor.execute("connect_v1", HashMap::new());
+        assert!(matches!(result, Err(VesperError::ExecutionError(_))));
+    }
+}
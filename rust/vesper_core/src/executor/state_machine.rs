@@ -0,0 +1,172 @@
+//! Persistence of `state_machine` nodes between executions
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{NodeType, Value, VesperNode};
+use std::collections::HashMap;
+
+/// Input identifying the workflow a state machine execution belongs to
+const WORKFLOW_ID_INPUT: &str = "workflow_id";
+
+/// Variable holding the current state of a workflow
+const STATE_VARIABLE: &str = "state";
+
+impl SemanticExecutor {
+    /// Bind the saved state and variables of the workflow `ctx` belongs to
+    ///
+    /// Only applies to `state_machine` nodes when a `WorkflowStateBackend`
+    /// is installed, returning the workflow ID to save under afterwards.
+    /// The saved state is bound as the `state` variable, overriding any
+    /// `state` input, which only sets the initial state of a new workflow.
+    pub(super) fn restore_workflow_state(
+        &self,
+        node: &VesperNode,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Option<String>> {
+        let Some(backend) = &self.state.workflow_state else {
+            return Ok(None);
+        };
+        if node.node_type != NodeType::StateMachine {
+            return Ok(None);
+        }
+        let workflow_id = match ctx.inputs.get(WORKFLOW_ID_INPUT) {
+            Some(Value::String(id)) => id.to_string(),
+            Some(Value::Int(id)) => id.to_string(),
+            _ => {
+                return Err(VesperError::MissingInput(WORKFLOW_ID_INPUT.to_string()));
+            }
+        };
+
+        if let Some((state, variables)) = backend.load(&workflow_id) {
+            tracing::debug!("Resuming workflow {} in state {}", workflow_id, state);
+            for (name, value) in variables {
+                ctx.set(name, value);
+            }
+            ctx.set(STATE_VARIABLE.to_string(), Value::from(state.as_str()));
+        }
+        Ok(Some(workflow_id))
+    }
+
+    /// Save the state a workflow's execution finished in, with its
+    /// variables other than secrets
+    pub(super) fn save_workflow_state(
+        &self,
+        workflow_id: &str,
+        ctx: &ExecutionContext,
+    ) -> Result<()> {
+        let Some(backend) = &self.state.workflow_state else {
+            return Ok(());
+        };
+        let state = match ctx.get(STATE_VARIABLE) {
+            Some(Value::String(state)) => state.to_string(),
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string state".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+        let variables: HashMap<String, Value> = ctx
+            .variables
+            .iter()
+            .filter(|(name, _)| name.as_str() != STATE_VARIABLE && !ctx.is_secret(name))
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        backend.save(workflow_id, &state, &variables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use crate::workflow_state::{FileWorkflowStateBackend, WorkflowStateBackend};
+    use std::sync::Arc;
+
+    const ORDER: &str = r#"
+node_id: order_v1
+type: state_machine
+intent: move an order through payment and shipping
+
+inputs:
+  workflow_id:
+    type: string
+  event:
+    type: string
+  state:
+    type: string
+    required: false
+
+flow:
+  - step: pay
+    operation: conditional
+    condition: "event == 'pay'"
+    then:
+      - operation: conditional
+        condition: "state == 'pending'"
+        then:
+          - operation: string_template
+            template: "paid"
+            output: state
+          - operation: string_template
+            template: "{event}"
+            output: last_event
+    else:
+      - operation: conditional
+        condition: "state == 'paid'"
+        then:
+          - operation: string_template
+            template: "shipped"
+            output: state
+  - step: report
+    operation: return
+    return_success:
+      state: "{state}"
+"#;
+
+    fn executor(directory: &std::path::Path) -> SemanticExecutor {
+        let backend = Arc::new(FileWorkflowStateBackend::new(directory));
+        let mut executor = SemanticExecutor::new().with_workflow_state(backend);
+        executor.register(VesperLoader::new().load_string(ORDER).unwrap());
+        executor
+    }
+
+    fn send(executor: &SemanticExecutor, event: &str, state: Option<&str>) -> Value {
+        let mut inputs = HashMap::from([
+            ("workflow_id".to_string(), Value::from("order-7")),
+            ("event".to_string(), Value::from(event)),
+        ]);
+        if let Some(state) = state {
+            inputs.insert("state".to_string(), Value::from(state));
+        }
+        let Some(Value::Object(result)) = executor.execute("order_v1", inputs).unwrap().data else {
+            panic!("expected object result");
+        };
+        result["state"].clone()
+    }
+
+    #[test]
+    fn test_state_machine_resumes_after_restart() {
+        let directory = std::env::temp_dir().join(format!("vesper-{}", uuid::Uuid::new_v4()));
+
+        let first = executor(&directory);
+        assert_eq!(send(&first, "pay", Some("pending")), Value::from("paid"));
+        drop(first);
+
+        let backend = FileWorkflowStateBackend::new(&directory);
+        assert_eq!(backend.list_workflows(), vec!["order-7"]);
+        let (state, variables) = backend.load("order-7").unwrap();
+        assert_eq!(state, "paid");
+        assert_eq!(variables["last_event"], Value::from("pay"));
+
+        let restarted = executor(&directory);
+        assert_eq!(
+            send(&restarted, "ship", Some("pending")),
+            Value::from("shipped")
+        );
+        assert_eq!(send(&restarted, "pay", None), Value::from("shipped"));
+        assert_eq!(backend.load("order-7").unwrap().0, "shipped");
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
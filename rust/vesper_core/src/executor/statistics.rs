@@ -0,0 +1,285 @@
+//! Statistical summary operation
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+
+/// Metrics computed when a step does not restrict them via `compute`
+const ALL_METRICS: &[&str] = &[
+    "mean", "median", "mode", "variance", "stddev", "min", "max", "p50", "p90", "p95", "p99",
+];
+
+impl SemanticExecutor {
+    /// Execute a statistics step
+    ///
+    /// Reads the numeric array named by `parameters["on"]` and returns an
+    /// object with one `Value::Float` per requested metric.
+    pub(super) fn execute_statistics(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let values = match self.resolve_parameter_variable(step, "on", ctx)? {
+            Value::Array(items) => items
+                .iter()
+                .map(|item| {
+                    item.as_float().ok_or_else(|| VesperError::TypeError {
+                        expected: "number".to_string(),
+                        actual: format!("{:?}", item),
+                    })
+                })
+                .collect::<Result<Vec<f64>>>()?,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "array".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        if values.is_empty() {
+            return Err(VesperError::ExecutionError(
+                "Statistics step requires a non-empty array".to_string(),
+            ));
+        }
+        if let Some(value) = values.iter().find(|v| !v.is_finite()) {
+            return Err(VesperError::ExecutionError(format!(
+                "Statistics step requires finite numbers, got {}",
+                value
+            )));
+        }
+
+        let metrics: Vec<String> = match step.parameters.get("compute") {
+            Some(serde_yaml::Value::Sequence(seq)) => seq
+                .iter()
+                .map(|m| {
+                    m.as_str().map(String::from).ok_or_else(|| {
+                        VesperError::ExecutionError(
+                            "Statistics compute entries must be strings".to_string(),
+                        )
+                    })
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => {
+                return Err(VesperError::ExecutionError(
+                    "Statistics compute parameter must be a sequence".to_string(),
+                ))
+            }
+            None => ALL_METRICS.iter().map(|m| m.to_string()).collect(),
+        };
+
+        let summary = Summary::new(values);
+        let mut result = HashMap::new();
+        for metric in metrics {
+            let value = summary.compute(&metric)?;
+            result.insert(metric, Value::Float(value));
+        }
+
+        let result = Value::Object(result);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Sorted sample with lazily computed metrics
+pub(super) struct Summary {
+    sorted: Vec<f64>,
+}
+
+impl Summary {
+    pub(super) fn new(mut values: Vec<f64>) -> Self {
+        values.sort_by(|a, b| a.total_cmp(b));
+        Self { sorted: values }
+    }
+
+    pub(super) fn compute(&self, metric: &str) -> Result<f64> {
+        Ok(match metric {
+            "mean" => self.mean(),
+            "median" | "p50" => self.quantile(0.5),
+            "mode" => self.mode(),
+            "variance" => self.variance(),
+            "stddev" => self.variance().sqrt(),
+            "min" => self.sorted[0],
+            "max" => self.sorted[self.sorted.len() - 1],
+            "p90" => self.quantile(0.9),
+            "p95" => self.quantile(0.95),
+            "p99" => self.quantile(0.99),
+            _ => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Unknown statistics metric: {}",
+                    metric
+                )))
+            }
+        })
+    }
+
+    fn mean(&self) -> f64 {
+        self.sorted.iter().sum::<f64>() / self.sorted.len() as f64
+    }
+
+    /// Population variance
+    fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / self.sorted.len() as f64
+    }
+
+    /// Most frequent value; ties resolve to the smallest value
+    fn mode(&self) -> f64 {
+        let mut best = self.sorted[0];
+        let mut best_count = 0;
+        let mut i = 0;
+        while i < self.sorted.len() {
+            let mut j = i;
+            // `total_cmp` so a NaN, unequal to itself, still ends its run
+            while j < self.sorted.len() && self.sorted[j].total_cmp(&self.sorted[i]).is_eq() {
+                j += 1;
+            }
+            if j - i > best_count {
+                best = self.sorted[i];
+                best_count = j - i;
+            }
+            i = j;
+        }
+        best
+    }
+
+    /// Quantile using linear interpolation between closest ranks
+    fn quantile(&self, q: f64) -> f64 {
+        let rank = q * (self.sorted.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        let weight = rank - lower as f64;
+        self.sorted[lower] + (self.sorted[upper] - self.sorted[lower]) * weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    fn executor_for(yaml: &str) -> SemanticExecutor {
+        let node = VesperLoader::new().load_string(yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+        executor
+    }
+
+    #[test]
+    fn test_statistics_summary() {
+        let executor = executor_for(
+            r#"
+node_id: stats_v1
+type: function
+intent: summarize numbers
+
+inputs:
+  samples:
+    type: array
+
+flow:
+  - step: summarize
+    operation: statistics
+    parameters:
+      on: samples
+      compute: [mean, median, mode, variance, min, max, p90]
+    output: summary
+"#,
+        );
+
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "samples".to_string(),
+            Value::Array(vec![
+                Value::Int(2),
+                Value::Int(4),
+                Value::Int(4),
+                Value::Int(4),
+                Value::Int(5),
+                Value::Int(5),
+                Value::Int(7),
+                Value::Int(9),
+            ]),
+        );
+
+        let result = executor.execute("stats_v1", inputs).unwrap();
+        let Some(Value::Object(summary)) = result.data else {
+            panic!("expected object result");
+        };
+
+        assert_eq!(summary.len(), 7);
+        assert_eq!(summary["mean"], Value::Float(5.0));
+        assert_eq!(summary["median"], Value::Float(4.5));
+        assert_eq!(summary["mode"], Value::Float(4.0));
+        assert_eq!(summary["variance"], Value::Float(4.0));
+        assert_eq!(summary["min"], Value::Float(2.0));
+        assert_eq!(summary["max"], Value::Float(9.0));
+        assert!((summary["p90"].as_float().unwrap() - 7.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_statistics_empty_array() {
+        let executor = executor_for(
+            r#"
+node_id: stats_v1
+type: function
+intent: summarize numbers
+
+inputs:
+  samples:
+    type: array
+
+flow:
+  - step: summarize
+    operation: statistics
+    parameters:
+      on: samples
+"#,
+        );
+
+        let mut inputs = HashMap::new();
+        inputs.insert("samples".to_string(), Value::Array(vec![]));
+
+        assert!(executor.execute("stats_v1", inputs).is_err());
+    }
+
+    #[test]
+    fn test_statistics_rejects_nan() {
+        let executor = executor_for(
+            r#"
+node_id: stats_v1
+type: function
+intent: summarize numbers
+
+inputs:
+  samples:
+    type: array
+
+flow:
+  - step: summarize
+    operation: statistics
+    parameters:
+      on: samples
+      compute: [mode]
+"#,
+        );
+
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "samples".to_string(),
+            Value::Array(vec![Value::Float(1.0), Value::Float(f64::NAN)]),
+        );
+
+        let Err(VesperError::ExecutionError(message)) = executor.execute("stats_v1", inputs) else {
+            panic!("expected execution error");
+        };
+        assert!(message.contains("NaN"));
+    }
+
+    #[test]
+    fn test_mode_terminates_on_nan() {
+        let summary = Summary::new(vec![f64::NAN, 2.0, f64::NAN, 1.0]);
+        assert!(summary.mode().is_nan());
+    }
+}
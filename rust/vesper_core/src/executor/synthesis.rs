@@ -0,0 +1,119 @@
+//! Contract synthesis operation
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::contracts::synthesize_contracts;
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+
+impl SemanticExecutor {
+    /// Execute a contract synthesis step
+    ///
+    /// Reads the array named by `parameters["examples"]`, each an object
+    /// with `inputs` and `output` objects, and returns candidate
+    /// `preconditions` and `postconditions` holding for all of them (see
+    /// `synthesize_contracts`).
+    pub(super) fn execute_contract_synthesis(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let examples = match self.resolve_parameter_variable(step, "examples", ctx)? {
+            Value::Array(items) => items
+                .into_iter()
+                .map(|item| {
+                    let fields = |key: &str| match &item {
+                        Value::Object(example) => match example.get(key) {
+                            Some(Value::Object(fields)) => Ok(fields.clone()),
+                            _ => Err(VesperError::ExecutionError(format!(
+                                "Example is missing a {} object",
+                                key
+                            ))),
+                        },
+                        other => Err(VesperError::TypeError {
+                            expected: "object".to_string(),
+                            actual: format!("{:?}", other),
+                        }),
+                    };
+                    Ok((fields("inputs")?, fields("output")?))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "array".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        let contracts = synthesize_contracts(&examples);
+        let strings = |conditions: Vec<String>| {
+            Value::Array(conditions.into_iter().map(Value::from).collect())
+        };
+        let mut result = HashMap::new();
+        result.insert(
+            "preconditions".to_string(),
+            strings(contracts.preconditions),
+        );
+        result.insert(
+            "postconditions".to_string(),
+            strings(contracts.postconditions),
+        );
+
+        let result = Value::Object(result);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    const SYNTHESIZE: &str = r#"
+node_id: synthesize_v1
+type: function
+intent: suggest contracts for a fee calculation
+
+inputs:
+  examples:
+    type: array
+
+flow:
+  - step: suggest
+    operation: contract_synthesis
+    parameters:
+      examples: examples
+"#;
+
+    #[test]
+    fn test_contract_synthesis() {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(SYNTHESIZE).unwrap());
+        let examples = serde_json::json!([
+            {"inputs": {"amount": 100, "fee": 0, "currency": "EUR"},
+             "output": {"result": 100, "status": "ok"}},
+            {"inputs": {"amount": 20, "fee": 5, "currency": "USD"},
+             "output": {"result": 25, "status": "ok"}},
+            {"inputs": {"amount": 7, "fee": 3, "currency": "EUR"},
+             "output": {"result": 10, "status": "held"}},
+        ]);
+        let mut inputs = HashMap::new();
+        inputs.insert("examples".to_string(), Value::from(examples));
+
+        let result = executor.execute("synthesize_v1", inputs).unwrap().data;
+        assert_eq!(
+            result.as_ref().map(serde_json::Value::from),
+            Some(serde_json::json!({
+                "preconditions": ["amount > 0", "currency != ''", "fee >= 0"],
+                "postconditions": [
+                    "result > 0",
+                    "result >= amount",
+                    "result >= fee",
+                    "status != ''",
+                ],
+            }))
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! CSV parsing and serialization operations
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+#[cfg(feature = "csv")]
+use crate::types::IntoString;
+use crate::types::{FlowStep, Value};
+
+impl SemanticExecutor {
+    /// Execute a CSV parse step
+    ///
+    /// Parses the CSV string named by `parameters["on"]`. With
+    /// `has_headers: true` each row becomes an object keyed by column name,
+    /// otherwise each row is an array of strings.
+    #[cfg(feature = "csv")]
+    pub(super) fn execute_csv_parse(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let source = match self.resolve_parameter_variable(step, "on", ctx)? {
+            Value::String(s) => s.into_string(),
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        let options = CsvOptions::from_step(step)?;
+        let mut reader = ::csv::ReaderBuilder::new()
+            .delimiter(options.delimiter)
+            .has_headers(options.has_headers)
+            .from_reader(source.as_bytes());
+
+        let headers: Option<Vec<String>> = if options.has_headers {
+            Some(
+                reader
+                    .headers()
+                    .map_err(csv_error)?
+                    .iter()
+                    .map(String::from)
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(csv_error)?;
+            let row = match &headers {
+                Some(headers) => Value::Object(
+                    headers
+                        .iter()
+                        .zip(record.iter())
+                        .map(|(h, v)| (h.clone(), Value::from(v)))
+                        .collect(),
+                ),
+                None => Value::Array(record.iter().map(Value::from).collect()),
+            };
+            rows.push(row);
+        }
+
+        let result = Value::Array(rows);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a CSV stringify step
+    ///
+    /// Serializes the array of rows named by `parameters["on"]`. Object
+    /// rows are written in `parameters["columns"]` order (sorted keys of
+    /// the first row by default), preceded by a header row unless
+    /// `has_headers: false`.
+    #[cfg(feature = "csv")]
+    pub(super) fn execute_csv_stringify(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let rows = match self.resolve_parameter_variable(step, "on", ctx)? {
+            Value::Array(rows) => rows,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "array".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        let options = CsvOptions::from_step(step)?;
+        let mut writer = ::csv::WriterBuilder::new()
+            .delimiter(options.delimiter)
+            .from_writer(Vec::new());
+
+        let columns: Option<Vec<String>> = match (step.parameters.get("columns"), rows.first()) {
+            (Some(serde_yaml::Value::Sequence(cols)), _) => Some(
+                cols.iter()
+                    .filter_map(|c| c.as_str().map(String::from))
+                    .collect(),
+            ),
+            (_, Some(Value::Object(first))) => {
+                let mut keys: Vec<String> = first.keys().cloned().collect();
+                keys.sort();
+                Some(keys)
+            }
+            _ => None,
+        };
+
+        if let (Some(columns), true) = (&columns, options.has_headers) {
+            writer.write_record(columns).map_err(csv_error)?;
+        }
+
+        for row in &rows {
+            let fields: Vec<String> = match (row, &columns) {
+                (Value::Object(map), Some(columns)) => columns
+                    .iter()
+                    .map(|c| map.get(c).map(field_text).unwrap_or_default())
+                    .collect(),
+                (Value::Array(items), _) => items.iter().map(field_text).collect(),
+                (other, _) => {
+                    return Err(VesperError::TypeError {
+                        expected: "object or array row".to_string(),
+                        actual: format!("{:?}", other),
+                    })
+                }
+            };
+            writer.write_record(&fields).map_err(csv_error)?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| VesperError::ExecutionError(format!("CSV error: {}", e)))?;
+        let result = Value::from(
+            String::from_utf8(bytes)
+                .map_err(|e| VesperError::ExecutionError(format!("CSV error: {}", e)))?,
+        );
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a CSV parse step (feature disabled)
+    #[cfg(not(feature = "csv"))]
+    pub(super) fn execute_csv_parse(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "csv_parse operation requires the `csv` feature".to_string(),
+        ))
+    }
+
+    /// Execute a CSV stringify step (feature disabled)
+    #[cfg(not(feature = "csv"))]
+    pub(super) fn execute_csv_stringify(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "csv_stringify operation requires the `csv` feature".to_string(),
+        ))
+    }
+}
+
+/// Step-level CSV dialect
+#[cfg(feature = "csv")]
+struct CsvOptions {
+    delimiter: u8,
+    has_headers: bool,
+}
+
+#[cfg(feature = "csv")]
+impl CsvOptions {
+    fn from_step(step: &FlowStep) -> Result<Self> {
+        let delimiter = match step.parameters.get("delimiter").and_then(|v| v.as_str()) {
+            Some(d) if d.len() == 1 => d.as_bytes()[0],
+            Some(d) => {
+                return Err(VesperError::ExecutionError(format!(
+                    "CSV delimiter must be a single byte: {:?}",
+                    d
+                )))
+            }
+            None => b',',
+        };
+        let has_headers = step
+            .parameters
+            .get("has_headers")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        Ok(Self {
+            delimiter,
+            has_headers,
+        })
+    }
+}
+
+#[cfg(feature = "csv")]
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_string(),
+        Value::Int(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => String::new(),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(feature = "csv")]
+fn csv_error(e: ::csv::Error) -> VesperError {
+    VesperError::ExecutionError(format!("CSV error: {}", e))
+}
+
+#[cfg(all(test, feature = "csv"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    const DATASET: &str = "city,note,population\n\
+Amsterdam,\"canals, bikes\",921402\n\
+Berlin,,3850809\n\
+\"Den Haag\",\"says \"\"hoi\"\"\",552995\n\
+Utrecht,cathedral,367984\n\
+Zwolle,,130592\n";
+
+    fn run(operation: &str, extra: &str, on: Value) -> Value {
+        let yaml = format!(
+            r#"
+node_id: csv_v1
+type: function
+intent: csv handling
+
+inputs:
+  data:
+    type: string
+
+flow:
+  - step: convert
+    operation: {}
+    parameters:
+      on: data
+      {}
+"#,
+            operation, extra
+        );
+        let node = VesperLoader::new().load_string(&yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+
+        let mut inputs = HashMap::new();
+        inputs.insert("data".to_string(), on);
+        executor.execute("csv_v1", inputs).unwrap().data.unwrap()
+    }
+
+    #[test]
+    fn test_csv_parse_with_headers() {
+        let Value::Array(rows) = run("csv_parse", "has_headers: true", Value::from(DATASET)) else {
+            panic!("expected array");
+        };
+        assert_eq!(rows.len(), 5);
+
+        let Value::Object(first) = &rows[0] else {
+            panic!("expected object row");
+        };
+        assert_eq!(first["note"], Value::from("canals, bikes"));
+
+        let Value::Object(third) = &rows[2] else {
+            panic!("expected object row");
+        };
+        assert_eq!(third["city"], Value::from("Den Haag"));
+        assert_eq!(third["note"], Value::from("says \"hoi\""));
+
+        let Value::Object(second) = &rows[1] else {
+            panic!("expected object row");
+        };
+        assert_eq!(second["note"], Value::from(""));
+    }
+
+    #[test]
+    fn test_csv_parse_without_headers() {
+        let Value::Array(rows) = run(
+            "csv_parse",
+            "has_headers: false\n      delimiter: \";\"",
+            Value::from("a;b\n1;2\n"),
+        ) else {
+            panic!("expected array");
+        };
+        assert_eq!(
+            rows[1],
+            Value::Array(vec![Value::from("1"), Value::from("2")])
+        );
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let parsed = run("csv_parse", "has_headers: true", Value::from(DATASET));
+        let written = run("csv_stringify", "has_headers: true", parsed.clone());
+        let reparsed = run("csv_parse", "has_headers: true", written);
+        assert_eq!(parsed, reparsed);
+    }
+}
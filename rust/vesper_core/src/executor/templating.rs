@@ -0,0 +1,496 @@
+//! Template engine operations
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Piece of a `string_template` template
+#[derive(Debug, Clone, PartialEq)]
+enum Fragment {
+    Literal(String),
+    /// `{name}` placeholder, kept verbatim if no input is named `name`
+    Input(String),
+}
+
+/// `string_template` template split into literals and placeholders
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct CompiledTemplate {
+    fragments: Vec<Fragment>,
+}
+
+impl CompiledTemplate {
+    /// Split a template at its `{name}` placeholders
+    pub(super) fn parse(template: &str) -> Self {
+        let mut fragments = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open + 1..].find(['{', '}']).map(|i| open + 1 + i) else {
+                break;
+            };
+            if &rest[close..=close] == "{" || close == open + 1 {
+                // Not a placeholder; keep scanning after this brace
+                fragments.push(Fragment::Literal(rest[..close].to_string()));
+                rest = &rest[close..];
+                continue;
+            }
+            if open > 0 {
+                fragments.push(Fragment::Literal(rest[..open].to_string()));
+            }
+            fragments.push(Fragment::Input(rest[open + 1..close].to_string()));
+            rest = &rest[close + 1..];
+        }
+        if !rest.is_empty() {
+            fragments.push(Fragment::Literal(rest.to_string()));
+        }
+        Self { fragments }
+    }
+
+    /// Substitute inputs for the placeholders
+    pub(super) fn render(&self, inputs: &HashMap<String, Value>) -> String {
+        let mut result = String::new();
+        for fragment in &self.fragments {
+            match fragment {
+                Fragment::Literal(text) => result.push_str(text),
+                Fragment::Input(name) => match inputs.get(name) {
+                    Some(Value::String(s)) => result.push_str(s),
+                    Some(Value::Int(i)) => result.push_str(&i.to_string()),
+                    Some(Value::Float(f)) => result.push_str(&f.to_string()),
+                    Some(Value::Bool(b)) => result.push_str(&b.to_string()),
+                    Some(value) => result.push_str(&format!("{:?}", value)),
+                    None => {
+                        result.push('{');
+                        result.push_str(name);
+                        result.push('}');
+                    }
+                },
+            }
+        }
+        result
+    }
+}
+
+/// Compiled `string_template` templates keyed by template text
+pub(super) type TemplateCache = Arc<RwLock<HashMap<String, CompiledTemplate>>>;
+
+impl SemanticExecutor {
+    /// Render a `string_template` template, compiling it on first use
+    pub(super) fn render_template(
+        &self,
+        template: &str,
+        inputs: &HashMap<String, Value>,
+    ) -> String {
+        if let Some(compiled) = self.state.templates.read().get(template) {
+            return compiled.render(inputs);
+        }
+        let compiled = CompiledTemplate::parse(template);
+        let result = compiled.render(inputs);
+        self.state
+            .templates
+            .write()
+            .insert(template.to_string(), compiled);
+        result
+    }
+
+    /// Execute a Tera template step
+    ///
+    /// Renders `parameters["template"]` (inline) or
+    /// `parameters["template_file"]` (path) with every context binding
+    /// available as a template variable.
+    #[cfg(feature = "tera")]
+    pub(super) fn execute_template_render(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let template = match (
+            step.parameters.get("template").and_then(|v| v.as_str()),
+            step.parameters
+                .get("template_file")
+                .and_then(|v| v.as_str()),
+        ) {
+            (Some(inline), _) => inline.to_string(),
+            (None, Some(path)) => std::fs::read_to_string(path)?,
+            (None, None) => {
+                return Err(VesperError::ExecutionError(
+                    "Template render step missing template or template_file".to_string(),
+                ))
+            }
+        };
+
+        let bindings = serde_json::Value::from(&Value::Object(ctx.bindings()));
+        let context = tera::Context::from_value(bindings)
+            .map_err(|e| VesperError::ExecutionError(format!("Template context error: {}", e)))?;
+
+        let rendered = tera::Tera::one_off(&template, &context, false).map_err(|e| {
+            VesperError::ExecutionError(format!("Template error: {}", tera_cause(&e)))
+        })?;
+
+        let result = Value::from(rendered);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a Handlebars template step
+    ///
+    /// Renders the template pre-compiled from `parameters["template"]` at
+    /// registration. `parameters["partials"]` either defines inline
+    /// partials (a mapping), visible only to this step's template, or lists
+    /// pre-registered partials the template depends on (a sequence), which
+    /// are checked before rendering.
+    #[cfg(feature = "handlebars")]
+    pub(super) fn execute_handlebars_render(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let template = handlebars_source(step).ok_or_else(|| {
+            VesperError::ExecutionError("Handlebars step missing template".to_string())
+        })?;
+
+        if let Some(serde_yaml::Value::Sequence(required)) = step.parameters.get("partials") {
+            for name in required.iter().filter_map(|n| n.as_str()) {
+                if !self.state.handlebars.has_template(name) {
+                    return Err(VesperError::ExecutionError(format!(
+                        "Handlebars partial not registered: {}",
+                        name
+                    )));
+                }
+            }
+        }
+
+        if !self.state.handlebars.has_template(&template) {
+            return Err(VesperError::ExecutionError(format!(
+                "Handlebars template for step {} failed to compile",
+                step.step
+            )));
+        }
+
+        let data = serde_json::Value::from(&Value::Object(ctx.bindings()));
+        let rendered = self
+            .state
+            .handlebars
+            .render(&template, &data)
+            .map_err(|e| VesperError::ExecutionError(format!("Handlebars error: {}", e)))?;
+
+        let result = Value::from(rendered);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a Handlebars template step (feature disabled)
+    #[cfg(not(feature = "handlebars"))]
+    pub(super) fn execute_handlebars_render(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "handlebars_render operation requires the `handlebars` feature".to_string(),
+        ))
+    }
+
+    /// Execute a Tera template step (feature disabled)
+    #[cfg(not(feature = "tera"))]
+    pub(super) fn execute_template_render(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "template_render operation requires the `tera` feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(feature = "handlebars")]
+impl SemanticExecutor {
+    /// Register a custom Handlebars helper available to every template
+    pub fn register_handlebars_helper(
+        &mut self,
+        name: &str,
+        helper: Box<dyn handlebars::HelperDef + Send + Sync>,
+    ) {
+        self.state_mut().handlebars.register_helper(name, helper);
+    }
+
+    /// Register a named Handlebars partial available to every template
+    pub fn register_handlebars_partial(&mut self, name: &str, source: &str) -> Result<()> {
+        self.state_mut()
+            .handlebars
+            .register_partial(name, source)
+            .map_err(|e| VesperError::ExecutionError(format!("Handlebars error: {}", e)))
+    }
+
+    /// Pre-compile the templates of every `handlebars_render` step of a
+    /// node, including nested and transform steps
+    ///
+    /// Templates are registered under their own source text, with their
+    /// inline partials, so that identical templates across nodes share one
+    /// compiled entry.
+    pub(super) fn compile_handlebars_templates(&mut self, node: &crate::types::VesperNode) {
+        for step in crate::loader::analysis::all_steps(node) {
+            if step.operation != "handlebars_render" {
+                continue;
+            }
+            let Some(source) = handlebars_source(&step) else {
+                continue;
+            };
+            if let Err(e) = self
+                .state_mut()
+                .handlebars
+                .register_template_string(&source, &source)
+            {
+                tracing::warn!(
+                    "Invalid Handlebars template in {}.{}: {}",
+                    node.node_id,
+                    step.step,
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Source of a `handlebars_render` step's template, starting with the
+/// definitions of its inline partials
+///
+/// Defining the partials inside the template keeps them out of the shared
+/// registry, so nodes can use the same partial names for different
+/// partials.
+#[cfg(feature = "handlebars")]
+fn handlebars_source(step: &FlowStep) -> Option<String> {
+    let template = step.parameters.get("template")?.as_str()?;
+    let mut source = String::new();
+    if let Some(serde_yaml::Value::Mapping(partials)) = step.parameters.get("partials") {
+        for (name, partial) in partials {
+            if let (Some(name), Some(partial)) = (name.as_str(), partial.as_str()) {
+                source.push_str(&format!(
+                    "{{{{#*inline \"{}\"}}}}{}{{{{/inline}}}}",
+                    name, partial
+                ));
+            }
+        }
+    }
+    source.push_str(template);
+    Some(source)
+}
+
+/// Flatten a Tera error chain, whose top level is usually just
+/// "Failed to render '__tera_one_off'"
+#[cfg(feature = "tera")]
+fn tera_cause(error: &tera::Error) -> String {
+    use std::error::Error;
+
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod compiled_tests {
+    use super::*;
+
+    #[test]
+    fn test_compiled_template() {
+        let template = CompiledTemplate::parse("{greeting}, {name}! {} {missing} {{x}");
+        let mut inputs = HashMap::new();
+        inputs.insert("greeting".to_string(), Value::from("Hello"));
+        inputs.insert("name".to_string(), Value::Int(42));
+        inputs.insert("x".to_string(), Value::Bool(true));
+        assert_eq!(template.render(&inputs), "Hello, 42! {} {missing} {true");
+    }
+}
+
+#[cfg(all(test, feature = "tera"))]
+mod tera_tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn executor(template: &str) -> SemanticExecutor {
+        let yaml = format!(
+            r#"
+node_id: report_v1
+type: function
+intent: render a report
+
+inputs:
+  title:
+    type: string
+  items:
+    type: array
+
+flow:
+  - step: render
+    operation: template_render
+    parameters:
+      template: {:?}
+    output: report
+"#,
+            template
+        );
+        let node = VesperLoader::new().load_string(&yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+        executor
+    }
+
+    fn inputs() -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        inputs.insert("title".to_string(), Value::from("stock"));
+        inputs.insert(
+            "items".to_string(),
+            Value::Array(vec![Value::from("pens"), Value::from("cups")]),
+        );
+        inputs
+    }
+
+    #[test]
+    fn test_template_render_loops_and_filters() {
+        let result = executor(
+            "# {{ title | upper }}\n{% for item in items %}- {{ item }}\n{% endfor %}\
+             {% if items | length > 1 %}many{% endif %}",
+        )
+        .execute("report_v1", inputs())
+        .unwrap();
+
+        assert_eq!(
+            result.data,
+            Some(Value::from("# STOCK\n- pens\n- cups\nmany"))
+        );
+    }
+
+    #[test]
+    fn test_template_render_error() {
+        let result = executor("{% if %}").execute("report_v1", inputs());
+        assert!(matches!(result, Err(VesperError::ExecutionError(_))));
+    }
+}
+
+#[cfg(all(test, feature = "handlebars"))]
+mod handlebars_tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    fn executor(parameters: &str) -> SemanticExecutor {
+        let yaml = format!(
+            r#"
+node_id: page_v1
+type: function
+intent: render a page
+
+inputs:
+  user:
+    type: object
+  items:
+    type: array
+
+flow:
+  - step: render
+    operation: handlebars_render
+    parameters:
+      {}
+"#,
+            parameters
+        );
+        let node = VesperLoader::new().load_string(&yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor
+            .register_handlebars_partial("footer", "-- {{user.name}}")
+            .unwrap();
+        executor.register(node);
+        executor
+    }
+
+    fn inputs() -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "user".to_string(),
+            Value::from(serde_json::json!({"name": "Ada", "admin": true})),
+        );
+        inputs.insert(
+            "items".to_string(),
+            Value::Array(vec![Value::from("a"), Value::from("b")]),
+        );
+        inputs
+    }
+
+    fn render(parameters: &str) -> Result<Value> {
+        executor(parameters)
+            .execute("page_v1", inputs())
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_handlebars_if_and_each() {
+        let result = render(
+            "template: \"{{#if user.admin}}admin {{/if}}{{#each items}}[{{this}}]{{/each}}\"",
+        );
+        assert_eq!(result.unwrap(), Value::from("admin [a][b]"));
+    }
+
+    #[test]
+    fn test_handlebars_partials() {
+        let inline = render(
+            "template: \"{{> greeting}} {{> footer}}\"\n      partials:\n        greeting: \"Hi {{user.name}}\"",
+        );
+        assert_eq!(inline.unwrap(), Value::from("Hi Ada -- Ada"));
+
+        let missing = render("template: \"{{> header}}\"\n      partials: [header]");
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_handlebars_nested_steps_and_node_partials() {
+        let node = |node_id: &str, greeting: &str| {
+            format!(
+                r#"
+node_id: {}
+type: function
+intent: greet admins
+
+inputs:
+  user:
+    type: object
+  admin:
+    type: boolean
+
+flow:
+  - step: check
+    operation: conditional
+    condition: "admin == true"
+    then:
+      - operation: handlebars_render
+        parameters:
+          template: "{{{{> greeting}}}}"
+          partials:
+            greeting: "{} {{{{user.name}}}}"
+"#,
+                node_id, greeting
+            )
+        };
+        let mut executor = SemanticExecutor::new();
+        for (node_id, greeting) in [("hello_v1", "Hello"), ("bonjour_v1", "Bonjour")] {
+            let node = VesperLoader::new()
+                .load_string(&node(node_id, greeting))
+                .unwrap();
+            executor.register(node);
+        }
+
+        for (node_id, expected) in [("hello_v1", "Hello Ada"), ("bonjour_v1", "Bonjour Ada")] {
+            let mut inputs = inputs();
+            inputs.insert("admin".to_string(), Value::Bool(true));
+            let result = executor.execute(node_id, inputs).unwrap();
+            assert_eq!(result.data, Some(Value::from(expected)));
+        }
+    }
+}
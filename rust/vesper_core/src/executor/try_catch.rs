@@ -0,0 +1,193 @@
+//! Structured error handling within a flow
+
+use super::parallel::sub_steps;
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::Result;
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+
+impl SemanticExecutor {
+    /// Execute a try/catch step
+    ///
+    /// Runs the steps in `parameters["try"]` in order. If one fails, the
+    /// rest are skipped, the error is bound as `{code, message}` to
+    /// `parameters["error_variable"]` (default `error`) and the steps in
+    /// `parameters["catch"]` run instead. Either way the flow continues
+    /// with the next step; only a failing catch step fails the step.
+    /// Returns the result of the last try or catch step run.
+    ///
+    /// The steps in `parameters["finally"]` always run afterwards, also
+    /// when a catch step failed. Their results are discarded and their
+    /// errors only logged.
+    pub(super) fn execute_try_catch(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let try_steps = sub_steps(step, "try")?;
+        let optional_steps = |key| match step.parameters.get(key) {
+            Some(_) => sub_steps(step, key),
+            None => Ok(Vec::new()),
+        };
+        let catch_steps = optional_steps("catch")?;
+        let finally_steps = optional_steps("finally")?;
+        let error_variable = step
+            .parameters
+            .get("error_variable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("error");
+
+        let mut result = Value::Null;
+        let mut failure = None;
+        for try_step in &try_steps {
+            match self.execute_step(try_step, ctx) {
+                Ok(value) => result = value,
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let outcome = match failure {
+            None => Ok(result),
+            Some(e) => {
+                tracing::debug!("Step {} caught: {}", step.step, e);
+                let mut error = HashMap::new();
+                error.insert("code".to_string(), Value::from(e.code()));
+                error.insert("message".to_string(), Value::from(e.to_string()));
+                ctx.set(error_variable.to_string(), Value::Object(error));
+
+                catch_steps.iter().try_fold(Value::Null, |_, catch_step| {
+                    self.execute_step(catch_step, ctx)
+                })
+            }
+        };
+
+        for finally_step in &finally_steps {
+            if let Err(e) = self.execute_step(finally_step, ctx) {
+                tracing::warn!("Finally step of {} failed: {}", step.step, e);
+            }
+        }
+
+        let result = outcome?;
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::{CacheBackend, InMemoryCacheBackend};
+    use crate::loader::VesperLoader;
+    use std::sync::Arc;
+
+    const SAFE_DIVIDE: &str = r#"
+node_id: safe_divide_v1
+type: function
+intent: divide, recovering from bad divisors
+
+inputs:
+  a:
+    type: integer
+  b:
+    type: integer
+
+flow:
+  - step: guarded
+    operation: try_catch
+    parameters:
+      try:
+        - operation: arithmetic
+          expression: "a / b"
+          output: ratio
+      catch:
+        - operation: arithmetic
+          expression: "-1"
+          output: ratio
+      finally:
+        - operation: arithmetic
+          expression: "missing + 1"
+        - operation: arithmetic
+          expression: "b + 100"
+          output: cleanup
+      error_variable: failure
+  - step: report
+    operation: return
+    return_success:
+      ratio: "{ratio}"
+      failure: "{failure}"
+      cleanup: "{cleanup}"
+"#;
+
+    fn divide(b: i64) -> HashMap<String, Value> {
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(SAFE_DIVIDE).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), Value::Int(8));
+        inputs.insert("b".to_string(), Value::Int(b));
+        match executor.execute("safe_divide_v1", inputs).unwrap().data {
+            Some(Value::Object(fields)) => fields,
+            other => panic!("expected object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_try_succeeds() {
+        let fields = divide(2);
+        assert_eq!(fields["ratio"], Value::Int(4));
+        // Unbound references resolve to their literal text
+        assert_eq!(fields["failure"], Value::from("{failure}"));
+        assert_eq!(fields["cleanup"], Value::Int(102));
+    }
+
+    #[test]
+    fn test_catch_binds_error() {
+        let fields = divide(0);
+        assert_eq!(fields["ratio"], Value::Int(-1));
+        let Value::Object(failure) = &fields["failure"] else {
+            panic!("expected error object, got {:?}", fields["failure"]);
+        };
+        assert_eq!(failure["code"], Value::from("execution_error"));
+        assert!(failure["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("Division by zero")));
+        assert_eq!(fields["cleanup"], Value::Int(100));
+    }
+
+    #[test]
+    fn test_finally_runs_when_catch_fails() {
+        let yaml = r#"
+node_id: rethrow_v1
+type: function
+intent: clean up before failing
+
+flow:
+  - step: guarded
+    operation: try_catch
+    parameters:
+      try:
+        - operation: arithmetic
+          expression: "1 / 0"
+      catch:
+        - operation: return
+          return_error:
+            error_code: rethrown
+            message: "Gave up"
+      finally:
+        - operation: cache_set
+          parameters:
+            key: cleanup
+            value: error
+"#;
+        let cache = Arc::new(InMemoryCacheBackend::new());
+        let mut executor = SemanticExecutor::new().with_cache(cache.clone());
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let error = executor.execute("rethrow_v1", HashMap::new()).unwrap_err();
+        assert!(error.to_string().contains("rethrown"), "{}", error);
+        assert!(cache.get("cleanup").is_some());
+    }
+}
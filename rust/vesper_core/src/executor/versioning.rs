@@ -0,0 +1,168 @@
+//! Semantic version operations
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use semver::Version;
+use std::collections::HashMap;
+
+impl SemanticExecutor {
+    /// Execute a version comparison step
+    ///
+    /// Compares the versions named by `parameters["a"]` and
+    /// `parameters["b"]` using `parameters["operator"]` (`eq`, `ne`, `gt`,
+    /// `lt`, `ge` or `le`) with semver precedence rules.
+    pub(super) fn execute_version_compare(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let a = parse_version(&self.resolve_parameter_variable(step, "a", ctx)?)?;
+        let b = parse_version(&self.resolve_parameter_variable(step, "b", ctx)?)?;
+        let operator = step
+            .parameters
+            .get("operator")
+            .and_then(|o| o.as_str())
+            .ok_or_else(|| {
+                VesperError::ExecutionError("Version compare step missing operator".to_string())
+            })?;
+
+        let holds = match operator {
+            "eq" => a == b,
+            "ne" => a != b,
+            "gt" => a > b,
+            "lt" => a < b,
+            "ge" => a >= b,
+            "le" => a <= b,
+            other => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Unknown version operator: {}",
+                    other
+                )))
+            }
+        };
+
+        let result = Value::Bool(holds);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute a version parse step
+    ///
+    /// Splits the version named by `parameters["on"]` into
+    /// `{major, minor, patch, pre}`.
+    pub(super) fn execute_version_parse(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let version = parse_version(&self.resolve_parameter_variable(step, "on", ctx)?)?;
+
+        let mut parts = HashMap::new();
+        parts.insert("major".to_string(), Value::Int(version.major as i64));
+        parts.insert("minor".to_string(), Value::Int(version.minor as i64));
+        parts.insert("patch".to_string(), Value::Int(version.patch as i64));
+        parts.insert("pre".to_string(), Value::from(version.pre.as_str()));
+
+        let result = Value::Object(parts);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Parse a version string, tolerating a leading `v`
+fn parse_version(value: &Value) -> Result<Version> {
+    let text = value.as_str().ok_or_else(|| VesperError::TypeError {
+        expected: "version string".to_string(),
+        actual: format!("{:?}", value),
+    })?;
+
+    let trimmed = text.trim();
+    Version::parse(trimmed.strip_prefix('v').unwrap_or(trimmed))
+        .map_err(|e| VesperError::ExecutionError(format!("Invalid version {}: {}", text, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    fn compare(a: &str, operator: &str, b: &str) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: compare_v1
+type: function
+intent: compare versions
+
+inputs:
+  a:
+    type: string
+  b:
+    type: string
+
+flow:
+  - step: compare
+    operation: version_compare
+    parameters:
+      a: a
+      b: b
+      operator: {}
+"#,
+            operator
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), Value::from(a));
+        inputs.insert("b".to_string(), Value::from(b));
+        executor
+            .execute("compare_v1", inputs)
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_version_compare() {
+        assert_eq!(compare("1.0.0", "lt", "1.0.1").unwrap(), Value::Bool(true));
+        assert_eq!(
+            compare("1.0.0-alpha", "lt", "1.0.0").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(compare("v2.1.0", "ge", "2.1.0").unwrap(), Value::Bool(true));
+        assert_eq!(compare("2.0.0", "eq", "2.0.1").unwrap(), Value::Bool(false));
+        assert!(compare("1.0", "eq", "1.0.0").is_err());
+    }
+
+    #[test]
+    fn test_version_parse() {
+        let yaml = r#"
+node_id: parse_v1
+type: function
+intent: parse a version
+
+inputs:
+  version:
+    type: string
+
+flow:
+  - step: parse
+    operation: version_parse
+    parameters:
+      on: version
+"#;
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(yaml).unwrap());
+
+        let mut inputs = HashMap::new();
+        inputs.insert("version".to_string(), Value::from("3.2.1-rc.1"));
+        let result = executor.execute("parse_v1", inputs).unwrap();
+
+        let Some(Value::Object(parts)) = result.data else {
+            panic!("expected object result");
+        };
+        assert_eq!(parts["major"], Value::Int(3));
+        assert_eq!(parts["minor"], Value::Int(2));
+        assert_eq!(parts["patch"], Value::Int(1));
+        assert_eq!(parts["pre"], Value::from("rc.1"));
+    }
+}
@@ -0,0 +1,341 @@
+//! Workflows: graphs of node calls wired together by their outputs
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+use crate::types::{FlowStep, Value};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+/// One node call in a workflow graph
+#[derive(Debug, Deserialize)]
+struct WorkflowStep {
+    /// Name other steps refer to this call by; defaults to the node ID
+    #[serde(default)]
+    step: Option<String>,
+    /// Node to execute
+    node: String,
+    /// Node inputs, each a `step.field` reference
+    #[serde(default)]
+    inputs_from: HashMap<String, String>,
+}
+
+impl WorkflowStep {
+    fn name(&self) -> &str {
+        self.step.as_deref().unwrap_or(&self.node)
+    }
+
+    /// Steps of the graph whose outputs this call reads
+    fn dependencies<'a>(&'a self, names: &HashSet<&str>) -> HashSet<&'a str> {
+        self.inputs_from
+            .values()
+            .map(|reference| reference.split('.').next().unwrap_or(reference))
+            .filter(|step| names.contains(step))
+            .collect()
+    }
+}
+
+impl SemanticExecutor {
+    /// Execute a workflow orchestrator step
+    ///
+    /// Runs the node calls `{step, node, inputs_from}` in
+    /// `parameters["graph"]`, each once every call it depends on has
+    /// finished. An `inputs_from` entry maps a node input to a
+    /// `step.field` reference: the field of the result of the call named
+    /// `step`, or with no field the whole result. References to names
+    /// outside the graph read variables of the current flow instead. Calls
+    /// whose dependencies are met run concurrently. Returns an object of
+    /// every result keyed by step name; the first failing call in graph
+    /// order fails the step, and a cyclic graph fails before anything runs.
+    pub(super) fn execute_workflow_orchestrator(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let graph: Vec<WorkflowStep> = match step.parameters.get("graph") {
+            Some(graph @ serde_yaml::Value::Sequence(_)) => serde_yaml::from_value(graph.clone())?,
+            _ => {
+                return Err(VesperError::ExecutionError(format!(
+                    "Step {} needs a sequence of workflow steps in graph",
+                    step.step
+                )))
+            }
+        };
+        let levels = workflow_levels(&graph)?;
+
+        let mut results: HashMap<String, Value> = HashMap::new();
+        for level in levels {
+            let calls = level
+                .iter()
+                .map(|&index| {
+                    let call = &graph[index];
+                    let inputs = call
+                        .inputs_from
+                        .iter()
+                        .map(|(input, reference)| {
+                            Ok((input.clone(), resolve_reference(reference, &results, ctx)?))
+                        })
+                        .collect::<Result<HashMap<_, _>>>()?;
+                    Ok((call, inputs))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let outcomes: Vec<Result<Value>> = std::thread::scope(|scope| {
+                let handles: Vec<_> = calls
+                    .into_iter()
+                    .map(|(call, inputs)| {
+                        scope.spawn(move || {
+                            self.execute(&call.node, inputs)
+                                .map(|r| r.data.unwrap_or(Value::Null))
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle.join().unwrap_or_else(|_| {
+                            Err(VesperError::ExecutionError("Node panicked".to_string()))
+                        })
+                    })
+                    .collect()
+            });
+            for (&index, outcome) in level.iter().zip(outcomes) {
+                let call = &graph[index];
+                let value = outcome.map_err(|e| {
+                    VesperError::ExecutionError(format!(
+                        "Workflow step {} ({}) failed: {}",
+                        call.name(),
+                        call.node,
+                        e
+                    ))
+                })?;
+                results.insert(call.name().to_string(), value);
+            }
+        }
+
+        let result = Value::Object(results);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+}
+
+/// Group the graph's calls into levels, each depending only on earlier ones
+fn workflow_levels(graph: &[WorkflowStep]) -> Result<Vec<Vec<usize>>> {
+    let mut names = HashSet::new();
+    for call in graph {
+        if !names.insert(call.name()) {
+            return Err(VesperError::ExecutionError(format!(
+                "Duplicate workflow step: {}",
+                call.name()
+            )));
+        }
+    }
+
+    let mut pending: Vec<(usize, HashSet<&str>)> = graph
+        .iter()
+        .enumerate()
+        .map(|(index, call)| (index, call.dependencies(&names)))
+        .collect();
+    let mut levels = Vec::new();
+    while !pending.is_empty() {
+        let (ready, blocked): (Vec<_>, Vec<_>) =
+            pending.into_iter().partition(|(_, deps)| deps.is_empty());
+        if ready.is_empty() {
+            let mut cycle: Vec<&str> = blocked.iter().map(|&(i, _)| graph[i].name()).collect();
+            cycle.sort_unstable();
+            return Err(VesperError::ExecutionError(format!(
+                "Workflow graph has a cycle through: {}",
+                cycle.join(", ")
+            )));
+        }
+        let level: Vec<usize> = ready.into_iter().map(|(index, _)| index).collect();
+        pending = blocked
+            .into_iter()
+            .map(|(index, mut deps)| {
+                for &done in &level {
+                    deps.remove(graph[done].name());
+                }
+                (index, deps)
+            })
+            .collect();
+        levels.push(level);
+    }
+    Ok(levels)
+}
+
+/// Look up a `step.field` reference among results, falling back to variables
+fn resolve_reference(
+    reference: &str,
+    results: &HashMap<String, Value>,
+    ctx: &ExecutionContext,
+) -> Result<Value> {
+    let mut path = reference.split('.');
+    let head = path.next().unwrap_or(reference);
+    let mut value = results
+        .get(head)
+        .or_else(|| ctx.get(head))
+        .ok_or_else(|| VesperError::ExecutionError(format!("Unknown variable: {}", head)))?;
+    for field in path {
+        value = match value {
+            Value::Object(fields) => fields.get(field),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            VesperError::ExecutionError(format!("Workflow reference {} not found", reference))
+        })?;
+    }
+    Ok(value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    fn executor(graph: &str) -> SemanticExecutor {
+        let workflow = format!(
+            r#"
+node_id: pricing_v1
+type: function
+intent: price an order from several services
+
+inputs:
+  order:
+    type: object
+
+flow:
+  - step: price
+    operation: workflow_orchestrator
+    parameters:
+      graph:
+{}
+"#,
+            graph
+        );
+        let stage = |node_id: &str, expression: &str| {
+            format!(
+                r#"
+node_id: {}
+type: function
+intent: one pricing stage
+
+inputs:
+  a:
+    type: integer
+  b:
+    type: integer
+    required: false
+    default: 0
+
+flow:
+  - step: compute
+    operation: arithmetic
+    expression: "{}"
+    output: amount
+  - step: done
+    operation: return
+    return_success:
+      amount: "{{amount}}"
+"#,
+                node_id, expression
+            )
+        };
+        let mut executor = SemanticExecutor::new();
+        let loader = VesperLoader::new();
+        for node in [
+            workflow,
+            stage("base_v1", "a * 10"),
+            stage("tax_v1", "a / 5"),
+            stage("discount_v1", "a / 10"),
+            stage("total_v1", "a - b"),
+        ] {
+            executor.register(loader.load_string(&node).unwrap());
+        }
+        executor
+    }
+
+    fn run(executor: &SemanticExecutor) -> Result<Value> {
+        let order = HashMap::from([("qty".to_string(), Value::Int(3))]);
+        let inputs = HashMap::from([("order".to_string(), Value::Object(order))]);
+        executor
+            .execute("pricing_v1", inputs)
+            .map(|r| r.data.unwrap())
+    }
+
+    #[test]
+    fn test_workflow_runs_diamond_in_dependency_order() {
+        // Listed out of order: total joins tax and discount, which both
+        // depend on base
+        let executor = executor(
+            r#"
+        - step: total
+          node: total_v1
+          inputs_from: { a: tax.amount, b: discount.amount }
+        - step: tax
+          node: tax_v1
+          inputs_from: { a: base.amount }
+        - step: discount
+          node: discount_v1
+          inputs_from: { a: base.amount }
+        - step: base
+          node: base_v1
+          inputs_from: { a: order.qty }
+"#,
+        );
+        let Value::Object(results) = run(&executor).unwrap() else {
+            panic!("expected object result");
+        };
+        let amount = |step: &str| {
+            let Value::Object(fields) = &results[step] else {
+                panic!("expected object result for {}", step);
+            };
+            fields["amount"].clone()
+        };
+        assert_eq!(amount("base"), Value::Int(30));
+        assert_eq!(amount("tax"), Value::Int(6));
+        assert_eq!(amount("discount"), Value::Int(3));
+        assert_eq!(amount("total"), Value::Int(3));
+
+        let graph = [
+            ("total", vec!["tax.amount", "discount.amount"]),
+            ("tax", vec!["base.amount"]),
+            ("discount", vec!["base.amount"]),
+            ("base", vec!["order.qty"]),
+        ];
+        let graph: Vec<WorkflowStep> = graph
+            .iter()
+            .map(|(name, refs)| WorkflowStep {
+                step: Some(name.to_string()),
+                node: format!("{}_v1", name),
+                inputs_from: refs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, r)| (format!("in{}", i), r.to_string()))
+                    .collect(),
+            })
+            .collect();
+        let mut levels = workflow_levels(&graph).unwrap();
+        levels[1].sort_unstable();
+        assert_eq!(levels, vec![vec![3], vec![1, 2], vec![0]]);
+    }
+
+    #[test]
+    fn test_workflow_rejects_cycles() {
+        let executor = executor(
+            r#"
+        - step: base
+          node: base_v1
+          inputs_from: { a: order.qty }
+        - step: tax
+          node: tax_v1
+          inputs_from: { a: total.amount }
+        - step: total
+          node: total_v1
+          inputs_from: { a: tax.amount }
+"#,
+        );
+        let Err(VesperError::ExecutionError(message)) = run(&executor) else {
+            panic!("expected execution error");
+        };
+        assert_eq!(message, "Workflow graph has a cycle through: tax, total");
+    }
+}
@@ -0,0 +1,415 @@
+//! XML parsing and serialization operations
+//!
+//! Documents map to objects keyed by element name. Attributes are stored
+//! under `{attribute_prefix}{name}` (default `@`), mixed text content under
+//! `#text`, and repeated sibling elements collapse into an array.
+
+use super::{ExecutionContext, SemanticExecutor};
+use crate::error::{Result, VesperError};
+#[cfg(feature = "xml")]
+use crate::types::IntoString;
+use crate::types::{FlowStep, Value};
+
+/// Key holding the text content of an element with attributes or children
+#[cfg(feature = "xml")]
+const TEXT_KEY: &str = "#text";
+
+impl SemanticExecutor {
+    /// Execute an XML parse step
+    ///
+    /// Converts the XML string named by `parameters["on"]` into an object
+    /// with a single key for the root element.
+    #[cfg(feature = "xml")]
+    pub(super) fn execute_xml_parse(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let source = match self.resolve_parameter_variable(step, "on", ctx)? {
+            Value::String(s) => s.into_string(),
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "string".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        let options = XmlOptions::from_step(step);
+        let result = parse_document(&source, &options)?;
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute an XML stringify step
+    ///
+    /// Serializes the single-rooted object named by `parameters["on"]`.
+    #[cfg(feature = "xml")]
+    pub(super) fn execute_xml_stringify(
+        &self,
+        step: &FlowStep,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        let root = match self.resolve_parameter_variable(step, "on", ctx)? {
+            Value::Object(map) if map.len() == 1 => map,
+            other => {
+                return Err(VesperError::TypeError {
+                    expected: "object with a single root element".to_string(),
+                    actual: format!("{:?}", other),
+                })
+            }
+        };
+
+        let options = XmlOptions::from_step(step);
+        let mut out = String::new();
+        for (name, value) in &root {
+            write_element(name, value, &options, &mut out);
+        }
+
+        let result = Value::from(out);
+        self.store_output(step, ctx, &result);
+        Ok(result)
+    }
+
+    /// Execute an XML parse step (feature disabled)
+    #[cfg(not(feature = "xml"))]
+    pub(super) fn execute_xml_parse(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "xml_parse operation requires the `xml` feature".to_string(),
+        ))
+    }
+
+    /// Execute an XML stringify step (feature disabled)
+    #[cfg(not(feature = "xml"))]
+    pub(super) fn execute_xml_stringify(
+        &self,
+        _step: &FlowStep,
+        _ctx: &mut ExecutionContext,
+    ) -> Result<Value> {
+        Err(VesperError::ExecutionError(
+            "xml_stringify operation requires the `xml` feature".to_string(),
+        ))
+    }
+}
+
+/// Step-level XML conventions
+#[cfg(feature = "xml")]
+struct XmlOptions {
+    attribute_prefix: String,
+    preserve_namespaces: bool,
+}
+
+#[cfg(feature = "xml")]
+impl XmlOptions {
+    fn from_step(step: &FlowStep) -> Self {
+        Self {
+            attribute_prefix: step
+                .parameters
+                .get("attribute_prefix")
+                .and_then(|v| v.as_str())
+                .unwrap_or("@")
+                .to_string(),
+            preserve_namespaces: step
+                .parameters
+                .get("preserve_namespaces")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// An element whose children are still being read
+#[cfg(feature = "xml")]
+struct OpenElement {
+    name: String,
+    fields: std::collections::HashMap<String, Value>,
+    text: String,
+}
+
+#[cfg(feature = "xml")]
+impl OpenElement {
+    fn new(element: &quick_xml::events::BytesStart, options: &XmlOptions) -> Result<Self> {
+        let name = if options.preserve_namespaces {
+            element.name().as_ref().to_vec()
+        } else {
+            element.local_name().as_ref().to_vec()
+        };
+
+        let mut fields = std::collections::HashMap::new();
+        for attr in element.attributes() {
+            let attr = attr.map_err(xml_error)?;
+            let key = attr.key.as_ref();
+            if !options.preserve_namespaces && (key == b"xmlns" || key.starts_with(b"xmlns:")) {
+                continue;
+            }
+            let key = if options.preserve_namespaces {
+                key.to_vec()
+            } else {
+                attr.key.local_name().as_ref().to_vec()
+            };
+            let value = attr.unescape_value().map_err(xml_error)?;
+            fields.insert(
+                format!("{}{}", options.attribute_prefix, utf8(key)?),
+                Value::from(value.into_owned()),
+            );
+        }
+
+        Ok(Self {
+            name: utf8(name)?,
+            fields,
+            text: String::new(),
+        })
+    }
+
+    /// Collapse the element to its value: bare text when it has no
+    /// attributes or children, otherwise an object
+    fn into_value(mut self) -> (String, Value) {
+        let text = self.text.trim().to_string();
+        if self.fields.is_empty() {
+            let value = if text.is_empty() {
+                Value::Null
+            } else {
+                Value::from(text)
+            };
+            return (self.name, value);
+        }
+        if !text.is_empty() {
+            self.fields.insert(TEXT_KEY.to_string(), Value::from(text));
+        }
+        (self.name, Value::Object(self.fields))
+    }
+}
+
+/// Insert a child into its parent, turning repeated names into arrays
+#[cfg(feature = "xml")]
+fn insert_child(fields: &mut std::collections::HashMap<String, Value>, name: String, value: Value) {
+    match fields.get_mut(&name) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = std::mem::replace(existing, Value::Null);
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            fields.insert(name, value);
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+fn parse_document(source: &str, options: &XmlOptions) -> Result<Value> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(source);
+    let mut stack: Vec<OpenElement> = Vec::new();
+    let mut root = std::collections::HashMap::new();
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => stack.push(OpenElement::new(&e, options)?),
+            Event::Empty(e) => {
+                let (name, value) = OpenElement::new(&e, options)?.into_value();
+                match stack.last_mut() {
+                    Some(parent) => insert_child(&mut parent.fields, name, value),
+                    None => insert_child(&mut root, name, value),
+                }
+            }
+            Event::End(_) => {
+                let element = stack.pop().ok_or_else(|| {
+                    VesperError::ExecutionError("Unbalanced XML end tag".to_string())
+                })?;
+                let (name, value) = element.into_value();
+                match stack.last_mut() {
+                    Some(parent) => insert_child(&mut parent.fields, name, value),
+                    None => insert_child(&mut root, name, value),
+                }
+            }
+            Event::Text(t) => {
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&t.unescape().map_err(xml_error)?);
+                }
+            }
+            Event::CData(c) => {
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&utf8(c.into_inner().into_owned())?);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(VesperError::ExecutionError(
+            "Unexpected end of XML document".to_string(),
+        ));
+    }
+
+    Ok(Value::Object(root))
+}
+
+#[cfg(feature = "xml")]
+fn write_element(name: &str, value: &Value, options: &XmlOptions, out: &mut String) {
+    use quick_xml::escape::escape;
+
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                write_element(name, item, options, out);
+            }
+        }
+        Value::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+
+            out.push('<');
+            out.push_str(name);
+            for key in &keys {
+                if let Some(attr) = key.strip_prefix(options.attribute_prefix.as_str()) {
+                    out.push_str(&format!(
+                        " {}=\"{}\"",
+                        attr,
+                        escape(scalar_text(&fields[*key]).as_str())
+                    ));
+                }
+            }
+
+            let children: Vec<&String> = keys
+                .into_iter()
+                .filter(|k| !k.starts_with(options.attribute_prefix.as_str()))
+                .collect();
+            if children.is_empty() {
+                out.push_str("/>");
+                return;
+            }
+
+            out.push('>');
+            for key in children {
+                if key == TEXT_KEY {
+                    out.push_str(&escape(scalar_text(&fields[key]).as_str()));
+                } else {
+                    write_element(key, &fields[key], options, out);
+                }
+            }
+            out.push_str(&format!("</{}>", name));
+        }
+        Value::Null => out.push_str(&format!("<{}/>", name)),
+        scalar => out.push_str(&format!(
+            "<{}>{}</{}>",
+            name,
+            escape(scalar_text(scalar).as_str()),
+            name
+        )),
+    }
+}
+
+#[cfg(feature = "xml")]
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_string(),
+        Value::Int(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => String::new(),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(feature = "xml")]
+fn utf8(bytes: Vec<u8>) -> Result<String> {
+    String::from_utf8(bytes)
+        .map_err(|e| VesperError::ExecutionError(format!("Invalid UTF-8 in XML: {}", e)))
+}
+
+#[cfg(feature = "xml")]
+fn xml_error(e: impl std::fmt::Display) -> VesperError {
+    VesperError::ExecutionError(format!("XML error: {}", e))
+}
+
+#[cfg(all(test, feature = "xml"))]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+    use std::collections::HashMap;
+
+    const DOCUMENT: &str = r#"<catalog xmlns:b="urn:books">
+  <b:book id="1" lang="en"><title>Dune</title><price>9.99</price></b:book>
+  <b:book id="2"><title>Emma</title><price>4.50</price></b:book>
+  <note priority="high">Restock soon</note>
+</catalog>"#;
+
+    fn run(operation: &str, extra: &str, on: Value) -> Result<Value> {
+        let yaml = format!(
+            r#"
+node_id: xml_v1
+type: function
+intent: xml handling
+
+inputs:
+  doc:
+    type: string
+
+flow:
+  - step: convert
+    operation: {}
+    parameters:
+      on: doc
+      {}
+"#,
+            operation, extra
+        );
+        let node = VesperLoader::new().load_string(&yaml).unwrap();
+        let mut executor = SemanticExecutor::new();
+        executor.register(node);
+
+        let mut inputs = HashMap::new();
+        inputs.insert("doc".to_string(), on);
+        executor
+            .execute("xml_v1", inputs)
+            .map(|r| r.data.unwrap_or(Value::Null))
+    }
+
+    #[test]
+    fn test_xml_parse_structure() {
+        let parsed = run("xml_parse", "", Value::from(DOCUMENT)).unwrap();
+        let expected = Value::from(serde_json::json!({
+            "catalog": {
+                "book": [
+                    {"@id": "1", "@lang": "en", "title": "Dune", "price": "9.99"},
+                    {"@id": "2", "title": "Emma", "price": "4.50"}
+                ],
+                "note": {"@priority": "high", "#text": "Restock soon"}
+            }
+        }));
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn test_xml_preserve_namespaces() {
+        let parsed = run(
+            "xml_parse",
+            "preserve_namespaces: true",
+            Value::from(DOCUMENT),
+        )
+        .unwrap();
+        let Value::Object(root) = parsed else {
+            panic!("expected object");
+        };
+        let Some(Value::Object(catalog)) = root.get("catalog") else {
+            panic!("expected catalog element");
+        };
+        assert!(catalog.contains_key("b:book"));
+        assert!(catalog.contains_key("@xmlns:b"));
+    }
+
+    #[test]
+    fn test_xml_round_trip() {
+        let parsed = run("xml_parse", "", Value::from(DOCUMENT)).unwrap();
+        let xml = run("xml_stringify", "", parsed.clone()).unwrap();
+        let reparsed = run("xml_parse", "", xml).unwrap();
+        assert_eq!(parsed, reparsed);
+    }
+}
@@ -0,0 +1,64 @@
+//! Comparators for the `a_b_test` operation
+
+use crate::types::Value;
+use std::sync::Mutex;
+
+/// Results of a control and a treatment node run on the same inputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantComparison {
+    /// Node whose result was returned to the caller
+    pub control: String,
+    /// Node run alongside it
+    pub treatment: String,
+    /// Result of the control node
+    pub control_result: Value,
+    /// Result of the treatment node, or its error message
+    pub treatment_result: Result<Value, String>,
+    /// Paths where the results differ, as reported by `diff_values`
+    pub differences: Vec<Value>,
+}
+
+impl VariantComparison {
+    /// Whether the treatment succeeded with the control's result
+    pub fn matches(&self) -> bool {
+        self.treatment_result.is_ok() && self.differences.is_empty()
+    }
+}
+
+/// Receiver of `a_b_test` comparisons, e.g. for metrics or reporting
+pub trait ResultComparator: Send + Sync {
+    /// Record one comparison; called for matching results too
+    fn record(&self, comparison: &VariantComparison);
+}
+
+/// Comparator keeping every comparison in memory
+pub struct InMemoryResultComparator {
+    /// Recorded comparisons, oldest first
+    comparisons: Mutex<Vec<VariantComparison>>,
+}
+
+impl InMemoryResultComparator {
+    /// Create a comparator with no comparisons
+    pub fn new() -> Self {
+        Self {
+            comparisons: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Comparisons recorded so far
+    pub fn comparisons(&self) -> Vec<VariantComparison> {
+        self.comparisons.lock().unwrap().clone()
+    }
+}
+
+impl Default for InMemoryResultComparator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResultComparator for InMemoryResultComparator {
+    fn record(&self, comparison: &VariantComparison) {
+        self.comparisons.lock().unwrap().push(comparison.clone());
+    }
+}
@@ -0,0 +1,148 @@
+//! Feature flag stores for the `feature_flag` operation
+
+use crate::types::Value;
+use std::collections::HashMap;
+
+/// Source of feature flag decisions
+pub trait FeatureFlagStore: Send + Sync {
+    /// Check whether a flag is enabled for the given execution context
+    fn is_enabled(&self, flag: &str, context: &HashMap<String, Value>) -> bool;
+}
+
+/// Fixed set of flags, independent of context
+///
+/// Unknown flags are disabled.
+pub struct StaticFeatureFlagStore {
+    /// Flag states by name
+    flags: HashMap<String, bool>,
+}
+
+impl StaticFeatureFlagStore {
+    /// Create a store with no flags enabled
+    pub fn new() -> Self {
+        Self {
+            flags: HashMap::new(),
+        }
+    }
+
+    /// Set the state of a flag
+    pub fn with_flag(mut self, flag: &str, enabled: bool) -> Self {
+        self.flags.insert(flag.to_string(), enabled);
+        self
+    }
+}
+
+impl Default for StaticFeatureFlagStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<HashMap<String, bool>> for StaticFeatureFlagStore {
+    fn from(flags: HashMap<String, bool>) -> Self {
+        Self { flags }
+    }
+}
+
+impl FeatureFlagStore for StaticFeatureFlagStore {
+    fn is_enabled(&self, flag: &str, _context: &HashMap<String, Value>) -> bool {
+        self.flags.get(flag).copied().unwrap_or(false)
+    }
+}
+
+/// Flag store backed by an Unleash-compatible client API
+///
+/// Looks up `GET {base_url}/api/client/features/{flag}` and reads its
+/// `enabled` field. LaunchDarkly and other providers can be used through
+/// a relay exposing the same shape. Lookup failures disable the flag.
+#[cfg(feature = "http")]
+pub struct HttpFeatureFlagStore {
+    /// Base URL of the flag service
+    base_url: String,
+    /// Value for the `Authorization` header
+    api_key: Option<String>,
+    /// HTTP client
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "http")]
+impl HttpFeatureFlagStore {
+    /// Create a store for the service at `base_url`
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Authenticate requests with an API key
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    fn fetch(&self, flag: &str) -> std::result::Result<bool, String> {
+        let url = format!("{}/api/client/features/{}", self.base_url, flag);
+        let mut request = self.client.get(url);
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", key);
+        }
+
+        let response = request.send().map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        let body: serde_json::Value = response.json().map_err(|e| e.to_string())?;
+        body.get("enabled")
+            .and_then(|e| e.as_bool())
+            .ok_or_else(|| "response missing `enabled`".to_string())
+    }
+}
+
+#[cfg(feature = "http")]
+impl FeatureFlagStore for HttpFeatureFlagStore {
+    fn is_enabled(&self, flag: &str, _context: &HashMap<String, Value>) -> bool {
+        self.fetch(flag).unwrap_or_else(|e| {
+            tracing::warn!("Feature flag {} lookup failed: {}", flag, e);
+            false
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_store() {
+        let store = StaticFeatureFlagStore::new()
+            .with_flag("beta", true)
+            .with_flag("legacy", false);
+
+        let context = HashMap::new();
+        assert!(store.is_enabled("beta", &context));
+        assert!(!store.is_enabled("legacy", &context));
+        assert!(!store.is_enabled("unknown", &context));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_http_store() {
+        let mut server = mockito::Server::new();
+        let _on = server
+            .mock("GET", "/api/client/features/beta")
+            .match_header("authorization", "token")
+            .with_body(r#"{"name": "beta", "enabled": true}"#)
+            .create();
+        let _missing = server
+            .mock("GET", "/api/client/features/gone")
+            .with_status(404)
+            .create();
+
+        let store = HttpFeatureFlagStore::new(&server.url()).with_api_key("token");
+        assert!(store.is_enabled("beta", &HashMap::new()));
+        assert!(!store.is_enabled("gone", &HashMap::new()));
+    }
+}
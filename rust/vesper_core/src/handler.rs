@@ -0,0 +1,533 @@
+//! Middleware pipeline for `HttpHandler` nodes
+//!
+//! An `HttpHandlerExecutor` turns an `HttpRequest` into node inputs, runs
+//! the node through a `SemanticExecutor` and maps its result (or error)
+//! back to an `HttpResponse`. Middleware wraps that call and can inspect
+//! or rewrite the request, short-circuit with its own response, or adjust
+//! the response on the way out.
+
+use crate::executor::SemanticExecutor;
+use crate::types::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Capability that makes a node require bearer authentication
+pub const HTTP_AUTH_CAPABILITY: &str = "http:auth";
+
+/// Incoming HTTP request
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// Request method, e.g. `GET`
+    pub method: String,
+    /// Request path
+    pub path: String,
+    /// Request headers
+    pub headers: HashMap<String, String>,
+    /// Query parameters
+    pub query: HashMap<String, String>,
+    /// Parsed request body
+    pub body: Value,
+    /// Values attached by middleware, passed to the node as inputs
+    pub context: HashMap<String, Value>,
+}
+
+impl HttpRequest {
+    /// Create a request with no headers, query or body
+    pub fn new(method: &str, path: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: HashMap::new(),
+            query: HashMap::new(),
+            body: Value::Null,
+            context: HashMap::new(),
+        }
+    }
+
+    /// Add a header
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Set the body
+    pub fn with_body(mut self, body: Value) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Look up a header, ignoring case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Inputs for the handling node
+    ///
+    /// Fields of an object body come first, then query parameters, then
+    /// middleware context; the whole request is also available as
+    /// `request`.
+    fn to_inputs(&self) -> HashMap<String, Value> {
+        let mut inputs = HashMap::new();
+        if let Value::Object(fields) = &self.body {
+            inputs.extend(fields.clone());
+        }
+        inputs.extend(
+            self.query
+                .iter()
+                .map(|(k, v)| (k.clone(), Value::from(v.as_str()))),
+        );
+        inputs.extend(self.context.clone());
+
+        let strings = |map: &HashMap<String, String>| {
+            Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), Value::from(v.as_str())))
+                    .collect(),
+            )
+        };
+        let mut request = HashMap::new();
+        request.insert("method".to_string(), Value::from(self.method.as_str()));
+        request.insert("path".to_string(), Value::from(self.path.as_str()));
+        request.insert("headers".to_string(), strings(&self.headers));
+        request.insert("query".to_string(), strings(&self.query));
+        request.insert("body".to_string(), self.body.clone());
+        inputs.insert("request".to_string(), Value::Object(request));
+        inputs
+    }
+}
+
+/// Outgoing HTTP response
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    /// Status code
+    pub status: u16,
+    /// Response headers
+    pub headers: HashMap<String, String>,
+    /// Response body
+    pub body: Value,
+}
+
+impl HttpResponse {
+    /// Create a response with no headers
+    pub fn new(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            headers: HashMap::new(),
+            body,
+        }
+    }
+
+    /// Add a header
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Map a node result to a response
+    ///
+    /// `{status, headers, body}` objects, as built by the
+    /// `http_response_builder` operation, are used as-is; any other value
+    /// becomes the body of a 200 response.
+    fn from_result(data: Value) -> Self {
+        if let Value::Object(fields) = &data {
+            if let (Some(Value::Int(status)), Some(body)) =
+                (fields.get("status"), fields.get("body"))
+            {
+                if let Ok(status) = u16::try_from(*status) {
+                    let headers = match fields.get("headers") {
+                        Some(Value::Object(headers)) => headers
+                            .iter()
+                            .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                            .collect(),
+                        _ => HashMap::new(),
+                    };
+                    return Self {
+                        status,
+                        headers,
+                        body: body.clone(),
+                    };
+                }
+            }
+        }
+        Self::new(200, data)
+    }
+}
+
+/// Continuation invoking the rest of the middleware stack
+pub type Next<'a> = &'a dyn Fn(&HttpRequest) -> Result<HttpResponse, String>;
+
+/// Pre- and post-processing around an `HttpHandler` node
+pub trait HttpMiddleware: Send + Sync {
+    /// Handle a request, calling `next` to continue down the stack
+    fn handle(&self, req: &mut HttpRequest, next: Next<'_>) -> Result<HttpResponse, String>;
+}
+
+/// Executor for `HttpHandler` nodes with a middleware stack
+pub struct HttpHandlerExecutor {
+    /// Executor running the nodes
+    executor: SemanticExecutor,
+    /// Middleware applied to every request, outermost first
+    middleware: Vec<Arc<dyn HttpMiddleware>>,
+    /// Authentication installed for nodes requiring `http:auth`
+    auth: Option<Arc<dyn HttpMiddleware>>,
+}
+
+impl HttpHandlerExecutor {
+    /// Wrap an executor with an empty middleware stack
+    pub fn new(executor: SemanticExecutor) -> Self {
+        Self {
+            executor,
+            middleware: Vec::new(),
+            auth: None,
+        }
+    }
+
+    /// Append middleware to the stack
+    pub fn with_middleware(mut self, middleware: Arc<dyn HttpMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Set the authentication installed automatically, innermost, for
+    /// nodes whose security section requires `http:auth`
+    pub fn with_auth(mut self, auth: Arc<dyn HttpMiddleware>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Get the wrapped executor
+    pub fn executor(&self) -> &SemanticExecutor {
+        &self.executor
+    }
+
+    /// Handle a request with the node `node_id`
+    ///
+    /// Node errors become JSON error responses with the error's HTTP
+    /// status; `Err` is reserved for failures of the pipeline itself.
+    pub fn handle(&self, node_id: &str, request: HttpRequest) -> Result<HttpResponse, String> {
+        let node = self
+            .executor
+            .node(node_id)
+            .ok_or_else(|| format!("Node not found: {}", node_id))?;
+
+        let mut stack = self.middleware.clone();
+        let requires_auth = node.security.as_ref().is_some_and(|security| {
+            security
+                .capabilities_required
+                .iter()
+                .any(|c| c == HTTP_AUTH_CAPABILITY)
+        });
+        if requires_auth {
+            let auth = self.auth.clone().ok_or_else(|| {
+                format!(
+                    "Node {} requires {} but no authentication is configured",
+                    node_id, HTTP_AUTH_CAPABILITY
+                )
+            })?;
+            stack.push(auth);
+        }
+
+        let endpoint = |req: &HttpRequest| {
+            Ok(match self.executor.execute(node_id, req.to_inputs()) {
+                Ok(result) => HttpResponse::from_result(result.data.unwrap_or(Value::Null)),
+                Err(e) => HttpResponse::new(e.http_status(), Value::from(e.to_json_error()))
+                    .with_header("Content-Type", "application/json"),
+            })
+        };
+        run_stack(&stack, &request, &endpoint)
+    }
+}
+
+/// Run `req` through `stack`, ending at `endpoint`
+fn run_stack(
+    stack: &[Arc<dyn HttpMiddleware>],
+    req: &HttpRequest,
+    endpoint: Next<'_>,
+) -> Result<HttpResponse, String> {
+    match stack.split_first() {
+        None => endpoint(req),
+        Some((middleware, rest)) => {
+            let mut req = req.clone();
+            middleware.handle(&mut req, &|req| run_stack(rest, req, endpoint))
+        }
+    }
+}
+
+/// Cross-origin resource sharing
+///
+/// Answers preflight `OPTIONS` requests directly and adds
+/// `Access-Control-Allow-Origin` to responses for allowed origins.
+pub struct CorsMiddleware {
+    /// Allowed origins; `*` allows any
+    allowed_origins: Vec<String>,
+    /// Methods advertised to preflight requests
+    allowed_methods: Vec<String>,
+    /// Headers advertised to preflight requests
+    allowed_headers: Vec<String>,
+}
+
+impl CorsMiddleware {
+    /// Allow the given origins with common methods and headers
+    pub fn new(allowed_origins: Vec<String>) -> Self {
+        Self {
+            allowed_origins,
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"]
+                .map(String::from)
+                .to_vec(),
+            allowed_headers: ["Authorization", "Content-Type"].map(String::from).to_vec(),
+        }
+    }
+
+    /// Allow any origin
+    pub fn permissive() -> Self {
+        Self::new(vec!["*".to_string()])
+    }
+
+    /// Replace the advertised methods
+    pub fn with_methods(mut self, methods: Vec<String>) -> Self {
+        self.allowed_methods = methods;
+        self
+    }
+
+    /// Replace the advertised headers
+    pub fn with_headers(mut self, headers: Vec<String>) -> Self {
+        self.allowed_headers = headers;
+        self
+    }
+
+    fn allow_origin(&self, origin: &str) -> Option<String> {
+        if self.allowed_origins.iter().any(|o| o == "*") {
+            Some("*".to_string())
+        } else if self.allowed_origins.iter().any(|o| o == origin) {
+            Some(origin.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+impl HttpMiddleware for CorsMiddleware {
+    fn handle(&self, req: &mut HttpRequest, next: Next<'_>) -> Result<HttpResponse, String> {
+        let allowed = req.header("Origin").and_then(|o| self.allow_origin(o));
+
+        if req.method.eq_ignore_ascii_case("OPTIONS") {
+            let mut response = HttpResponse::new(204, Value::Null);
+            if let Some(origin) = allowed {
+                response = response
+                    .with_header("Access-Control-Allow-Origin", &origin)
+                    .with_header(
+                        "Access-Control-Allow-Methods",
+                        &self.allowed_methods.join(", "),
+                    )
+                    .with_header(
+                        "Access-Control-Allow-Headers",
+                        &self.allowed_headers.join(", "),
+                    )
+                    .with_header("Vary", "Origin");
+            }
+            return Ok(response);
+        }
+
+        let response = next(req)?;
+        Ok(match allowed {
+            Some(origin) => response
+                .with_header("Access-Control-Allow-Origin", &origin)
+                .with_header("Vary", "Origin"),
+            None => response,
+        })
+    }
+}
+
+/// Bearer token authentication with JWT validation
+///
+/// Rejects requests without a valid `Authorization: Bearer` token with
+/// 401; otherwise passes the token's claims to the node as `claims`.
+#[cfg(feature = "jwt")]
+pub struct BearerAuthMiddleware {
+    key: jsonwebtoken::DecodingKey,
+    validation: jsonwebtoken::Validation,
+}
+
+#[cfg(feature = "jwt")]
+impl BearerAuthMiddleware {
+    /// Validate HS256 tokens signed with `secret`
+    pub fn hmac(secret: &[u8]) -> Self {
+        Self::new(
+            jsonwebtoken::DecodingKey::from_secret(secret),
+            jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256),
+        )
+    }
+
+    /// Validate tokens with an explicit key and validation rules
+    pub fn new(key: jsonwebtoken::DecodingKey, validation: jsonwebtoken::Validation) -> Self {
+        Self { key, validation }
+    }
+
+    fn unauthorized(message: &str) -> HttpResponse {
+        HttpResponse::new(
+            401,
+            Value::from(serde_json::json!({ "error": "unauthorized", "message": message })),
+        )
+        .with_header("WWW-Authenticate", "Bearer")
+        .with_header("Content-Type", "application/json")
+    }
+}
+
+#[cfg(feature = "jwt")]
+impl HttpMiddleware for BearerAuthMiddleware {
+    fn handle(&self, req: &mut HttpRequest, next: Next<'_>) -> Result<HttpResponse, String> {
+        let Some(token) = req
+            .header("Authorization")
+            .and_then(|h| h.strip_prefix("Bearer "))
+        else {
+            return Ok(Self::unauthorized("Missing bearer token"));
+        };
+
+        match jsonwebtoken::decode::<serde_json::Value>(token.trim(), &self.key, &self.validation) {
+            Ok(decoded) => {
+                req.context
+                    .insert("claims".to_string(), Value::from(decoded.claims));
+                next(req)
+            }
+            Err(e) => Ok(Self::unauthorized(&format!("Invalid token: {}", e))),
+        }
+    }
+}
+
+/// Request logging
+///
+/// Emits one `tracing` event per request with method, path, status and
+/// duration.
+#[derive(Default)]
+pub struct RequestLoggingMiddleware;
+
+impl RequestLoggingMiddleware {
+    /// Create the middleware
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl HttpMiddleware for RequestLoggingMiddleware {
+    fn handle(&self, req: &mut HttpRequest, next: Next<'_>) -> Result<HttpResponse, String> {
+        let start = std::time::Instant::now();
+        let result = next(req);
+        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+        match &result {
+            Ok(response) => tracing::info!(
+                method = %req.method,
+                path = %req.path,
+                status = response.status,
+                duration_ms,
+                "HTTP request"
+            ),
+            Err(e) => tracing::error!(
+                method = %req.method,
+                path = %req.path,
+                error = %e,
+                duration_ms,
+                "HTTP request failed"
+            ),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    fn handler(security: &str) -> HttpHandlerExecutor {
+        let yaml = format!(
+            r#"
+node_id: greet_v1
+type: http_handler
+intent: greet a caller
+
+inputs:
+  name:
+    type: string
+{}
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello, {{name}}!"
+"#,
+            security
+        );
+        let mut executor = SemanticExecutor::new();
+        executor.register(VesperLoader::new().load_string(&yaml).unwrap());
+        HttpHandlerExecutor::new(executor)
+    }
+
+    fn greet_request() -> HttpRequest {
+        let mut body = HashMap::new();
+        body.insert("name".to_string(), Value::from("Ada"));
+        HttpRequest::new("POST", "/greet")
+            .with_header("Origin", "https://app.example")
+            .with_body(Value::Object(body))
+    }
+
+    #[test]
+    fn test_cors_middleware() {
+        let handler = handler("").with_middleware(Arc::new(CorsMiddleware::new(vec![
+            "https://app.example".to_string(),
+        ])));
+
+        let response = handler.handle("greet_v1", greet_request()).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, Value::from("Hello, Ada!"));
+        assert_eq!(
+            response.headers["Access-Control-Allow-Origin"],
+            "https://app.example"
+        );
+
+        let preflight =
+            HttpRequest::new("OPTIONS", "/greet").with_header("Origin", "https://evil.example");
+        let response = handler.handle("greet_v1", preflight).unwrap();
+        assert_eq!(response.status, 204);
+        assert!(!response.headers.contains_key("Access-Control-Allow-Origin"));
+    }
+
+    #[test]
+    fn test_node_errors_become_responses() {
+        let handler = handler("").with_middleware(Arc::new(RequestLoggingMiddleware::new()));
+
+        let response = handler
+            .handle("greet_v1", HttpRequest::new("POST", "/greet"))
+            .unwrap();
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn test_http_auth_requires_configured_auth() {
+        let handler = handler("security:\n  capabilities_required: [\"http:auth\"]\n");
+        assert!(handler.handle("greet_v1", greet_request()).is_err());
+    }
+
+    #[cfg(feature = "jwt")]
+    #[test]
+    fn test_bearer_auth_is_installed_for_http_auth() {
+        let secret = b"handler-secret";
+        let handler = handler("security:\n  capabilities_required: [\"http:auth\"]\n")
+            .with_auth(Arc::new(BearerAuthMiddleware::hmac(secret)));
+
+        let response = handler.handle("greet_v1", greet_request()).unwrap();
+        assert_eq!(response.status, 401);
+
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "sub": "ada", "exp": 4_000_000_000u64 }),
+            &jsonwebtoken::EncodingKey::from_secret(secret),
+        )
+        .unwrap();
+        let request = greet_request().with_header("Authorization", &format!("Bearer {}", token));
+        let response = handler.handle("greet_v1", request).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, Value::from("Hello, Ada!"));
+    }
+}
@@ -0,0 +1,68 @@
+//! Stores of completed results for the `idempotency_key` operation
+
+use crate::types::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Results of protected steps, by idempotency key
+pub trait IdempotencyStore: Send + Sync {
+    /// Result stored under `key`, or `None` if absent or expired
+    fn get(&self, key: &str) -> Option<Value>;
+
+    /// Store the result for `key`, optionally forgetting it after `ttl`
+    fn put(&self, key: &str, result: Value, ttl: Option<Duration>);
+}
+
+/// Process-local idempotency store, for tests and single instances
+pub struct InMemoryIdempotencyStore {
+    /// Stored results with their optional expiry deadline
+    results: Mutex<HashMap<String, (Value, Option<Instant>)>>,
+}
+
+impl InMemoryIdempotencyStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self {
+            results: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of stored results, including expired ones not yet evicted
+    pub fn len(&self) -> usize {
+        self.results.lock().unwrap().len()
+    }
+
+    /// Check if the store holds no results
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for InMemoryIdempotencyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    fn get(&self, key: &str) -> Option<Value> {
+        let mut results = self.results.lock().unwrap();
+        match results.get(key) {
+            Some((_, Some(deadline))) if Instant::now() >= *deadline => {
+                results.remove(key);
+                None
+            }
+            Some((result, _)) => Some(result.clone()),
+            None => None,
+        }
+    }
+
+    fn put(&self, key: &str, result: Value, ttl: Option<Duration>) {
+        let deadline = ttl.map(|ttl| Instant::now() + ttl);
+        self.results
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (result, deadline));
+    }
+}
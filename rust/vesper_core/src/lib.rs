@@ -4,13 +4,44 @@
 //! This crate provides direct execution of Vesper specifications
 //! without intermediate Python code generation.
 
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+pub mod async_executor;
+pub mod builder;
+pub mod cache;
+pub mod checkpoints;
 pub mod contracts;
+pub mod currency;
+pub mod database;
 pub mod error;
+pub mod event_store;
+pub mod events;
 pub mod executor;
+pub mod experiments;
+pub mod feature_flags;
+pub mod handler;
+pub mod idempotency;
 pub mod loader;
+pub mod locks;
+pub mod models;
+pub mod node_diff;
+pub mod queue;
+pub mod remote;
+pub mod schema_infer;
+pub mod secrets;
 pub mod types;
+pub mod workflow_state;
+
+// Applications choose the allocator; the tests need jemalloc to measure
+#[cfg(all(test, feature = "memory-tracking"))]
+#[global_allocator]
+static ALLOCATOR: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 pub use error::{Result, VesperError};
 pub use executor::SemanticExecutor;
 pub use loader::VesperLoader;
 pub use types::{Value, VesperNode};
+
+// Used by code generated with `vesper_macros::vesper_node!`
+#[doc(hidden)]
+pub use serde_yaml;
@@ -1,44 +1,156 @@
 //! Vesper specification loader
 
+pub(crate) mod analysis;
+#[cfg(feature = "binary-format")]
+pub(crate) mod binary;
+
 use crate::error::{Result, VesperError};
-use crate::types::VesperNode;
+use crate::executor::BUILTIN_OPERATIONS;
+use crate::types::{VesperNode, BUILTIN_TYPES};
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::OnceLock;
+
+#[cfg(feature = "binary-format")]
+pub use binary::{BINARY_EXTENSION, BINARY_FORMAT_VERSION};
+#[cfg(feature = "tree-sitter")]
+pub use vesper_tree_sitter::SyntaxError;
 
 /// Loads Vesper specification files
 pub struct VesperLoader {
     /// Base path for resolving relative imports
-    #[allow(dead_code)]
     base_path: Option<std::path::PathBuf>,
+    /// Whether analysis warnings fail validation
+    strict: bool,
+    /// Environment selecting which `imports` entry to merge
+    environment: Option<String>,
+    /// Operations accepted besides the built-in ones
+    custom_operations: HashSet<String>,
 }
 
 impl VesperLoader {
     /// Create a new loader
     pub fn new() -> Self {
-        Self { base_path: None }
+        Self {
+            base_path: None,
+            strict: false,
+            environment: None,
+            custom_operations: HashSet::new(),
+        }
     }
 
     /// Create a loader with a base path
     pub fn with_base_path<P: AsRef<Path>>(path: P) -> Self {
         Self {
             base_path: Some(path.as_ref().to_path_buf()),
+            strict: false,
+            environment: None,
+            custom_operations: HashSet::new(),
         }
     }
 
+    /// Reject nodes with analysis warnings instead of logging them
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Accept these operation names in flow steps besides the built-in ones
+    pub fn with_custom_operations<I, S>(mut self, operations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.custom_operations
+            .extend(operations.into_iter().map(Into::into));
+        self
+    }
+
+    /// Merge the `imports` entry for this environment into loaded nodes
+    pub fn with_environment(mut self, environment: &str) -> Self {
+        self.environment = Some(environment.to_string());
+        self
+    }
+
     /// Load a Vesper node from a file
+    ///
+    /// With the `binary-format` feature, a binary file saved next to the
+    /// YAML file with the `vspb` extension is loaded instead if it is newer.
     pub fn load_file<P: AsRef<Path>>(&self, path: P) -> Result<VesperNode> {
+        #[cfg(feature = "binary-format")]
+        if let Some(node) = self.load_fresh_binary(path.as_ref()) {
+            return Ok(node);
+        }
         let content = std::fs::read_to_string(path)?;
         self.load_string(&content)
     }
 
     /// Load a Vesper node from a YAML string
     pub fn load_string(&self, content: &str) -> Result<VesperNode> {
-        let node: VesperNode = serde_yaml::from_str(content)?;
+        let mut spec: serde_yaml::Value = serde_yaml::from_str(content)?;
+        self.apply_environment_import(&mut spec)?;
+        let node: VesperNode = serde_yaml::from_value(spec)?;
         self.validate(&node)?;
         Ok(node)
     }
 
+    /// Check the structure of a YAML spec without loading it
+    ///
+    /// Unlike `load_string`, which stops at the first deserialization
+    /// failure, this reports every YAML syntax error and every malformed
+    /// field with its line and column. Imports and semantic rules are not
+    /// checked; an empty list means the spec is structurally sound.
+    #[cfg(feature = "tree-sitter")]
+    pub fn validate_syntax(&self, content: &str) -> Result<Vec<SyntaxError>> {
+        vesper_tree_sitter::validate(content).map_err(VesperError::ParseError)
+    }
+
+    /// Merge the spec imported for the active environment over `spec`
+    ///
+    /// Imports name a YAML file or a node ID, loaded from `<node_id>.yaml`;
+    /// relative paths resolve against the base path. The imported spec's
+    /// fields override the base recursively, except `node_id` and `imports`.
+    fn apply_environment_import(&self, spec: &mut serde_yaml::Value) -> Result<()> {
+        let Some(environment) = &self.environment else {
+            return Ok(());
+        };
+        let Some(target) = spec
+            .get("imports")
+            .and_then(|imports| imports.get(environment.as_str()))
+            .and_then(|target| target.as_str())
+        else {
+            return Ok(());
+        };
+
+        let file = if target.ends_with(".yaml") || target.ends_with(".yml") {
+            std::path::PathBuf::from(target)
+        } else {
+            std::path::PathBuf::from(format!("{}.yaml", target))
+        };
+        let file = match &self.base_path {
+            Some(base) if file.is_relative() => base.join(file),
+            _ => file,
+        };
+        let import_error = |message: String| VesperError::ValidationError {
+            path: format!("imports.{}", environment),
+            message,
+        };
+        let content = std::fs::read_to_string(&file)
+            .map_err(|e| import_error(format!("Cannot read {}: {}", file.display(), e)))?;
+        let mut overrides: serde_yaml::Value = serde_yaml::from_str(&content)
+            .map_err(|e| import_error(format!("Cannot parse {}: {}", file.display(), e)))?;
+
+        if let serde_yaml::Value::Mapping(fields) = &mut overrides {
+            fields.remove("node_id");
+            fields.remove("imports");
+        }
+        tracing::debug!("Merging {} import from {}", environment, file.display());
+        merge_yaml(spec, overrides);
+        Ok(())
+    }
+
     /// Validate a loaded node
-    fn validate(&self, node: &VesperNode) -> Result<()> {
+    pub(crate) fn validate(&self, node: &VesperNode) -> Result<()> {
         // Validate node_id format
         if !node.node_id.contains("_v") {
             return Err(VesperError::ValidationError {
@@ -60,13 +172,96 @@ impl VesperLoader {
             }
         }
 
+        // Flag output types that cannot be checked by `assert_schema`
+        for (name, field) in node
+            .outputs
+            .iter()
+            .flat_map(|outputs| outputs.success.iter().chain(&outputs.error))
+        {
+            if let Some(output_type) = &field.output_type {
+                if !is_known_type(node, output_type) {
+                    tracing::warn!("outputs.{}: Unrecognized type {}", name, output_type);
+                }
+            }
+        }
+
         // Validate flow is not empty
         if node.flow.is_empty() {
             tracing::warn!("Node {} has no flow steps defined", node.node_id);
         }
 
+        // Reject references to variables that cannot exist at runtime
+        if let Some((path, name)) = analysis::undefined_references(node).into_iter().next() {
+            return Err(VesperError::ValidationError {
+                path,
+                message: format!("Undefined variable: {}", name),
+            });
+        }
+
+        // Flag operations nothing dispatches, such as misspelled ones
+        for (path, step) in analysis::flatten_steps(&node.flow) {
+            let operation = step.operation.as_str();
+            if !builtin_operations().contains(operation)
+                && !self.custom_operations.contains(operation)
+            {
+                self.warn_or_reject(
+                    format!("{}.operation", path),
+                    format!("Unknown operation: {}", operation),
+                )?;
+            }
+        }
+
+        // Flag step outputs that are never read
+        for (path, output) in analysis::unused_outputs(node) {
+            self.warn_or_reject(path, format!("Output {} is never used", output))?;
+        }
+
         Ok(())
     }
+
+    /// Log an analysis finding, or fail with it in strict mode
+    fn warn_or_reject(&self, path: String, message: String) -> Result<()> {
+        if self.strict {
+            return Err(VesperError::ValidationError { path, message });
+        }
+        tracing::warn!("{}: {}", path, message);
+        Ok(())
+    }
+}
+
+/// Operations the executor dispatches
+fn builtin_operations() -> &'static HashSet<&'static str> {
+    static OPERATIONS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    OPERATIONS.get_or_init(|| BUILTIN_OPERATIONS.iter().copied().collect())
+}
+
+/// Whether `name` is a built-in type, a custom type of `node` or an
+/// `array<T>` of either
+fn is_known_type(node: &VesperNode, name: &str) -> bool {
+    match name
+        .strip_prefix("array<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        Some(element) => is_known_type(node, element.trim()),
+        None => BUILTIN_TYPES.contains(&name) || node.types.contains_key(name),
+    }
+}
+
+/// Recursively merge `overrides` into `base`, overriding non-mapping values
+fn merge_yaml(base: &mut serde_yaml::Value, overrides: serde_yaml::Value) {
+    match (base, overrides) {
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
 }
 
 impl Default for VesperLoader {
@@ -125,4 +320,326 @@ flow: []
 
         assert!(result.is_err());
     }
+
+    const UNUSED_TEMP: &str = r#"
+node_id: total_v1
+type: function
+intent: compute a total
+
+inputs:
+  a:
+    type: integer
+  b:
+    type: integer
+
+flow:
+  - step: scratch
+    operation: arithmetic
+    expression: "a * 2"
+    output: temp
+  - step: total
+    operation: arithmetic
+    expression: "a + b"
+    output: result
+"#;
+
+    #[test]
+    fn test_unused_output_is_rejected_in_strict_mode() {
+        assert!(VesperLoader::new().load_string(UNUSED_TEMP).is_ok());
+
+        let result = VesperLoader::new().strict().load_string(UNUSED_TEMP);
+        let Err(VesperError::ValidationError { path, message }) = result else {
+            panic!("expected validation error");
+        };
+        assert_eq!(path, "flow[0].output");
+        assert!(message.contains("temp"));
+    }
+
+    #[test]
+    fn test_unknown_operation_is_rejected_in_strict_mode() {
+        let yaml = UNUSED_TEMP.replace("a + b", "temp + b");
+        let typo = yaml.replacen("operation: arithmetic", "operation: arithmatic", 1);
+        assert!(VesperLoader::new().load_string(&typo).is_ok());
+
+        let result = VesperLoader::new().strict().load_string(&typo);
+        let Err(VesperError::ValidationError { path, message }) = result else {
+            panic!("expected validation error");
+        };
+        assert_eq!(path, "flow[0].operation");
+        assert_eq!(message, "Unknown operation: arithmatic");
+
+        let custom = VesperLoader::new()
+            .strict()
+            .with_custom_operations(["arithmatic"]);
+        assert!(custom.load_string(&typo).is_ok());
+    }
+
+    #[test]
+    fn test_used_outputs_pass_strict_mode() {
+        let yaml = UNUSED_TEMP.replace("a + b", "temp + b");
+        assert!(VesperLoader::new().strict().load_string(&yaml).is_ok());
+    }
+
+    #[test]
+    fn test_undefined_variable_is_rejected() {
+        let yaml = UNUSED_TEMP.replace("a + b", "temp + bb");
+        let result = VesperLoader::new().load_string(&yaml);
+        let Err(VesperError::ValidationError { path, message }) = result else {
+            panic!("expected validation error");
+        };
+        assert_eq!(path, "flow[1].expression");
+        assert_eq!(message, "Undefined variable: bb");
+    }
+
+    #[test]
+    fn test_variable_used_before_definition_is_rejected() {
+        let yaml = UNUSED_TEMP.replace("a * 2", "result * 2");
+        let result = VesperLoader::new().load_string(&yaml);
+        assert!(matches!(
+            result,
+            Err(VesperError::ValidationError { path, .. }) if path == "flow[0].expression"
+        ));
+    }
+
+    #[test]
+    fn test_nested_sub_step_outputs_are_defined() {
+        let yaml = r#"
+node_id: fastest_v1
+type: function
+intent: answer from the fastest mirror
+
+inputs:
+  query:
+    type: string
+
+flow:
+  - step: fetch
+    operation: race
+    parameters:
+      steps:
+        - operation: string_template
+          template: "primary: {query}"
+          output: answer
+        - operation: conditional
+          condition: "query == 'ping'"
+          then:
+            - operation: string_template
+              template: "pong"
+              output: answer
+  - step: report
+    operation: string_template
+    template: "{answer}"
+"#;
+        assert!(VesperLoader::new().strict().load_string(yaml).is_ok());
+    }
+
+    #[test]
+    fn test_input_transform_outputs_are_defined() {
+        let yaml = r#"
+node_id: scaled_v1
+type: function
+intent: scale a derived input
+
+inputs:
+  a:
+    type: integer
+
+input_transform:
+  - step: derive
+    operation: arithmetic
+    expression: "a * 10"
+    output: computed
+
+flow:
+  - step: scale
+    operation: arithmetic
+    expression: "computed + 1"
+    output: result
+"#;
+        assert!(VesperLoader::new().strict().load_string(yaml).is_ok());
+    }
+
+    #[test]
+    fn test_distributed_lock_sub_step_outputs_are_defined() {
+        let yaml = r#"
+node_id: reserve_v1
+type: function
+intent: reserve a seat under a lock
+
+inputs:
+  seat:
+    type: string
+
+flow:
+  - step: reserve
+    operation: distributed_lock
+    parameters:
+      resource: "seat-{seat}"
+      steps:
+        - operation: string_template
+          template: "reserved {seat}"
+          output: reservation
+  - step: confirm
+    operation: string_template
+    template: "{reservation}"
+"#;
+        assert!(VesperLoader::new().strict().load_string(yaml).is_ok());
+    }
+
+    #[test]
+    fn test_idempotency_key_sub_step_outputs_are_defined() {
+        let yaml = r#"
+node_id: charge_v1
+type: function
+intent: charge a card once per request
+
+inputs:
+  request_id:
+    type: string
+  amount:
+    type: integer
+
+flow:
+  - step: charge
+    operation: idempotency_key
+    parameters:
+      key: request_id
+      steps:
+        - operation: arithmetic
+          expression: "amount * 100"
+          output: cents
+  - step: receipt
+    operation: string_template
+    template: "charged {cents}"
+"#;
+        assert!(VesperLoader::new().strict().load_string(yaml).is_ok());
+    }
+
+    #[test]
+    fn test_cqrs_handler_sub_step_outputs_are_defined() {
+        let yaml = r#"
+node_id: rename_v1
+type: function
+intent: rename a user and read the result back
+
+inputs:
+  name:
+    type: string
+
+flow:
+  - step: rename
+    operation: cqrs_command
+    parameters:
+      event_type: user_renamed
+      steps:
+        - operation: string_template
+          template: "{name}"
+          output: new_name
+  - step: lookup
+    operation: cqrs_query
+    parameters:
+      steps:
+        - operation: string_template
+          template: "user {new_name}"
+          output: profile
+  - step: report
+    operation: string_template
+    template: "{new_name}: {profile}"
+"#;
+        assert!(VesperLoader::new().strict().load_string(yaml).is_ok());
+    }
+
+    #[test]
+    fn test_environment_import_overrides_base() {
+        let dir = std::env::temp_dir().join(format!("vesper-imports-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("report_prod.yaml"),
+            "performance:\n  timeout_seconds: 120\n",
+        )
+        .unwrap();
+        let yaml = r#"
+node_id: report_v1
+type: function
+intent: build a report
+
+imports:
+  prod: report_prod.yaml
+
+performance:
+  expected_latency_ms: 50
+  timeout_seconds: 10
+
+flow:
+  - step: build
+    operation: string_template
+    template: "report"
+"#;
+
+        let base = VesperLoader::with_base_path(&dir)
+            .load_string(yaml)
+            .unwrap();
+        let prod = VesperLoader::with_base_path(&dir)
+            .with_environment("prod")
+            .load_string(yaml)
+            .unwrap();
+        let dev = VesperLoader::with_base_path(&dir)
+            .with_environment("dev")
+            .load_string(yaml)
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let timeout = |node: &VesperNode| node.performance.as_ref().unwrap().timeout_seconds;
+        assert_eq!(timeout(&base), Some(10));
+        assert_eq!(timeout(&dev), Some(10));
+        assert_eq!(timeout(&prod), Some(120));
+        assert_eq!(prod.node_id, "report_v1");
+        assert_eq!(prod.performance.unwrap().expected_latency_ms, Some(50));
+    }
+
+    #[test]
+    fn test_known_output_types() {
+        let yaml = r#"
+node_id: types_v1
+type: function
+intent: declare a custom type
+types:
+  Money:
+    fields:
+      amount: decimal
+flow: []
+"#;
+        let node = VesperLoader::new().load_string(yaml).unwrap();
+        for known in [
+            "string",
+            "decimal",
+            "Money",
+            "array<Money>",
+            "array<integer>",
+        ] {
+            assert!(is_known_type(&node, known), "{}", known);
+        }
+        for unknown in ["str", "Currency", "array<Currency>"] {
+            assert!(!is_known_type(&node, unknown), "{}", unknown);
+        }
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    #[test]
+    fn test_validate_syntax_locates_errors() {
+        let yaml = r#"
+node_id: greet_v1
+type: function
+
+flow:
+  - step: greet
+    operation: [string_template]
+"#;
+        let errors = VesperLoader::new().validate_syntax(yaml).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "7:16: flow[0].operation: expected a string, found a sequence"
+        );
+    }
 }
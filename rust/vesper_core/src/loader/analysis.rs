@@ -0,0 +1,586 @@
+//! Static analysis of flow variables
+//!
+//! References are found syntactically: identifiers in expressions and
+//! conditions, `{name}` placeholders, and parameter values that are bare
+//! variable names. This over-approximates uses, which keeps false alarms
+//! about unused outputs rare.
+
+use crate::types::{FlowStep, VesperNode};
+use std::collections::HashSet;
+
+/// Words in expressions and conditions that are never variables
+const KEYWORDS: &[&str] = &[
+    "and", "or", "not", "in", "is", "if", "else", "true", "false", "True", "False", "null", "None",
+];
+
+/// Every step of a flow in execution order, with its path
+///
+/// Nested `then` / `else` and `on_success` / `on_error` steps follow the
+/// step that contains them.
+pub(super) fn flatten_steps(flow: &[FlowStep]) -> Vec<(String, &FlowStep)> {
+    fn walk<'a>(steps: &'a [FlowStep], prefix: &str, out: &mut Vec<(String, &'a FlowStep)>) {
+        for (index, step) in steps.iter().enumerate() {
+            let path = format!("{}[{}]", prefix, index);
+            out.push((path.clone(), step));
+            walk(&step.then_steps, &format!("{}.then", path), out);
+            walk(&step.else_steps, &format!("{}.else", path), out);
+            for (handler, name) in [
+                (&step.on_success, "on_success"),
+                (&step.on_error, "on_error"),
+            ] {
+                if let Some(handler) = handler {
+                    walk(handler, &format!("{}.{}", path, name), out);
+                }
+            }
+        }
+    }
+
+    let mut steps = Vec::new();
+    walk(flow, "flow", &mut steps);
+    steps
+}
+
+/// Identifiers referenced by an expression or condition
+///
+/// Skips quoted strings, numbers, keywords, attribute names after `.` and
+/// function names before `(`.
+pub(crate) fn expression_identifiers(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut identifiers = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' || c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += 1;
+            }
+            i += 1;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let after_dot = start > 0 && chars[start - 1] == '.';
+            let before_call = chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&'(');
+            if !after_dot && !before_call && !KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(&word))
+            {
+                identifiers.push(word);
+            }
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    identifiers
+}
+
+/// Identifiers inside `{...}` placeholders of a template
+pub(super) fn placeholder_identifiers(text: &str) -> Vec<String> {
+    let mut identifiers = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        identifiers.extend(expression_identifiers(&rest[open + 1..open + close]));
+        rest = &rest[open + close + 1..];
+    }
+    identifiers
+}
+
+/// Whether `text` could name a variable
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Variables a YAML parameter value may refer to
+fn yaml_references(value: &serde_yaml::Value, out: &mut HashSet<String>) {
+    match value {
+        serde_yaml::Value::String(s) => {
+            if is_identifier(s) {
+                out.insert(s.clone());
+            }
+            out.extend(placeholder_identifiers(s));
+        }
+        serde_yaml::Value::Sequence(items) => {
+            for item in items {
+                yaml_references(item, out);
+            }
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            for value in mapping.values() {
+                yaml_references(value, out);
+            }
+        }
+        serde_yaml::Value::Tagged(tagged) => yaml_references(&tagged.value, out),
+        _ => {}
+    }
+}
+
+/// Variables a single step (excluding nested steps) may read
+pub(crate) fn step_references(step: &FlowStep) -> HashSet<String> {
+    let mut refs = HashSet::new();
+    for text in step
+        .expression
+        .iter()
+        .chain(&step.condition)
+        .chain(&step.guards)
+    {
+        refs.extend(expression_identifiers(text));
+    }
+    if let Some(template) = &step.template {
+        refs.extend(placeholder_identifiers(template));
+    }
+    for value in step.parameters.values() {
+        yaml_references(value, &mut refs);
+    }
+    for returned in step.return_success.iter().chain(&step.return_error) {
+        for value in returned.values() {
+            yaml_references(value, &mut refs);
+        }
+    }
+    if let Some(handler) = &step.on_failure {
+        yaml_references(handler, &mut refs);
+    }
+    refs
+}
+
+/// Step outputs that nothing reads, with the path of the defining step
+///
+/// Outputs are used if another step references them, a postcondition
+/// mentions them, they are declared node outputs, or they hold the result
+/// of the final step.
+pub(super) fn unused_outputs(node: &VesperNode) -> Vec<(String, String)> {
+    let steps = flatten_steps(&node.flow);
+    let references: Vec<HashSet<String>> = steps
+        .iter()
+        .map(|(_, step)| step_references(step))
+        .collect();
+
+    let mut used: HashSet<String> = HashSet::new();
+    if let Some(contracts) = &node.contracts {
+        for condition in &contracts.postconditions {
+            used.extend(expression_identifiers(condition));
+        }
+    }
+    if let Some(outputs) = &node.outputs {
+        used.extend(outputs.success.keys().cloned());
+        used.extend(outputs.error.keys().cloned());
+    }
+    if let Some(output) = node.flow.last().and_then(|step| step.output.as_ref()) {
+        used.insert(output.clone());
+    }
+
+    steps
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (path, step))| {
+            let output = step.output.as_ref()?;
+            let read = used.contains(output)
+                || references
+                    .iter()
+                    .enumerate()
+                    .any(|(other, refs)| other != index && refs.contains(output));
+            (!read).then(|| (format!("{}.output", path), output.clone()))
+        })
+        .collect()
+}
+
+/// Steps nested in the parameters of a meta-operation, such as the
+/// `steps` of `parallel` or the `try` / `catch` / `finally` of `try_catch`
+///
+/// Every sequence parameter whose entries are step definitions counts; the
+/// entries of a `saga` contribute their forward `step` and their
+/// `compensate` step.
+pub(crate) fn parameter_steps(step: &FlowStep) -> Vec<FlowStep> {
+    let mut steps = Vec::new();
+    for value in step.parameters.values() {
+        let serde_yaml::Value::Sequence(entries) = value else {
+            continue;
+        };
+        for entry in entries {
+            let stages: Vec<&serde_yaml::Value> = ["step", "compensate"]
+                .iter()
+                .filter_map(|key| entry.get(key))
+                .filter(|inner| inner.is_mapping())
+                .collect();
+            let definitions = if stages.is_empty() {
+                vec![entry]
+            } else {
+                stages
+            };
+            for definition in definitions {
+                if definition.get("operation").is_none() {
+                    continue;
+                }
+                if let Ok(sub_step) = serde_yaml::from_value(definition.clone()) {
+                    steps.push(sub_step);
+                }
+            }
+        }
+    }
+    steps
+}
+
+/// Every step of a node, including its input and output transforms and
+/// the steps nested in other steps, each followed by those nested in it
+#[cfg_attr(not(feature = "handlebars"), allow(dead_code))]
+pub(crate) fn all_steps(node: &VesperNode) -> Vec<FlowStep> {
+    fn walk(steps: &[FlowStep], out: &mut Vec<FlowStep>) {
+        for (_, step) in flatten_steps(steps) {
+            out.push(step.clone());
+            walk(&parameter_steps(step), out);
+        }
+    }
+
+    let mut steps = Vec::new();
+    for flow in [
+        node.input_transform.as_deref().unwrap_or_default(),
+        &node.flow,
+        node.output_transform.as_deref().unwrap_or_default(),
+    ] {
+        walk(flow, &mut steps);
+    }
+    steps
+}
+
+/// Variables a step defines once it has run
+pub(crate) fn defined_by(step: &FlowStep) -> Vec<String> {
+    let mut defined: Vec<String> = step.output.iter().cloned().collect();
+    if let Some(cursor) = step
+        .parameters
+        .get("cursor_variable")
+        .and_then(|c| c.as_str())
+    {
+        defined.push(cursor.to_string());
+    }
+    // Sub-steps of the meta-operations write their outputs to the context
+    for sub_step in parameter_steps(step) {
+        for (_, inner) in flatten_steps(std::slice::from_ref(&sub_step)) {
+            defined.extend(defined_by(inner));
+        }
+    }
+    if step.operation == "try_catch" {
+        let error_variable = step
+            .parameters
+            .get("error_variable")
+            .and_then(|v| v.as_str())
+            .unwrap_or("error");
+        defined.push(error_variable.to_string());
+    }
+    defined
+}
+
+/// Operations with effects beyond the variables of the flow, such as
+/// writing to a database, calling a service or holding a lock
+const SIDE_EFFECT_OPERATIONS: &[&str] = &[
+    "notify",
+    "cache_set",
+    "oauth2_token_exchange",
+    "graphql_query",
+    "grpc_call",
+    "kubernetes_api",
+    "s3_put",
+    "prometheus_push",
+    "structured_log",
+    "opentelemetry_baggage_set",
+    "trace_span_start",
+    "trace_span_end",
+    "compensating_transaction",
+    "approval_gate",
+    "scatter_gather",
+    "workflow_orchestrator",
+    "fuzzer",
+    "distributed_lock",
+    "idempotency_key",
+    "event_sourcing_append",
+    "cqrs_command",
+    "cqrs_query",
+    "load_balance",
+    "model_invoke",
+    "text_embed",
+    "structured_extract",
+    "a_b_test",
+    "shadow_mode",
+    "message_queue_publish",
+    "message_queue_consume",
+    "database_execute",
+    "write_file",
+];
+
+/// Whether a step, or any step nested in it, has side effects
+fn has_side_effects(step: &FlowStep) -> bool {
+    flatten_steps(std::slice::from_ref(step))
+        .into_iter()
+        .any(|(_, inner)| {
+            SIDE_EFFECT_OPERATIONS.contains(&inner.operation.as_str())
+                || parameter_steps(inner).iter().any(has_side_effects)
+        })
+}
+
+/// Group the top-level steps of a flow into dependency levels
+///
+/// A step joins the level after the latest earlier step it reads from,
+/// writes over, or whose reads it would overwrite, so the steps within a
+/// level share no variables and may run in any order. Nested steps count
+/// towards the step containing them. A step with side effects is ordered
+/// against every other step, since its effects may be observed without
+/// sharing a variable: it gets a level of its own after all earlier
+/// steps, and later steps come after it. Levels list step indices in flow
+/// order.
+#[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+pub(crate) fn dependency_levels(flow: &[FlowStep]) -> Vec<Vec<usize>> {
+    let usage: Vec<(HashSet<String>, HashSet<String>)> = flow
+        .iter()
+        .map(|step| {
+            let mut reads = HashSet::new();
+            let mut writes = HashSet::new();
+            for (_, inner) in flatten_steps(std::slice::from_ref(step)) {
+                reads.extend(step_references(inner));
+                writes.extend(defined_by(inner));
+            }
+            (reads, writes)
+        })
+        .collect();
+
+    let mut level_of: Vec<usize> = Vec::with_capacity(flow.len());
+    let mut levels: Vec<Vec<usize>> = Vec::new();
+    // Earliest level after the latest step with side effects
+    let mut floor = 0;
+    for (index, (reads, writes)) in usage.iter().enumerate() {
+        let level = if has_side_effects(&flow[index]) {
+            let level = levels.len();
+            floor = level + 1;
+            level
+        } else {
+            usage[..index]
+                .iter()
+                .zip(&level_of)
+                .filter(|((earlier_reads, earlier_writes), _)| {
+                    !earlier_writes.is_disjoint(reads)
+                        || !earlier_writes.is_disjoint(writes)
+                        || !earlier_reads.is_disjoint(writes)
+                })
+                .map(|(_, level)| level + 1)
+                .max()
+                .unwrap_or(0)
+                .max(floor)
+        };
+        level_of.push(level);
+        if level == levels.len() {
+            levels.push(Vec::new());
+        }
+        levels[level].push(index);
+    }
+    levels
+}
+
+/// References to variables that are neither inputs nor defined by an
+/// earlier step, as `(path, variable)` pairs
+///
+/// Outputs of the input transform steps count as inputs. Checks each
+/// step's `expression`, `condition`, `template` and `parameters["value"]`.
+pub(super) fn undefined_references(node: &VesperNode) -> Vec<(String, String)> {
+    let mut defined: HashSet<String> = node.inputs.keys().cloned().collect();
+    for (_, step) in flatten_steps(node.input_transform.as_deref().unwrap_or_default()) {
+        defined.extend(defined_by(step));
+    }
+    let mut undefined = Vec::new();
+
+    for (path, step) in flatten_steps(&node.flow) {
+        let mut checked: Vec<(&str, Vec<String>)> = Vec::new();
+        if let Some(expression) = &step.expression {
+            checked.push(("expression", expression_identifiers(expression)));
+        }
+        if let Some(condition) = &step.condition {
+            checked.push(("condition", expression_identifiers(condition)));
+        }
+        if let Some(template) = &step.template {
+            checked.push(("template", placeholder_identifiers(template)));
+        }
+        if let Some(serde_yaml::Value::String(value)) = step.parameters.get("value") {
+            let mut names = placeholder_identifiers(value);
+            if is_identifier(value) {
+                names.push(value.clone());
+            }
+            checked.push(("parameters.value", names));
+        }
+
+        for (field, names) in checked {
+            for name in names {
+                if !defined.contains(&name) {
+                    undefined.push((format!("{}.{}", path, field), name));
+                }
+            }
+        }
+        defined.extend(defined_by(step));
+    }
+    undefined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    #[test]
+    fn test_expression_identifiers() {
+        assert_eq!(
+            expression_identifiers("len(items) > 2 and user.age >= limit or name == 'bob'"),
+            vec!["items", "user", "limit", "name"]
+        );
+        assert_eq!(
+            expression_identifiers("idempotency_key IS NOT NULL"),
+            vec!["idempotency_key"]
+        );
+    }
+
+    #[test]
+    fn test_dependency_levels() {
+        let node = VesperLoader::new()
+            .load_string(
+                r#"
+node_id: levels_v1
+type: function
+intent: group independent steps
+
+inputs:
+  a:
+    type: integer
+  b:
+    type: integer
+
+flow:
+  - step: double_a
+    operation: arithmetic
+    expression: "a * 2"
+    output: x
+  - step: double_b
+    operation: arithmetic
+    expression: "b * 2"
+    output: y
+  - step: total
+    operation: arithmetic
+    expression: "x + y"
+    output: total
+  - step: shadow
+    operation: arithmetic
+    expression: "b + 1"
+    output: y
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            dependency_levels(&node.flow),
+            vec![vec![0, 1], vec![2], vec![3]]
+        );
+    }
+
+    #[test]
+    fn test_side_effects_order_levels() {
+        let node = VesperLoader::new()
+            .load_string(
+                r#"
+node_id: ledger_v1
+type: function
+intent: record an entry and count the entries
+
+flow:
+  - step: before
+    operation: database_query
+    parameters:
+      sql: SELECT COUNT(*) FROM ledger
+    output: before
+  - step: insert
+    operation: database_execute
+    parameters:
+      sql: INSERT INTO ledger VALUES (1)
+  - step: after
+    operation: database_query
+    parameters:
+      sql: SELECT COUNT(*) FROM ledger
+    output: after
+  - step: audit
+    operation: try_catch
+    parameters:
+      try:
+        - operation: notify
+          parameters:
+            message: recorded
+  - step: total
+    operation: database_query
+    parameters:
+      sql: SELECT SUM(amount) FROM ledger
+    output: total
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            dependency_levels(&node.flow),
+            vec![vec![0], vec![1], vec![2], vec![3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn test_saga_compensation_and_model_calls_order_levels() {
+        let node = VesperLoader::new()
+            .load_string(
+                r#"
+node_id: booking_v1
+type: function
+intent: reserve a seat and summarize it
+
+flow:
+  - step: price
+    operation: database_query
+    parameters:
+      sql: SELECT price FROM seats
+    output: price
+  - step: reserve
+    operation: saga
+    parameters:
+      steps:
+        - step:
+            operation: arithmetic
+            expression: "1 + 1"
+            output: seats
+          compensate:
+            operation: notify
+            parameters:
+              message: "released {price}"
+  - step: summary
+    operation: model_invoke
+    parameters:
+      prompt: Summarize the booking
+  - step: stock
+    operation: database_query
+    parameters:
+      sql: SELECT COUNT(*) FROM seats
+    output: stock
+"#,
+            )
+            .unwrap();
+
+        let steps = parameter_steps(&node.flow[1]);
+        assert_eq!(
+            steps
+                .iter()
+                .map(|s| s.operation.as_str())
+                .collect::<Vec<_>>(),
+            vec!["arithmetic", "notify"]
+        );
+        assert_eq!(
+            dependency_levels(&node.flow),
+            vec![vec![0], vec![1], vec![2], vec![3]]
+        );
+    }
+}
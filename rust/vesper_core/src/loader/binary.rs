@@ -0,0 +1,313 @@
+//! Binary node files for fast loading
+//!
+//! A binary file is the `MAGIC` bytes, the little-endian `u32` format
+//! version and the bincode-encoded node.
+
+use super::VesperLoader;
+use crate::error::{Result, VesperError};
+use crate::types::VesperNode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Leading bytes of every binary node file
+const MAGIC: &[u8; 4] = b"VSPB";
+
+/// Version of the binary layout; files with another version are rejected
+pub const BINARY_FORMAT_VERSION: u32 = 1;
+
+/// Extension of binary node files saved alongside their YAML source
+pub const BINARY_EXTENSION: &str = "vspb";
+
+/// YAML value in a form bincode can decode without type hints
+#[derive(Serialize, Deserialize)]
+pub(crate) enum Tree {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    Sequence(Vec<Tree>),
+    Mapping(Vec<(Tree, Tree)>),
+}
+
+impl From<&serde_yaml::Value> for Tree {
+    fn from(value: &serde_yaml::Value) -> Self {
+        match value {
+            serde_yaml::Value::Null => Tree::Null,
+            serde_yaml::Value::Bool(b) => Tree::Bool(*b),
+            serde_yaml::Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(i), _) => Tree::Int(i),
+                (None, Some(u)) => Tree::UInt(u),
+                _ => Tree::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_yaml::Value::String(s) => Tree::String(s.clone()),
+            serde_yaml::Value::Sequence(items) => {
+                Tree::Sequence(items.iter().map(Tree::from).collect())
+            }
+            serde_yaml::Value::Mapping(fields) => Tree::Mapping(
+                fields
+                    .iter()
+                    .map(|(k, v)| (Tree::from(k), Tree::from(v)))
+                    .collect(),
+            ),
+            serde_yaml::Value::Tagged(tagged) => Tree::from(&tagged.value),
+        }
+    }
+}
+
+impl From<Tree> for serde_yaml::Value {
+    fn from(tree: Tree) -> Self {
+        match tree {
+            Tree::Null => serde_yaml::Value::Null,
+            Tree::Bool(b) => serde_yaml::Value::Bool(b),
+            Tree::Int(i) => serde_yaml::Value::Number(i.into()),
+            Tree::UInt(u) => serde_yaml::Value::Number(u.into()),
+            Tree::Float(f) => serde_yaml::Value::Number(f.into()),
+            Tree::String(s) => serde_yaml::Value::String(s),
+            Tree::Sequence(items) => {
+                serde_yaml::Value::Sequence(items.into_iter().map(Into::into).collect())
+            }
+            Tree::Mapping(fields) => serde_yaml::Value::Mapping(
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Node field types holding YAML values, encoded as `Tree`s in binary
+pub(crate) trait YamlField: Serialize + DeserializeOwned {
+    /// Binary representation
+    type Binary: Serialize + DeserializeOwned;
+
+    fn to_binary(&self) -> Self::Binary;
+
+    fn from_binary(binary: Self::Binary) -> Self;
+}
+
+impl YamlField for serde_yaml::Value {
+    type Binary = Tree;
+
+    fn to_binary(&self) -> Tree {
+        Tree::from(self)
+    }
+
+    fn from_binary(binary: Tree) -> Self {
+        binary.into()
+    }
+}
+
+impl<T: YamlField> YamlField for Option<T> {
+    type Binary = Option<T::Binary>;
+
+    fn to_binary(&self) -> Self::Binary {
+        self.as_ref().map(T::to_binary)
+    }
+
+    fn from_binary(binary: Self::Binary) -> Self {
+        binary.map(T::from_binary)
+    }
+}
+
+impl<T: YamlField> YamlField for HashMap<String, T> {
+    type Binary = Vec<(String, T::Binary)>;
+
+    fn to_binary(&self) -> Self::Binary {
+        self.iter()
+            .map(|(k, v)| (k.clone(), v.to_binary()))
+            .collect()
+    }
+
+    fn from_binary(binary: Self::Binary) -> Self {
+        binary
+            .into_iter()
+            .map(|(k, v)| (k, T::from_binary(v)))
+            .collect()
+    }
+}
+
+/// Serde adapter for `YamlField`s: unchanged in human-readable formats,
+/// `Tree`s in binary ones
+pub(crate) mod yaml_field {
+    use super::*;
+
+    pub fn serialize<T: YamlField, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            value.serialize(serializer)
+        } else {
+            value.to_binary().serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, T: YamlField, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<T, D::Error> {
+        if deserializer.is_human_readable() {
+            T::deserialize(deserializer)
+        } else {
+            T::Binary::deserialize(deserializer).map(T::from_binary)
+        }
+    }
+}
+
+impl VesperLoader {
+    /// Encode a node in the binary format
+    pub fn serialize_binary(node: &VesperNode) -> Result<Vec<u8>> {
+        let mut bytes = Vec::from(*MAGIC);
+        bytes.extend_from_slice(&BINARY_FORMAT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, node)
+            .map_err(|e| VesperError::ParseError(format!("Cannot encode node: {}", e)))?;
+        Ok(bytes)
+    }
+
+    /// Decode a node from the binary format
+    ///
+    /// Fails on a missing header or a different format version, so callers
+    /// can fall back to the YAML source.
+    pub fn deserialize_binary(bytes: &[u8]) -> Result<VesperNode> {
+        let body = bytes
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or_else(|| VesperError::ParseError("Not a binary Vesper node".to_string()))?;
+        let (version, body) = body
+            .split_at_checked(4)
+            .ok_or_else(|| VesperError::ParseError("Truncated binary Vesper node".to_string()))?;
+        let version = u32::from_le_bytes(version.try_into().unwrap_or_default());
+        if version != BINARY_FORMAT_VERSION {
+            return Err(VesperError::ParseError(format!(
+                "Binary format version {} is not supported (expected {})",
+                version, BINARY_FORMAT_VERSION
+            )));
+        }
+        bincode::deserialize(body)
+            .map_err(|e| VesperError::ParseError(format!("Cannot decode node: {}", e)))
+    }
+
+    /// Load and validate a node from a binary file
+    pub fn load_binary_file<P: AsRef<Path>>(&self, path: P) -> Result<VesperNode> {
+        let node = Self::deserialize_binary(&std::fs::read(path)?)?;
+        self.validate(&node)?;
+        Ok(node)
+    }
+
+    /// Save a node to a binary file
+    pub fn save_binary_file<P: AsRef<Path>>(&self, node: &VesperNode, path: P) -> Result<()> {
+        std::fs::write(path, Self::serialize_binary(node)?)?;
+        Ok(())
+    }
+
+    /// Load the binary file next to the YAML file at `path`, if it is newer
+    ///
+    /// Binary files store nodes with their environment import already
+    /// merged, so they are only used by loaders without an environment.
+    /// Unreadable or outdated binary files are ignored.
+    pub(super) fn load_fresh_binary(&self, path: &Path) -> Option<VesperNode> {
+        if self.environment.is_some() {
+            return None;
+        }
+        let binary = path.with_extension(BINARY_EXTENSION);
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified(&binary)? <= modified(path)? {
+            return None;
+        }
+        match self.load_binary_file(&binary) {
+            Ok(node) => Some(node),
+            Err(e) => {
+                tracing::debug!("Ignoring {}: {}", binary.display(), e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    const NODE: &str = r#"
+node_id: greet_v1
+type: function
+intent: greet a user
+
+inputs:
+  name:
+    type: string
+    default: world
+
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello {name}!"
+    output: message
+  - step: limited
+    operation: rate_limit_step
+    parameters:
+      max_per_second: 2.5
+      steps:
+        - step: shout
+          operation: string_template
+          template: "{message}!"
+"#;
+
+    #[test]
+    fn test_binary_round_trip() {
+        let node = VesperLoader::new().load_string(NODE).unwrap();
+        let bytes = VesperLoader::serialize_binary(&node).unwrap();
+        assert_eq!(&bytes[..4], MAGIC);
+
+        let decoded = VesperLoader::deserialize_binary(&bytes).unwrap();
+        assert_eq!(
+            serde_yaml::to_value(&decoded).unwrap(),
+            serde_yaml::to_value(&node).unwrap()
+        );
+
+        let mut outdated = bytes.clone();
+        outdated[4..8].copy_from_slice(&(BINARY_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(VesperLoader::deserialize_binary(&outdated).is_err());
+        assert!(VesperLoader::deserialize_binary(b"greet").is_err());
+    }
+
+    #[test]
+    fn test_load_file_prefers_newer_binary() {
+        let dir = std::env::temp_dir().join(format!("vesper-binary-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let yaml = dir.join("greet.yaml");
+        let binary = dir.join("greet.vspb");
+        std::fs::write(&yaml, NODE).unwrap();
+
+        // A binary holding a different intent shows which file was loaded
+        let loader = VesperLoader::new();
+        let mut node = loader.load_file(&yaml).unwrap();
+        node.intent = "from binary".to_string();
+        loader.save_binary_file(&node, &binary).unwrap();
+
+        let now = SystemTime::now();
+        let set_modified = |path: &Path, time: SystemTime| {
+            let file = std::fs::File::options().write(true).open(path).unwrap();
+            file.set_modified(time).unwrap();
+        };
+        set_modified(&yaml, now - Duration::from_secs(60));
+        set_modified(&binary, now);
+        assert_eq!(loader.load_file(&yaml).unwrap().intent, "from binary");
+
+        set_modified(&yaml, now + Duration::from_secs(60));
+        assert_eq!(loader.load_file(&yaml).unwrap().intent, "greet a user");
+
+        // Outdated binary files fall back to the YAML source
+        let mut bytes = std::fs::read(&binary).unwrap();
+        bytes[4..8].copy_from_slice(&0u32.to_le_bytes());
+        std::fs::write(&binary, bytes).unwrap();
+        set_modified(&binary, now + Duration::from_secs(120));
+        assert_eq!(loader.load_file(&yaml).unwrap().intent, "greet a user");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+//! Machine learning model providers for the `model_invoke`, `text_embed`
+//! and `structured_extract` operations
+
+use crate::types::Value;
+use std::sync::Arc;
+
+/// A text generation request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelRequest {
+    /// Model name understood by the provider
+    pub model: String,
+    /// Prompt text
+    pub prompt: String,
+    /// Upper bound on generated tokens
+    pub max_tokens: Option<u32>,
+    /// Sampling temperature
+    pub temperature: Option<f64>,
+}
+
+/// A generated completion
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelResponse {
+    /// Generated text
+    pub text: String,
+    /// Tokens consumed by the prompt and completion together
+    pub tokens_used: i64,
+    /// Why generation stopped, as reported by the provider
+    pub finish_reason: String,
+}
+
+/// Inference endpoint for the model operations
+pub trait ModelProvider: Send + Sync {
+    /// Generate a completion for `request`
+    fn complete(&self, request: &ModelRequest) -> Result<ModelResponse, String>;
+
+    /// Compute the embedding vector of `text` with `model`
+    fn embed(&self, model: &str, _text: &str) -> Result<Vec<f64>, String> {
+        Err(format!("Provider does not support embeddings ({})", model))
+    }
+}
+
+/// Source of structured data for the `structured_extract` operation
+pub trait StructuredExtractor: Send + Sync {
+    /// Extract an object with the fields of `schema` from `text`
+    ///
+    /// `schema` maps field names to type names (`string`, `integer`,
+    /// `number`, `boolean`, `array`, `object`). The result is validated by
+    /// the caller, which may retry.
+    fn extract(&self, text: &str, schema: &Value) -> Result<Value, String>;
+}
+
+/// Extractor prompting a model for JSON matching the schema
+pub struct ModelExtractor {
+    /// Provider generating the JSON
+    provider: Arc<dyn ModelProvider>,
+    /// Model name passed to the provider
+    model: String,
+}
+
+impl ModelExtractor {
+    /// Create an extractor using `model` through `provider`
+    pub fn new(provider: Arc<dyn ModelProvider>, model: &str) -> Self {
+        Self {
+            provider,
+            model: model.to_string(),
+        }
+    }
+}
+
+impl StructuredExtractor for ModelExtractor {
+    fn extract(&self, text: &str, schema: &Value) -> Result<Value, String> {
+        let prompt = format!(
+            "Extract the following fields from the text and reply with a single JSON \
+             object and nothing else. Fields and their types: {}\n\nText:\n{}",
+            serde_json::Value::from(schema),
+            text
+        );
+        let response = self.provider.complete(&ModelRequest {
+            model: self.model.clone(),
+            prompt,
+            max_tokens: None,
+            temperature: Some(0.0),
+        })?;
+
+        // Models often wrap JSON in prose or code fences
+        let reply = response.text;
+        let json = match (reply.find('{'), reply.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &reply[start..=end],
+            _ => return Err(format!("No JSON object in model reply: {}", reply)),
+        };
+        serde_json::from_str::<serde_json::Value>(json)
+            .map(Value::from)
+            .map_err(|e| format!("Invalid JSON in model reply: {}", e))
+    }
+}
+
+/// Wire format of an inference API
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelApi {
+    OpenAi,
+    Anthropic,
+    Ollama,
+    /// `{model, prompt, max_tokens, temperature}` in,
+    /// `{text, tokens_used, finish_reason}` out
+    Generic,
+}
+
+/// Provider calling a hosted or local inference API over HTTP
+#[cfg(feature = "http")]
+pub struct HttpModelProvider {
+    /// Wire format of the API
+    api: ModelApi,
+    /// Base URL, or the full endpoint URL for generic APIs
+    base_url: String,
+    /// Credential sent in the API's authentication header
+    api_key: Option<String>,
+    /// HTTP client
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "http")]
+impl HttpModelProvider {
+    fn with_api(api: ModelApi, base_url: &str, api_key: Option<&str>) -> Self {
+        Self {
+            api,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.map(str::to_string),
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Create a provider for the OpenAI chat completions API
+    pub fn openai(api_key: &str) -> Self {
+        Self::with_api(ModelApi::OpenAi, "https://api.openai.com", Some(api_key))
+    }
+
+    /// Create a provider for the Anthropic messages API
+    pub fn anthropic(api_key: &str) -> Self {
+        Self::with_api(
+            ModelApi::Anthropic,
+            "https://api.anthropic.com",
+            Some(api_key),
+        )
+    }
+
+    /// Create a provider for a local Ollama server
+    pub fn ollama() -> Self {
+        Self::with_api(ModelApi::Ollama, "http://localhost:11434", None)
+    }
+
+    /// Create a provider POSTing the generic request shape to `url`
+    pub fn new(url: &str) -> Self {
+        Self::with_api(ModelApi::Generic, url, None)
+    }
+
+    /// Send requests to a different host, e.g. a proxy or compatible server
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Authenticate requests with an API key
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    fn post(&self, path: &str, body: serde_json::Value) -> Result<serde_json::Value, String> {
+        let mut request = self.client.post(format!("{}{}", self.base_url, path));
+        request = match (self.api, &self.api_key) {
+            (ModelApi::Anthropic, Some(key)) => request
+                .header("x-api-key", key)
+                .header("anthropic-version", "2023-06-01"),
+            (_, Some(key)) => request.bearer_auth(key),
+            (_, None) => request,
+        };
+
+        let response = request.json(&body).send().map_err(|e| e.to_string())?;
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().unwrap_or_default();
+            return Err(format!("HTTP {}: {}", status, detail));
+        }
+        response.json().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "http")]
+impl ModelProvider for HttpModelProvider {
+    fn complete(&self, request: &ModelRequest) -> Result<ModelResponse, String> {
+        use serde_json::json;
+
+        let messages = json!([{"role": "user", "content": request.prompt}]);
+        let (path, mut body) = match self.api {
+            ModelApi::OpenAi => (
+                "/v1/chat/completions",
+                json!({"model": request.model, "messages": messages}),
+            ),
+            ModelApi::Anthropic => (
+                "/v1/messages",
+                json!({
+                    "model": request.model,
+                    "messages": messages,
+                    // Required by the messages API
+                    "max_tokens": request.max_tokens.unwrap_or(1024),
+                }),
+            ),
+            ModelApi::Ollama => (
+                "/api/generate",
+                json!({"model": request.model, "prompt": request.prompt, "stream": false}),
+            ),
+            ModelApi::Generic => (
+                "",
+                json!({"model": request.model, "prompt": request.prompt}),
+            ),
+        };
+        // Ollama nests sampling settings under `options`
+        let settings = match self.api {
+            ModelApi::Ollama => {
+                body["options"] = json!({});
+                &mut body["options"]
+            }
+            _ => &mut body,
+        };
+        if let Some(max_tokens) = request.max_tokens {
+            let key = match self.api {
+                ModelApi::Ollama => "num_predict",
+                _ => "max_tokens",
+            };
+            settings[key] = json!(max_tokens);
+        }
+        if let Some(temperature) = request.temperature {
+            settings["temperature"] = json!(temperature);
+        }
+
+        let reply = self.post(path, body)?;
+        let text = |value: &serde_json::Value| value.as_str().map(str::to_string);
+        let count = |value: &serde_json::Value| value.as_i64().unwrap_or(0);
+        let parsed = match self.api {
+            ModelApi::OpenAi => {
+                let choice = &reply["choices"][0];
+                text(&choice["message"]["content"]).map(|content| ModelResponse {
+                    text: content,
+                    tokens_used: count(&reply["usage"]["total_tokens"]),
+                    finish_reason: text(&choice["finish_reason"]).unwrap_or_default(),
+                })
+            }
+            ModelApi::Anthropic => reply["content"].as_array().map(|blocks| ModelResponse {
+                text: blocks.iter().filter_map(|b| b["text"].as_str()).collect(),
+                tokens_used: count(&reply["usage"]["input_tokens"])
+                    + count(&reply["usage"]["output_tokens"]),
+                finish_reason: text(&reply["stop_reason"]).unwrap_or_default(),
+            }),
+            ModelApi::Ollama => text(&reply["response"]).map(|response| ModelResponse {
+                text: response,
+                tokens_used: count(&reply["prompt_eval_count"]) + count(&reply["eval_count"]),
+                finish_reason: text(&reply["done_reason"]).unwrap_or_default(),
+            }),
+            ModelApi::Generic => text(&reply["text"]).map(|generated| ModelResponse {
+                text: generated,
+                tokens_used: count(&reply["tokens_used"]),
+                finish_reason: text(&reply["finish_reason"]).unwrap_or_default(),
+            }),
+        };
+        parsed.ok_or_else(|| format!("Unexpected model response: {}", reply))
+    }
+
+    fn embed(&self, model: &str, text: &str) -> Result<Vec<f64>, String> {
+        use serde_json::json;
+
+        let reply = match self.api {
+            ModelApi::OpenAi => {
+                let reply = self.post("/v1/embeddings", json!({"model": model, "input": text}))?;
+                reply["data"][0]["embedding"].clone()
+            }
+            ModelApi::Ollama => {
+                let reply =
+                    self.post("/api/embeddings", json!({"model": model, "prompt": text}))?;
+                reply["embedding"].clone()
+            }
+            ModelApi::Anthropic | ModelApi::Generic => {
+                return Err(format!("Provider does not support embeddings ({})", model))
+            }
+        };
+        reply
+            .as_array()
+            .and_then(|values| values.iter().map(|v| v.as_f64()).collect())
+            .ok_or_else(|| format!("Unexpected embedding response: {}", reply))
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openai_completion() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_header("authorization", "Bearer sk-test")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "gpt-4o-mini",
+                "max_tokens": 16,
+            })))
+            .with_body(
+                r#"{"choices": [{"message": {"role": "assistant", "content": "Hi!"},
+                    "finish_reason": "stop"}], "usage": {"total_tokens": 12}}"#,
+            )
+            .create();
+
+        let provider = HttpModelProvider::openai("sk-test").with_base_url(&server.url());
+        let response = provider
+            .complete(&ModelRequest {
+                model: "gpt-4o-mini".to_string(),
+                prompt: "Say hi".to_string(),
+                max_tokens: Some(16),
+                temperature: None,
+            })
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(
+            response,
+            ModelResponse {
+                text: "Hi!".to_string(),
+                tokens_used: 12,
+                finish_reason: "stop".to_string(),
+            }
+        );
+    }
+}
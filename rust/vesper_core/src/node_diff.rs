@@ -0,0 +1,330 @@
+//! Signature differences between two versions of a node
+//!
+//! Only the caller-visible signature is compared: inputs and success
+//! outputs. A removed input paired with an added input of the same type
+//! whose name contains it (or is contained in it) counts as a rename.
+
+use crate::types::{InputSpec, VesperNode};
+use std::collections::BTreeMap;
+
+/// A single signature change between two node versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureChange {
+    /// An input was renamed, keeping its type
+    InputRenamed { from: String, to: String },
+    /// A new input was added
+    InputAdded {
+        name: String,
+        input_type: String,
+        required: bool,
+    },
+    /// An input was removed; callers still passing it are unaffected
+    InputRemoved { name: String },
+    /// An input changed type
+    InputTypeChanged {
+        name: String,
+        from: String,
+        to: String,
+    },
+    /// An optional input became required, or the other way around
+    InputRequirednessChanged { name: String, required: bool },
+    /// A success output field was added
+    OutputAdded { name: String },
+    /// A success output field was removed
+    OutputRemoved { name: String },
+    /// A success output field changed type
+    OutputTypeChanged {
+        name: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl SignatureChange {
+    /// Whether existing callers must change to keep working
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            Self::InputRenamed { .. }
+            | Self::InputTypeChanged { .. }
+            | Self::OutputRemoved { .. }
+            | Self::OutputTypeChanged { .. } => true,
+            Self::InputAdded { required, .. } | Self::InputRequirednessChanged { required, .. } => {
+                *required
+            }
+            Self::InputRemoved { .. } | Self::OutputAdded { .. } => false,
+        }
+    }
+
+    /// Instruction to callers, phrased to follow "To migrate, ..."
+    fn instruction(&self) -> String {
+        match self {
+            Self::InputRenamed { from, to } => format!("rename input `{}` to `{}`", from, to),
+            Self::InputAdded {
+                name,
+                input_type,
+                required,
+            } => format!(
+                "add new {} input `{}` of type `{}`",
+                if *required { "required" } else { "optional" },
+                name,
+                input_type
+            ),
+            Self::InputRemoved { name } => format!("stop passing removed input `{}`", name),
+            Self::InputTypeChanged { name, from, to } => {
+                format!("pass input `{}` as `{}` instead of `{}`", name, to, from)
+            }
+            Self::InputRequirednessChanged { name, required } => format!(
+                "{} input `{}`",
+                if *required {
+                    "always pass"
+                } else {
+                    "optionally omit"
+                },
+                name
+            ),
+            Self::OutputAdded { name } => format!("optionally read new output `{}`", name),
+            Self::OutputRemoved { name } => format!("stop reading removed output `{}`", name),
+            Self::OutputTypeChanged { name, from, to } => {
+                format!("read output `{}` as `{}` instead of `{}`", name, to, from)
+            }
+        }
+    }
+}
+
+/// Signature changes from one node version to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeDiff {
+    /// Node ID of the old version
+    pub from: String,
+    /// Node ID of the new version
+    pub to: String,
+    /// Changes, inputs before outputs, each ordered by name
+    pub changes: Vec<SignatureChange>,
+}
+
+impl NodeDiff {
+    /// Compare the signatures of `old` and `new`
+    pub fn compute(old: &VesperNode, new: &VesperNode) -> Self {
+        let old_inputs: BTreeMap<&String, &InputSpec> = old.inputs.iter().collect();
+        let new_inputs: BTreeMap<&String, &InputSpec> = new.inputs.iter().collect();
+
+        let mut removed: Vec<&String> = old_inputs
+            .keys()
+            .filter(|name| !new_inputs.contains_key(*name))
+            .copied()
+            .collect();
+        let mut added: Vec<&String> = new_inputs
+            .keys()
+            .filter(|name| !old_inputs.contains_key(*name))
+            .copied()
+            .collect();
+
+        let mut changes = Vec::new();
+        removed.retain(|from| {
+            let renamed = added.iter().position(|to| {
+                old_inputs[*from].input_type == new_inputs[*to].input_type
+                    && (to.contains(from.as_str()) || from.contains(to.as_str()))
+            });
+            match renamed {
+                Some(index) => {
+                    changes.push(SignatureChange::InputRenamed {
+                        from: (*from).clone(),
+                        to: added.remove(index).clone(),
+                    });
+                    false
+                }
+                None => true,
+            }
+        });
+
+        for name in added {
+            let spec = new_inputs[name];
+            changes.push(SignatureChange::InputAdded {
+                name: name.clone(),
+                input_type: spec.input_type.clone(),
+                required: spec.required,
+            });
+        }
+        for name in removed {
+            changes.push(SignatureChange::InputRemoved { name: name.clone() });
+        }
+        for (name, old_spec) in &old_inputs {
+            let Some(new_spec) = new_inputs.get(name) else {
+                continue;
+            };
+            if old_spec.input_type != new_spec.input_type {
+                changes.push(SignatureChange::InputTypeChanged {
+                    name: (*name).clone(),
+                    from: old_spec.input_type.clone(),
+                    to: new_spec.input_type.clone(),
+                });
+            }
+            if old_spec.required != new_spec.required {
+                changes.push(SignatureChange::InputRequirednessChanged {
+                    name: (*name).clone(),
+                    required: new_spec.required,
+                });
+            }
+        }
+
+        let outputs = |node: &VesperNode| -> BTreeMap<String, Option<String>> {
+            node.outputs
+                .iter()
+                .flat_map(|outputs| &outputs.success)
+                .map(|(name, field)| (name.clone(), field.output_type.clone()))
+                .collect()
+        };
+        let old_outputs = outputs(old);
+        let new_outputs = outputs(new);
+        for (name, new_type) in &new_outputs {
+            match old_outputs.get(name) {
+                None => changes.push(SignatureChange::OutputAdded { name: name.clone() }),
+                Some(old_type) if old_type != new_type => {
+                    let type_name = |t: &Option<String>| t.clone().unwrap_or("any".to_string());
+                    changes.push(SignatureChange::OutputTypeChanged {
+                        name: name.clone(),
+                        from: type_name(old_type),
+                        to: type_name(new_type),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        for name in old_outputs.keys() {
+            if !new_outputs.contains_key(name) {
+                changes.push(SignatureChange::OutputRemoved { name: name.clone() });
+            }
+        }
+
+        Self {
+            from: old.node_id.clone(),
+            to: new.node_id.clone(),
+            changes,
+        }
+    }
+
+    /// Whether any change breaks existing callers
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(SignatureChange::is_breaking)
+    }
+
+    /// Markdown upgrade instructions for callers of the old version
+    ///
+    /// Opens with a sentence listing the required (breaking) steps,
+    /// followed by bullet lists of breaking and non-breaking changes.
+    pub fn migration_guide(&self) -> String {
+        let (breaking, compatible): (Vec<_>, Vec<_>) =
+            self.changes.iter().partition(|change| change.is_breaking());
+
+        let mut guide = format!("# Migrating from `{}` to `{}`\n\n", self.from, self.to);
+        if breaking.is_empty() {
+            guide.push_str(&format!(
+                "`{}` is compatible with `{}`; no changes are required.\n",
+                self.to, self.from
+            ));
+        } else {
+            let steps: Vec<String> = breaking.iter().map(|c| c.instruction()).collect();
+            guide.push_str(&format!(
+                "To migrate from `{}` to `{}`, {}.\n",
+                self.from,
+                self.to,
+                steps.join(", ")
+            ));
+        }
+
+        for (title, changes) in [
+            ("Breaking changes", &breaking),
+            ("Non-breaking changes", &compatible),
+        ] {
+            if changes.is_empty() {
+                continue;
+            }
+            guide.push_str(&format!("\n## {}\n\n", title));
+            for change in changes {
+                guide.push_str(&format!("- {}\n", change.instruction()));
+            }
+        }
+        guide
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::VesperLoader;
+
+    #[test]
+    fn test_compute_classifies_changes() {
+        let loader = VesperLoader::new();
+        let old = loader
+            .load_string(
+                r#"
+node_id: scale_v1
+type: function
+intent: scale a value
+
+inputs:
+  value:
+    type: integer
+  factor:
+    type: integer
+    required: false
+  unit:
+    type: string
+
+outputs:
+  success:
+    scaled:
+      type: integer
+"#,
+            )
+            .unwrap();
+        let new = loader
+            .load_string(
+                r#"
+node_id: scale_v2
+type: function
+intent: scale a value
+
+inputs:
+  value:
+    type: float
+  factor:
+    type: integer
+
+outputs:
+  success:
+    scaled:
+      type: integer
+    overflow:
+      type: boolean
+"#,
+            )
+            .unwrap();
+
+        let diff = NodeDiff::compute(&old, &new);
+        assert_eq!(
+            diff.changes,
+            vec![
+                SignatureChange::InputRemoved {
+                    name: "unit".to_string()
+                },
+                SignatureChange::InputRequirednessChanged {
+                    name: "factor".to_string(),
+                    required: true
+                },
+                SignatureChange::InputTypeChanged {
+                    name: "value".to_string(),
+                    from: "integer".to_string(),
+                    to: "float".to_string()
+                },
+                SignatureChange::OutputAdded {
+                    name: "overflow".to_string()
+                },
+            ]
+        );
+        assert!(diff.is_breaking());
+        assert!(!diff.changes[0].is_breaking());
+        assert!(!diff.changes[3].is_breaking());
+    }
+}
@@ -0,0 +1,98 @@
+//! Message queue backends for the `message_queue_*` operations
+
+use crate::types::Value;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Transport for publishing and consuming messages by topic
+pub trait MessageQueueBackend: Send + Sync {
+    /// Publish a message to a topic
+    fn publish(&self, topic: &str, message: Value) -> Result<(), String>;
+
+    /// Take the next message from a topic, waiting up to `timeout` for one
+    /// to arrive. Returns `None` if the topic stays empty.
+    fn consume(&self, topic: &str, timeout: Option<Duration>) -> Result<Option<Value>, String>;
+}
+
+/// One in-process channel per topic
+type Topic = (Sender<Value>, Arc<Mutex<Receiver<Value>>>);
+
+/// Process-local queue backed by `std::sync::mpsc` channels
+///
+/// Topics are created on first use. Each message is delivered to exactly
+/// one consumer.
+pub struct InMemoryQueueBackend {
+    /// Channels by topic name
+    topics: Mutex<HashMap<String, Topic>>,
+}
+
+impl InMemoryQueueBackend {
+    /// Create a backend with no topics
+    pub fn new() -> Self {
+        Self {
+            topics: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn topic(&self, name: &str) -> Topic {
+        let mut topics = self.topics.lock().unwrap();
+        let (sender, receiver) = topics.entry(name.to_string()).or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            (sender, Arc::new(Mutex::new(receiver)))
+        });
+        (sender.clone(), Arc::clone(receiver))
+    }
+}
+
+impl Default for InMemoryQueueBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageQueueBackend for InMemoryQueueBackend {
+    fn publish(&self, topic: &str, message: Value) -> Result<(), String> {
+        let (sender, _) = self.topic(topic);
+        sender.send(message).map_err(|e| e.to_string())
+    }
+
+    fn consume(&self, topic: &str, timeout: Option<Duration>) -> Result<Option<Value>, String> {
+        // The topic map is released before waiting so publishers are not blocked
+        let (_, receiver) = self.topic(topic);
+        let receiver = receiver.lock().unwrap();
+        match timeout {
+            Some(timeout) => match receiver.recv_timeout(timeout) {
+                Ok(message) => Ok(Some(message)),
+                Err(RecvTimeoutError::Timeout) => Ok(None),
+                Err(e) => Err(e.to_string()),
+            },
+            None => match receiver.try_recv() {
+                Ok(message) => Ok(Some(message)),
+                Err(TryRecvError::Empty) => Ok(None),
+                Err(e) => Err(e.to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_queue() {
+        let queue = InMemoryQueueBackend::new();
+        queue.publish("orders", Value::Int(1)).unwrap();
+        queue.publish("orders", Value::Int(2)).unwrap();
+
+        assert_eq!(queue.consume("orders", None), Ok(Some(Value::Int(1))));
+        assert_eq!(queue.consume("orders", None), Ok(Some(Value::Int(2))));
+        assert_eq!(queue.consume("orders", None), Ok(None));
+        assert_eq!(
+            queue.consume("refunds", Some(Duration::from_millis(10))),
+            Ok(None)
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Remote executors for the `load_balance` operation
+
+use crate::executor::SemanticExecutor;
+use crate::types::{FlowStep, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Connection to another executor that can run individual steps
+pub trait RemoteExecutorClient: Send + Sync {
+    /// Run `step` against `bindings` (the caller's inputs and variables)
+    /// and return its result
+    fn execute_step(
+        &self,
+        step: &FlowStep,
+        bindings: &HashMap<String, Value>,
+    ) -> Result<Value, String>;
+
+    /// Steps currently running on the executor, for `least_loaded`
+    fn load(&self) -> usize {
+        0
+    }
+}
+
+/// Client running steps on an executor in the same process
+///
+/// Useful for tests and for spreading work over differently configured
+/// executors (e.g. with separate database backends).
+pub struct InProcessExecutorClient {
+    /// Executor running the steps
+    executor: Arc<SemanticExecutor>,
+    /// Steps in progress
+    in_flight: AtomicUsize,
+}
+
+impl InProcessExecutorClient {
+    /// Create a client for `executor`
+    pub fn new(executor: Arc<SemanticExecutor>) -> Self {
+        Self {
+            executor,
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl RemoteExecutorClient for InProcessExecutorClient {
+    fn execute_step(
+        &self,
+        step: &FlowStep,
+        bindings: &HashMap<String, Value>,
+    ) -> Result<Value, String> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let result = self
+            .executor
+            .execute_standalone_step(step, bindings.clone())
+            .map_err(|e| e.to_string());
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        result
+    }
+
+    fn load(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+}
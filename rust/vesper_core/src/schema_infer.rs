@@ -0,0 +1,134 @@
+//! Input specification inference from sample data
+//!
+//! Generates `inputs` and `types` sections for a spec from a
+//! representative input object, for users to review and paste in.
+
+use crate::types::{CustomType, InputSpec, Value};
+use std::collections::HashMap;
+
+/// Infer an input specification for each field of a sample object
+///
+/// Types follow the runtime value: `string`, `integer`, `float`,
+/// `boolean`, `array` or `object`. Nested objects refer to a custom type
+/// named after the field in PascalCase (see `infer_types`). Null fields
+/// become optional inputs of type `any`. Non-object samples have no
+/// fields and yield an empty map.
+pub fn infer_inputs(sample: &Value) -> HashMap<String, InputSpec> {
+    let Value::Object(fields) = sample else {
+        return HashMap::new();
+    };
+    fields
+        .iter()
+        .map(|(name, value)| {
+            let spec = InputSpec {
+                input_type: value_type(name, value),
+                required: !matches!(value, Value::Null),
+                constraints: Vec::new(),
+                default: None,
+                description: None,
+            };
+            (name.clone(), spec)
+        })
+        .collect()
+}
+
+/// Custom types referenced by `infer_inputs` for nested objects
+///
+/// Types are generated for objects at any depth, keyed by type name.
+pub fn infer_types(sample: &Value) -> HashMap<String, CustomType> {
+    let mut types = HashMap::new();
+    if let Value::Object(fields) = sample {
+        collect_types(fields, &mut types);
+    }
+    types
+}
+
+fn collect_types(fields: &HashMap<String, Value>, types: &mut HashMap<String, CustomType>) {
+    for (name, value) in fields {
+        let Value::Object(nested) = value else {
+            continue;
+        };
+        let mut type_fields = HashMap::new();
+        for (field, field_value) in nested {
+            let mut spec = serde_yaml::Mapping::new();
+            spec.insert("type".into(), value_type(field, field_value).into());
+            type_fields.insert(field.clone(), serde_yaml::Value::Mapping(spec));
+        }
+        types.insert(
+            type_name(name),
+            CustomType {
+                base: None,
+                fields: type_fields,
+                constraints: Vec::new(),
+            },
+        );
+        collect_types(nested, types);
+    }
+}
+
+/// Spec type for a sample value held by field `name`
+fn value_type(name: &str, value: &Value) -> String {
+    match value {
+        Value::Null => "any".to_string(),
+        Value::Bool(_) => "boolean".to_string(),
+        Value::Int(_) => "integer".to_string(),
+        Value::Float(_) => "float".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::Array(_) => "array".to_string(),
+        Value::Object(_) => type_name(name),
+    }
+}
+
+/// PascalCase type name for a field, e.g. `shipping_address` → `ShippingAddress`
+fn type_name(field: &str) -> String {
+    field
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_inputs_and_types() {
+        let sample: Value = serde_json::json!({
+            "order_id": "ord-1",
+            "quantity": 3,
+            "price": 9.5,
+            "gift": false,
+            "tags": ["new"],
+            "coupon": null,
+            "shipping_address": {"city": "Utrecht", "geo": {"lat": 52.1}}
+        })
+        .into();
+
+        let inputs = infer_inputs(&sample);
+        let types: HashMap<&str, &str> = inputs
+            .iter()
+            .map(|(name, spec)| (name.as_str(), spec.input_type.as_str()))
+            .collect();
+        assert_eq!(types["order_id"], "string");
+        assert_eq!(types["quantity"], "integer");
+        assert_eq!(types["price"], "float");
+        assert_eq!(types["gift"], "boolean");
+        assert_eq!(types["tags"], "array");
+        assert_eq!(types["shipping_address"], "ShippingAddress");
+        assert!(!inputs["coupon"].required);
+
+        let custom = infer_types(&sample);
+        assert_eq!(custom["ShippingAddress"].fields["geo"]["type"], "Geo");
+        assert_eq!(custom["Geo"].fields["lat"]["type"], "float");
+
+        let yaml = serde_yaml::to_string(&inputs).unwrap();
+        assert!(yaml.contains("type: ShippingAddress"));
+    }
+}
@@ -0,0 +1,175 @@
+//! Secret stores for operations that need credentials
+//!
+//! Flows refer to secrets by name so that raw credentials never appear in
+//! node definitions or execution inputs.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Source of named secrets
+pub trait SecretStore: Send + Sync {
+    /// Look up a secret by name, or `None` if it is not known
+    fn get_secret(&self, name: &str) -> Option<String>;
+}
+
+/// Fixed set of secrets for testing and embedded use
+pub struct StaticSecretStore {
+    /// Secret values by name
+    secrets: HashMap<String, String>,
+}
+
+impl StaticSecretStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self {
+            secrets: HashMap::new(),
+        }
+    }
+
+    /// Add a secret
+    pub fn with_secret(mut self, name: &str, value: &str) -> Self {
+        self.secrets.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+impl Default for StaticSecretStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<HashMap<String, String>> for StaticSecretStore {
+    fn from(secrets: HashMap<String, String>) -> Self {
+        Self { secrets }
+    }
+}
+
+impl SecretStore for StaticSecretStore {
+    fn get_secret(&self, name: &str) -> Option<String> {
+        self.secrets.get(name).cloned()
+    }
+}
+
+/// Secrets read from environment variables
+///
+/// A secret `stripe_api_key` is read from `{prefix}STRIPE_API_KEY`.
+pub struct EnvSecretStore {
+    /// Prefix prepended to the upper-cased secret name
+    prefix: String,
+}
+
+impl EnvSecretStore {
+    /// Create a store reading variables with the given prefix
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+impl SecretStore for EnvSecretStore {
+    fn get_secret(&self, name: &str) -> Option<String> {
+        std::env::var(format!("{}{}", self.prefix, name.to_uppercase())).ok()
+    }
+}
+
+/// Secret manager for the `secrets_manager_get` operation
+///
+/// Unlike a `SecretStore`, lookups report why a secret is unavailable.
+pub trait SecretsManager: Send + Sync {
+    /// Fetch a secret by name
+    fn get(&self, name: &str) -> Result<String, String>;
+}
+
+/// Secrets read from `SECRET_<NAME>` environment variables
+#[derive(Default)]
+pub struct EnvSecretsManager;
+
+impl EnvSecretsManager {
+    /// Create a manager reading the process environment
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl SecretsManager for EnvSecretsManager {
+    fn get(&self, name: &str) -> Result<String, String> {
+        let variable = format!("SECRET_{}", name.to_uppercase());
+        std::env::var(&variable).map_err(|_| format!("{} is not set", variable))
+    }
+}
+
+/// Secrets read from files, one per secret, as mounted by Docker and
+/// Kubernetes under `/run/secrets`
+///
+/// A single trailing newline is stripped from the file contents.
+pub struct FileSecretsManager {
+    /// Directory holding one file per secret
+    directory: PathBuf,
+}
+
+impl FileSecretsManager {
+    /// Create a manager reading `/run/secrets`
+    pub fn new() -> Self {
+        Self::with_directory("/run/secrets")
+    }
+
+    /// Create a manager reading secrets from `directory`
+    pub fn with_directory(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+}
+
+impl Default for FileSecretsManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretsManager for FileSecretsManager {
+    fn get(&self, name: &str) -> Result<String, String> {
+        if name.is_empty() || name.contains(['/', '\\']) || name == ".." {
+            return Err(format!("Invalid secret name: {}", name));
+        }
+        let path = self.directory.join(name);
+        let mut secret = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Reading {} failed: {}", path.display(), e))?;
+        if secret.ends_with('\n') {
+            secret.pop();
+            if secret.ends_with('\r') {
+                secret.pop();
+            }
+        }
+        Ok(secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_secrets() {
+        let store = StaticSecretStore::new().with_secret("api_key", "hunter2");
+
+        assert_eq!(store.get_secret("api_key"), Some("hunter2".to_string()));
+        assert_eq!(store.get_secret("other"), None);
+    }
+
+    #[test]
+    fn test_file_secrets_manager() {
+        let dir = std::env::temp_dir().join(format!("vesper-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("db_password"), "hunter2\n").unwrap();
+
+        let manager = FileSecretsManager::with_directory(&dir);
+        assert_eq!(manager.get("db_password"), Ok("hunter2".to_string()));
+        assert!(manager.get("missing").is_err());
+        assert!(manager.get("../db_password").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -20,6 +20,10 @@ pub struct VesperNode {
     #[serde(default)]
     pub metadata: Option<Metadata>,
 
+    /// Per-environment override specs, by file path or node ID
+    #[serde(default)]
+    pub imports: Option<HashMap<String, String>>,
+
     /// Input specifications
     #[serde(default)]
     pub inputs: HashMap<String, InputSpec>,
@@ -36,10 +40,18 @@ pub struct VesperNode {
     #[serde(default)]
     pub contracts: Option<Contracts>,
 
+    /// Steps pre-processing the inputs before the flow runs
+    #[serde(default)]
+    pub input_transform: Option<Vec<FlowStep>>,
+
     /// Execution flow
     #[serde(default)]
     pub flow: Vec<FlowStep>,
 
+    /// Steps post-processing the flow result, bound as `_output`
+    #[serde(default)]
+    pub output_transform: Option<Vec<FlowStep>>,
+
     /// Performance requirements
     #[serde(default)]
     pub performance: Option<Performance>,
@@ -47,6 +59,10 @@ pub struct VesperNode {
     /// Security configuration
     #[serde(default)]
     pub security: Option<Security>,
+
+    /// Result caching configuration
+    #[serde(default)]
+    pub cache: Option<CacheSpec>,
 }
 
 /// Types of semantic nodes
@@ -62,6 +78,17 @@ pub enum NodeType {
     ScheduledJob,
 }
 
+/// Node type names as written in specs
+pub const NODE_TYPES: &[&str] = &[
+    "function",
+    "http_handler",
+    "event_handler",
+    "data_transform",
+    "state_machine",
+    "aggregation",
+    "scheduled_job",
+];
+
 /// Node metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metadata {
@@ -87,6 +114,10 @@ pub struct InputSpec {
     #[serde(default)]
     pub constraints: Vec<String>,
 
+    #[cfg_attr(
+        feature = "binary-format",
+        serde(default, with = "crate::loader::binary::yaml_field")
+    )]
     pub default: Option<serde_yaml::Value>,
 
     pub description: Option<String>,
@@ -106,6 +137,22 @@ pub struct Outputs {
     pub error: HashMap<String, OutputField>,
 }
 
+/// Field type names checked at runtime, besides `array<T>` and custom types
+pub(crate) const BUILTIN_TYPES: &[&str] = &[
+    "any",
+    "array",
+    "boolean",
+    "bytes",
+    "decimal",
+    "enum",
+    "float",
+    "integer",
+    "number",
+    "object",
+    "string",
+    "timestamp",
+];
+
 /// Output field specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputField {
@@ -124,6 +171,10 @@ pub struct CustomType {
     pub base: Option<String>,
 
     #[serde(default)]
+    #[cfg_attr(
+        feature = "binary-format",
+        serde(with = "crate::loader::binary::yaml_field")
+    )]
     pub fields: HashMap<String, serde_yaml::Value>,
 
     #[serde(default)]
@@ -144,9 +195,10 @@ pub struct Contracts {
 }
 
 /// A step in the execution flow
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FlowStep {
-    /// Step name
+    /// Step name (optional for steps nested in `then` / `else`)
+    #[serde(default)]
     pub step: String,
 
     /// Operation type
@@ -157,6 +209,10 @@ pub struct FlowStep {
 
     /// Operation parameters
     #[serde(default)]
+    #[cfg_attr(
+        feature = "binary-format",
+        serde(with = "crate::loader::binary::yaml_field")
+    )]
     pub parameters: HashMap<String, serde_yaml::Value>,
 
     /// Guard conditions
@@ -166,6 +222,14 @@ pub struct FlowStep {
     /// Condition for conditional operations
     pub condition: Option<String>,
 
+    /// Steps run when the condition holds
+    #[serde(default, rename = "then")]
+    pub then_steps: Vec<FlowStep>,
+
+    /// Steps run when the condition does not hold
+    #[serde(default, rename = "else")]
+    pub else_steps: Vec<FlowStep>,
+
     /// String template for template operations
     pub template: Option<String>,
 
@@ -175,19 +239,42 @@ pub struct FlowStep {
     /// Output variable name
     pub output: Option<String>,
 
-    /// On success handler
-    pub on_success: Option<serde_yaml::Value>,
+    /// Number of retries for operations with transient failures
+    pub retry_count: Option<u32>,
+
+    /// Cache the step result by the values of the variables it reads
+    #[serde(default)]
+    pub memoize: bool,
+
+    /// Expiry of memoized results (never, if unset)
+    pub memo_ttl_seconds: Option<u64>,
 
-    /// On error handler
-    pub on_error: Option<serde_yaml::Value>,
+    /// Steps run after the step succeeds, in a copy of the context
+    pub on_success: Option<Vec<FlowStep>>,
+
+    /// Steps run when the step fails; the error propagates only if one of
+    /// them fails too
+    pub on_error: Option<Vec<FlowStep>>,
 
     /// On failure handler
+    #[cfg_attr(
+        feature = "binary-format",
+        serde(default, with = "crate::loader::binary::yaml_field")
+    )]
     pub on_failure: Option<serde_yaml::Value>,
 
     /// Return success data
+    #[cfg_attr(
+        feature = "binary-format",
+        serde(default, with = "crate::loader::binary::yaml_field")
+    )]
     pub return_success: Option<HashMap<String, serde_yaml::Value>>,
 
     /// Return error data
+    #[cfg_attr(
+        feature = "binary-format",
+        serde(default, with = "crate::loader::binary::yaml_field")
+    )]
     pub return_error: Option<HashMap<String, serde_yaml::Value>>,
 }
 
@@ -216,6 +303,21 @@ pub struct Security {
     pub audit_level: Option<String>,
 }
 
+/// Node-level result caching
+///
+/// Results are cached by node ID and the values of `key_inputs`, so inputs
+/// not listed there do not distinguish cache entries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheSpec {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    pub ttl_seconds: u64,
+
+    #[serde(default)]
+    pub key_inputs: Vec<String>,
+}
+
 /// Runtime value type
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -224,7 +326,7 @@ pub enum Value {
     Bool(bool),
     Int(i64),
     Float(f64),
-    String(String),
+    String(SmallString),
     Array(Vec<Value>),
     Object(HashMap<String, Value>),
 }
@@ -269,15 +371,90 @@ impl Value {
     }
 }
 
+/// String storage for `Value::String`
+///
+/// With the `sso` feature, strings of up to 23 bytes are stored inline
+/// instead of on the heap; without it this is `String`. Either way it
+/// dereferences to `str` and converts to and from `String`.
+#[cfg(feature = "sso")]
+pub type SmallString = smol_str::SmolStr;
+
+/// String storage for `Value::String`
+///
+/// With the `sso` feature, strings of up to 23 bytes are stored inline
+/// instead of on the heap; without it this is `String`. Either way it
+/// dereferences to `str` and converts to and from `String`.
+#[cfg(not(feature = "sso"))]
+pub type SmallString = String;
+
+/// Conversion of a `Value::String`'s contents into a `String`, which only
+/// copies with `sso`
+pub(crate) trait IntoString {
+    fn into_string(self) -> String;
+}
+
+impl IntoString for SmallString {
+    fn into_string(self) -> String {
+        #[cfg(feature = "sso")]
+        return self.into();
+        #[cfg(not(feature = "sso"))]
+        return self;
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(json: serde_json::Value) -> Self {
+        match json {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => n.as_f64().map(Value::Float).unwrap_or(Value::Null),
+            },
+            serde_json::Value::String(s) => Value::from(s),
+            serde_json::Value::Array(items) => {
+                Value::Array(items.into_iter().map(Value::from).collect())
+            }
+            serde_json::Value::Object(map) => {
+                Value::Object(map.into_iter().map(|(k, v)| (k, Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl From<&Value> for serde_json::Value {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(*b),
+            Value::Int(i) => serde_json::Value::from(*i),
+            Value::Float(f) => serde_json::Number::from_f64(*f)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            Value::String(s) => serde_json::Value::String(s.to_string()),
+            Value::Array(items) => {
+                serde_json::Value::Array(items.iter().map(serde_json::Value::from).collect())
+            }
+            Value::Object(map) => serde_json::Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), serde_json::Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
 impl From<String> for Value {
     fn from(s: String) -> Self {
+        #[cfg(feature = "sso")]
+        let s = SmallString::from(s);
         Value::String(s)
     }
 }
 
 impl From<&str> for Value {
     fn from(s: &str) -> Self {
-        Value::String(s.to_string())
+        Value::String(s.into())
     }
 }
 
@@ -298,3 +475,62 @@ impl From<bool> for Value {
         Value::Bool(b)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::hash::{BuildHasher, RandomState};
+
+    const INLINE: &str = "abcdefghijklmnopqrstuvw";
+    const SPILLED: &str = "abcdefghijklmnopqrstuvwx";
+
+    #[test]
+    #[cfg(feature = "sso")]
+    fn test_small_string_inline_up_to_23_bytes() {
+        assert_eq!(INLINE.len(), 23);
+        assert_eq!(SPILLED.len(), 24);
+        assert!(!SmallString::from(INLINE).is_heap_allocated());
+        assert!(!SmallString::from(INLINE.to_string()).is_heap_allocated());
+        assert!(SmallString::from(SPILLED).is_heap_allocated());
+        assert!(SmallString::from(SPILLED.to_string()).is_heap_allocated());
+    }
+
+    #[test]
+    fn test_small_string_clone() {
+        for text in [INLINE, SPILLED] {
+            let value = Value::from(text);
+            let copy = value.clone();
+            assert_eq!(copy, value);
+            assert_eq!(copy.as_str(), Some(text));
+        }
+    }
+
+    #[test]
+    fn test_small_string_serde_round_trip() {
+        for text in [INLINE, SPILLED] {
+            let value = Value::from(text);
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(json, format!("\"{}\"", text));
+            assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+
+            let yaml = serde_yaml::to_string(&value).unwrap();
+            assert_eq!(serde_yaml::from_str::<Value>(&yaml).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_small_string_hash_and_eq_match_str() {
+        let hasher = RandomState::new();
+        let mut set = HashSet::new();
+        for text in [INLINE, SPILLED] {
+            let small = SmallString::from(text);
+            assert_eq!(small, text);
+            assert_eq!(small, text.to_string());
+            assert_eq!(hasher.hash_one(&small), hasher.hash_one(text));
+            set.insert(small);
+        }
+        assert!(set.contains(INLINE) && set.contains(SPILLED));
+        assert!(!set.contains("abc"));
+    }
+}
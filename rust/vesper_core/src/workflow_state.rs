@@ -0,0 +1,100 @@
+//! Persisted state of long-running `state_machine` nodes
+
+use crate::error::{Result, VesperError};
+use crate::types::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Storage for the current state of each workflow
+///
+/// A workflow is one instance of a state machine, identified by the
+/// `workflow_id` input of its executions.
+pub trait WorkflowStateBackend: Send + Sync {
+    /// Save the state and variables of a workflow, replacing any saved
+    /// before
+    fn save(&self, workflow_id: &str, state: &str, context: &HashMap<String, Value>) -> Result<()>;
+
+    /// State and variables a workflow was last saved with
+    fn load(&self, workflow_id: &str) -> Option<(String, HashMap<String, Value>)>;
+
+    /// IDs of every saved workflow, sorted
+    fn list_workflows(&self) -> Vec<String>;
+}
+
+/// Workflow state as written to disk
+#[derive(Serialize, Deserialize)]
+struct SavedWorkflow {
+    state: String,
+    context: HashMap<String, Value>,
+}
+
+/// Backend keeping each workflow in a JSON file named after its ID
+pub struct FileWorkflowStateBackend {
+    /// Directory holding the files, created on first save
+    directory: PathBuf,
+}
+
+impl FileWorkflowStateBackend {
+    /// Create a backend storing workflows in `directory`
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// File of a workflow, if its ID is a valid file name
+    fn path(&self, workflow_id: &str) -> Option<PathBuf> {
+        let valid = !workflow_id.is_empty()
+            && !workflow_id.starts_with('.')
+            && !workflow_id.contains(['/', '\\']);
+        valid.then(|| self.directory.join(format!("{}.json", workflow_id)))
+    }
+}
+
+impl WorkflowStateBackend for FileWorkflowStateBackend {
+    fn save(&self, workflow_id: &str, state: &str, context: &HashMap<String, Value>) -> Result<()> {
+        let path = self.path(workflow_id).ok_or_else(|| {
+            VesperError::ExecutionError(format!("Invalid workflow ID: {}", workflow_id))
+        })?;
+        let saved = SavedWorkflow {
+            state: state.to_string(),
+            context: context.clone(),
+        };
+        std::fs::create_dir_all(&self.directory)?;
+        // Write then rename, so a crash never leaves a truncated file
+        let partial = path.with_extension("json.tmp");
+        std::fs::write(&partial, serde_json::to_vec(&saved)?)?;
+        std::fs::rename(partial, path)?;
+        Ok(())
+    }
+
+    fn load(&self, workflow_id: &str) -> Option<(String, HashMap<String, Value>)> {
+        let content = std::fs::read(self.path(workflow_id)?).ok()?;
+        match serde_json::from_slice::<SavedWorkflow>(&content) {
+            Ok(saved) => Some((saved.state, saved.context)),
+            Err(e) => {
+                tracing::warn!("Ignoring corrupt state of workflow {}: {}", workflow_id, e);
+                None
+            }
+        }
+    }
+
+    fn list_workflows(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.directory) else {
+            return Vec::new();
+        };
+        let mut workflows: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .filter_map(|path| {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map(str::to_string)
+            })
+            .collect();
+        workflows.sort();
+        workflows
+    }
+}
@@ -0,0 +1,26 @@
+//! Benchmarks for hot path detection
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use vesper_jit::HotPathDetector;
+
+/// Record 100,000 executions spread over a varying number of nodes
+fn hot_path_detection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path_detection");
+    group.sample_size(10);
+
+    for nodes in [10, 50, 100] {
+        let node_ids: Vec<String> = (0..nodes).map(|i| format!("node{}_v1", i)).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(nodes), &node_ids, |b, ids| {
+            b.iter(|| {
+                let mut detector = HotPathDetector::new();
+                for i in 0..100_000 {
+                    black_box(detector.record_execution(&ids[i % ids.len()]));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, hot_path_detection);
+criterion_main!(benches);
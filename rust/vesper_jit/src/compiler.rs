@@ -3,6 +3,7 @@
 //! This module provides JIT compilation of hot paths to native code.
 //! Currently a placeholder - full implementation would use LLVM.
 
+use crate::hot_path::HotPathDetector;
 use std::collections::HashMap;
 use vesper_core::types::VesperNode;
 
@@ -90,6 +91,24 @@ impl JitCompiler {
         Ok(self.cache.get(&node.node_id).unwrap())
     }
 
+    /// Compile a node once the detector considers it hot by both call
+    /// count and cumulative cost
+    ///
+    /// Returns `None` for nodes not (yet) worth compiling.
+    pub fn compile_if_hot(
+        &mut self,
+        node: &VesperNode,
+        detector: &HotPathDetector,
+    ) -> Result<Option<&CompiledCode>, String> {
+        let node_id = node.node_id.as_str();
+        if !detector.should_compile(node_id)
+            || !detector.should_compile_by_cost(node_id, detector.cost_threshold())
+        {
+            return Ok(None);
+        }
+        self.compile(node).map(Some)
+    }
+
     /// Check if a node is already compiled
     pub fn is_compiled(&self, node_id: &str) -> bool {
         self.cache.contains_key(node_id)
@@ -163,4 +182,30 @@ flow:
         let stats = compiler.cache_stats();
         assert_eq!(stats.entries, 1);
     }
+
+    #[test]
+    fn test_fast_nodes_are_not_compiled() {
+        let yaml = r#"
+node_id: fast_v1
+type: function
+intent: test
+
+flow:
+  - step: noop
+    operation: return
+"#;
+        let node = VesperLoader::new().load_string(yaml).unwrap();
+        let mut detector = HotPathDetector::with_threshold(100).with_cost_threshold(100_000);
+        let mut compiler = JitCompiler::new();
+
+        for _ in 0..10_000 {
+            detector.record_execution_cost("fast_v1", 2);
+        }
+        assert!(compiler.compile_if_hot(&node, &detector).unwrap().is_none());
+        assert!(!compiler.is_compiled("fast_v1"));
+
+        detector.record_execution_cost("fast_v1", 100_000);
+        assert!(compiler.compile_if_hot(&node, &detector).unwrap().is_some());
+        assert!(compiler.is_compiled("fast_v1"));
+    }
 }
@@ -1,32 +1,51 @@
 //! Hot path detection for JIT compilation
 
+use dashmap::DashMap;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Tracks execution counts and determines which paths should be JIT compiled
 pub struct HotPathDetector {
     /// Execution counts per node
     call_counts: HashMap<String, usize>,
+    /// Cumulative execution cost per node
+    costs: HashMap<String, u64>,
     /// Threshold for triggering compilation
     compilation_threshold: usize,
+    /// Cumulative cost a node must reach before compiling pays off
+    cost_threshold: u64,
 }
 
+/// Default cost threshold: one second of cumulative execution time in µs
+const DEFAULT_COST_THRESHOLD: u64 = 1_000_000;
+
 impl HotPathDetector {
     /// Create a new detector with default threshold
     pub fn new() -> Self {
-        Self {
-            call_counts: HashMap::new(),
-            compilation_threshold: 100,
-        }
+        Self::with_threshold(100)
     }
 
     /// Create a detector with custom threshold
     pub fn with_threshold(threshold: usize) -> Self {
         Self {
             call_counts: HashMap::new(),
+            costs: HashMap::new(),
             compilation_threshold: threshold,
+            cost_threshold: DEFAULT_COST_THRESHOLD,
         }
     }
 
+    /// Set the cumulative cost required by `record_execution_cost`
+    pub fn with_cost_threshold(mut self, cost_threshold: u64) -> Self {
+        self.cost_threshold = cost_threshold;
+        self
+    }
+
+    /// The cumulative cost required by `record_execution_cost`
+    pub fn cost_threshold(&self) -> u64 {
+        self.cost_threshold
+    }
+
     /// Record an execution and check if compilation should be triggered
     pub fn record_execution(&mut self, node_id: &str) -> bool {
         let count = self.call_counts.entry(node_id.to_string()).or_insert(0);
@@ -34,6 +53,28 @@ impl HotPathDetector {
         *count >= self.compilation_threshold
     }
 
+    /// Record an execution costing `cost_units` (typically its duration in
+    /// µs) and check if compilation should be triggered
+    ///
+    /// Compilation requires both the call count threshold and the cost
+    /// threshold, so frequently called but fast nodes are left alone.
+    pub fn record_execution_cost(&mut self, node_id: &str, cost_units: u64) -> bool {
+        let hot = self.record_execution(node_id);
+        let cost = self.costs.entry(node_id.to_string()).or_insert(0);
+        *cost = cost.saturating_add(cost_units);
+        hot && *cost >= self.cost_threshold
+    }
+
+    /// Check if a node's cumulative cost reached `cost_threshold`
+    pub fn should_compile_by_cost(&self, node_id: &str, cost_threshold: u64) -> bool {
+        self.get_cost(node_id) >= cost_threshold
+    }
+
+    /// Get the cumulative cost recorded for a node
+    pub fn get_cost(&self, node_id: &str) -> u64 {
+        self.costs.get(node_id).copied().unwrap_or(0)
+    }
+
     /// Check if a node should be compiled
     pub fn should_compile(&self, node_id: &str) -> bool {
         self.call_counts
@@ -50,6 +91,12 @@ impl HotPathDetector {
     /// Reset all counts
     pub fn reset(&mut self) {
         self.call_counts.clear();
+        self.costs.clear();
+    }
+
+    /// Create a detector that can be shared between threads
+    pub fn with_concurrent(threshold: usize) -> ConcurrentHotPathDetector {
+        ConcurrentHotPathDetector::with_threshold(threshold)
     }
 }
 
@@ -59,6 +106,74 @@ impl Default for HotPathDetector {
     }
 }
 
+/// Hot path detector recording executions from many threads without a lock
+///
+/// Counts are atomics in a sharded map, so recording only takes a shard
+/// lock the first time a node is seen.
+pub struct ConcurrentHotPathDetector {
+    /// Execution counts per node
+    call_counts: DashMap<String, AtomicUsize>,
+    /// Threshold for triggering compilation
+    compilation_threshold: usize,
+}
+
+impl ConcurrentHotPathDetector {
+    /// Create a new detector with default threshold
+    pub fn new() -> Self {
+        Self::with_threshold(100)
+    }
+
+    /// Create a detector with custom threshold
+    pub fn with_threshold(threshold: usize) -> Self {
+        Self {
+            call_counts: DashMap::new(),
+            compilation_threshold: threshold,
+        }
+    }
+
+    /// Record an execution and check if compilation should be triggered
+    pub fn record_execution(&self, node_id: &str) -> bool {
+        let count = match self.call_counts.get(node_id) {
+            Some(count) => count.fetch_add(1, Ordering::Relaxed) + 1,
+            None => {
+                self.call_counts
+                    .entry(node_id.to_string())
+                    .or_insert_with(|| AtomicUsize::new(0))
+                    .fetch_add(1, Ordering::Relaxed)
+                    + 1
+            }
+        };
+        count >= self.compilation_threshold
+    }
+
+    /// Check if a node should be compiled
+    pub fn should_compile(&self, node_id: &str) -> bool {
+        self.call_counts
+            .get(node_id)
+            .map(|count| count.load(Ordering::Relaxed) >= self.compilation_threshold)
+            .unwrap_or(false)
+    }
+
+    /// Get the execution count for a node
+    pub fn get_count(&self, node_id: &str) -> usize {
+        self.call_counts
+            .get(node_id)
+            .map(|count| count.load(Ordering::Relaxed))
+            .unwrap_or(0)
+    }
+
+    /// Reset all counts
+    pub fn reset(&self) {
+        self.call_counts.clear();
+    }
+}
+
+impl Default for ConcurrentHotPathDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,4 +203,42 @@ mod tests {
         assert!(detector.should_compile("node_a_v1"));
         assert!(!detector.should_compile("node_b_v1"));
     }
+
+    #[test]
+    fn test_cost_weighted_detection() {
+        let mut detector = HotPathDetector::with_threshold(10).with_cost_threshold(50_000);
+
+        // 1000 calls of 1µs: hot by count, not worth compiling
+        for _ in 0..1_000 {
+            assert!(!detector.record_execution_cost("fast_v1", 1));
+        }
+        assert!(detector.should_compile("fast_v1"));
+        assert!(!detector.should_compile_by_cost("fast_v1", detector.cost_threshold()));
+
+        // 10 calls of 5ms reach both thresholds
+        for _ in 0..9 {
+            assert!(!detector.record_execution_cost("slow_v1", 5_000));
+        }
+        assert!(detector.record_execution_cost("slow_v1", 5_000));
+        assert_eq!(detector.get_cost("slow_v1"), 50_000);
+    }
+
+    #[test]
+    fn test_concurrent_recording() {
+        let detector = HotPathDetector::with_concurrent(1_000);
+
+        std::thread::scope(|scope| {
+            for _ in 0..16 {
+                scope.spawn(|| {
+                    for _ in 0..500 {
+                        detector.record_execution("shared_v1");
+                    }
+                });
+            }
+        });
+
+        assert_eq!(detector.get_count("shared_v1"), 8_000);
+        assert!(detector.should_compile("shared_v1"));
+        assert!(!detector.should_compile("other_v1"));
+    }
 }
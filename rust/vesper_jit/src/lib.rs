@@ -7,4 +7,4 @@ pub mod compiler;
 pub mod hot_path;
 
 pub use compiler::JitCompiler;
-pub use hot_path::HotPathDetector;
+pub use hot_path::{ConcurrentHotPathDetector, HotPathDetector};
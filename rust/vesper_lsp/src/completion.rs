@@ -0,0 +1,191 @@
+//! Completion of operations, node types and input names
+
+use crate::workspace::{line_prefix, Workspace};
+use lsp_types::{CompletionItem, CompletionItemKind, Documentation, Position, Url};
+use std::collections::BTreeMap;
+use vesper_core::executor::BUILTIN_OPERATIONS;
+use vesper_core::types::{InputSpec, NODE_TYPES};
+
+/// What the text before the cursor is about to be
+#[derive(Debug, PartialEq, Eq)]
+enum Context {
+    /// Value of an `operation` field
+    Operation,
+    /// Value of the top-level `type` field or a `node_type` parameter
+    NodeType,
+    /// Variable name inside a `{...}` reference
+    Variable,
+    /// Key of the top-level `inputs` mapping
+    InputName,
+}
+
+/// Completions at `position` of the document `uri`
+///
+/// Input names come from the other specs of the workspace, so inputs that
+/// mean the same thing keep the same name across nodes.
+pub fn completions(workspace: &Workspace, uri: &Url, position: Position) -> Vec<CompletionItem> {
+    let Some(document) = workspace.document(uri) else {
+        return Vec::new();
+    };
+    match context(&document.text, position) {
+        Some(Context::Operation) => BUILTIN_OPERATIONS
+            .iter()
+            .map(|name| item(name, CompletionItemKind::FUNCTION, None, None))
+            .collect(),
+        Some(Context::NodeType) => NODE_TYPES
+            .iter()
+            .map(|name| item(name, CompletionItemKind::ENUM_MEMBER, None, None))
+            .collect(),
+        Some(Context::Variable) => document
+            .node
+            .iter()
+            .flat_map(|node| &node.inputs)
+            .map(|(name, spec)| input_item(name, spec, None))
+            .collect(),
+        Some(Context::InputName) => {
+            let mut inputs = BTreeMap::new();
+            let others = workspace
+                .specs()
+                .filter(|(other, _)| *other != uri)
+                .filter_map(|(_, other)| other.node.as_ref());
+            for node in others {
+                for (name, input) in &node.inputs {
+                    inputs
+                        .entry(name.as_str())
+                        .or_insert((input, node.node_id.as_str()));
+                }
+            }
+            inputs
+                .into_iter()
+                .map(|(name, (spec, node_id))| input_item(name, spec, Some(node_id)))
+                .collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Context of the text before `position`
+fn context(text: &str, position: Position) -> Option<Context> {
+    let prefix = line_prefix(text, position);
+    if prefix.rfind('{') > prefix.rfind('}') {
+        return Some(Context::Variable);
+    }
+
+    let indent = prefix.len() - prefix.trim_start().len();
+    let entry = prefix.trim_start();
+    let entry = entry.strip_prefix("- ").unwrap_or(entry);
+    match entry.split_once(':') {
+        Some((_, value)) if value.contains(':') => None,
+        Some(("operation", _)) => Some(Context::Operation),
+        Some(("type", _)) if indent == 0 => Some(Context::NodeType),
+        Some(("node_type", _)) => Some(Context::NodeType),
+        Some(_) => None,
+        None => {
+            let parent = text
+                .lines()
+                .take(position.line as usize)
+                .filter(|line| {
+                    let content = line.trim_start();
+                    !content.is_empty()
+                        && !content.starts_with('#')
+                        && line.len() - content.len() < indent
+                })
+                .last()?;
+            (indent > 0 && parent.trim_end() == "inputs:").then_some(Context::InputName)
+        }
+    }
+}
+
+fn input_item(name: &str, spec: &InputSpec, node_id: Option<&str>) -> CompletionItem {
+    let detail = match node_id {
+        Some(node_id) => format!("{} (from {})", spec.input_type, node_id),
+        None => spec.input_type.clone(),
+    };
+    item(
+        name,
+        CompletionItemKind::VARIABLE,
+        Some(detail),
+        spec.description.clone(),
+    )
+}
+
+fn item(
+    label: &str,
+    kind: CompletionItemKind,
+    detail: Option<String>,
+    documentation: Option<String>,
+) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        detail,
+        documentation: documentation.map(Documentation::String),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{uri, workspace};
+
+    const GREET: &str = r#"node_id: greet_v1
+type: function
+intent: greet a user
+inputs:
+  name:
+    type: string
+    description: Name to greet
+
+flow:
+  - step: greet
+    operation: str
+    template: "Hello, {na"
+"#;
+
+    const SHOUT: &str = r#"node_id: shout_v1
+type: function
+intent: shout at a user
+inputs:
+  volume:
+    type: integer
+flow:
+  - step: shout
+    operation: string_template
+    template: "HEY"
+"#;
+
+    fn labels(items: Vec<CompletionItem>) -> Vec<String> {
+        items.into_iter().map(|item| item.label).collect()
+    }
+
+    #[test]
+    fn test_completions_follow_context() {
+        let specs = workspace(&[("greet", GREET), ("shout", SHOUT)]);
+        let complete =
+            |line, character| completions(&specs, &uri("greet"), Position::new(line, character));
+
+        let operations = labels(complete(10, 18));
+        assert!(operations.contains(&"string_template".to_string()));
+        assert_eq!(operations.len(), BUILTIN_OPERATIONS.len());
+        assert_eq!(labels(complete(1, 6)).len(), NODE_TYPES.len());
+
+        let variables = complete(11, 24);
+        assert_eq!(labels(variables.clone()), vec!["name"]);
+        assert_eq!(
+            variables[0].documentation,
+            Some(Documentation::String("Name to greet".to_string()))
+        );
+
+        let mut specs = specs;
+        specs.update(
+            uri("greet"),
+            GREET.replace("Name to greet\n", "Name to greet\n  vo\n"),
+        );
+        let inputs = completions(&specs, &uri("greet"), Position::new(7, 4));
+        assert_eq!(labels(inputs.clone()), vec!["volume"]);
+        assert_eq!(inputs[0].detail.as_deref(), Some("integer (from shout_v1)"));
+
+        assert!(completions(&specs, &uri("greet"), Position::new(2, 8)).is_empty());
+    }
+}
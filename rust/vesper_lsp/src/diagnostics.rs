@@ -0,0 +1,135 @@
+//! Errors of a spec as diagnostics
+
+use crate::workspace::{position, scalar_range};
+use lsp_types::{Diagnostic, DiagnosticSeverity, Range};
+use vesper_core::{VesperError, VesperLoader};
+use vesper_tree_sitter::{scalars, Scalar};
+
+/// Errors of a spec, each at the construct it is about
+///
+/// Structural errors are all reported at once. Only a structurally sound
+/// spec is loaded, reporting the first error `VesperLoader` finds, such as
+/// a malformed node ID or an undefined variable, at the field it names.
+pub fn diagnostics(text: &str) -> Vec<Diagnostic> {
+    let loader = VesperLoader::new();
+    let found = scalars(text).unwrap_or_default();
+    match loader.validate_syntax(text) {
+        Ok(errors) if !errors.is_empty() => {
+            return errors
+                .into_iter()
+                .map(|error| {
+                    let message = match error.path.as_str() {
+                        "" => error.message,
+                        path => format!("{}: {}", path, error.message),
+                    };
+                    diagnostic(text, &found, (error.line, error.column), message)
+                })
+                .collect();
+        }
+        Ok(_) => {}
+        Err(e) => return vec![diagnostic(text, &found, (1, 1), e.to_string())],
+    }
+
+    match loader.load_string(text) {
+        Ok(_) => Vec::new(),
+        Err(error) => {
+            let at = match &error {
+                VesperError::ValidationError { path, .. } => locate(&found, path),
+                VesperError::YamlError(e) => e
+                    .location()
+                    .map_or((1, 1), |location| (location.line(), location.column())),
+                _ => (1, 1),
+            };
+            vec![diagnostic(text, &found, at, error.to_string())]
+        }
+    }
+}
+
+/// Position of the key of the field at `path`, or of its closest
+/// enclosing field
+fn locate(found: &[Scalar], path: &str) -> (usize, usize) {
+    let mut path = path;
+    loop {
+        if let Some(key) = found.iter().find(|s| s.is_key && s.path == path) {
+            return (key.line, key.column);
+        }
+        match path.rfind(['.', '[']) {
+            Some(end) => path = &path[..end],
+            None => return (1, 1),
+        }
+    }
+}
+
+/// Error covering the scalar starting at `at`, or the rest of its line
+fn diagnostic(text: &str, found: &[Scalar], at: (usize, usize), message: String) -> Diagnostic {
+    let (line, column) = at;
+    let range = match found.iter().find(|s| (s.line, s.column) == at) {
+        Some(scalar) => scalar_range(text, scalar),
+        None => {
+            let length = text.lines().nth(line.saturating_sub(1)).map_or(0, str::len);
+            Range::new(
+                position(text, line, column),
+                position(text, line, length + 1),
+            )
+        }
+    };
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("vesper".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn test_errors_are_reported_at_their_field() {
+        let structural =
+            "node_id: greet_v1\ntype: function\nflow:\n  - step: greet\n    operation: 42\n";
+        let found = diagnostics(structural);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].message,
+            "flow[0].operation: expected a string, found a number"
+        );
+        assert_eq!(
+            found[0].range,
+            Range::new(Position::new(4, 15), Position::new(4, 17))
+        );
+
+        let invalid = r#"
+node_id: greet
+type: function
+intent: greet a user
+inputs:
+  name:
+    type: string
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello, {nickname}!"
+"#;
+        let found = diagnostics(invalid);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("Invalid node_id format"));
+        assert_eq!(found[0].range.start, Position::new(1, 0));
+
+        let undefined = invalid.replace("node_id: greet\n", "node_id: greet_v1\n");
+        let found = diagnostics(&undefined);
+        assert_eq!(
+            found[0].message,
+            "Validation error at flow[0].template: Undefined variable: nickname"
+        );
+        assert_eq!(found[0].range.start, Position::new(10, 4));
+
+        assert_eq!(
+            diagnostics(&undefined.replace("nickname", "name")),
+            Vec::new()
+        );
+    }
+}
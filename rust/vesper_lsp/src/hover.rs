@@ -0,0 +1,61 @@
+//! Input descriptions on hover
+
+use crate::workspace::{word_at, Workspace};
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position, Url};
+
+/// Type and description of the input of the document's node named by the
+/// word at `position`, in its declaration or in any reference to it
+pub fn hover(workspace: &Workspace, uri: &Url, position: Position) -> Option<Hover> {
+    let document = workspace.document(uri)?;
+    let (word, range) = word_at(&document.text, position)?;
+    let spec = document.node.as_ref()?.inputs.get(&word)?;
+
+    let mut value = format!("`{}`: `{}`", word, spec.input_type);
+    if !spec.required {
+        value.push_str(" (optional)");
+    }
+    if let Some(description) = &spec.description {
+        value.push_str("\n\n");
+        value.push_str(description);
+    }
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(range),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{uri, workspace};
+
+    #[test]
+    fn test_hover_shows_input_description() {
+        let spec = r#"node_id: greet_v1
+type: function
+intent: greet a user
+inputs:
+  name:
+    type: string
+    description: Name to greet
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello, {name}!"
+"#;
+        let specs = workspace(&[("greet", spec)]);
+        let Some(Hover {
+            contents: HoverContents::Markup(markup),
+            ..
+        }) = hover(&specs, &uri("greet"), Position::new(10, 24))
+        else {
+            panic!("expected markup hover");
+        };
+        assert_eq!(markup.value, "`name`: `string`\n\nName to greet");
+        assert!(hover(&specs, &uri("greet"), Position::new(4, 3)).is_some());
+        assert!(hover(&specs, &uri("greet"), Position::new(10, 18)).is_none());
+    }
+}
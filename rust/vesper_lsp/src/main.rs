@@ -0,0 +1,32 @@
+//! Vesper language server
+//!
+//! Speaks the Language Server Protocol over stdin and stdout, giving
+//! editors completion, hover, diagnostics, go to definition and rename for
+//! Vesper YAML specs. Every spec under the client's workspace folders is
+//! indexed at startup, so node IDs resolve across files.
+
+mod completion;
+mod diagnostics;
+mod hover;
+mod navigation;
+mod server;
+mod workspace;
+
+use lsp_server::Connection;
+use lsp_types::InitializeParams;
+use server::{Server, ServerError};
+
+fn main() -> Result<(), ServerError> {
+    // Stdout carries the protocol, so logs go to stderr
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let (connection, io_threads) = Connection::stdio();
+    let params = connection.initialize(serde_json::to_value(server::capabilities())?)?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    Server::new(&params).run(&connection)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//! Going to and renaming nodes across the workspace
+
+use crate::workspace::{scalar_range, word_at, Workspace};
+use lsp_types::{Location, Position, TextEdit, Url, WorkspaceEdit};
+use std::collections::HashMap;
+use vesper_tree_sitter::scalars;
+
+/// Spec of the node whose ID is at `position`
+///
+/// Node IDs appear wherever one node names another, such as the `node` of a
+/// `workflow_orchestrator` graph entry or the `nodes` of a `scatter_gather`
+/// step; the location is the `node_id` field of the target's spec.
+pub fn definition(workspace: &Workspace, uri: &Url, position: Position) -> Option<Location> {
+    let document = workspace.document(uri)?;
+    let (word, _) = word_at(&document.text, position)?;
+    let (target, spec) = workspace.definition_of(&word)?;
+    let found = scalars(&spec.text).ok()?;
+    let node_id = found.iter().find(|s| s.path == "node_id" && !s.is_key)?;
+    Some(Location::new(
+        target.clone(),
+        scalar_range(&spec.text, node_id),
+    ))
+}
+
+/// Rename the node whose ID is at `position` in every spec of the workspace
+///
+/// Every scalar holding exactly the old ID is replaced; IDs inside
+/// templates or expressions are left alone.
+pub fn rename(
+    workspace: &Workspace,
+    uri: &Url,
+    position: Position,
+    new_name: &str,
+) -> Result<WorkspaceEdit, String> {
+    let document = workspace
+        .document(uri)
+        .ok_or_else(|| format!("Unknown document {}", uri))?;
+    let (old_name, _) =
+        word_at(&document.text, position).ok_or_else(|| "No node ID here".to_string())?;
+    if workspace.definition_of(&old_name).is_none() {
+        return Err(format!("{} is not the ID of a node", old_name));
+    }
+    if !new_name.contains("_v") {
+        return Err(format!(
+            "Invalid node_id format: {}. Expected: name_vN",
+            new_name
+        ));
+    }
+    if workspace.definition_of(new_name).is_some() {
+        return Err(format!("Node {} already exists", new_name));
+    }
+
+    let mut changes = HashMap::new();
+    for (spec_uri, spec) in workspace.specs() {
+        let edits: Vec<TextEdit> = scalars(&spec.text)?
+            .iter()
+            .filter(|s| !s.is_key && s.value == old_name)
+            .map(|s| TextEdit::new(scalar_range(&spec.text, s), new_name.to_string()))
+            .collect();
+        if !edits.is_empty() {
+            changes.insert(spec_uri.clone(), edits);
+        }
+    }
+    Ok(WorkspaceEdit::new(changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{uri, workspace};
+    use lsp_types::Range;
+
+    const GREET: &str = r#"node_id: greet_v1
+type: function
+intent: greet a user
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello!"
+"#;
+
+    const WELCOME: &str = r#"node_id: welcome_v1
+type: function
+intent: greet everyone
+flow:
+  - step: fan_out
+    operation: scatter_gather
+    parameters:
+      nodes: [greet_v1, "greet_v1"]
+  - step: note
+    operation: string_template
+    template: "uses greet_v1"
+"#;
+
+    #[test]
+    fn test_definition_jumps_to_node_id() {
+        let specs = workspace(&[("greet", GREET), ("welcome", WELCOME)]);
+        let location = definition(&specs, &uri("welcome"), Position::new(7, 16)).unwrap();
+        assert_eq!(location.uri, uri("greet"));
+        assert_eq!(
+            location.range,
+            Range::new(Position::new(0, 9), Position::new(0, 17))
+        );
+        assert!(definition(&specs, &uri("welcome"), Position::new(5, 20)).is_none());
+    }
+
+    #[test]
+    fn test_rename_replaces_every_reference() {
+        let specs = workspace(&[("greet", GREET), ("welcome", WELCOME)]);
+        let edit = rename(&specs, &uri("welcome"), Position::new(7, 16), "hello_v1").unwrap();
+        let changes = edit.changes.unwrap();
+        assert_eq!(changes[&uri("greet")].len(), 1);
+        let ranges: Vec<Range> = changes[&uri("welcome")].iter().map(|e| e.range).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                Range::new(Position::new(7, 14), Position::new(7, 22)),
+                Range::new(Position::new(7, 25), Position::new(7, 33)),
+            ]
+        );
+
+        let taken = rename(&specs, &uri("greet"), Position::new(0, 10), "welcome_v1");
+        assert_eq!(taken.unwrap_err(), "Node welcome_v1 already exists");
+        assert!(rename(&specs, &uri("greet"), Position::new(0, 10), "hello").is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Message loop of the language server
+
+use crate::workspace::Workspace;
+use crate::{completion, diagnostics, hover, navigation};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, Rename, Request as _};
+use lsp_types::{
+    CompletionOptions, CompletionResponse, GotoDefinitionResponse, HoverProviderCapability,
+    InitializeParams, OneOf, PublishDiagnosticsParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use std::error::Error;
+
+/// Error ending the message loop
+pub type ServerError = Box<dyn Error + Send + Sync>;
+
+/// Features announced to the client
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["{".to_string(), " ".to_string()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+/// Language server state
+pub struct Server {
+    workspace: Workspace,
+}
+
+impl Server {
+    /// Create a server knowing every spec under the client's workspace
+    /// folders
+    pub fn new(params: &InitializeParams) -> Self {
+        #[allow(deprecated)]
+        let roots: Vec<&Url> = match &params.workspace_folders {
+            Some(folders) => folders.iter().map(|folder| &folder.uri).collect(),
+            None => params.root_uri.iter().collect(),
+        };
+        let mut workspace = Workspace::default();
+        for root in roots {
+            if let Ok(path) = root.to_file_path() {
+                workspace.scan(&path);
+            }
+        }
+        Self { workspace }
+    }
+
+    /// Handle messages until the client shuts the server down
+    pub fn run(&mut self, connection: &Connection) -> Result<(), ServerError> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(uri) = self.handle_notification(notification) {
+                        self.publish_diagnostics(connection, uri)?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            Completion::METHOD => respond::<Completion>(request, |params| {
+                let position = params.text_document_position;
+                let items = completion::completions(
+                    &self.workspace,
+                    &position.text_document.uri,
+                    position.position,
+                );
+                Ok(Some(CompletionResponse::Array(items)))
+            }),
+            HoverRequest::METHOD => respond::<HoverRequest>(request, |params| {
+                let position = params.text_document_position_params;
+                Ok(hover::hover(
+                    &self.workspace,
+                    &position.text_document.uri,
+                    position.position,
+                ))
+            }),
+            GotoDefinition::METHOD => respond::<GotoDefinition>(request, |params| {
+                let position = params.text_document_position_params;
+                let location = navigation::definition(
+                    &self.workspace,
+                    &position.text_document.uri,
+                    position.position,
+                );
+                Ok(location.map(GotoDefinitionResponse::Scalar))
+            }),
+            Rename::METHOD => respond::<Rename>(request, |params| {
+                let position = params.text_document_position;
+                navigation::rename(
+                    &self.workspace,
+                    &position.text_document.uri,
+                    position.position,
+                    &params.new_name,
+                )
+                .map(Some)
+            }),
+            method => Err((
+                ErrorCode::MethodNotFound,
+                format!("Unsupported request {}", method),
+            )),
+        };
+        match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err((code, message)) => Response::new_err(id, code as i32, message),
+        }
+    }
+
+    /// Apply a document notification, returning the document whose
+    /// diagnostics changed
+    fn handle_notification(&mut self, notification: Notification) -> Option<Url> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = parse::<DidOpenTextDocument>(notification)?;
+                let document = params.text_document;
+                self.workspace.update(document.uri.clone(), document.text);
+                Some(document.uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = parse::<DidChangeTextDocument>(notification)?;
+                let text = params.content_changes.into_iter().last()?.text;
+                let uri = params.text_document.uri;
+                self.workspace.update(uri.clone(), text);
+                Some(uri)
+            }
+            DidSaveTextDocument::METHOD => {
+                let params = parse::<DidSaveTextDocument>(notification)?;
+                Some(params.text_document.uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = parse::<DidCloseTextDocument>(notification)?;
+                self.workspace.close(&params.text_document.uri);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn publish_diagnostics(&self, connection: &Connection, uri: Url) -> Result<(), ServerError> {
+        let Some(document) = self.workspace.document(&uri) else {
+            return Ok(());
+        };
+        let params = PublishDiagnosticsParams {
+            diagnostics: diagnostics::diagnostics(&document.text),
+            uri,
+            version: None,
+        };
+        connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                params,
+            )))?;
+        Ok(())
+    }
+}
+
+/// Run `handler` on the parameters of a request, serializing its result
+fn respond<R: lsp_types::request::Request>(
+    request: Request,
+    handler: impl FnOnce(R::Params) -> Result<R::Result, String>,
+) -> Result<serde_json::Value, (ErrorCode, String)> {
+    let params = serde_json::from_value(request.params)
+        .map_err(|e| (ErrorCode::InvalidParams, e.to_string()))?;
+    let result = handler(params).map_err(|message| (ErrorCode::RequestFailed, message))?;
+    serde_json::to_value(result).map_err(|e| (ErrorCode::InternalError, e.to_string()))
+}
+
+fn parse<N: lsp_types::notification::Notification>(
+    notification: Notification,
+) -> Option<N::Params> {
+    match serde_json::from_value(notification.params) {
+        Ok(params) => Some(params),
+        Err(e) => {
+            tracing::warn!("Malformed {} notification: {}", N::METHOD, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::uri;
+    use lsp_types::{
+        DidOpenTextDocumentParams, Location, Position, TextDocumentIdentifier, TextDocumentItem,
+        TextDocumentPositionParams,
+    };
+
+    #[test]
+    fn test_server_answers_over_connection() {
+        let (server, client) = Connection::memory();
+        let handle = std::thread::spawn(move || {
+            Server {
+                workspace: Workspace::default(),
+            }
+            .run(&server)
+            .unwrap()
+        });
+
+        let spec = "node_id: greet_v1\ntype: function\nintent: greet\nflow:\n  - step: greet\n    operation: string_template\n    template: \"{nickname}\"\n";
+        let open = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri("greet"),
+                "yaml".to_string(),
+                1,
+                spec.to_string(),
+            ),
+        };
+        client
+            .sender
+            .send(Message::Notification(Notification::new(
+                DidOpenTextDocument::METHOD.to_string(),
+                open,
+            )))
+            .unwrap();
+        let Ok(Message::Notification(published)) = client.receiver.recv() else {
+            panic!("expected diagnostics");
+        };
+        let published: PublishDiagnosticsParams = serde_json::from_value(published.params).unwrap();
+        assert_eq!(published.diagnostics.len(), 1);
+
+        let params = TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(uri("greet")),
+            Position::new(0, 12),
+        );
+        client
+            .sender
+            .send(Message::Request(Request::new(
+                1.into(),
+                GotoDefinition::METHOD.to_string(),
+                params,
+            )))
+            .unwrap();
+        let Ok(Message::Response(response)) = client.receiver.recv() else {
+            panic!("expected response");
+        };
+        let location: Location = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(location.uri, uri("greet"));
+
+        client
+            .sender
+            .send(Message::Request(Request::new(
+                2.into(),
+                "shutdown".to_string(),
+                (),
+            )))
+            .unwrap();
+        client
+            .sender
+            .send(Message::Notification(Notification::new(
+                "exit".to_string(),
+                (),
+            )))
+            .unwrap();
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! Specs known to the server and positions within them
+
+use lsp_types::{Position, Range, Url};
+use std::collections::HashMap;
+use std::path::Path;
+use vesper_core::VesperNode;
+use vesper_tree_sitter::Scalar;
+
+/// Directories never scanned for specs, besides hidden ones
+const SKIPPED_DIRECTORIES: &[&str] = &["target", "node_modules"];
+
+/// Spec file and the node it parses to
+pub struct Document {
+    pub text: String,
+    /// Node of the latest text that deserialized, kept while edits break it
+    pub node: Option<VesperNode>,
+}
+
+/// Every spec of the workspace, open in the editor or not
+#[derive(Default)]
+pub struct Workspace {
+    documents: HashMap<Url, Document>,
+}
+
+impl Workspace {
+    /// Load every YAML file under `root`, skipping hidden and build
+    /// directories
+    pub fn scan(&mut self, root: &Path) {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if path.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRECTORIES.contains(&name.as_str()) {
+                    self.scan(&path);
+                }
+            } else if matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("yaml" | "yml")
+            ) {
+                if let (Ok(text), Ok(uri)) =
+                    (std::fs::read_to_string(&path), Url::from_file_path(&path))
+                {
+                    self.update(uri, text);
+                }
+            }
+        }
+    }
+
+    /// Replace the text of a document
+    pub fn update(&mut self, uri: Url, text: String) {
+        let node = serde_yaml::from_str::<VesperNode>(&text).ok();
+        match self.documents.get_mut(&uri) {
+            Some(document) => {
+                document.node = node.or(document.node.take());
+                document.text = text;
+            }
+            None => {
+                self.documents.insert(uri, Document { text, node });
+            }
+        }
+    }
+
+    /// Go back to the saved text of a closed document, or forget it if it
+    /// was never saved
+    pub fn close(&mut self, uri: &Url) {
+        match uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+        {
+            Some(text) => self.update(uri.clone(), text),
+            None => {
+                self.documents.remove(uri);
+            }
+        }
+    }
+
+    pub fn document(&self, uri: &Url) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    /// Documents holding a Vesper node
+    pub fn specs(&self) -> impl Iterator<Item = (&Url, &Document)> {
+        self.documents
+            .iter()
+            .filter(|(_, document)| document.node.is_some())
+    }
+
+    /// Document defining the node `node_id`
+    pub fn definition_of(&self, node_id: &str) -> Option<(&Url, &Document)> {
+        self.specs().find(|(_, document)| {
+            document
+                .node
+                .as_ref()
+                .is_some_and(|node| node.node_id == node_id)
+        })
+    }
+}
+
+/// LSP position of a 1-based line and byte column
+pub fn position(text: &str, line: usize, column: usize) -> Position {
+    let row = line.saturating_sub(1);
+    let line_text = text.lines().nth(row).unwrap_or_default();
+    let prefix = line_text
+        .get(..column.saturating_sub(1))
+        .unwrap_or(line_text);
+    Position::new(row as u32, prefix.encode_utf16().count() as u32)
+}
+
+/// Range of the text of a scalar
+pub fn scalar_range(text: &str, scalar: &Scalar) -> Range {
+    Range::new(
+        position(text, scalar.line, scalar.column),
+        position(text, scalar.end_line, scalar.end_column),
+    )
+}
+
+/// Text of the line at `position`, up to it
+pub fn line_prefix(text: &str, position: Position) -> &str {
+    let line = text.lines().nth(position.line as usize).unwrap_or_default();
+    &line[..byte_column(line, position.character)]
+}
+
+/// Identifier under `position`, e.g. a node ID or variable name, and its
+/// range
+pub fn word_at(text: &str, position: Position) -> Option<(String, Range)> {
+    let line = text.lines().nth(position.line as usize)?;
+    let at = byte_column(line, position.character);
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let start = line[..at]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_word(*c))
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    let end = line[at..]
+        .find(|c: char| !is_word(c))
+        .map_or(line.len(), |index| at + index);
+    if start == end {
+        return None;
+    }
+    let character = |byte: usize| line[..byte].encode_utf16().count() as u32;
+    let range = Range::new(
+        Position::new(position.line, character(start)),
+        Position::new(position.line, character(end)),
+    );
+    Some((line[start..end].to_string(), range))
+}
+
+/// Byte offset of a UTF-16 `character` offset in `line`
+fn byte_column(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (index, c) in line.char_indices() {
+        if units >= character as usize {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+#[cfg(test)]
+pub(crate) fn workspace(specs: &[(&str, &str)]) -> Workspace {
+    let mut workspace = Workspace::default();
+    for (name, text) in specs {
+        workspace.update(uri(name), text.to_string());
+    }
+    workspace
+}
+
+#[cfg(test)]
+pub(crate) fn uri(name: &str) -> Url {
+    Url::parse(&format!("file:///specs/{}.yaml", name)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_count_utf16_units() {
+        let text = "intent: grüße {name}\n";
+        let (word, range) = word_at(text, Position::new(0, 16)).unwrap();
+        assert_eq!(word, "name");
+        assert_eq!(
+            range,
+            Range::new(Position::new(0, 15), Position::new(0, 19))
+        );
+        assert_eq!(line_prefix(text, Position::new(0, 14)), "intent: grüße ");
+        assert_eq!(position(text, 1, 18), Position::new(0, 15));
+        assert_eq!(word_at(text, Position::new(0, 14)), None);
+    }
+}
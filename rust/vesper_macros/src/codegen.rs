@@ -0,0 +1,367 @@
+//! Rust expressions reconstructing a loaded node
+//!
+//! Empty optional fields, collections and flow step fields are generated
+//! as `None`, empty constructors or `Default::default()`, so only data the
+//! spec contains ends up in the binary.
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use std::collections::HashMap;
+use vesper_core::types::{
+    CacheSpec, Contracts, CustomType, FlowStep, InputSpec, Metadata, NodeType, OutputField,
+    Outputs, Performance, Security, VesperNode,
+};
+
+/// Expression evaluating to `node`
+pub(crate) fn node(node: &VesperNode) -> TokenStream {
+    let node_id = string(&node.node_id);
+    let node_type = node_type(node.node_type);
+    let intent = string(&node.intent);
+    let metadata = option(&node.metadata, metadata);
+    let imports = option(&node.imports, |imports| map(imports, |v| string(v)));
+    let inputs = map(&node.inputs, input_spec);
+    let outputs = option(&node.outputs, outputs);
+    let types = map(&node.types, custom_type);
+    let contracts = option(&node.contracts, contracts);
+    let input_transform = option(&node.input_transform, |steps| vec(steps, flow_step));
+    let flow = vec(&node.flow, flow_step);
+    let output_transform = option(&node.output_transform, |steps| vec(steps, flow_step));
+    let performance = option(&node.performance, performance);
+    let security = option(&node.security, security);
+    let cache = option(&node.cache, cache_spec);
+
+    quote! {
+        ::vesper_core::types::VesperNode {
+            node_id: #node_id,
+            node_type: #node_type,
+            intent: #intent,
+            metadata: #metadata,
+            imports: #imports,
+            inputs: #inputs,
+            outputs: #outputs,
+            types: #types,
+            contracts: #contracts,
+            input_transform: #input_transform,
+            flow: #flow,
+            output_transform: #output_transform,
+            performance: #performance,
+            security: #security,
+            cache: #cache,
+        }
+    }
+}
+
+fn node_type(node_type: NodeType) -> TokenStream {
+    let variant = match node_type {
+        NodeType::Function => quote!(Function),
+        NodeType::HttpHandler => quote!(HttpHandler),
+        NodeType::EventHandler => quote!(EventHandler),
+        NodeType::DataTransform => quote!(DataTransform),
+        NodeType::StateMachine => quote!(StateMachine),
+        NodeType::Aggregation => quote!(Aggregation),
+        NodeType::ScheduledJob => quote!(ScheduledJob),
+    };
+    quote!(::vesper_core::types::NodeType::#variant)
+}
+
+fn metadata(metadata: &Metadata) -> TokenStream {
+    let author = option(&metadata.author, |s| string(s));
+    let created = option(&metadata.created, |s| string(s));
+    let version = option(&metadata.version, |s| string(s));
+    let description = option(&metadata.description, |s| string(s));
+    let tags = vec(&metadata.tags, |s| string(s));
+    let dependencies = vec(&metadata.dependencies, |s| string(s));
+    quote! {
+        ::vesper_core::types::Metadata {
+            author: #author,
+            created: #created,
+            version: #version,
+            description: #description,
+            tags: #tags,
+            dependencies: #dependencies,
+        }
+    }
+}
+
+fn input_spec(spec: &InputSpec) -> TokenStream {
+    let input_type = string(&spec.input_type);
+    let required = spec.required;
+    let constraints = vec(&spec.constraints, |s| string(s));
+    let default = option(&spec.default, yaml);
+    let description = option(&spec.description, |s| string(s));
+    quote! {
+        ::vesper_core::types::InputSpec {
+            input_type: #input_type,
+            required: #required,
+            constraints: #constraints,
+            default: #default,
+            description: #description,
+        }
+    }
+}
+
+fn outputs(outputs: &Outputs) -> TokenStream {
+    let success = map(&outputs.success, output_field);
+    let error = map(&outputs.error, output_field);
+    quote! {
+        ::vesper_core::types::Outputs {
+            success: #success,
+            error: #error,
+        }
+    }
+}
+
+fn output_field(field: &OutputField) -> TokenStream {
+    let output_type = option(&field.output_type, |s| string(s));
+    let description = option(&field.description, |s| string(s));
+    let values = vec(&field.values, |s| string(s));
+    quote! {
+        ::vesper_core::types::OutputField {
+            output_type: #output_type,
+            description: #description,
+            values: #values,
+        }
+    }
+}
+
+fn custom_type(custom: &CustomType) -> TokenStream {
+    let base = option(&custom.base, |s| string(s));
+    let fields = map(&custom.fields, yaml);
+    let constraints = vec(&custom.constraints, |s| string(s));
+    quote! {
+        ::vesper_core::types::CustomType {
+            base: #base,
+            fields: #fields,
+            constraints: #constraints,
+        }
+    }
+}
+
+fn contracts(contracts: &Contracts) -> TokenStream {
+    let preconditions = vec(&contracts.preconditions, |s| string(s));
+    let postconditions = vec(&contracts.postconditions, |s| string(s));
+    let invariants = vec(&contracts.invariants, |s| string(s));
+    quote! {
+        ::vesper_core::types::Contracts {
+            preconditions: #preconditions,
+            postconditions: #postconditions,
+            invariants: #invariants,
+        }
+    }
+}
+
+/// Step with only its non-empty fields set explicitly
+fn flow_step(step: &FlowStep) -> TokenStream {
+    let mut fields = Vec::new();
+    let mut set = |name: &str, value: TokenStream| {
+        let name = syn::Ident::new(name, proc_macro2::Span::call_site());
+        fields.push(quote!(#name: #value));
+    };
+    let strings = |s: &Option<String>| option(s, |s| string(s));
+
+    if !step.step.is_empty() {
+        set("step", string(&step.step));
+    }
+    set("operation", string(&step.operation));
+    if step.description.is_some() {
+        set("description", strings(&step.description));
+    }
+    if !step.parameters.is_empty() {
+        set("parameters", map(&step.parameters, yaml));
+    }
+    if !step.guards.is_empty() {
+        set("guards", vec(&step.guards, |s| string(s)));
+    }
+    if step.condition.is_some() {
+        set("condition", strings(&step.condition));
+    }
+    if !step.then_steps.is_empty() {
+        set("then_steps", vec(&step.then_steps, flow_step));
+    }
+    if !step.else_steps.is_empty() {
+        set("else_steps", vec(&step.else_steps, flow_step));
+    }
+    if step.template.is_some() {
+        set("template", strings(&step.template));
+    }
+    if step.expression.is_some() {
+        set("expression", strings(&step.expression));
+    }
+    if step.output.is_some() {
+        set("output", strings(&step.output));
+    }
+    if let Some(retry_count) = step.retry_count {
+        set("retry_count", quote!(Some(#retry_count)));
+    }
+    if step.memoize {
+        set("memoize", quote!(true));
+    }
+    if let Some(ttl) = step.memo_ttl_seconds {
+        set("memo_ttl_seconds", quote!(Some(#ttl)));
+    }
+    if step.on_success.is_some() {
+        set(
+            "on_success",
+            option(&step.on_success, |steps| vec(steps, flow_step)),
+        );
+    }
+    if step.on_error.is_some() {
+        set(
+            "on_error",
+            option(&step.on_error, |steps| vec(steps, flow_step)),
+        );
+    }
+    if step.on_failure.is_some() {
+        set("on_failure", option(&step.on_failure, yaml));
+    }
+    if step.return_success.is_some() {
+        set(
+            "return_success",
+            option(&step.return_success, |m| map(m, yaml)),
+        );
+    }
+    if step.return_error.is_some() {
+        set("return_error", option(&step.return_error, |m| map(m, yaml)));
+    }
+
+    quote! {
+        ::vesper_core::types::FlowStep {
+            #(#fields,)*
+            ..::std::default::Default::default()
+        }
+    }
+}
+
+fn performance(performance: &Performance) -> TokenStream {
+    let number = |n: &Option<u64>| option(n, |n| quote!(#n));
+    let expected_latency_ms = number(&performance.expected_latency_ms);
+    let p99_latency_ms = number(&performance.p99_latency_ms);
+    let max_latency_ms = number(&performance.max_latency_ms);
+    let memory_limit_mb = number(&performance.memory_limit_mb);
+    let timeout_seconds = number(&performance.timeout_seconds);
+    quote! {
+        ::vesper_core::types::Performance {
+            expected_latency_ms: #expected_latency_ms,
+            p99_latency_ms: #p99_latency_ms,
+            max_latency_ms: #max_latency_ms,
+            memory_limit_mb: #memory_limit_mb,
+            timeout_seconds: #timeout_seconds,
+        }
+    }
+}
+
+fn security(security: &Security) -> TokenStream {
+    let capabilities_required = vec(&security.capabilities_required, |s| string(s));
+    let denied_capabilities = vec(&security.denied_capabilities, |s| string(s));
+    let sensitive_data = vec(&security.sensitive_data, |s| string(s));
+    let audit_level = option(&security.audit_level, |s| string(s));
+    quote! {
+        ::vesper_core::types::Security {
+            capabilities_required: #capabilities_required,
+            denied_capabilities: #denied_capabilities,
+            sensitive_data: #sensitive_data,
+            audit_level: #audit_level,
+        }
+    }
+}
+
+fn cache_spec(cache: &CacheSpec) -> TokenStream {
+    let enabled = cache.enabled;
+    let ttl_seconds = cache.ttl_seconds;
+    let key_inputs = vec(&cache.key_inputs, |s| string(s));
+    quote! {
+        ::vesper_core::types::CacheSpec {
+            enabled: #enabled,
+            ttl_seconds: #ttl_seconds,
+            key_inputs: #key_inputs,
+        }
+    }
+}
+
+/// Expression evaluating to a YAML value
+fn yaml(value: &serde_yaml::Value) -> TokenStream {
+    let module = quote!(::vesper_core::serde_yaml);
+    match value {
+        serde_yaml::Value::Null => quote!(#module::Value::Null),
+        serde_yaml::Value::Bool(b) => quote!(#module::Value::Bool(#b)),
+        serde_yaml::Value::Number(n) => {
+            let number = if let Some(i) = n.as_i64() {
+                quote!(#i)
+            } else if let Some(u) = n.as_u64() {
+                quote!(#u)
+            } else {
+                let f = n.as_f64().unwrap_or(f64::NAN);
+                if f.is_finite() {
+                    let literal = Literal::f64_suffixed(f);
+                    quote!(#literal)
+                } else {
+                    let bits = f.to_bits();
+                    quote!(f64::from_bits(#bits))
+                }
+            };
+            quote!(#module::Value::Number(#module::Number::from(#number)))
+        }
+        serde_yaml::Value::String(s) => quote!(#module::Value::String(#s.to_string())),
+        serde_yaml::Value::Sequence(items) => {
+            let items = items.iter().map(yaml);
+            quote!(#module::Value::Sequence(vec![#(#items),*]))
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            let entries = mapping.iter().map(|(key, value)| {
+                let key = yaml(key);
+                let value = yaml(value);
+                quote!(mapping.insert(#key, #value);)
+            });
+            quote!({
+                let mut mapping = #module::Mapping::new();
+                #(#entries)*
+                #module::Value::Mapping(mapping)
+            })
+        }
+        serde_yaml::Value::Tagged(tagged) => {
+            let tag = tagged.tag.to_string();
+            let value = yaml(&tagged.value);
+            quote!(#module::Value::Tagged(Box::new(#module::value::TaggedValue {
+                tag: #module::value::Tag::new(#tag),
+                value: #value,
+            })))
+        }
+    }
+}
+
+fn string(s: &str) -> TokenStream {
+    quote!(::std::string::String::from(#s))
+}
+
+fn option<T>(value: &Option<T>, f: impl Fn(&T) -> TokenStream) -> TokenStream {
+    match value {
+        Some(value) => {
+            let value = f(value);
+            quote!(Some(#value))
+        }
+        None => quote!(None),
+    }
+}
+
+fn vec<T>(items: &[T], f: impl Fn(&T) -> TokenStream) -> TokenStream {
+    if items.is_empty() {
+        return quote!(::std::vec::Vec::new());
+    }
+    let items = items.iter().map(f);
+    quote!(vec![#(#items),*])
+}
+
+/// Map literal, with entries sorted by key for reproducible builds
+fn map<V>(map: &HashMap<String, V>, f: impl Fn(&V) -> TokenStream) -> TokenStream {
+    if map.is_empty() {
+        return quote!(::std::collections::HashMap::new());
+    }
+    let mut entries: Vec<(&String, &V)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    let entries = entries.into_iter().map(|(key, value)| {
+        let key = string(key);
+        let value = f(value);
+        quote!((#key, #value))
+    });
+    quote!(::std::collections::HashMap::from([#(#entries),*]))
+}
@@ -0,0 +1,63 @@
+//! Vesper Macros
+//!
+//! Compile-time embedding of Vesper nodes. `vesper_node!` parses and
+//! validates a YAML spec while the crate using it compiles, so malformed
+//! specs fail the build instead of the first execution.
+
+mod codegen;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, LitStr};
+use vesper_core::VesperLoader;
+
+/// Build a `VesperNode` from a YAML spec checked at compile time
+///
+/// The spec is loaded with `VesperLoader::load_string`, so any error it
+/// reports becomes a compile error. The macro expands to an expression
+/// constructing the node directly; no YAML is parsed at runtime, and
+/// fields the spec leaves empty are not embedded.
+///
+/// ```ignore
+/// let node = vesper_node!(r#"
+/// node_id: greet_v1
+/// type: function
+/// intent: greet a user
+/// flow:
+///   - step: greet
+///     operation: string_template
+///     template: "Hello!"
+/// "#);
+/// ```
+#[proc_macro]
+pub fn vesper_node(input: TokenStream) -> TokenStream {
+    let spec = parse_macro_input!(input as LitStr);
+    expand(&spec)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Load the spec and generate the expression building its node
+fn expand(spec: &LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let node = VesperLoader::new()
+        .load_string(&spec.value())
+        .map_err(|e| syn::Error::new(spec.span(), format!("Invalid Vesper node: {}", e)))?;
+    Ok(codegen::node(&node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+
+    #[test]
+    fn test_invalid_specs_fail_expansion() {
+        for yaml in [
+            "node_id: [unclosed",
+            "node_id: add\ntype: function\nintent: add",
+            "node_id: add_v1\ntype: function\nintent: add\nflow:\n  - step: sum\n    operation: arithmetic\n    expression: a + b\n",
+        ] {
+            let error = expand(&LitStr::new(yaml, Span::call_site())).unwrap_err();
+            assert!(error.to_string().starts_with("Invalid Vesper node"));
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use vesper_core::{SemanticExecutor, Value, VesperLoader};
+use vesper_macros::vesper_node;
+
+const SPEC: &str = r#"
+node_id: greet_v1
+type: function
+intent: greet a user in their language
+
+metadata:
+  version: 1.2.0
+  tags: [greeting]
+
+inputs:
+  name:
+    type: string
+  language:
+    type: string
+    required: false
+    default: en
+
+outputs:
+  success:
+    greeting:
+      type: string
+
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello, {name}!"
+    output: greeting
+    parameters:
+      fallback: { language: en, retries: 2, ratio: 0.5, tags: [a, b] }
+"#;
+
+#[test]
+fn test_embedded_node_matches_loaded_node() {
+    // The macro needs a literal, so this repeats `SPEC`
+    let embedded = vesper_node!(
+        r#"
+node_id: greet_v1
+type: function
+intent: greet a user in their language
+
+metadata:
+  version: 1.2.0
+  tags: [greeting]
+
+inputs:
+  name:
+    type: string
+  language:
+    type: string
+    required: false
+    default: en
+
+outputs:
+  success:
+    greeting:
+      type: string
+
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello, {name}!"
+    output: greeting
+    parameters:
+      fallback: { language: en, retries: 2, ratio: 0.5, tags: [a, b] }
+"#
+    );
+    let loaded = VesperLoader::new().load_string(SPEC).unwrap();
+    assert_eq!(
+        serde_json::to_value(&embedded).unwrap(),
+        serde_json::to_value(&loaded).unwrap()
+    );
+
+    let mut executor = SemanticExecutor::new();
+    executor.register(embedded);
+    let inputs = HashMap::from([("name".to_string(), Value::from("Ada"))]);
+    let result = executor.execute("greet_v1", inputs).unwrap();
+    assert_eq!(result.data, Some(Value::from("Hello, Ada!")));
+}
@@ -0,0 +1,641 @@
+//! Structural grammar of Vesper specifications
+//!
+//! Specs are parsed with the tree-sitter YAML grammar, which keeps going
+//! past errors and records where every construct starts. The Vesper rules
+//! on top of it, such as `flow` being a sequence of step mappings whose
+//! `operation` is a string, are declared in the tables below and checked
+//! against the concrete syntax tree, so each violation is reported at its
+//! exact position rather than as a deserialization failure.
+
+use std::fmt;
+use tree_sitter::{Node, Parser, Tree};
+
+/// Malformed construct in a spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// 1-based line of the construct
+    pub line: usize,
+    /// 1-based column of the construct
+    pub column: usize,
+    /// Dotted path of the field, e.g. `flow[2].operation`; empty for YAML
+    /// syntax errors
+    pub path: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Shape a field's value must have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// Scalar string, quoted or plain
+    String,
+    /// Mapping of anything
+    Mapping,
+    /// Sequence of anything
+    Sequence,
+    /// Sequence of flow step mappings
+    Steps,
+    /// Mapping of input names to input spec mappings
+    Inputs,
+}
+
+impl Shape {
+    fn describe(self) -> &'static str {
+        match self {
+            Shape::String => "a string",
+            Shape::Mapping | Shape::Inputs => "a mapping",
+            Shape::Sequence | Shape::Steps => "a sequence",
+        }
+    }
+}
+
+/// Field of a mapping and the shape of its value
+struct Field {
+    name: &'static str,
+    shape: Shape,
+    /// Whether the value may not be left empty
+    required: bool,
+}
+
+const fn field(name: &'static str, shape: Shape) -> Field {
+    Field {
+        name,
+        shape,
+        required: false,
+    }
+}
+
+const fn required(name: &'static str, shape: Shape) -> Field {
+    Field {
+        name,
+        shape,
+        required: true,
+    }
+}
+
+/// Top-level fields of a node
+const NODE_FIELDS: &[Field] = &[
+    required("node_id", Shape::String),
+    required("type", Shape::String),
+    field("intent", Shape::String),
+    field("metadata", Shape::Mapping),
+    field("imports", Shape::Mapping),
+    field("inputs", Shape::Inputs),
+    field("outputs", Shape::Mapping),
+    field("types", Shape::Mapping),
+    field("contracts", Shape::Mapping),
+    field("input_transform", Shape::Steps),
+    required("flow", Shape::Steps),
+    field("output_transform", Shape::Steps),
+    field("performance", Shape::Mapping),
+    field("security", Shape::Mapping),
+    field("cache", Shape::Mapping),
+];
+
+/// Fields of a flow step
+const STEP_FIELDS: &[Field] = &[
+    field("step", Shape::String),
+    required("operation", Shape::String),
+    field("description", Shape::String),
+    field("parameters", Shape::Mapping),
+    field("guards", Shape::Sequence),
+    field("condition", Shape::String),
+    field("then", Shape::Steps),
+    field("else", Shape::Steps),
+    field("template", Shape::String),
+    field("expression", Shape::String),
+    field("output", Shape::String),
+    field("on_success", Shape::Steps),
+    field("on_error", Shape::Steps),
+    field("return_success", Shape::Mapping),
+    field("return_error", Shape::Mapping),
+];
+
+/// Fields of an input spec
+const INPUT_FIELDS: &[Field] = &[
+    required("type", Shape::String),
+    field("constraints", Shape::Sequence),
+    field("description", Shape::String),
+];
+
+/// Check the structure of a spec
+///
+/// Returns every YAML syntax error and every field with the wrong shape,
+/// in document order. Fields the grammar does not know are ignored. The
+/// structure is only checked once the YAML itself parses.
+pub fn validate(content: &str) -> Result<Vec<SyntaxError>, String> {
+    let tree = parse(content)?;
+    let mut checker = Checker {
+        source: content.as_bytes(),
+        errors: Vec::new(),
+    };
+    let root = tree.root_node();
+    if root.has_error() {
+        checker.yaml_errors(root);
+    } else if let Some(document) = first_named_child(root, "document") {
+        match content_of(document) {
+            Some(node) => checker.mapping(node, "", NODE_FIELDS),
+            None => checker.error(document, "", "spec is empty".to_string()),
+        }
+    }
+    Ok(checker.errors)
+}
+
+/// Scalar of a spec and where it is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scalar {
+    /// Dotted path of the field the scalar names or holds, e.g.
+    /// `flow[2].operation`
+    pub path: String,
+    /// Whether the scalar is a mapping key rather than a value
+    pub is_key: bool,
+    /// Text of the scalar, without quotes
+    pub value: String,
+    /// 1-based line of the first character of the text
+    pub line: usize,
+    /// 1-based byte column of the first character of the text
+    pub column: usize,
+    /// 1-based line of the end of the text
+    pub end_line: usize,
+    /// 1-based byte column just past the last character of the text
+    pub end_column: usize,
+}
+
+/// Every scalar of the first document of a spec, keys included, in
+/// document order
+///
+/// Works on specs with syntax errors too, skipping the regions the parser
+/// could not make sense of.
+pub fn scalars(content: &str) -> Result<Vec<Scalar>, String> {
+    let tree = parse(content)?;
+    let mut collector = Collector {
+        source: content.as_bytes(),
+        scalars: Vec::new(),
+    };
+    if let Some(node) = first_named_child(tree.root_node(), "document").and_then(content_of) {
+        collector.collect(node, "");
+    }
+    Ok(collector.scalars)
+}
+
+fn parse(content: &str) -> Result<Tree, String> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_yaml::LANGUAGE.into())
+        .map_err(|e| format!("Cannot load YAML grammar: {}", e))?;
+    parser
+        .parse(content, None)
+        .ok_or_else(|| "YAML parser produced no tree".to_string())
+}
+
+/// Collects the scalars of one spec
+struct Collector<'a> {
+    source: &'a [u8],
+    scalars: Vec<Scalar>,
+}
+
+impl Collector<'_> {
+    fn collect(&mut self, node: Node, path: &str) {
+        let mut cursor = node.walk();
+        match node.kind() {
+            "block_mapping" | "flow_mapping" => {
+                let pairs: Vec<Node> = node.named_children(&mut cursor).collect();
+                for pair in pairs {
+                    let Some(key) = pair.child_by_field_name("key").and_then(content_of) else {
+                        continue;
+                    };
+                    let name = self.push(key, path, true);
+                    let field_path = join(path, &name);
+                    if let Some(value) = pair.child_by_field_name("value").and_then(content_of) {
+                        self.collect(value, &field_path);
+                    }
+                }
+            }
+            "block_sequence" | "flow_sequence" => {
+                let items: Vec<Node> = node
+                    .named_children(&mut cursor)
+                    .filter(|item| item.kind() != "comment")
+                    .collect();
+                for (index, item) in items.into_iter().enumerate() {
+                    let item = match item.kind() {
+                        "block_sequence_item" => item.named_child(0).and_then(content_of),
+                        _ => content_of(item),
+                    };
+                    if let Some(item) = item {
+                        self.collect(item, &format!("{}[{}]", path, index));
+                    }
+                }
+            }
+            "plain_scalar" | "double_quote_scalar" | "single_quote_scalar" | "block_scalar" => {
+                self.push(node, path, false);
+            }
+            _ => {}
+        }
+    }
+
+    /// Record the scalar in `node` and return its text
+    fn push(&mut self, node: Node, path: &str, is_key: bool) -> String {
+        let mut start = node.start_position();
+        let mut end = node.end_position();
+        let text = node.utf8_text(self.source).unwrap_or_default();
+        let value = match node.kind() {
+            "double_quote_scalar" | "single_quote_scalar" if text.len() >= 2 => {
+                start.column += 1;
+                end.column = end.column.saturating_sub(1);
+                &text[1..text.len() - 1]
+            }
+            _ => text,
+        };
+        let name = value.to_string();
+        self.scalars.push(Scalar {
+            path: if is_key {
+                join(path, value)
+            } else {
+                path.to_string()
+            },
+            is_key,
+            value: name.clone(),
+            line: start.row + 1,
+            column: start.column + 1,
+            end_line: end.row + 1,
+            end_column: end.column + 1,
+        });
+        name
+    }
+}
+
+/// Collects the errors of one spec
+struct Checker<'a> {
+    source: &'a [u8],
+    errors: Vec<SyntaxError>,
+}
+
+impl Checker<'_> {
+    fn error(&mut self, node: Node, path: &str, message: String) {
+        let position = node.start_position();
+        self.errors.push(SyntaxError {
+            line: position.row + 1,
+            column: position.column + 1,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    /// Report the innermost error and missing nodes under `node`
+    fn yaml_errors(&mut self, node: Node) {
+        if node.is_missing() {
+            self.error(node, "", format!("missing {}", node.kind()));
+            return;
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        let nested = children.iter().any(|child| child.has_error());
+        if node.is_error() && !nested {
+            // The parser recovers at the first token it could not fit, which
+            // follows the skipped region
+            let (position, byte) = match node.next_sibling() {
+                Some(next) => (next.start_position(), next.start_byte()),
+                None => (node.end_position(), node.end_byte()),
+            };
+            let rest = std::str::from_utf8(&self.source[byte..]).unwrap_or_default();
+            let message = match rest.lines().next().map(str::trim) {
+                Some(token) if !token.is_empty() => format!("unexpected `{}`", token),
+                _ => "unexpected end of spec".to_string(),
+            };
+            self.errors.push(SyntaxError {
+                line: position.row + 1,
+                column: position.column + 1,
+                path: String::new(),
+                message,
+            });
+            return;
+        }
+        for child in children.into_iter().filter(|child| child.has_error()) {
+            self.yaml_errors(child);
+        }
+    }
+
+    /// Check the known fields of the mapping in `node`
+    fn mapping(&mut self, node: Node, path: &str, fields: &[Field]) {
+        let Some(pairs) = self.pairs(node) else {
+            self.error(
+                node,
+                path,
+                format!("expected a mapping, found {}", describe(node)),
+            );
+            return;
+        };
+        for field in fields {
+            let field_path = join(path, field.name);
+            let Some((key, value)) = pairs
+                .iter()
+                .find(|(key, _, _)| key == field.name)
+                .map(|(_, key, value)| (*key, *value))
+            else {
+                if field.required {
+                    self.error(
+                        node,
+                        path,
+                        format!("missing required field `{}`", field.name),
+                    );
+                }
+                continue;
+            };
+            match value {
+                Some(value) => self.value(value, &field_path, field.shape),
+                None if field.required => self.error(
+                    key,
+                    &field_path,
+                    format!("expected {}, found nothing", field.shape.describe()),
+                ),
+                None => {}
+            }
+        }
+    }
+
+    /// Check that `node` has `shape`, then check its contents
+    fn value(&mut self, node: Node, path: &str, shape: Shape) {
+        let kind = node.kind();
+        if kind == "alias" {
+            return;
+        }
+        let matches = match shape {
+            Shape::String => is_string(node),
+            Shape::Mapping | Shape::Inputs => matches!(kind, "block_mapping" | "flow_mapping"),
+            Shape::Sequence | Shape::Steps => matches!(kind, "block_sequence" | "flow_sequence"),
+        };
+        if !matches {
+            self.error(
+                node,
+                path,
+                format!("expected {}, found {}", shape.describe(), describe(node)),
+            );
+            return;
+        }
+
+        match shape {
+            Shape::Steps => {
+                for (index, item) in self.items(node).into_iter().enumerate() {
+                    let item_path = format!("{}[{}]", path, index);
+                    match item {
+                        Some(step) => self.mapping(step, &item_path, STEP_FIELDS),
+                        None => self.error(node, &item_path, "empty flow step".to_string()),
+                    }
+                }
+            }
+            Shape::Inputs => {
+                for (name, key, spec) in self.pairs(node).unwrap_or_default() {
+                    let input_path = join(path, &name);
+                    match spec {
+                        Some(spec) => self.mapping(spec, &input_path, INPUT_FIELDS),
+                        None => self.error(key, &input_path, "input has no spec".to_string()),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Key, key node and value content of each pair of a mapping
+    #[allow(clippy::type_complexity)]
+    fn pairs<'t>(&self, node: Node<'t>) -> Option<Vec<(String, Node<'t>, Option<Node<'t>>)>> {
+        if !matches!(node.kind(), "block_mapping" | "flow_mapping") {
+            return None;
+        }
+        let mut cursor = node.walk();
+        let pairs = node
+            .named_children(&mut cursor)
+            .filter_map(|pair| match pair.kind() {
+                "block_mapping_pair" | "flow_pair" => {
+                    let key = pair.child_by_field_name("key")?;
+                    let value = pair.child_by_field_name("value").and_then(content_of);
+                    Some((self.key(key), key, value))
+                }
+                "flow_node" => Some((self.key(pair), pair, None)),
+                _ => None,
+            })
+            .collect();
+        Some(pairs)
+    }
+
+    /// Content of each item of a sequence, `None` for empty items
+    fn items<'t>(&self, node: Node<'t>) -> Vec<Option<Node<'t>>> {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .filter(|item| item.kind() != "comment")
+            .map(|item| match item.kind() {
+                "block_sequence_item" => {
+                    let mut cursor = item.walk();
+                    let value = item
+                        .named_children(&mut cursor)
+                        .find(|c| c.kind() != "comment");
+                    value.and_then(content_of)
+                }
+                _ => content_of(item),
+            })
+            .collect()
+    }
+
+    /// Text of a key, without quotes
+    fn key(&self, node: Node) -> String {
+        let node = content_of(node).unwrap_or(node);
+        let text = self.text(node);
+        match node.kind() {
+            "double_quote_scalar" | "single_quote_scalar" => text
+                .get(1..text.len().saturating_sub(1))
+                .unwrap_or_default()
+                .to_string(),
+            _ => text.to_string(),
+        }
+    }
+
+    fn text(&self, node: Node) -> &str {
+        node.utf8_text(self.source).unwrap_or_default()
+    }
+}
+
+/// Value inside a `block_node` / `flow_node`, past any anchor or tag
+fn content_of(node: Node) -> Option<Node> {
+    if !matches!(node.kind(), "block_node" | "flow_node" | "document") {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let content = node
+        .named_children(&mut cursor)
+        .find(|child| !matches!(child.kind(), "anchor" | "tag" | "comment"));
+    content.and_then(content_of)
+}
+
+fn first_named_child<'t>(node: Node<'t>, kind: &str) -> Option<Node<'t>> {
+    let mut cursor = node.walk();
+    let child = node
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == kind);
+    child
+}
+
+/// Whether a scalar holds a string rather than a number, boolean or null
+fn is_string(node: Node) -> bool {
+    match node.kind() {
+        "double_quote_scalar" | "single_quote_scalar" | "block_scalar" => true,
+        "plain_scalar" => node
+            .named_child(0)
+            .is_some_and(|scalar| matches!(scalar.kind(), "string_scalar" | "timestamp_scalar")),
+        _ => false,
+    }
+}
+
+/// Name of the kind of value in `node`, for messages
+fn describe(node: Node) -> String {
+    let kind = match node.kind() {
+        "plain_scalar" => node
+            .named_child(0)
+            .map_or("plain_scalar", |scalar| scalar.kind()),
+        kind => kind,
+    };
+    match kind {
+        "block_mapping" | "flow_mapping" => "a mapping".to_string(),
+        "block_sequence" | "flow_sequence" => "a sequence".to_string(),
+        "double_quote_scalar" | "single_quote_scalar" | "string_scalar" | "block_scalar" => {
+            "a string".to_string()
+        }
+        "integer_scalar" | "float_scalar" => "a number".to_string(),
+        "boolean_scalar" => "a boolean".to_string(),
+        "null_scalar" => "null".to_string(),
+        other => other.replace('_', " "),
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"
+node_id: greet_v1
+type: function
+intent: greet a user
+
+inputs:
+  name:
+    type: string
+    constraints: [non_empty]
+
+flow:
+  - step: greet
+    operation: string_template
+    template: "Hello, {name}!"
+    parameters: { fallback: friend }
+  - step: check
+    operation: conditional
+    condition: "name == 'Ada'"
+    then:
+      - operation: return
+        return_success: { vip: true }
+"#;
+
+    #[test]
+    fn test_valid_spec_has_no_errors() {
+        assert_eq!(validate(VALID).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_structural_errors_are_located() {
+        let spec = r#"node_id: greet_v1
+type: function
+inputs:
+  - name
+flow:
+  - step: greet
+    operation: 42
+  - step: nested
+    operation: conditional
+    then:
+      step: oops
+"#;
+        let errors = validate(spec).unwrap();
+        let found: Vec<(usize, usize, &str)> = errors
+            .iter()
+            .map(|e| (e.line, e.column, e.path.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (4, 3, "inputs"),
+                (7, 16, "flow[0].operation"),
+                (11, 7, "flow[1].then"),
+            ]
+        );
+        assert_eq!(errors[0].message, "expected a mapping, found a sequence");
+        assert_eq!(errors[1].message, "expected a string, found a number");
+        assert_eq!(
+            errors[1].to_string(),
+            "7:16: flow[0].operation: expected a string, found a number"
+        );
+    }
+
+    #[test]
+    fn test_flow_must_be_a_sequence_and_required_fields_present() {
+        let errors = validate("node_id: x_v1\nflow: greet\n").unwrap();
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "1:1: missing required field `type`",
+                "2:7: flow: expected a sequence, found a string",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scalars_are_located() {
+        let found = scalars(VALID).unwrap();
+        let operation = found
+            .iter()
+            .find(|s| s.path == "flow[1].operation" && !s.is_key)
+            .unwrap();
+        assert_eq!(operation.value, "conditional");
+        assert_eq!((operation.line, operation.column), (17, 16));
+        assert_eq!(operation.end_column, 27);
+
+        let template = found.iter().find(|s| s.path == "flow[0].template").unwrap();
+        assert!(template.is_key);
+        let fallback = found
+            .iter()
+            .find(|s| s.path == "flow[0].parameters.fallback" && !s.is_key)
+            .unwrap();
+        assert_eq!(fallback.value, "friend");
+        let constraint = found
+            .iter()
+            .find(|s| s.path == "inputs.name.constraints[0]")
+            .unwrap();
+        assert_eq!(constraint.value, "non_empty");
+        let greeting = found.iter().find(|s| s.value == "Hello, {name}!").unwrap();
+        assert_eq!((greeting.line, greeting.column), (14, 16));
+    }
+
+    #[test]
+    fn test_yaml_syntax_errors() {
+        let spec = "node_id: x_v1\ntype: function\nflow:\n  - step: a\n    operation: x: y\n";
+        let errors = validate(spec).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), "5:17: unexpected `: y`");
+    }
+}